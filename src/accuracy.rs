@@ -0,0 +1,67 @@
+use crate::float::Float;
+
+/// Результаты по модулю не больше этого числа считаются нулевыми: делить
+/// на них погрешность бессмысленно.
+pub const RELATIVE_ERROR_MIN_RESULT: f64 = 1e-12;
+
+/// Погрешность результата относительно эталона.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccuracyReport<T: Float = f64> {
+    pub absolute: T,
+    /// Относительная погрешность; `None`, если результат близок к нулю.
+    pub relative: Option<T>,
+}
+
+impl<T: Float> AccuracyReport<T> {
+    /// Отчёт по уже известной абсолютной погрешности результата `result`.
+    pub fn from_absolute(result: T, absolute: T) -> Self {
+        let relative = (result.abs() > T::from_f64(RELATIVE_ERROR_MIN_RESULT)).then(|| absolute / result.abs());
+        Self { absolute, relative }
+    }
+
+    /// Относительная погрешность в процентах, например `0.0123%` или `4.56e-9%`.
+    pub fn relative_percent(&self) -> Option<String> {
+        self.relative.map(|relative| {
+            let percent = relative.to_f64() * 100.0;
+            if percent == 0.0 || (1e-3..1e3).contains(&percent) {
+                format!("{:.4}%", percent)
+            } else {
+                format!("{:.3e}%", percent)
+            }
+        })
+    }
+}
+
+/// Абсолютная и, если результат не близок к нулю, относительная
+/// погрешность `result` по эталонному значению `reference`.
+pub fn analyze_accuracy<T: Float>(result: T, reference: T) -> AccuracyReport<T> {
+    AccuracyReport::from_absolute(result, (reference - result).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_result_has_no_relative_error() {
+        let report = analyze_accuracy(0.0, 1e-9);
+        assert_eq!(report, AccuracyReport { absolute: 1e-9, relative: None });
+        assert_eq!(report.relative_percent(), None);
+    }
+
+    #[test]
+    fn tiny_result_has_no_relative_error() {
+        let report = analyze_accuracy(1e-300, 0.0);
+        assert_eq!(report.absolute, 1e-300);
+        assert_eq!(report.relative, None);
+    }
+
+    #[test]
+    fn normal_result() {
+        let report = analyze_accuracy(2.0, 2.001);
+        assert!((report.absolute - 1e-3).abs() < 1e-15, "{}", report.absolute);
+        assert_eq!(report.relative_percent().as_deref(), Some("0.0500%"));
+        assert_eq!(analyze_accuracy(1.0, 1.0 + 1e-10).relative_percent().as_deref(), Some("1.000e-8%"));
+        assert_eq!(analyze_accuracy(1.0, 1.0).relative_percent().as_deref(), Some("0.0000%"));
+    }
+}
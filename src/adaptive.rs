@@ -0,0 +1,347 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::integrate::default_threads;
+use crate::options::IntegrationOptions;
+use crate::result::IntegrationResult;
+
+/// Наибольшая глубина деления отрезка пополам.
+pub const ADAPTIVE_MAX_DEPTH: u32 = 50;
+
+/// Отрезок, ожидающий проверки: значения f на концах и в середине уже
+/// вычислены, `whole` — оценка Симпсона по одной панели.
+#[derive(Debug, Clone, Copy)]
+struct Segment<T: Float> {
+    lower_bound: T,
+    upper_bound: T,
+    lower_value: T,
+    middle_value: T,
+    upper_value: T,
+    whole: T,
+    eps: T,
+    depth: u32,
+}
+
+/// Принятый отрезок: левая граница, уточнённое значение и оценка погрешности.
+type Accepted<T> = (T, T, T);
+
+fn simpson<T: Float>(lower_bound: T, upper_bound: T, lower_value: T, middle_value: T, upper_value: T) -> T {
+    (upper_bound - lower_bound) / T::from_f64(6.0) * (lower_value + T::from_f64(4.0) * middle_value + upper_value)
+}
+
+/// Очередь отрезков, общая для рабочих потоков: свободный поток забирает
+/// отрезок, отложенный другим.
+struct Queue<T: Float> {
+    segments: Mutex<Vec<Segment<T>>>,
+    available: Condvar,
+    /// Отрезки, которые ещё не приняты: в очереди, в локальных стеках и в работе.
+    pending: AtomicUsize,
+    stopped: AtomicBool,
+    failure: Mutex<Option<IntegralCalcError>>,
+}
+
+impl<T: Float> Queue<T> {
+    fn stop(&self, error: IntegralCalcError) {
+        if let Ok(mut failure) = self.failure.lock() {
+            failure.get_or_insert(error);
+        }
+        self.stopped.store(true, Ordering::SeqCst);
+        let _guard = self.segments.lock();
+        self.available.notify_all();
+    }
+
+    fn finish_one(&self) {
+        if self.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            let _guard = self.segments.lock();
+            self.available.notify_all();
+        }
+    }
+
+    /// Ждёт отрезок из общей очереди; `None` — работа закончена.
+    fn take(&self) -> Option<Segment<T>> {
+        let mut segments = self.segments.lock().ok()?;
+        loop {
+            if self.stopped.load(Ordering::SeqCst) || self.pending.load(Ordering::SeqCst) == 0 {
+                return None;
+            }
+            if let Some(segment) = segments.pop() {
+                return Some(segment);
+            }
+            segments = self.available.wait(segments).ok()?;
+        }
+    }
+
+    /// Откладывает отрезок в общую очередь, если там пусто и кто-то может ждать.
+    fn offer(&self, segment: Segment<T>) -> Option<Segment<T>> {
+        match self.segments.try_lock() {
+            Ok(mut segments) if segments.is_empty() => {
+                segments.push(segment);
+                self.available.notify_one();
+                None
+            }
+            _ => Some(segment),
+        }
+    }
+}
+
+/// Рабочий цикл: обрабатывает отрезки из локального стека, делясь ими через
+/// общую очередь. Возвращает принятые отрезки и число вычислений f.
+fn work<T, I>(queue: &Queue<T>, f: &I, cancel: &dyn Fn() -> bool) -> (Vec<Accepted<T>>, u64)
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    let mut accepted = Vec::new();
+    let mut evaluations = 0;
+    let mut stack = Vec::new();
+    while let Some(segment) = stack.pop().or_else(|| queue.take()) {
+        if queue.stopped.load(Ordering::SeqCst) {
+            break;
+        }
+        if cancel() {
+            queue.stop(IntegralCalcError::Cancelled);
+            break;
+        }
+        let Segment { lower_bound, upper_bound, lower_value, middle_value, upper_value, whole, eps, depth } = segment;
+        let two = T::from_f64(2.0);
+        let middle = (lower_bound + upper_bound) / two;
+        let left_value = f.eval((lower_bound + middle) / two);
+        let right_value = f.eval((middle + upper_bound) / two);
+        evaluations += 2;
+        let left = simpson(lower_bound, middle, lower_value, left_value, middle_value);
+        let right = simpson(middle, upper_bound, middle_value, right_value, upper_value);
+        let difference = left + right - whole;
+        // Поправка Ричардсона: погрешность двух панелей ≈ difference / 15
+        if difference.abs() <= T::from_f64(15.0) * eps {
+            let fifteen = T::from_f64(15.0);
+            accepted.push((lower_bound, left + right + difference / fifteen, difference.abs() / fifteen));
+            queue.finish_one();
+            continue;
+        }
+        if depth >= ADAPTIVE_MAX_DEPTH {
+            queue.stop(IntegralCalcError::MaxDepthReached {
+                lower: lower_bound.to_f64(),
+                upper: upper_bound.to_f64(),
+            });
+            break;
+        }
+        queue.pending.fetch_add(1, Ordering::SeqCst);
+        let half_eps = eps / two;
+        stack.push(Segment {
+            lower_bound: middle,
+            upper_bound,
+            lower_value: middle_value,
+            middle_value: right_value,
+            upper_value,
+            whole: right,
+            eps: half_eps,
+            depth: depth + 1,
+        });
+        let left_segment = Segment {
+            lower_bound,
+            upper_bound: middle,
+            lower_value,
+            middle_value: left_value,
+            upper_value: middle_value,
+            whole: left,
+            eps: half_eps,
+            depth: depth + 1,
+        };
+        if let Some(left_segment) = queue.offer(left_segment) {
+            stack.push(left_segment);
+        }
+    }
+    (accepted, evaluations)
+}
+
+/// Адаптивная формула Симпсона: отрезок делится пополам, пока разность
+/// оценок по одной и двум панелям больше доли `eps`, пропорциональной его
+/// длине. Глубже `ADAPTIVE_MAX_DEPTH` делений — `IntegralCalcError::MaxDepthReached`.
+pub fn adaptive_simpson<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    eps: T,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    adaptive_simpson_with_options(f, lower_bound, upper_bound, eps, &IntegrationOptions::default())
+}
+
+/// То же, что `adaptive_simpson`; из `options` используются только `threads`
+/// и `cancel`. Потоки забирают друг у друга ещё не проверенные отрезки, а
+/// принятые складываются по возрастанию левой границы, поэтому результат
+/// не зависит от числа потоков и порядка их работы.
+pub fn adaptive_simpson_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    eps: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    for value in [lower_bound, upper_bound] {
+        if !value.is_finite() {
+            return Err(IntegralCalcError::NonFiniteBound { value: value.to_f64() });
+        }
+    }
+    if lower_bound > upper_bound {
+        return Err(IntegralCalcError::BoundsReversed { lower: lower_bound.to_f64(), upper: upper_bound.to_f64() });
+    }
+    if !(eps > T::ZERO && eps.is_finite()) {
+        return Err(IntegralCalcError::InvalidTolerance { value: eps.to_f64() });
+    }
+    let threads = options.threads.unwrap_or_else(default_threads);
+    if threads == 0 {
+        return Err(IntegralCalcError::ZeroThreads);
+    }
+    let start = Instant::now();
+    let middle = (lower_bound + upper_bound) / T::from_f64(2.0);
+    let (lower_value, middle_value, upper_value) = (f.eval(lower_bound), f.eval(middle), f.eval(upper_bound));
+    let queue = Queue {
+        segments: Mutex::new(vec![Segment {
+            lower_bound,
+            upper_bound,
+            lower_value,
+            middle_value,
+            upper_value,
+            whole: simpson(lower_bound, upper_bound, lower_value, middle_value, upper_value),
+            eps,
+            depth: 0,
+        }]),
+        available: Condvar::new(),
+        pending: AtomicUsize::new(1),
+        stopped: AtomicBool::new(false),
+        failure: Mutex::new(None),
+    };
+    let cancel = || options.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled());
+    let parts = if threads == 1 {
+        vec![work(&queue, &f, &cancel)]
+    } else {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads).map(|_| scope.spawn(|| work(&queue, &f, &cancel))).collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().map_err(|_| IntegralCalcError::WorkerPanicked))
+                .collect::<Result<Vec<_>, _>>()
+        })?
+    };
+    if let Some(error) = queue.failure.lock().map_err(|_| IntegralCalcError::WorkerPanicked)?.take() {
+        return Err(error);
+    }
+    let evaluations = 3 + parts.iter().map(|(_, evaluations)| evaluations).sum::<u64>();
+    let mut accepted: Vec<Accepted<T>> = parts.into_iter().flat_map(|(accepted, _)| accepted).collect();
+    accepted.sort_by(|left, right| left.0.partial_cmp(&right.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut value = T::ZERO;
+    let mut error = T::ZERO;
+    let mut step = upper_bound - lower_bound;
+    for (index, &(segment_lower_bound, segment_value, segment_error)) in accepted.iter().enumerate() {
+        value += segment_value;
+        error += segment_error;
+        let segment_upper_bound = accepted.get(index + 1).map_or(upper_bound, |next| next.0);
+        step = step.min(segment_upper_bound - segment_lower_bound);
+    }
+    Ok(IntegrationResult {
+        value,
+        step,
+        intervals: accepted.len() as u64,
+        samples: evaluations,
+        threads,
+        elapsed: start.elapsed(),
+        remainder_bound: None,
+        remainder_elapsed: Duration::ZERO,
+        error_estimate: Some(error),
+        singularity: None,
+        workers: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::CancelToken;
+    use crate::integrand::AtanOverQuartic;
+    use crate::integrate::calculate_integral_with_options;
+    use crate::method::Method;
+
+    fn with_threads(threads: usize) -> IntegrationOptions {
+        IntegrationOptions { threads: Some(threads), ..Default::default() }
+    }
+
+    #[test]
+    fn cubic_needs_one_split() {
+        // Формула Симпсона точна для кубов: первая же проверка принимает отрезок
+        let result = adaptive_simpson(|x: f64| x * x * x, 0.0, 2.0, 1e-12).unwrap();
+        assert!((result.value - 4.0).abs() < 1e-14, "{}", result.value);
+        assert_eq!((result.intervals, result.samples, result.step), (1, 5, 2.0));
+    }
+
+    #[test]
+    fn local_feature_is_refined() {
+        let cusp = |x: f64| (x - 0.3).abs().sqrt();
+        let exact = 2.0 / 3.0 * (0.3f64.powf(1.5) + 0.7f64.powf(1.5));
+        let result = adaptive_simpson_with_options(cusp, 0.0, 1.0, 1e-8, &with_threads(1)).unwrap();
+        assert!((result.value - exact).abs() < 1e-7, "{}", result.value - exact);
+        assert!(result.error_estimate.unwrap() < 1e-7);
+        // Каждая проверка отрезка — два новых вычисления f
+        assert_eq!(result.samples, 3 + 2 * (2 * result.intervals - 1));
+        // Самые короткие отрезки — у излома
+        assert!(result.step < 1e-4, "{}", result.step);
+        // Равномерная сетка Симпсона с тем же числом вычислений f заметно хуже
+        let uniform = IntegrationOptions { samples: result.samples / 2 * 2, method: Method::Simpson, ..with_threads(1) };
+        let uniform = calculate_integral_with_options(cusp, 0.0, 1.0, &uniform).unwrap();
+        assert!((uniform.value - exact).abs() > 100.0 * (result.value - exact).abs());
+    }
+
+    #[test]
+    fn threads_do_not_change_result() {
+        let single = adaptive_simpson_with_options(AtanOverQuartic, 0.0, 1.0, 1e-12, &with_threads(1)).unwrap();
+        for threads in [2, 4, 7] {
+            let result = adaptive_simpson_with_options(AtanOverQuartic, 0.0, 1.0, 1e-12, &with_threads(threads)).unwrap();
+            assert_eq!((result.value, result.intervals, result.samples, result.step), (single.value, single.intervals, single.samples, single.step));
+            assert_eq!(result.threads, threads);
+        }
+        assert!((single.value - 0.349446289808078).abs() < 1e-12);
+    }
+
+    #[test]
+    fn jump_reaches_max_depth() {
+        let step = |x: f64| if x < 0.3 { 0.0 } else { 1.0 };
+        for threads in [1, 3] {
+            match adaptive_simpson_with_options(step, 0.0, 1.0, 1e-300, &with_threads(threads)) {
+                Err(IntegralCalcError::MaxDepthReached { lower, upper }) => {
+                    assert!(lower <= 0.3 && 0.3 <= upper, "[{}, {}]", lower, upper);
+                    assert!(upper - lower < 1e-12);
+                }
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn cancelled_run() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let options = IntegrationOptions { cancel: Some(cancel), ..with_threads(2) };
+        assert!(matches!(adaptive_simpson_with_options(f64::sin, 0.0, 1.0, 1e-10, &options), Err(IntegralCalcError::Cancelled)));
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(matches!(adaptive_simpson(f64::sin, 1.0, 0.0, 1e-6), Err(IntegralCalcError::BoundsReversed { .. })));
+        assert!(matches!(adaptive_simpson(f64::sin, 0.0, f64::INFINITY, 1e-6), Err(IntegralCalcError::NonFiniteBound { .. })));
+        for eps in [0.0, -1e-6, f64::NAN] {
+            assert!(matches!(adaptive_simpson(f64::sin, 0.0, 1.0, eps), Err(IntegralCalcError::InvalidTolerance { .. })));
+        }
+        assert!(matches!(adaptive_simpson_with_options(f64::sin, 0.0, 1.0, 1e-6, &with_threads(0)), Err(IntegralCalcError::ZeroThreads)));
+    }
+}
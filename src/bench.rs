@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::{Integrand, IntegrandRef};
+use crate::improper::calculate_improper_integral_with_options;
+use crate::method::Method;
+use crate::options::IntegrationOptions;
+
+/// Число панелей Гаусса–Кронрода для опорного значения сравнения.
+pub const BENCH_REFERENCE_PANELS: u64 = 10_000;
+
+/// Пробный расчёт для подбора числа отсчётов тратит не больше этой доли
+/// бюджета вычислений.
+const PROBE_FRACTION: u64 = 16;
+
+/// Одна строка сравнения методов.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchRow<T: Float = f64> {
+    pub method: Method,
+    /// Число отсчётов, подобранное под бюджет вычислений.
+    pub samples: u64,
+    pub value: T,
+    /// Модуль разности с опорным значением.
+    pub error: T,
+    /// Сколько раз фактически вычислялась f.
+    pub evaluations: u64,
+    pub elapsed: Duration,
+}
+
+/// Итог `bench_methods`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bench<T: Float = f64> {
+    /// Опорное значение: Гаусс–Кронрод на `BENCH_REFERENCE_PANELS` панелях.
+    pub reference: T,
+    /// Оценка погрешности опорного значения.
+    pub reference_error: Option<T>,
+    /// Время расчёта опорного значения.
+    pub reference_elapsed: Duration,
+    /// Строки в порядке `Method::ALL`.
+    pub rows: Vec<BenchRow<T>>,
+}
+
+/// Считает интеграл каждой формулой из `Method::ALL`, подбирая число
+/// отсчётов так, чтобы f вычислялась примерно `evaluations` раз, и сравнивает
+/// итоги с общим опорным значением, посчитанным один раз. Границы могут быть
+/// бесконечными, как в `calculate_improper_integral_with_options`;
+/// `options.method` и `options.samples` не используются.
+pub fn bench_methods<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    evaluations: u64,
+    options: &IntegrationOptions,
+) -> Result<Bench<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    if evaluations == 0 {
+        return Err(IntegralCalcError::ZeroSamples);
+    }
+    let reference_options =
+        IntegrationOptions { samples: BENCH_REFERENCE_PANELS, method: Method::GaussKronrod, ..options.clone() };
+    let reference = calculate_improper_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &reference_options)?;
+    let mut rows = Vec::with_capacity(Method::ALL.len());
+    for &method in Method::ALL {
+        let samples = samples_for_evaluations(&f, lower_bound, upper_bound, method, evaluations, options)?;
+        let options = IntegrationOptions { samples, method, ..options.clone() };
+        let result = calculate_improper_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &options)?;
+        rows.push(BenchRow {
+            method,
+            samples: result.intervals,
+            value: result.value,
+            error: (result.value - reference.value).abs(),
+            evaluations: result.samples,
+            elapsed: result.elapsed,
+        });
+    }
+    Ok(Bench { reference: reference.value, reference_error: reference.error_estimate, reference_elapsed: reference.elapsed, rows })
+}
+
+/// Число отсчётов, при котором формула `method` вычисляет f примерно
+/// `evaluations` раз. Число вычислений на отрезок зависит от формулы (15 у
+/// Гаусса–Кронрода, у tanh-sinh — ещё и от f), поэтому оно измеряется
+/// пробными расчётами на удваиваемой сетке, пока они не потратят
+/// 1/`PROBE_FRACTION` бюджета, и пересчитывается на весь бюджет.
+fn samples_for_evaluations<T, I>(
+    f: &I,
+    lower_bound: T,
+    upper_bound: T,
+    method: Method,
+    evaluations: u64,
+    options: &IntegrationOptions,
+) -> Result<u64, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let granularity = method.rule::<T>().granularity();
+    let mut options = IntegrationOptions { samples: granularity, method, progress: None, ..options.clone() };
+    loop {
+        let used = calculate_improper_integral_with_options(IntegrandRef(f), lower_bound, upper_bound, &options)?.samples.max(1);
+        let doubled = options.samples.saturating_mul(2);
+        if used.saturating_mul(PROBE_FRACTION) >= evaluations || doubled > options.max_samples {
+            let samples = (options.samples as f64 * evaluations as f64 / used as f64).round() as u64;
+            let samples = samples.div_ceil(granularity).max(1).saturating_mul(granularity);
+            return Ok(samples.min(options.max_samples / granularity * granularity));
+        }
+        options.samples = doubled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+
+    fn row(bench: &Bench, method: Method) -> BenchRow {
+        *bench.rows.iter().find(|row| row.method == method).unwrap()
+    }
+
+    #[test]
+    fn row_per_method_within_budget() {
+        let bench = bench_methods(AtanOverQuartic, 0.0, 1.0, 10_000, &IntegrationOptions::default()).unwrap();
+        assert!((bench.reference - 0.349446289808078).abs() < 1e-14);
+        assert_eq!(bench.rows.iter().map(|row| row.method).collect::<Vec<_>>(), Method::ALL);
+        for row in &bench.rows {
+            assert!((5000..=12_000).contains(&row.evaluations), "{:?}", row);
+            assert_eq!(row.error, (row.value - bench.reference).abs());
+        }
+    }
+
+    #[test]
+    fn errors_follow_order_of_accuracy() {
+        let bench = bench_methods(AtanOverQuartic, 0.0, 1.0, 2000, &IntegrationOptions::default()).unwrap();
+        let error = |method| row(&bench, method).error;
+        assert!(error(Method::Midpoint) < error(Method::Left) / 100.0);
+        assert!(error(Method::Trapezoid) < error(Method::Right) / 100.0);
+        assert!(error(Method::Simpson) < error(Method::Trapezoid) / 100.0);
+        assert!(error(Method::GaussKronrod) < error(Method::Midpoint) / 100.0);
+    }
+
+    #[test]
+    fn infinite_interval_and_empty_budget() {
+        let cauchy = |x: f64| 1.0 / (1.0 + x * x);
+        let bench = bench_methods(cauchy, 0.0, f64::INFINITY, 4000, &IntegrationOptions::default()).unwrap();
+        assert!((bench.reference - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        assert!(row(&bench, Method::Midpoint).error < 1e-3);
+        assert_eq!(bench_methods(cauchy, 0.0, 1.0, 0, &IntegrationOptions::default()), Err(IntegralCalcError::ZeroSamples));
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::dual::second_derivative_auto;
+use crate::error::IntegralCalcError;
+use crate::integrand::{AtanOverQuartic, Integrand};
+
+/// Встроенная подынтегральная функция: короткое имя `key` для выбора, формула,
+/// сама функция и, если она известна, её вторая производная.
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinFunction {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub f: fn(f64) -> f64,
+    pub second_derivative: Option<fn(f64) -> f64>,
+}
+
+/// Все встроенные функции; первая — функция по умолчанию, номера при
+/// выборе считаются с единицы.
+pub const BUILTIN_FUNCTIONS: &[BuiltinFunction] = &[
+    BuiltinFunction {
+        key: "atan",
+        description: "atan(x)/(x^4+1)",
+        f: |x| AtanOverQuartic.eval(x),
+        second_derivative: Some(|x| second_derivative_auto(&AtanOverQuartic, x)),
+    },
+    BuiltinFunction {
+        key: "sinc",
+        description: "sin(x)/x",
+        f: |x| if x == 0.0 { 1.0 } else { x.sin() / x },
+        // Формула вычитает близкие величины при малых x: берётся ряд Тейлора
+        second_derivative: Some(|x| {
+            if x.abs() < 1e-2 {
+                -1.0 / 3.0 + x * x / 10.0
+            } else {
+                -x.sin() / x - 2.0 * x.cos() / (x * x) + 2.0 * x.sin() / x.powi(3)
+            }
+        }),
+    },
+    BuiltinFunction {
+        key: "gauss",
+        description: "exp(-x^2)",
+        f: |x| (-x * x).exp(),
+        second_derivative: Some(|x| (4.0 * x * x - 2.0) * (-x * x).exp()),
+    },
+    BuiltinFunction {
+        key: "cauchy",
+        description: "1/(1+x^2)",
+        f: |x| 1.0 / (1.0 + x * x),
+        second_derivative: Some(|x| (6.0 * x * x - 2.0) / (1.0 + x * x).powi(3)),
+    },
+    BuiltinFunction {
+        key: "xlnx",
+        description: "x*ln(x)",
+        f: |x| if x == 0.0 { 0.0 } else { x * x.ln() },
+        // f″ = 1/x не ограничена у нуля, оценка остаточного члена по ней бесполезна
+        second_derivative: None,
+    },
+];
+
+impl BuiltinFunction {
+    /// Функция по номеру (с единицы) или имени.
+    pub fn find(key: &str) -> Option<&'static BuiltinFunction> {
+        let key = key.trim();
+        if let Ok(number) = key.parse::<usize>() {
+            return number.checked_sub(1).and_then(|index| BUILTIN_FUNCTIONS.get(index));
+        }
+        let name = key.to_ascii_lowercase();
+        BUILTIN_FUNCTIONS.iter().find(|function| function.key == name)
+    }
+}
+
+impl Default for BuiltinFunction {
+    fn default() -> Self {
+        BUILTIN_FUNCTIONS[0]
+    }
+}
+
+impl Integrand<f64> for BuiltinFunction {
+    fn eval(&self, x: f64) -> f64 {
+        (self.f)(x)
+    }
+
+    fn second_derivative(&self, x: f64) -> Option<f64> {
+        self.second_derivative.map(|second_derivative| second_derivative(x))
+    }
+
+    fn name(&self) -> &str {
+        self.description
+    }
+}
+
+impl Display for BuiltinFunction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} — {}", self.key, self.description)
+    }
+}
+
+impl FromStr for BuiltinFunction {
+    type Err = IntegralCalcError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BuiltinFunction::find(s)
+            .copied()
+            .ok_or_else(|| IntegralCalcError::UnknownFunction { name: s.trim().to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::numeric_second_derivative;
+    use crate::integrate::calculate_integral;
+
+    #[test]
+    fn integrals_match_analytic_values() {
+        let pi = std::f64::consts::PI;
+        let cases = [
+            ("atan", 0.0, 1.0, 0.349446289808078),
+            // Si(π)
+            ("sinc", 0.0, pi, 1.851937051982466),
+            // √π/2·erf(1)
+            ("gauss", 0.0, 1.0, 0.746824132812427),
+            ("cauchy", -1.0, 1.0, pi / 2.0),
+            ("xlnx", 0.0, 1.0, -0.25),
+        ];
+        assert_eq!(cases.len(), BUILTIN_FUNCTIONS.len());
+        for (key, lower, upper, exact) in cases {
+            let function = *BuiltinFunction::find(key).unwrap();
+            let result = calculate_integral(function, lower, upper, 100_000).unwrap();
+            assert!((result.value - exact).abs() < 1e-8, "{}: {} != {}", key, result.value, exact);
+        }
+    }
+
+    #[test]
+    fn second_derivatives_match_differences() {
+        for function in BUILTIN_FUNCTIONS.iter().filter(|function| function.second_derivative.is_some()) {
+            for x in [-1.5, -0.3, 0.0, 0.005, 0.2, 1.0, 2.5] {
+                let analytic = function.second_derivative(x).unwrap();
+                let numeric = numeric_second_derivative(function, x);
+                assert!((analytic - numeric).abs() < 1e-5, "{} at {}: {} != {}", function.key, x, analytic, numeric);
+            }
+        }
+    }
+
+    #[test]
+    fn found_by_number_or_name() {
+        assert_eq!(BuiltinFunction::find("1").unwrap().key, "atan");
+        assert_eq!(BuiltinFunction::find(" Gauss ").unwrap().key, "gauss");
+        assert!(BuiltinFunction::find("0").is_none());
+        assert_eq!(
+            "bessel".parse::<BuiltinFunction>().unwrap_err(),
+            IntegralCalcError::UnknownFunction { name: "bessel".to_string() }
+        );
+        assert_eq!(BuiltinFunction::default().name(), "atan(x)/(x^4+1)");
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Флаг отмены, который рабочие потоки проверяют во время расчёта.
+/// Клоны токена разделяют один и тот же флаг.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+}
+
+/// Через сколько вычислений функции рабочий поток проверяет флаг отмены.
+pub(crate) const CANCEL_CHECK_INTERVAL: u64 = 4096;
+
+#[cfg(unix)]
+mod interrupt {
+    use std::os::raw::c_int;
+    use std::sync::OnceLock;
+
+    use super::CancelToken;
+
+    const SIGINT: c_int = 2;
+
+    static INTERRUPT_TOKEN: OnceLock<CancelToken> = OnceLock::new();
+
+    /// Код выхода при повторном Ctrl+C, как у оболочки: 128 + SIGINT.
+    const EXIT_INTERRUPTED_TWICE: c_int = 128 + SIGINT;
+
+    extern "C" {
+        fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+        fn _exit(status: c_int) -> !;
+    }
+
+    extern "C" fn handle_interrupt(_: c_int) {
+        if let Some(token) = INTERRUPT_TOKEN.get() {
+            if token.is_cancelled() {
+                // SAFETY: _exit допустим в обработчике сигнала
+                unsafe { _exit(EXIT_INTERRUPTED_TWICE) }
+            }
+            token.cancel();
+        }
+    }
+
+    pub fn cancel_on_interrupt(token: &CancelToken) {
+        if INTERRUPT_TOKEN.set(token.clone()).is_ok() {
+            // SAFETY: обработчик только выставляет атомарный флаг или
+            // немедленно завершает процесс
+            unsafe {
+                signal(SIGINT, handle_interrupt);
+            }
+        }
+    }
+}
+
+/// Отменяет `token` по Ctrl+C вместо завершения процесса; повторный Ctrl+C,
+/// пока расчёт останавливается, завершает процесс сразу. Действует только
+/// для первого переданного токена.
+#[cfg(unix)]
+pub use interrupt::cancel_on_interrupt;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+
+    use super::*;
+    use crate::error::IntegralCalcError;
+    use crate::integrate::calculate_integral_with_options;
+    use crate::options::{ExecutionMode, IntegrationOptions};
+
+    #[test]
+    fn cancelled_run_stops_promptly() {
+        let token = CancelToken::new();
+        let evaluations = AtomicU64::new(0);
+        let f = |x: f64| {
+            if evaluations.fetch_add(1, Ordering::Relaxed) == 100_000 {
+                token.cancel();
+            }
+            x.sin()
+        };
+        for execution in [ExecutionMode::Sequential, ExecutionMode::Threaded] {
+            evaluations.store(0, Ordering::Relaxed);
+            token.cancelled.store(false, Ordering::Relaxed);
+            let options = IntegrationOptions {
+                samples: 1_000_000_000,
+                execution,
+                threads: Some(4),
+                cancel: Some(token.clone()),
+                ..Default::default()
+            };
+            let error = calculate_integral_with_options(f, 0.0, 1.0, &options).unwrap_err();
+            let IntegralCalcError::Interrupted { estimate, completed } = error else {
+                panic!("{:?}", error);
+            };
+            assert!(completed < 0.001, "{}", completed);
+            assert!(estimate.is_finite());
+            // Каждый поток досчитывает не больше одной порции после отмены
+            assert!(evaluations.load(Ordering::Relaxed) < 100_000 + 4 * 2 * CANCEL_CHECK_INTERVAL);
+        }
+    }
+}
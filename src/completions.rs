@@ -0,0 +1,207 @@
+use super::flags::{Flag, FlagValue, FLAGS, SHELLS, SUBCOMMANDS};
+
+/// Имя программы, для которой пишутся сценарии.
+const PROGRAM: &str = "kryl_04";
+
+/// Оболочка подкоманды `completions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl std::str::FromStr for Shell {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Сценарий дополнения для `shell` по таблице `FLAGS`.
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash(),
+        Shell::Zsh => zsh(),
+        Shell::Fish => fish(),
+    }
+}
+
+/// Флаги со значением `value` через `|` для `case` в bash.
+fn bash_pattern(filter: impl Fn(FlagValue) -> bool) -> Option<String> {
+    let names: Vec<&str> = FLAGS.iter().filter(|flag| filter(flag.value)).flat_map(|flag| flag.names.iter().copied()).collect();
+    (!names.is_empty()).then(|| names.join("|"))
+}
+
+fn bash() -> String {
+    let mut cases = Vec::new();
+    for flag in FLAGS.iter().filter(|flag| !flag.value.choices().is_empty()) {
+        cases.push(format!("        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+                           flag.names.join("|"), flag.value.choices().join(" ")));
+    }
+    if let Some(paths) = bash_pattern(|value| value == FlagValue::Path) {
+        cases.push(format!("        {}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;", paths));
+    }
+    if let Some(values) = bash_pattern(|value| value == FlagValue::Value) {
+        cases.push(format!("        {}) return ;;", values));
+    }
+    let flags: Vec<&str> = FLAGS.iter().flat_map(|flag| flag.names.iter().copied()).collect();
+    format!(
+        "_{program}() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    case \"$prev\" in
+{cases}
+    esac
+    if [[ $COMP_CWORD -eq 2 && ${{COMP_WORDS[1]}} == completions ]]; then
+        COMPREPLY=($(compgen -W \"{shells}\" -- \"$cur\"))
+        return
+    fi
+    local words=\"{flags}\"
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        words=\"{subcommands} $words\"
+    fi
+    COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))
+}}
+complete -F _{program} {program}
+",
+        program = PROGRAM,
+        cases = cases.join("\n"),
+        shells = SHELLS.join(" "),
+        flags = flags.join(" "),
+        subcommands = SUBCOMMANDS.join(" "),
+    )
+}
+
+/// Описание флага для `_arguments` в zsh.
+fn zsh_spec(name: &str, flag: &Flag) -> String {
+    match flag.value {
+        FlagValue::Switch => format!("'{}'", name),
+        FlagValue::Value => format!("'{}=:value: '", name),
+        FlagValue::Attached => format!("'{}=-::value: '", name),
+        FlagValue::Path => format!("'{}=:file:_files'", name),
+        value => format!("'{}=:value:({})'", name, value.choices().join(" ")),
+    }
+}
+
+fn zsh() -> String {
+    let specs: Vec<String> = FLAGS.iter()
+        .flat_map(|flag| flag.names.iter().map(move |name| format!("        {} \\", zsh_spec(name, flag))))
+        .collect();
+    format!(
+        "#compdef {program}
+
+_{program}() {{
+    if (( CURRENT == 3 )) && [[ $words[2] == completions ]]; then
+        compadd {shells}
+        return
+    fi
+    _arguments -s \\
+{specs}
+        '1::command:({subcommands})'
+}}
+
+if [ \"$funcstack[1]\" = \"_{program}\" ]; then
+    _{program} \"$@\"
+else
+    compdef _{program} {program}
+fi
+",
+        program = PROGRAM,
+        shells = SHELLS.join(" "),
+        specs = specs.join("\n"),
+        subcommands = SUBCOMMANDS.join(" "),
+    )
+}
+
+fn fish() -> String {
+    let mut lines = vec![
+        format!("complete -c {} -f", PROGRAM),
+        format!("complete -c {} -n __fish_use_subcommand -a '{}'", PROGRAM, SUBCOMMANDS.join(" ")),
+        format!("complete -c {} -n '__fish_seen_subcommand_from completions' -a '{}'", PROGRAM, SHELLS.join(" ")),
+    ];
+    for flag in FLAGS {
+        // `-q` — короткий флаг, `-vv` — «старый» флаг из нескольких букв
+        let names: Vec<String> = flag.names.iter().map(|name| match name.strip_prefix("--") {
+            Some(long) => format!("-l {}", long),
+            None if name.len() == 2 => format!("-s {}", &name[1..]),
+            None => format!("-o {}", &name[1..]),
+        }).collect();
+        let value = match flag.value {
+            FlagValue::Switch | FlagValue::Attached => String::new(),
+            FlagValue::Value => " -x".to_string(),
+            FlagValue::Path => " -r -F".to_string(),
+            value => format!(" -x -a '{}'", value.choices().join(" ")),
+        };
+        lines.push(format!("complete -c {} {}{}", PROGRAM, names.join(" "), value));
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Длинные флаги, которые упоминаются строковыми литералами в `source`.
+    fn literal_flags(source: &str) -> Vec<String> {
+        source.split("\"--").skip(1).filter_map(|rest| {
+            let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))?;
+            (end > 0 && rest[end..].starts_with('"')).then(|| format!("--{}", &rest[..end]))
+        }).collect()
+    }
+
+    #[test]
+    fn bash_lists_every_long_flag() {
+        let script = script(Shell::Bash);
+        for flag in FLAGS {
+            for name in flag.names.iter().filter(|name| name.starts_with("--")) {
+                assert!(script.contains(&format!(" {}", name)) || script.contains(&format!("|{}", name)), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn bash_knows_flags_used_by_parser() {
+        let script = script(Shell::Bash);
+        let flags = literal_flags(include_str!("main.rs"));
+        assert!(flags.iter().any(|name| name == "--lower"));
+        for name in flags {
+            assert!(script.contains(&name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn values_are_completed() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = script(shell);
+            for value in ["midpoint", "gk15", "tanh-sinh", "json", "csv", "scientific", "bench", "completions"] {
+                assert!(script.contains(value), "{:?}: {}", shell, value);
+            }
+        }
+    }
+
+    #[test]
+    fn zsh_and_fish_list_every_flag() {
+        let zsh = script(Shell::Zsh);
+        let fish = script(Shell::Fish);
+        for flag in FLAGS {
+            for name in flag.names {
+                assert!(zsh.contains(&format!("'{}", name)), "zsh: {}", name);
+                let fish_name = name.strip_prefix("--").map_or_else(|| name[1..].to_string(), str::to_string);
+                assert!(fish.contains(&format!(" {}", fish_name)), "fish: {}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn shells_parse() {
+        assert_eq!("Bash".parse(), Ok(Shell::Bash));
+        assert_eq!(" fish ".parse(), Ok(Shell::Fish));
+        assert_eq!("tcsh".parse::<Shell>(), Err(()));
+    }
+}
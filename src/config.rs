@@ -0,0 +1,400 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use kryl_04::{tr, Message};
+
+/// Тип значения в файле настроек.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+}
+
+impl Kind {
+    fn message(self) -> Message {
+        match self {
+            Kind::String => Message::TypeString,
+            Kind::Integer => Message::TypeInteger,
+            Kind::Float => Message::TypeFloat,
+            Kind::Boolean => Message::TypeBoolean,
+        }
+    }
+}
+
+/// Значение ключа из файла настроек.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl Value {
+    fn kind(&self) -> Kind {
+        match self {
+            Value::String(_) => Kind::String,
+            Value::Integer(_) => Kind::Integer,
+            Value::Float(_) => Kind::Float,
+            Value::Boolean(_) => Kind::Boolean,
+        }
+    }
+
+    /// Значение в том виде, в каком оно было бы задано флагом.
+    pub fn to_flag(&self) -> String {
+        match self {
+            Value::String(value) => value.clone(),
+            Value::Integer(value) => value.to_string(),
+            Value::Float(value) => value.to_string(),
+            Value::Boolean(value) => value.to_string(),
+        }
+    }
+}
+
+/// Ключ файла настроек: имя флага без `--`, допустимые типы значения,
+/// переменная окружения, которая его перекрывает, и значение по умолчанию
+/// (для `--print-config`).
+#[derive(Debug)]
+pub struct Key {
+    pub name: &'static str,
+    pub expected: &'static [Kind],
+    pub env: Option<&'static str>,
+    pub default: Option<&'static str>,
+}
+
+const NUMBER: &[Kind] = &[Kind::Integer, Kind::Float, Kind::String];
+const COUNT: &[Kind] = &[Kind::Integer, Kind::String];
+const TEXT: &[Kind] = &[Kind::String];
+const INTEGER: &[Kind] = &[Kind::Integer];
+const SWITCH: &[Kind] = &[Kind::Boolean];
+
+const fn key(name: &'static str, expected: &'static [Kind], default: Option<&'static str>) -> Key {
+    Key { name, expected, env: None, default }
+}
+
+/// Все ключи файла настроек в порядке вывода `--print-config`.
+pub const KEYS: &[Key] = &[
+    key("lower", NUMBER, None),
+    key("upper", NUMBER, None),
+    key("samples", COUNT, None),
+    key("tolerance", &[Kind::Float, Kind::String], None),
+    key("evals", COUNT, None),
+    key("start", COUNT, Some("16")),
+    key("rows", INTEGER, Some("12")),
+    Key { name: "threads", expected: INTEGER, env: Some("KRYL_THREADS"), default: Some("0") },
+    key("method", TEXT, Some("midpoint")),
+    key("function", &[Kind::String, Kind::Integer], Some("atan")),
+    key("expr", TEXT, None),
+    key("with-reference", &[Kind::Boolean, Kind::Integer, Kind::String], Some("false")),
+    // Устаревшие, см. `with-reference`
+    key("reference", SWITCH, None),
+    key("reference-samples", COUNT, None),
+    key("negate-reversed", SWITCH, Some("false")),
+    key("propagate-nan", SWITCH, Some("false")),
+    key("seed", INTEGER, None),
+    key("compare", SWITCH, Some("false")),
+    key("batch", SWITCH, Some("false")),
+    key("validate", SWITCH, Some("false")),
+    key("repl", SWITCH, Some("false")),
+    key("history-size", INTEGER, Some("100")),
+    key("input", TEXT, None),
+    key("output", TEXT, None),
+    key("format", TEXT, Some("text")),
+    key("sort", TEXT, Some("method")),
+    key("plot", SWITCH, Some("false")),
+    key("plot-height", INTEGER, Some("12")),
+    key("dump-plot", TEXT, None),
+    key("dump-points", COUNT, Some("1000")),
+    key("2d", SWITCH, Some("false")),
+    key("convergence", SWITCH, Some("false")),
+    key("table", TEXT, None),
+    key("precision", INTEGER, Some("12")),
+    key("format-number", TEXT, Some("auto")),
+    key("quiet", SWITCH, Some("false")),
+    key("no-warnings", SWITCH, Some("false")),
+    key("interactive", SWITCH, None),
+    Key { name: "no-color", expected: SWITCH, env: Some("NO_COLOR"), default: Some("false") },
+    key("verbose", &[Kind::Integer, Kind::Boolean], Some("0")),
+    Key { name: "lang", expected: TEXT, env: Some("LANG"), default: Some("ru") },
+];
+
+/// Замечание к файлу настроек; `line` — номер строки с единицы.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// Файл не удалось прочитать.
+    Read { reason: String },
+    /// Строка не разбирается как `ключ = значение`.
+    Syntax { line: usize },
+    /// Неизвестный ключ: только предупреждение.
+    UnknownKey { line: usize, key: String },
+    WrongType { line: usize, key: &'static str, expected: &'static [Kind] },
+}
+
+impl Problem {
+    /// Предупреждение, а не ошибка: с ним программа продолжает работу.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Problem::UnknownKey { .. })
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Problem::Read { reason } => write!(f, "{}: {}", tr(Message::ConfigReadError), reason),
+            Problem::Syntax { line } => write!(f, "{}: {}", line, tr(Message::ConfigSyntax)),
+            Problem::UnknownKey { line, key } => write!(f, "{}: {} \"{}\"", line, tr(Message::ConfigUnknownKey), key),
+            Problem::WrongType { line, key, expected } => {
+                let expected: Vec<&str> = expected.iter().map(|kind| tr(kind.message())).collect();
+                write!(f, "{}: {} \"{}\": {} {}", line, tr(Message::ConfigWrongType), key,
+                       tr(Message::ConfigExpected), expected.join(&format!(" {} ", tr(Message::Or))))
+            }
+        }
+    }
+}
+
+/// Прочитанный файл настроек.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Путь к файлу; `None`, если файла нет.
+    pub path: Option<PathBuf>,
+    values: Vec<(&'static str, Value)>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Путь по умолчанию: `$XDG_CONFIG_HOME/kryl_04/config.toml`, иначе
+/// `~/.config/kryl_04/config.toml`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|value| !value.is_empty()) {
+        Some(base) => PathBuf::from(base),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("kryl_04").join("config.toml"))
+}
+
+/// Читает файл настроек `explicit` (`--config`) или файл по умолчанию, если
+/// он есть, и возвращает замечания к нему. Действует только первый вызов.
+pub fn load(explicit: Option<&str>) -> Vec<Problem> {
+    let path = match explicit {
+        Some(path) => PathBuf::from(path),
+        None => match default_path().filter(|path| path.exists()) {
+            Some(path) => path,
+            None => {
+                let _ = CONFIG.set(Config::default());
+                return Vec::new();
+            }
+        },
+    };
+    let (config, problems) = match std::fs::read_to_string(&path) {
+        Ok(text) => {
+            let (values, problems) = parse(&text);
+            (Config { path: Some(path), values }, problems)
+        }
+        Err(e) => (Config::default(), vec![Problem::Read { reason: format!("{}: {}", path.display(), e) }]),
+    };
+    let _ = CONFIG.set(config);
+    problems
+}
+
+pub fn current() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Значение ключа `name` из файла настроек.
+pub fn get(name: &str) -> Option<&'static Value> {
+    current().values.iter().rev().find(|(key, _)| *key == name).map(|(_, value)| value)
+}
+
+/// Разбор подмножества TOML: строки `ключ = значение` со значениями-строками
+/// в двойных или одинарных кавычках, целыми и дробными числами и `true` или
+/// `false`; `#` начинает комментарий. Таблиц и массивов нет.
+fn parse(text: &str) -> (Vec<(&'static str, Value)>, Vec<Problem>) {
+    let mut values = Vec::new();
+    let mut problems = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let parsed = line.split_once('=').and_then(|(name, value)| {
+            let name = name.trim();
+            let name = name.strip_prefix('"').and_then(|name| name.strip_suffix('"')).unwrap_or(name);
+            let bare = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            bare.then_some(name).zip(parse_value(value.trim()))
+        });
+        let Some((name, value)) = parsed else {
+            problems.push(Problem::Syntax { line: line_number });
+            continue;
+        };
+        match KEYS.iter().find(|key| key.name == name) {
+            None => problems.push(Problem::UnknownKey { line: line_number, key: name.to_string() }),
+            Some(key) if !key.expected.contains(&value.kind()) => {
+                problems.push(Problem::WrongType { line: line_number, key: key.name, expected: key.expected })
+            }
+            Some(key) => values.push((key.name, value)),
+        }
+    }
+    (values, problems)
+}
+
+/// Строка без комментария; `#` внутри кавычек комментария не начинает.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(open), c) if c == open && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+fn parse_value(text: &str) -> Option<Value> {
+    if let Some(literal) = text.strip_prefix('\'') {
+        return literal.strip_suffix('\'').filter(|literal| !literal.contains('\'')).map(|literal| Value::String(literal.to_string()));
+    }
+    if let Some(basic) = text.strip_prefix('"') {
+        return unescape(basic.strip_suffix('"')?).map(Value::String);
+    }
+    match text {
+        "true" => return Some(Value::Boolean(true)),
+        "false" => return Some(Value::Boolean(false)),
+        _ => {}
+    }
+    // Разделители `_` допустимы только между цифрами
+    if text.starts_with('_') || text.ends_with('_') || text.contains("__") {
+        return None;
+    }
+    let number: String = text.chars().filter(|&c| c != '_').collect();
+    if let Ok(integer) = number.parse::<i64>() {
+        return Some(Value::Integer(integer));
+    }
+    let digits = number.trim_start_matches(['+', '-']);
+    let float = matches!(digits, "inf" | "nan")
+        || (digits.starts_with(|c: char| c.is_ascii_digit()) && digits.chars().all(|c| c.is_ascii_digit() || "._eE+-".contains(c)));
+    float.then(|| number.parse().ok().map(Value::Float)).flatten()
+}
+
+/// Строка в двойных кавычках без кавычек по краям: `\"`, `\\`, `\n`, `\t`.
+fn unescape(text: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return None,
+            '\\' => result.push(match chars.next()? {
+                '"' => '"',
+                '\\' => '\\',
+                'n' => '\n',
+                't' => '\t',
+                _ => return None,
+            }),
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+/// Значение `value` ключа `key` в записи TOML: числа и логические значения
+/// как есть, остальное — строкой в кавычках.
+pub fn toml_value(key: &Key, value: &str) -> String {
+    let raw = (key.expected.contains(&Kind::Boolean) && matches!(value, "true" | "false"))
+        || (key.expected.contains(&Kind::Integer) && value.parse::<i64>().is_ok())
+        || (key.expected.contains(&Kind::Float) && value.parse::<f64>().is_ok_and(f64::is_finite));
+    if raw {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> &'static Key {
+        KEYS.iter().find(|key| key.name == name).unwrap()
+    }
+
+    #[test]
+    fn values_and_comments() {
+        let (values, problems) = parse("# настройки\nthreads = 4 # поток\nmethod = \"simp#son\"\n\"lang\" = 'en'\n\nlower = -1.5\nquiet = true\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(values, [
+            ("threads", Value::Integer(4)),
+            ("method", Value::String("simp#son".to_string())),
+            ("lang", Value::String("en".to_string())),
+            ("lower", Value::Float(-1.5)),
+            ("quiet", Value::Boolean(true)),
+        ]);
+    }
+
+    #[test]
+    fn problems_name_line_and_key() {
+        let (values, problems) = parse("threads = 2\nbogus = 1\nprecision = \"x\"\nmethod midpoint\nsamples = 1__0\n");
+        assert_eq!(values, [("threads", Value::Integer(2))]);
+        assert_eq!(problems, [
+            Problem::UnknownKey { line: 2, key: "bogus".to_string() },
+            Problem::WrongType { line: 3, key: "precision", expected: key("precision").expected },
+            Problem::Syntax { line: 4 },
+            Problem::Syntax { line: 5 },
+        ]);
+        assert!(problems[0].is_warning());
+        assert!(problems[1..].iter().all(|problem| !problem.is_warning()));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_value("1_000"), Some(Value::Integer(1000)));
+        assert_eq!(parse_value("+7"), Some(Value::Integer(7)));
+        assert_eq!(parse_value("1e-3"), Some(Value::Float(1e-3)));
+        assert_eq!(parse_value("-inf"), Some(Value::Float(f64::NEG_INFINITY)));
+        for text in ["_1", "1_", "1__0", "e5", "pi", "1.2.3", ""] {
+            assert_eq!(parse_value(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(unescape(r#"a\"b\\c\n\t"#).as_deref(), Some("a\"b\\c\n\t"));
+        assert_eq!(unescape(r"\x"), None);
+        assert_eq!(unescape("a\"b"), None);
+        assert_eq!(parse_value(r"'C:\path'"), Some(Value::String(r"C:\path".to_string())));
+        assert_eq!(parse_value("'a'b'"), None);
+        assert_eq!(strip_comment(r#"expr = "x \" # y" # комментарий"#), r#"expr = "x \" # y" "#);
+    }
+
+    #[test]
+    fn toml_values_round_trip() {
+        assert_eq!(toml_value(key("threads"), "4"), "4");
+        assert_eq!(toml_value(key("quiet"), "true"), "true");
+        assert_eq!(toml_value(key("lower"), "-1.5"), "-1.5");
+        assert_eq!(toml_value(key("lower"), "inf"), "\"inf\"");
+        assert_eq!(toml_value(key("method"), "4"), "\"4\"");
+        let quoted = toml_value(key("expr"), "\"x\"\\\n");
+        assert_eq!(quoted, r#""\"x\"\\\n""#);
+        let (values, problems) = parse(&format!("expr = {}", quoted));
+        assert!(problems.is_empty());
+        assert_eq!(values, [("expr", Value::String("\"x\"\\\n".to_string()))]);
+    }
+
+    #[test]
+    fn last_value_wins() {
+        let (values, _) = parse("threads = 2\nthreads = 3\n");
+        let last = values.iter().rev().find(|(key, _)| *key == "threads");
+        assert_eq!(last, Some(&("threads", Value::Integer(3))));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::{Integrand, IntegrandRef};
+use crate::integrate::calculate_integral_with_options;
+use crate::options::IntegrationOptions;
+
+/// Одна строка исследования сходимости.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceRow<T: Float = f64> {
+    pub samples: u64,
+    pub value: T,
+    /// Модуль разности с предыдущей строкой; у первой строки его нет.
+    pub delta: Option<T>,
+    /// Верхняя граница остаточного члена, если известна f″.
+    pub remainder_bound: Option<T>,
+}
+
+/// Строка классической таблицы сходимости, см. `ConvergenceStudy::table`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceTableRow<T: Float = f64> {
+    pub samples: u64,
+    pub value: T,
+    /// Δₙ = |Iₙ − I₂ₙ|; у последней строки её нет.
+    pub difference: Option<T>,
+    /// Наблюдаемый порядок p = log₂(Δₙ/Δ₂ₙ); нужны две следующие строки и
+    /// ненулевые разности.
+    pub order: Option<T>,
+    pub remainder_bound: Option<T>,
+}
+
+/// Итог `convergence_study`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvergenceStudy<T: Float = f64> {
+    pub rows: Vec<ConvergenceRow<T>>,
+    /// Порядок сходимости p из δ ≈ C·n^(−p): наклон прямой, проведённой
+    /// методом наименьших квадратов через точки (ln n, ln δ). Нужны хотя бы
+    /// две ненулевые разности.
+    pub order: Option<T>,
+    /// Последняя разность оказалась меньше `eps`.
+    pub converged: bool,
+}
+
+/// Считает интеграл при `start_samples`, вдвое большем, вчетверо большем и
+/// т. д. числе отсчётов, пока разность соседних оценок не станет меньше
+/// `eps` или не будет сделано `max_doublings` удвоений.
+pub fn convergence_study<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    start_samples: u64,
+    max_doublings: u32,
+    eps: T,
+) -> Result<ConvergenceStudy<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples: start_samples, ..Default::default() };
+    convergence_study_with_options(f, lower_bound, upper_bound, max_doublings, eps, &options)
+}
+
+/// То же, что `convergence_study`, с заданными параметрами расчёта;
+/// `options.samples` — начальное число отсчётов. Удвоения прекращаются и
+/// по достижении `options.max_samples`; строки с числом отсчётов не меньше
+/// `options.async_threshold` считаются в нескольких потоках.
+pub fn convergence_study_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    max_doublings: u32,
+    eps: T,
+    options: &IntegrationOptions,
+) -> Result<ConvergenceStudy<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    if !(eps > T::ZERO && eps.is_finite()) {
+        return Err(IntegralCalcError::InvalidTolerance { value: eps.to_f64() });
+    }
+    study(f, lower_bound, upper_bound, max_doublings, Some(eps), options)
+}
+
+/// Таблица сходимости из `rows` строк: число отсчётов удваивается, начиная с
+/// `options.samples`, без остановки по точности (но не дальше
+/// `options.max_samples`). Строки выводятся методом `ConvergenceStudy::table`.
+pub fn convergence_table_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    rows: u32,
+    options: &IntegrationOptions,
+) -> Result<ConvergenceStudy<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    study(f, lower_bound, upper_bound, rows.saturating_sub(1), None, options)
+}
+
+fn study<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    max_doublings: u32,
+    eps: Option<T>,
+    options: &IntegrationOptions,
+) -> Result<ConvergenceStudy<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let mut options = options.clone();
+    let first = calculate_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &options)?;
+    let mut rows = vec![ConvergenceRow {
+        samples: options.samples,
+        value: first.value,
+        delta: None,
+        remainder_bound: first.remainder_bound,
+    }];
+    let mut converged = false;
+    for _ in 0..max_doublings {
+        let Some(samples) = options.samples.checked_mul(2).filter(|&samples| samples <= options.max_samples) else {
+            break;
+        };
+        options.samples = samples;
+        let result = calculate_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &options)?;
+        let delta = (result.value - rows[rows.len() - 1].value).abs();
+        rows.push(ConvergenceRow { samples, value: result.value, delta: Some(delta), remainder_bound: result.remainder_bound });
+        if eps.is_some_and(|eps| delta < eps) {
+            converged = true;
+            break;
+        }
+    }
+    let order = convergence_order(&rows);
+    Ok(ConvergenceStudy { rows, order, converged })
+}
+
+impl<T: Float> ConvergenceStudy<T> {
+    /// Строки в виде классической таблицы: у строки n — разность со строкой
+    /// 2n и порядок по двум следующим разностям.
+    pub fn table(&self) -> Vec<ConvergenceTableRow<T>> {
+        let difference = |index: usize| self.rows.get(index + 1).and_then(|row| row.delta);
+        self.rows.iter().enumerate().map(|(index, row)| ConvergenceTableRow {
+            samples: row.samples,
+            value: row.value,
+            difference: difference(index),
+            order: difference(index)
+                .zip(difference(index + 1))
+                .filter(|&(current, next)| current > T::ZERO && next > T::ZERO)
+                .map(|(current, next)| (current / next).ln() / T::from_f64(2.0).ln()),
+            remainder_bound: row.remainder_bound,
+        }).collect()
+    }
+}
+
+fn convergence_order<T: Float>(rows: &[ConvergenceRow<T>]) -> Option<T> {
+    let points: Vec<(T, T)> = rows
+        .iter()
+        .filter_map(|row| {
+            let delta = row.delta.filter(|&delta| delta > T::ZERO)?;
+            Some((T::from_u64(row.samples).ln(), delta.ln()))
+        })
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+    let count = T::from_u64(points.len() as u64);
+    let mean_x = points.iter().fold(T::ZERO, |sum, &(x, _)| sum + x) / count;
+    let mean_y = points.iter().fold(T::ZERO, |sum, &(_, y)| sum + y) / count;
+    let (covariance, variance) = points.iter().fold((T::ZERO, T::ZERO), |(covariance, variance), &(x, y)| {
+        (covariance + (x - mean_x) * (y - mean_y), variance + (x - mean_x) * (x - mean_x))
+    });
+    Some(-covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+    use crate::method::Method;
+
+    #[test]
+    fn midpoint_order_is_two() {
+        let options = IntegrationOptions { samples: 16, ..Default::default() };
+        let study = convergence_table_with_options(AtanOverQuartic, 0.0, 1.0, 12, &options).unwrap();
+        assert_eq!(study.rows.len(), 12);
+        assert_eq!(study.rows[11].samples, 16 << 11);
+        assert!(!study.converged);
+        assert!((study.order.unwrap() - 2.0).abs() < 0.2);
+        let table = study.table();
+        assert_eq!(table[11].difference, None);
+        assert_eq!(table[10].order, None);
+        for row in &table[6..10] {
+            assert!((row.order.unwrap() - 2.0).abs() < 0.2, "{:?}", row);
+        }
+        // Граница остаточного члена не меньше фактической разности
+        for row in &table[..11] {
+            assert!(row.difference.unwrap() <= row.remainder_bound.unwrap(), "{:?}", row);
+        }
+    }
+
+    #[test]
+    fn simpson_order_is_four() {
+        let options = IntegrationOptions { samples: 8, method: Method::Simpson, ..Default::default() };
+        let study = convergence_table_with_options(AtanOverQuartic, 0.0, 1.0, 5, &options).unwrap();
+        for row in &study.table()[1..3] {
+            assert!((row.order.unwrap() - 4.0).abs() < 0.3, "{:?}", row);
+        }
+    }
+
+    #[test]
+    fn study_stops_at_tolerance() {
+        let study = convergence_study(AtanOverQuartic, 0.0, 1.0, 16, 30, 1e-8).unwrap();
+        assert!(study.converged);
+        let last = study.rows.last().unwrap();
+        assert!(last.delta.unwrap() < 1e-8);
+        assert!(study.rows[study.rows.len() - 2].delta.unwrap() >= 1e-8);
+        assert_eq!(study.rows[0].delta, None);
+        assert!(matches!(
+            convergence_study(AtanOverQuartic, 0.0, 1.0, 16, 30, 0.0),
+            Err(IntegralCalcError::InvalidTolerance { .. })
+        ));
+    }
+
+    #[test]
+    fn doublings_stop_at_max_samples() {
+        let options = IntegrationOptions { samples: 16, max_samples: 100, async_threshold: 100, ..Default::default() };
+        let study = convergence_table_with_options(AtanOverQuartic, 0.0, 1.0, 12, &options).unwrap();
+        assert_eq!(study.rows.iter().map(|row| row.samples).collect::<Vec<_>>(), [16, 32, 64]);
+    }
+
+    #[test]
+    fn threaded_rows_match_serial() {
+        let serial = IntegrationOptions { samples: 1000, async_threshold: IntegrationOptions::default().max_samples, ..Default::default() };
+        let threaded = IntegrationOptions { async_threshold: 0, threads: Some(4), ..serial.clone() };
+        let serial = convergence_table_with_options(AtanOverQuartic, 0.0, 1.0, 4, &serial).unwrap();
+        let threaded = convergence_table_with_options(AtanOverQuartic, 0.0, 1.0, 4, &threaded).unwrap();
+        for (serial, threaded) in serial.rows.iter().zip(&threaded.rows) {
+            assert!((serial.value - threaded.value).abs() < 1e-12);
+        }
+    }
+}
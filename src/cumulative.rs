@@ -0,0 +1,208 @@
+use std::thread::ScopedJoinHandle;
+
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::integrate::{reduce_partial_sums, reduction_blocks, RuleCursor};
+use crate::integrator::Integrator;
+use crate::rule::IntegrationRule;
+use crate::summation::CompensatedSum;
+
+/// Проходит участок теми же порциями, что и `Integrator::run`, и записывает
+/// накопленную сумму на каждой границе из `granularity()` отрезков; последняя
+/// запись — итог участка. f вычисляется один раз в узле: внутри порции суммы
+/// по `granularity()` отрезков собираются той же формулой из уже полученных
+/// значений, а на концах порций стоит сумма самого прохода.
+fn record_prefix_sums<T, R, I>(rule: &R, f: &I, lower_bound: T, step: T, samples: u64) -> Vec<T>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+    I: Integrand<T> + ?Sized,
+{
+    let granularity = rule.granularity().max(1);
+    let mut cursor = RuleCursor::new(lower_bound, step, samples);
+    let mut prefix_sums = Vec::with_capacity(samples.div_ceil(granularity) as usize);
+    let mut values = Vec::new();
+    let mut finished = samples == 0;
+    while !finished {
+        let (before, offset) = (cursor.done, cursor.sum);
+        values.clear();
+        // Бюджет в один отрезок — ровно одна порция
+        finished = cursor.advance(rule, &mut |x| {
+            let value = f.eval(x);
+            values.push((x, value));
+            value
+        }, 1);
+        values.sort_by(|a: &(T, T), b| a.0.to_f64().total_cmp(&b.0.to_f64()));
+        // Узлы отрезка совпадают с узлами порции с точностью до округления
+        let mut lookup = |x: T| {
+            let index = values.partition_point(|&(node, _)| node < x);
+            let nearest = [index.saturating_sub(1), index.min(values.len() - 1)]
+                .into_iter()
+                .min_by(|&i, &j| (values[i].0 - x).abs().to_f64().total_cmp(&(values[j].0 - x).abs().to_f64()))
+                .unwrap();
+            values[nearest].1
+        };
+        let mut piece_sum = CompensatedSum::new();
+        let mut node = before;
+        while node < cursor.done {
+            let count = granularity.min(cursor.done - node);
+            let chunk_lower_bound = lower_bound + T::from_u64(node) * step;
+            let chunk_upper_bound = lower_bound + T::from_u64(node + count) * step;
+            piece_sum.add(rule.accumulate(&mut lookup, chunk_lower_bound, chunk_upper_bound, count));
+            node += count;
+            prefix_sums.push(if node == cursor.done { cursor.sum } else { offset + piece_sum.value() });
+        }
+    }
+    prefix_sums
+}
+
+/// Накопленные суммы по участкам сетки из `granularity()` отрезков. Сетка
+/// делится на те же участки свёртки, что и в `Integrator::run`, и в
+/// многопоточном режиме потоки делят их между собой так же; каждый участок
+/// проходится один раз теми же порциями. Суммы внутри участка сдвигаются на
+/// попарную свёртку итогов предыдущих, а на границах участков стоит сама
+/// свёртка, поэтому последняя сумма — итог `Integrator::run` до последнего бита.
+pub(crate) fn cumulative_sums<T, R, I>(
+    integrator: &Integrator<T>,
+    rule: &R,
+    f: &I,
+) -> Result<Vec<T>, IntegralCalcError>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+    I: Integrand<T> + Sync + ?Sized,
+{
+    let samples = integrator.samples();
+    let step = integrator.step();
+    let blocks = reduction_blocks(integrator.lower_bound(), integrator.upper_bound(), samples, rule.granularity());
+    let record = |blocks: &[(T, u64)]| -> Vec<Vec<T>> {
+        blocks.iter().map(|&(block_lower_bound, block_samples)| record_prefix_sums(rule, f, block_lower_bound, step, block_samples)).collect()
+    };
+    let parts = if integrator.is_threaded() {
+        let threads = integrator.threads().clamp(1, blocks.len());
+        std::thread::scope(|scope| {
+            let handles: Vec<ScopedJoinHandle<_>> = (0..threads)
+                .map(|index| {
+                    let blocks = &blocks[index * blocks.len() / threads..(index + 1) * blocks.len() / threads];
+                    scope.spawn(move || record(blocks))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().map_err(|_| IntegralCalcError::WorkerPanicked))
+                .collect::<Result<Vec<_>, _>>()
+        })?
+        .into_iter()
+        .flatten()
+        .collect()
+    } else {
+        record(&blocks)
+    };
+    let mut prefix_sums = Vec::with_capacity(parts.iter().map(Vec::len).sum());
+    let mut totals = Vec::with_capacity(parts.len());
+    for part in parts {
+        let (offset, _) = reduce_partial_sums(&totals);
+        prefix_sums.extend(part.iter().map(|&sum| offset + sum));
+        totals.push((part.last().copied().unwrap_or(T::ZERO), 0));
+        if let Some(last) = prefix_sums.last_mut() {
+            *last = reduce_partial_sums(&totals).0;
+        }
+    }
+    Ok(prefix_sums)
+}
+
+/// Точки (x_i, F(x_i)) по накопленным суммам; первая точка — (a, 0),
+/// последняя приходится ровно на b.
+pub(crate) fn cumulative_points<T, R>(integrator: &Integrator<T>, rule: &R, prefix_sums: &[T]) -> Vec<(T, T)>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+{
+    let lower_bound = integrator.lower_bound();
+    let upper_bound = integrator.upper_bound();
+    let samples = integrator.samples();
+    let step = integrator.step();
+    let granularity = rule.granularity().max(1);
+    let mut points = Vec::with_capacity(prefix_sums.len() + 1);
+    points.push((lower_bound, T::ZERO));
+    for (index, &sum) in prefix_sums.iter().enumerate() {
+        let node = ((index as u64 + 1) * granularity).min(samples);
+        let x = if node == samples {
+            upper_bound
+        } else {
+            lower_bound + T::from_u64(node) * step
+        };
+        points.push((x, rule.scale(sum, lower_bound, upper_bound, samples)));
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::integrand::AtanOverQuartic;
+    use crate::integrate::{calculate_cumulative_integral, calculate_cumulative_integral_with_options, calculate_integral_with_options};
+    use crate::method::Method;
+    use crate::options::{ExecutionMode, IntegrationOptions};
+
+    #[test]
+    fn constant_grows_linearly() {
+        let points = calculate_cumulative_integral(|_: f64| 2.0, 1.0, 3.0, 4).unwrap();
+        assert_eq!(points, [(1.0, 0.0), (1.5, 1.0), (2.0, 2.0), (2.5, 3.0), (3.0, 4.0)]);
+    }
+
+    #[test]
+    fn last_point_is_the_integral() {
+        // Сетка из одного участка свёртки и из многих
+        for samples in [1000, 100_020] {
+            for method in [Method::Midpoint, Method::Trapezoid, Method::Simpson, Method::Boole, Method::GaussKronrod] {
+                for execution in [ExecutionMode::Sequential, ExecutionMode::Threaded] {
+                    let options = IntegrationOptions { samples, method, threads: Some(3), execution, ..Default::default() };
+                    let points = calculate_cumulative_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options).unwrap();
+                    let result = calculate_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options).unwrap();
+                    let granularity = method.rule::<f64>().granularity();
+                    assert_eq!(points.len() as u64, samples.div_ceil(granularity) + 1, "{}", method);
+                    assert_eq!(points[points.len() - 1], (1.0, result.value), "{} {} {:?}", samples, method, execution);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn threads_match_single_pass() {
+        let run = |execution| {
+            let options = IntegrationOptions { samples: 99_999, threads: Some(4), execution, ..Default::default() };
+            calculate_cumulative_integral_with_options(f64::exp, 0.0, 1.0, &options).unwrap()
+        };
+        let sequential = run(ExecutionMode::Sequential);
+        // Участки свёртки не зависят от числа потоков
+        assert_eq!(run(ExecutionMode::Threaded), sequential);
+        assert_eq!(sequential.len(), 100_000);
+        for &(x, integral) in sequential.iter().step_by(997) {
+            assert!((integral - (x.exp() - 1.0)).abs() < 1e-9, "{}", x);
+        }
+    }
+
+    #[test]
+    fn integrand_is_evaluated_once_per_node() {
+        for method in [Method::Midpoint, Method::Simpson, Method::GaussKronrod] {
+            let options = IntegrationOptions { samples: 100_020, method, ..Default::default() };
+            let evaluations = AtomicU64::new(0);
+            let counted = |x: f64| {
+                evaluations.fetch_add(1, Ordering::Relaxed);
+                x.exp()
+            };
+            calculate_cumulative_integral_with_options(counted, 0.0, 1.0, &options).unwrap();
+            let result = calculate_integral_with_options(f64::exp, 0.0, 1.0, &options).unwrap();
+            assert_eq!(evaluations.load(Ordering::Relaxed), result.samples, "{}", method);
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(calculate_cumulative_integral(f64::exp, 1.0, 0.0, 10).is_err());
+        assert!(calculate_cumulative_integral(f64::exp, 0.0, 1.0, 0).is_err());
+    }
+}
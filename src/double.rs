@@ -0,0 +1,80 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrate::{ASYNC_THRESHOLD_SAMPLES_COUNT, MAX_SAMPLES_COUNT};
+use crate::integrator::Integrator;
+use crate::result::IntegrationResult;
+use crate::rule::{IntegrationRule, MidpointRule};
+
+/// Двойной интеграл ∫∫ f(x, y) по прямоугольнику `x_bounds` × `y_bounds`
+/// формулой средних прямоугольников по обеим осям. Внешний проход по x
+/// делится между потоками так же, как в `calculate_integral_async`; для
+/// каждого x внутренний интеграл по y считается целиком. Ограничение
+/// `MAX_SAMPLES_COUNT` относится к произведению `samples_x * samples_y`.
+pub fn calculate_integral_2d<T, F>(
+    f: F,
+    x_bounds: (T, T),
+    y_bounds: (T, T),
+    samples_x: u64,
+    samples_y: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    F: Fn(T, T) -> T + Send + Sync,
+{
+    // Границы и число отсчётов по y проверяются так же, как по x
+    let inner = Integrator::builder().bounds(y_bounds.0, y_bounds.1).samples(samples_y).build()?;
+    let samples = samples_x.saturating_mul(samples_y);
+    if samples > MAX_SAMPLES_COUNT {
+        return Err(IntegralCalcError::TooManySamples { requested: samples, max: MAX_SAMPLES_COUNT });
+    }
+    let outer = Integrator::builder()
+        .bounds(x_bounds.0, x_bounds.1)
+        .samples(samples_x)
+        .async_threshold(ASYNC_THRESHOLD_SAMPLES_COUNT / samples_y.max(1))
+        .build()?;
+    let (lower_bound, upper_bound) = (inner.lower_bound(), inner.upper_bound());
+    let mut result = outer.run_with_factory(|| {
+        let f = &f;
+        move |x| {
+            let sum = MidpointRule.accumulate(&mut |y| f(x, y), lower_bound, upper_bound, samples_y);
+            MidpointRule.scale(sum, lower_bound, upper_bound, samples_y)
+        }
+    })?;
+    result.intervals = samples;
+    result.samples *= samples_y;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separable_product() {
+        // Формула средних прямоугольников точна для x·y
+        let result = calculate_integral_2d(|x: f64, y: f64| x * y, (0.0, 1.0), (0.0, 1.0), 10, 10).unwrap();
+        assert!((result.value - 0.25).abs() < 1e-15, "{}", result.value);
+        assert_eq!((result.intervals, result.samples), (100, 100));
+    }
+
+    #[test]
+    fn non_separable_against_fine_grid() {
+        let f = |x: f64, y: f64| (x * y).sin() + x / (1.0 + y);
+        let reference = calculate_integral_2d(f, (0.0, 2.0), (0.5, 1.5), 2000, 2000).unwrap().value;
+        for (samples_x, samples_y) in [(100, 100), (20_000, 30), (30, 200)] {
+            let value = calculate_integral_2d(f, (0.0, 2.0), (0.5, 1.5), samples_x, samples_y).unwrap().value;
+            assert!((value - reference).abs() < 1e-3, "{} × {}: {}", samples_x, samples_y, value);
+        }
+    }
+
+    #[test]
+    fn bounds_of_both_axes_are_checked() {
+        let f = |x: f64, y: f64| x + y;
+        assert!(matches!(calculate_integral_2d(f, (1.0, 0.0), (0.0, 1.0), 10, 10), Err(IntegralCalcError::BoundsReversed { .. })));
+        assert!(matches!(calculate_integral_2d(f, (0.0, 1.0), (1.0, 0.0), 10, 10), Err(IntegralCalcError::BoundsReversed { .. })));
+        assert!(matches!(
+            calculate_integral_2d(f, (0.0, 1.0), (0.0, 1.0), 100_000, 100_000),
+            Err(IntegralCalcError::TooManySamples { .. })
+        ));
+    }
+}
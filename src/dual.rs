@@ -0,0 +1,290 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::float::Float;
+use crate::integrand::Integrand;
+
+/// Число с первой и второй производными по одной переменной (усечённый
+/// ряд Тейлора второго порядка). Арифметика и элементарные функции
+/// переносят производные по правилам дифференцирования, поэтому функция,
+/// записанная для любого `T: Float`, в `Dual2` даёт f, f′ и f″ без
+/// погрешности разностных схем.
+///
+/// Сравнения (`==`, `<`, `max`, `min`) учитывают только значение.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dual2<T: Float = f64> {
+    pub value: T,
+    pub first: T,
+    pub second: T,
+}
+
+impl<T: Float> Dual2<T> {
+    pub fn constant(value: T) -> Self {
+        Self { value, first: T::ZERO, second: T::ZERO }
+    }
+
+    /// Независимая переменная в точке x.
+    pub fn variable(x: T) -> Self {
+        Self { value: x, first: T::ONE, second: T::ZERO }
+    }
+
+    /// φ(self) по значениям φ, φ′ и φ″ в точке `self.value`.
+    fn chain(self, value: T, first: T, second: T) -> Self {
+        Self {
+            value,
+            first: first * self.first,
+            second: second * self.first * self.first + first * self.second,
+        }
+    }
+}
+
+impl<T: Float> PartialEq for Dual2<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Float> PartialOrd for Dual2<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Float> Display for Dual2<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} (f′ = {}, f″ = {})", self.value, self.first, self.second)
+    }
+}
+
+impl<T: Float> Add for Dual2<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { value: self.value + other.value, first: self.first + other.first, second: self.second + other.second }
+    }
+}
+
+impl<T: Float> Sub for Dual2<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self { value: self.value - other.value, first: self.first - other.first, second: self.second - other.second }
+    }
+}
+
+impl<T: Float> Mul for Dual2<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            value: self.value * other.value,
+            first: self.first * other.value + self.value * other.first,
+            second: self.second * other.value
+                + T::from_f64(2.0) * self.first * other.first
+                + self.value * other.second,
+        }
+    }
+}
+
+impl<T: Float> Div for Dual2<T> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let inverse = T::ONE / other.value;
+        self * other.chain(inverse, -inverse * inverse, T::from_f64(2.0) * inverse * inverse * inverse)
+    }
+}
+
+impl<T: Float> Neg for Dual2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { value: -self.value, first: -self.first, second: -self.second }
+    }
+}
+
+impl<T: Float> AddAssign for Dual2<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Float> SubAssign for Dual2<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Float> MulAssign for Dual2<T> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl<T: Float> DivAssign for Dual2<T> {
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+
+impl<T: Float> Float for Dual2<T> {
+    const ZERO: Self = Self { value: T::ZERO, first: T::ZERO, second: T::ZERO };
+    const ONE: Self = Self { value: T::ONE, first: T::ZERO, second: T::ZERO };
+    const EPSILON: Self = Self { value: T::EPSILON, first: T::ZERO, second: T::ZERO };
+
+    fn from_f64(value: f64) -> Self {
+        Self::constant(T::from_f64(value))
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::constant(T::from_u64(value))
+    }
+
+    fn to_f64(self) -> f64 {
+        self.value.to_f64()
+    }
+
+    fn abs(self) -> Self {
+        if self.value < T::ZERO { -self } else { self }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other.value > self.value { other } else { self }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if other.value < self.value { other } else { self }
+    }
+
+    fn powi(self, n: i32) -> Self {
+        match n {
+            0 => Self::ONE,
+            1 => self,
+            _ => {
+                let power = T::from_f64(n as f64);
+                let lower = self.value.powi(n - 2);
+                self.chain(
+                    lower * self.value * self.value,
+                    power * lower * self.value,
+                    power * T::from_f64((n - 1) as f64) * lower,
+                )
+            }
+        }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        (n * self.ln()).exp()
+    }
+
+    fn sqrt(self) -> Self {
+        let root = self.value.sqrt();
+        let first = T::ONE / (T::from_f64(2.0) * root);
+        self.chain(root, first, -first / (T::from_f64(2.0) * self.value))
+    }
+
+    fn exp(self) -> Self {
+        let exp = self.value.exp();
+        self.chain(exp, exp, exp)
+    }
+
+    fn ln(self) -> Self {
+        let inverse = T::ONE / self.value;
+        self.chain(self.value.ln(), inverse, -inverse * inverse)
+    }
+
+    fn sin(self) -> Self {
+        let (sin, cos) = (self.value.sin(), self.value.cos());
+        self.chain(sin, cos, -sin)
+    }
+
+    fn cos(self) -> Self {
+        let (sin, cos) = (self.value.sin(), self.value.cos());
+        self.chain(cos, -sin, -cos)
+    }
+
+    fn tan(self) -> Self {
+        let tan = self.value.tan();
+        let secant2 = T::ONE + tan * tan;
+        self.chain(tan, secant2, T::from_f64(2.0) * tan * secant2)
+    }
+
+    fn atan(self) -> Self {
+        let inverse = T::ONE / (T::ONE + self.value * self.value);
+        self.chain(self.value.atan(), inverse, T::from_f64(-2.0) * self.value * inverse * inverse)
+    }
+
+    fn is_finite(self) -> bool {
+        self.value.is_finite()
+    }
+
+    fn is_nan(self) -> bool {
+        self.value.is_nan()
+    }
+}
+
+/// Значение, первая и вторая производные f в точке x, вычисленные в `Dual2`.
+pub fn derivatives_auto<T, I>(f: &I, x: T) -> (T, T, T)
+where
+    T: Float,
+    I: Integrand<Dual2<T>> + ?Sized,
+{
+    let result = f.eval(Dual2::variable(x));
+    (result.value, result.first, result.second)
+}
+
+/// Вторая производная f в точке x по `Dual2`: f должна быть записана для
+/// любого скалярного типа, как встроенные функции.
+pub fn second_derivative_auto<T, I>(f: &I, x: T) -> T
+where
+    T: Float,
+    I: Integrand<Dual2<T>> + ?Sized,
+{
+    derivatives_auto(f, x).2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+
+    /// f″ для atan(x)/(x⁴+1), выписанная по правилу для дроби u/v.
+    fn symbolic_second_derivative(x: f64) -> f64 {
+        let (u, u1, u2) = (x.atan(), 1.0 / (1.0 + x * x), -2.0 * x / (1.0 + x * x).powi(2));
+        let (v, v1, v2) = (x.powi(4) + 1.0, 4.0 * x.powi(3), 12.0 * x * x);
+        u2 / v - 2.0 * u1 * v1 / (v * v) - u * v2 / (v * v) + 2.0 * u * v1 * v1 / v.powi(3)
+    }
+
+    #[test]
+    fn builtin_matches_symbolic_reference() {
+        for x in [-3.0, -1.0, -0.25, 0.0, 0.3, 0.5, 1.0, 2.0, 10.0] {
+            let (value, first, second) = derivatives_auto(&AtanOverQuartic, x);
+            let expected = symbolic_second_derivative(x);
+            assert!((second - expected).abs() <= 1e-14 * expected.abs().max(1.0), "{}: {} != {}", x, second, expected);
+            assert!((value - Integrand::<f64>::eval(&AtanOverQuartic, x)).abs() < 1e-15, "{}", x);
+            let analytic = Integrand::<f64>::first_derivative(&AtanOverQuartic, x).unwrap();
+            assert!((first - analytic).abs() < 1e-14, "{}: {} != {}", x, first, analytic);
+        }
+    }
+
+    #[test]
+    fn elementary_functions() {
+        let x = 0.7;
+        let composite = |x: Dual2| x.sin() * x.exp() + x.powi(3) / x.sqrt();
+        let (value, first, second) = derivatives_auto(&composite, x);
+        let expected_first = (x.cos() + x.sin()) * x.exp() + 2.5 * x.powf(1.5);
+        let expected_second = 2.0 * x.cos() * x.exp() + 3.75 * x.sqrt();
+        assert!((value - (x.sin() * x.exp() + x.powf(2.5))).abs() < 1e-15);
+        assert!((first - expected_first).abs() < 1e-14, "{}", first);
+        assert!((second - expected_second).abs() < 1e-14, "{}", second);
+    }
+
+    #[test]
+    fn comparisons_use_value() {
+        let (a, b) = (Dual2::variable(1.0), Dual2::constant(1.0));
+        assert_eq!(a, b);
+        assert!(Dual2::constant(2.0) > a);
+        assert_eq!(Dual2::variable(-1.0).abs().first, -1.0);
+    }
+}
@@ -0,0 +1,238 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::builtin::BUILTIN_FUNCTIONS;
+use crate::messages::{Lang, Message};
+use crate::method::Method;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegralCalcError {
+    BoundsReversed { lower: f64, upper: f64 },
+    TooManySamples { requested: u64, max: u64 },
+    ZeroSamples,
+    SamplesNotMultiple { samples: u64, multiple: u64 },
+    NonFiniteBound { value: f64 },
+    EqualInfiniteBounds { value: f64 },
+    IntervalOverflow { lower: f64, upper: f64 },
+    InvalidStep { step: f64 },
+    ZeroThreads,
+    MissingBounds,
+    EmptyBounds,
+    NonFiniteAxisBound { axis: usize, value: f64 },
+    AxisBoundsReversed { axis: usize, lower: f64, upper: f64 },
+    TooFewPoints { count: usize },
+    NonFinitePoint { index: usize },
+    DuplicatePoint { index: usize },
+    NonMonotonePoints { index: usize },
+    NonUniformPoints { index: usize },
+    NonFiniteFrequency { value: f64 },
+    ThresholdAboveMax { threshold: u64, max: u64 },
+    WorkerPanicked,
+    Cancelled,
+    Interrupted { estimate: f64, completed: f64 },
+    InvalidTolerance { value: f64 },
+    InvalidOrder { order: u32, min: u32, max: u32 },
+    MaxDepthReached { lower: f64, upper: f64 },
+    ToleranceNotReached { best: f64, samples: u64 },
+    UnknownMethod { name: String, suggestion: Option<Method> },
+    UnknownFunction { name: String },
+    InvalidExpression { position: usize, token: String },
+    InputFile { path: String, reason: String },
+    OutputFile { path: String, reason: String },
+    SuspectedSingularity { x: f64 },
+    NonFiniteValue { x: f64 },
+}
+
+impl IntegralCalcError {
+    /// Сообщение об ошибке на заданном языке; `Display` использует `Lang::current()`.
+    pub fn localized(&self, lang: Lang) -> LocalizedError<'_> {
+        LocalizedError { error: self, lang }
+    }
+
+    fn write_ru(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::BoundsReversed { lower, upper } =>
+                write!(f, "нижняя граница ({}) больше верхней ({})", lower, upper),
+            Self::TooManySamples { requested, max } =>
+                write!(f, "превышено максимальное число отсчётов ({} > {})", requested, max),
+            Self::ZeroSamples => write!(f, "число отсчётов должно быть больше нуля"),
+            Self::SamplesNotMultiple { samples, multiple } =>
+                write!(f, "число отсчётов ({}) должно быть кратно {} для выбранного метода", samples, multiple),
+            Self::NonFiniteBound { value } => write!(f, "граница не является конечным числом ({})", value),
+            Self::EqualInfiniteBounds { value } => write!(f, "обе границы равны {}", value),
+            Self::IntervalOverflow { lower, upper } =>
+                write!(f, "длина отрезка [{:e}, {:e}] не представима конечным числом", lower, upper),
+            Self::InvalidStep { step } => write!(f, "шаг сетки ({}) не является конечным положительным числом", step),
+            Self::ZeroThreads => write!(f, "число потоков должно быть больше нуля"),
+            Self::MissingBounds => write!(f, "не заданы границы интегрирования"),
+            Self::EmptyBounds => write!(f, "список границ по осям пуст"),
+            Self::NonFiniteAxisBound { axis, value } =>
+                write!(f, "граница по оси {} не является конечным числом ({})", axis, value),
+            Self::AxisBoundsReversed { axis, lower, upper } =>
+                write!(f, "нижняя граница по оси {} ({}) больше верхней ({})", axis, lower, upper),
+            Self::TooFewPoints { count } => write!(f, "в таблице должно быть не меньше двух точек ({})", count),
+            Self::NonFinitePoint { index } => write!(f, "точка {} таблицы не является конечным числом", index),
+            Self::DuplicatePoint { index } => write!(f, "точка {} таблицы повторяет x предыдущей", index),
+            Self::NonMonotonePoints { index } => write!(f, "x точки {} таблицы меньше x предыдущей", index),
+            Self::NonUniformPoints { index } => write!(f, "шаг таблицы перед точкой {} отличается от остальных", index),
+            Self::NonFiniteFrequency { value } => write!(f, "частота не является конечным числом ({})", value),
+            Self::ThresholdAboveMax { threshold, max } =>
+                write!(f, "порог многопоточного расчёта больше максимального числа отсчётов ({} > {})", threshold, max),
+            Self::WorkerPanicked => write!(f, "рабочий поток завершился аварийно"),
+            Self::Cancelled => write!(f, "расчёт прерван"),
+            Self::Interrupted { estimate, completed } =>
+                write!(f, "расчёт прерван на {:.1}% отсчётов, неполная оценка {}", completed * 100.0, estimate),
+            Self::InvalidTolerance { value } =>
+                write!(f, "точность должна быть положительным числом ({})", value),
+            Self::InvalidOrder { order, min, max } =>
+                write!(f, "порядок формулы ({}) вне допустимого диапазона [{}, {}]", order, min, max),
+            Self::MaxDepthReached { lower, upper } =>
+                write!(f, "достигнута наибольшая глубина деления на отрезке [{}, {}]", lower, upper),
+            Self::ToleranceNotReached { best, samples } =>
+                write!(f, "заданная точность не достигнута, лучшая оценка {} при {} отсчётах", best, samples),
+            Self::UnknownMethod { name, suggestion: Some(suggestion) } =>
+                write!(f, "неизвестный метод \"{}\", возможно, имелся в виду {}; допустимые значения: {}",
+                       name, suggestion, method_names()),
+            Self::UnknownMethod { name, suggestion: None } =>
+                write!(f, "неизвестный метод \"{}\", допустимые значения: {}", name, method_names()),
+            Self::UnknownFunction { name } =>
+                write!(f, "неизвестная функция \"{}\", допустимые значения: {}", name, function_names()),
+            Self::InvalidExpression { position, token } if token.is_empty() =>
+                write!(f, "выражение обрывается в позиции {}", position),
+            Self::InvalidExpression { position, token } =>
+                write!(f, "ошибка в выражении в позиции {}: неожиданное \"{}\"", position, token),
+            Self::InputFile { path, reason } =>
+                write!(f, "не удалось прочитать файл {}: {}", path, reason),
+            Self::OutputFile { path, reason } =>
+                write!(f, "не удалось записать файл {}: {}", path, reason),
+            Self::SuspectedSingularity { x } =>
+                write!(f, "подынтегральная функция, по-видимому, имеет особенность вблизи x ≈ {}", x),
+            Self::NonFiniteValue { x } => write!(f, "значение подынтегральной функции в x = {} не является конечным числом", x),
+        }
+    }
+
+    fn write_en(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::BoundsReversed { lower, upper } =>
+                write!(f, "the lower bound ({}) is greater than the upper bound ({})", lower, upper),
+            Self::TooManySamples { requested, max } =>
+                write!(f, "maximum number of samples exceeded ({} > {})", requested, max),
+            Self::ZeroSamples => write!(f, "the number of samples must be positive"),
+            Self::SamplesNotMultiple { samples, multiple } =>
+                write!(f, "the number of samples ({}) must be a multiple of {} for the chosen method", samples, multiple),
+            Self::NonFiniteBound { value } => write!(f, "a bound is not a finite number ({})", value),
+            Self::EqualInfiniteBounds { value } => write!(f, "both bounds are {}", value),
+            Self::IntervalOverflow { lower, upper } =>
+                write!(f, "the length of the interval [{:e}, {:e}] is not a finite number", lower, upper),
+            Self::InvalidStep { step } => write!(f, "the grid step ({}) is not a finite positive number", step),
+            Self::ZeroThreads => write!(f, "the number of threads must be positive"),
+            Self::MissingBounds => write!(f, "integration bounds are not set"),
+            Self::EmptyBounds => write!(f, "the list of axis bounds is empty"),
+            Self::NonFiniteAxisBound { axis, value } =>
+                write!(f, "a bound on axis {} is not a finite number ({})", axis, value),
+            Self::AxisBoundsReversed { axis, lower, upper } =>
+                write!(f, "the lower bound on axis {} ({}) is greater than the upper bound ({})", axis, lower, upper),
+            Self::TooFewPoints { count } => write!(f, "the table must contain at least two points ({})", count),
+            Self::NonFinitePoint { index } => write!(f, "table point {} is not a finite number", index),
+            Self::DuplicatePoint { index } => write!(f, "table point {} repeats the x of the previous one", index),
+            Self::NonMonotonePoints { index } => write!(f, "the x of table point {} is less than the previous one", index),
+            Self::NonUniformPoints { index } => write!(f, "the table step before point {} differs from the others", index),
+            Self::NonFiniteFrequency { value } => write!(f, "the frequency is not a finite number ({})", value),
+            Self::ThresholdAboveMax { threshold, max } =>
+                write!(f, "the multithreading threshold exceeds the maximum number of samples ({} > {})", threshold, max),
+            Self::WorkerPanicked => write!(f, "a worker thread panicked"),
+            Self::Cancelled => write!(f, "the calculation was cancelled"),
+            Self::Interrupted { estimate, completed } =>
+                write!(f, "the calculation was cancelled at {:.1}% of samples, incomplete estimate {}", completed * 100.0, estimate),
+            Self::InvalidTolerance { value } =>
+                write!(f, "the tolerance must be a positive number ({})", value),
+            Self::InvalidOrder { order, min, max } =>
+                write!(f, "the rule order ({}) is outside the allowed range [{}, {}]", order, min, max),
+            Self::MaxDepthReached { lower, upper } =>
+                write!(f, "maximum subdivision depth reached on [{}, {}]", lower, upper),
+            Self::ToleranceNotReached { best, samples } =>
+                write!(f, "tolerance not reached, best estimate {} with {} samples", best, samples),
+            Self::UnknownMethod { name, suggestion: Some(suggestion) } =>
+                write!(f, "unknown method \"{}\", did you mean {}? Expected one of: {}", name, suggestion, method_names()),
+            Self::UnknownMethod { name, suggestion: None } =>
+                write!(f, "unknown method \"{}\", expected one of: {}", name, method_names()),
+            Self::UnknownFunction { name } =>
+                write!(f, "unknown function \"{}\", expected one of: {}", name, function_names()),
+            Self::InvalidExpression { position, token } if token.is_empty() =>
+                write!(f, "the expression ends unexpectedly at position {}", position),
+            Self::InvalidExpression { position, token } =>
+                write!(f, "invalid expression at position {}: unexpected \"{}\"", position, token),
+            Self::InputFile { path, reason } =>
+                write!(f, "failed to read the file {}: {}", path, reason),
+            Self::OutputFile { path, reason } =>
+                write!(f, "failed to write the file {}: {}", path, reason),
+            Self::SuspectedSingularity { x } =>
+                write!(f, "the integrand appears to be singular near x ≈ {}", x),
+            Self::NonFiniteValue { x } => write!(f, "the integrand value at x = {} is not a finite number", x),
+        }
+    }
+}
+
+fn method_names() -> String {
+    Method::ALL.iter().map(|method| method.name()).collect::<Vec<_>>().join(", ")
+}
+
+fn function_names() -> String {
+    BUILTIN_FUNCTIONS.iter().map(|function| function.key).collect::<Vec<_>>().join(", ")
+}
+
+/// Ошибка вместе с языком, на котором её нужно вывести.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalizedError<'a> {
+    error: &'a IntegralCalcError,
+    lang: Lang,
+}
+
+impl Display for LocalizedError<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: ", Message::CalculationError.text(self.lang))?;
+        match self.lang {
+            Lang::Ru => self.error.write_ru(f),
+            Lang::En => self.error.write_en(f),
+        }
+    }
+}
+
+impl Display for IntegralCalcError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.localized(Lang::current()), f)
+    }
+}
+
+impl Error for IntegralCalcError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_owns_its_data() {
+        // Ошибка не заимствует ничего из ввода: её можно вернуть как Box<dyn Error>
+        fn boxed(name: String) -> Box<dyn Error + Send + Sync + 'static> {
+            Box::new(IntegralCalcError::UnknownFunction { name })
+        }
+        for index in 0..1000 {
+            let error = boxed(format!("f{}", index));
+            assert!(error.to_string().contains(&format!("\"f{}\"", index)));
+        }
+    }
+
+    #[test]
+    fn bounds_reversed_in_both_languages() {
+        let error = IntegralCalcError::BoundsReversed { lower: 2.0, upper: 1.0 };
+        assert_eq!(
+            error.localized(Lang::Ru).to_string(),
+            "Ошибка вычисления интеграла: нижняя граница (2) больше верхней (1)"
+        );
+        assert_eq!(
+            error.localized(Lang::En).to_string(),
+            "Integral calculation error: the lower bound (2) is greater than the upper bound (1)"
+        );
+    }
+}
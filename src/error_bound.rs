@@ -0,0 +1,99 @@
+use crate::float::Float;
+use crate::integrand::{numeric_derivative_within, Integrand};
+
+/// Вид остаточного члена составной квадратурной формулы:
+/// |R| ≤ (b − a) · h^`step_power` / `denominator` · max|f⁽ᵏ⁾|, k = `derivative_order`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorTerm {
+    pub derivative_order: u32,
+    pub step_power: i32,
+    pub denominator: f64,
+}
+
+impl ErrorTerm {
+    pub const RIEMANN: Self = Self { derivative_order: 1, step_power: 1, denominator: 2.0 };
+    pub const MIDPOINT: Self = Self { derivative_order: 2, step_power: 2, denominator: 24.0 };
+    pub const TRAPEZOID: Self = Self { derivative_order: 2, step_power: 2, denominator: 12.0 };
+    pub const SIMPSON: Self = Self { derivative_order: 4, step_power: 4, denominator: 180.0 };
+    pub const SIMPSON_THREE_EIGHTHS: Self = Self { derivative_order: 4, step_power: 4, denominator: 80.0 };
+    pub const BOOLE: Self = Self { derivative_order: 6, step_power: 6, denominator: 472.5 };
+}
+
+/// Число узлов, по которым `Integrator::run` ищет наибольшую производную:
+/// граница пересчитывается на шаг расчёта по степени шага, так что при любом
+/// числе отсчётов она опирается на одну и ту же оценку производной.
+pub const REMAINDER_SCAN_NODES: u64 = 1000;
+
+/// Оценка остаточного члена и точка, где производная по модулю наибольшая.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorBound<T: Float = f64> {
+    pub bound: T,
+    pub argmax: T,
+    pub max_derivative: T,
+}
+
+/// Оценивает остаточный член по узлам сетки шага `step` на [a, b].
+///
+/// `derivative` — аналитическая производная нужного порядка; без неё
+/// производная оценивается конечными разностями по `f`, не выходящими
+/// за [a, b].
+pub fn error_bound<T, I>(
+    term: ErrorTerm,
+    f: &I,
+    derivative: Option<&dyn Fn(T) -> T>,
+    lower_bound: T,
+    upper_bound: T,
+    step: T,
+) -> ErrorBound<T>
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    let nodes = ((upper_bound - lower_bound) / step).to_f64().round().max(0.0) as u64;
+    let mut max_derivative = T::ZERO;
+    let mut argmax = lower_bound;
+    for node in 0..=nodes {
+        let x = if node == nodes {
+            upper_bound
+        } else {
+            lower_bound + T::from_u64(node) * step
+        };
+        let value = match derivative {
+            Some(derivative) => derivative(x),
+            None => numeric_derivative_within(f, x, term.derivative_order, lower_bound, upper_bound),
+        }
+            .abs();
+        if value > max_derivative {
+            max_derivative = value;
+            argmax = x;
+        }
+    }
+    let factor = (upper_bound - lower_bound) * step.powi(term.step_power) / T::from_f64(term.denominator);
+    ErrorBound {
+        bound: factor * max_derivative,
+        argmax,
+        max_derivative,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::integrate::{calculate_integral_with_method, get_remaining_term_for};
+    use crate::method::Method;
+
+    #[test]
+    fn bound_covers_error_on_polynomials() {
+        // x^(k + 1): k-я производная не постоянна, интеграл по [0, 2] — 2^(k + 2)/(k + 2)
+        for method in Method::ALL.iter().copied().filter(|method| method.error_term().is_some()) {
+            let order = method.error_term().unwrap().derivative_order as i32;
+            let f = move |x: f64| x.powi(order + 1);
+            let exact = 2f64.powi(order + 2) / (order + 2) as f64;
+            for samples in [12, 24, 48] {
+                let result = calculate_integral_with_method(f, 0.0, 2.0, samples, method).unwrap();
+                let bound = get_remaining_term_for(method, &f, 0.0, 2.0, result.step).unwrap();
+                let error = (result.value - exact).abs();
+                assert!(error > 0.0 && error <= bound, "{} при {}: {} > {}", method, samples, error, bound);
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::process::exit;
+
+use kryl_04::{tr, IntegralCalcError, Message};
+
+use super::report::Verbosity;
+
+/// Причина завершения программы с ошибкой; значение — код завершения.
+/// Коды не меняются и не используются повторно: на них полагаются сценарии
+/// (см. `--list-exit-codes`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    IncorrectLowerBound = 1,
+    IncorrectUpperBound = 2,
+    IncorrectSamplesCount = 3,
+    UnableToCalculate = 4,
+    BoundsReversed = 5,
+    NonFiniteBound = 6,
+    WorkerPanicked = 7,
+    IncorrectThreadsCount = 8,
+    Cancelled = 9,
+    ToleranceNotReached = 10,
+    IncorrectArguments = 11,
+    UnknownMethod = 12,
+    IncorrectTable = 13,
+    ZeroSamples = 14,
+    InvalidInterval = 15,
+    EndOfInput = 16,
+    UnknownFunction = 17,
+    InvalidExpression = 18,
+    BatchFailed = 19,
+    FileError = 20,
+    Interrupted = 21,
+    CompareMismatch = 22,
+    /// Повторный Ctrl+C, как у оболочки: 128 + SIGINT. Программа
+    /// завершается прямо из обработчика сигнала (`cancel_on_interrupt`).
+    InterruptedTwice = 130,
+    /// Читатель закрыл стандартный вывод (`| head`), как у оболочки:
+    /// 128 + SIGPIPE. Сообщение не выводится.
+    BrokenPipe = 141,
+}
+
+impl ExitReason {
+    /// Все причины в порядке кодов.
+    pub const ALL: &'static [Self] = &[
+        Self::IncorrectLowerBound,
+        Self::IncorrectUpperBound,
+        Self::IncorrectSamplesCount,
+        Self::UnableToCalculate,
+        Self::BoundsReversed,
+        Self::NonFiniteBound,
+        Self::WorkerPanicked,
+        Self::IncorrectThreadsCount,
+        Self::Cancelled,
+        Self::ToleranceNotReached,
+        Self::IncorrectArguments,
+        Self::UnknownMethod,
+        Self::IncorrectTable,
+        Self::ZeroSamples,
+        Self::InvalidInterval,
+        Self::EndOfInput,
+        Self::UnknownFunction,
+        Self::InvalidExpression,
+        Self::BatchFailed,
+        Self::FileError,
+        Self::Interrupted,
+        Self::CompareMismatch,
+        Self::InterruptedTwice,
+        Self::BrokenPipe,
+    ];
+
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    pub fn description(self) -> Message {
+        match self {
+            Self::IncorrectLowerBound => Message::ExitIncorrectLowerBound,
+            Self::IncorrectUpperBound => Message::ExitIncorrectUpperBound,
+            Self::IncorrectSamplesCount => Message::ExitIncorrectSamplesCount,
+            Self::UnableToCalculate => Message::ExitUnableToCalculate,
+            Self::BoundsReversed => Message::ExitBoundsReversed,
+            Self::NonFiniteBound => Message::ExitNonFiniteBound,
+            Self::WorkerPanicked => Message::ExitWorkerPanicked,
+            Self::IncorrectThreadsCount => Message::ExitIncorrectThreadsCount,
+            Self::Cancelled => Message::ExitCancelled,
+            Self::ToleranceNotReached => Message::ExitToleranceNotReached,
+            Self::IncorrectArguments => Message::ExitIncorrectArguments,
+            Self::UnknownMethod => Message::ExitUnknownMethod,
+            Self::IncorrectTable => Message::ExitIncorrectTable,
+            Self::ZeroSamples => Message::ExitZeroSamples,
+            Self::InvalidInterval => Message::ExitInvalidInterval,
+            Self::EndOfInput => Message::ExitEndOfInput,
+            Self::UnknownFunction => Message::ExitUnknownFunction,
+            Self::InvalidExpression => Message::ExitInvalidExpression,
+            Self::BatchFailed => Message::ExitBatchFailed,
+            Self::FileError => Message::ExitFileError,
+            Self::Interrupted => Message::ExitInterrupted,
+            Self::CompareMismatch => Message::ExitCompareMismatch,
+            Self::InterruptedTwice => Message::ExitInterruptedTwice,
+            Self::BrokenPipe => Message::ExitBrokenPipe,
+        }
+    }
+}
+
+impl From<&IntegralCalcError> for ExitReason {
+    fn from(error: &IntegralCalcError) -> Self {
+        match error {
+            IntegralCalcError::BoundsReversed { .. } => Self::BoundsReversed,
+            IntegralCalcError::NonFiniteBound { .. }
+            | IntegralCalcError::EqualInfiniteBounds { .. } => Self::NonFiniteBound,
+            IntegralCalcError::ZeroSamples => Self::ZeroSamples,
+            IntegralCalcError::IntervalOverflow { .. }
+            | IntegralCalcError::InvalidStep { .. } => Self::InvalidInterval,
+            IntegralCalcError::TooManySamples { .. }
+            | IntegralCalcError::SamplesNotMultiple { .. } => Self::IncorrectSamplesCount,
+            IntegralCalcError::WorkerPanicked => Self::WorkerPanicked,
+            IntegralCalcError::ZeroThreads => Self::IncorrectThreadsCount,
+            IntegralCalcError::Cancelled => Self::Cancelled,
+            IntegralCalcError::Interrupted { .. } => Self::Interrupted,
+            IntegralCalcError::InvalidTolerance { .. }
+            | IntegralCalcError::ToleranceNotReached { .. } => Self::ToleranceNotReached,
+            IntegralCalcError::UnknownMethod { .. } => Self::UnknownMethod,
+            IntegralCalcError::UnknownFunction { .. } => Self::UnknownFunction,
+            IntegralCalcError::InvalidExpression { .. } => Self::InvalidExpression,
+            IntegralCalcError::InputFile { .. }
+            | IntegralCalcError::OutputFile { .. } => Self::FileError,
+            IntegralCalcError::TooFewPoints { .. }
+            | IntegralCalcError::NonFinitePoint { .. }
+            | IntegralCalcError::DuplicatePoint { .. }
+            | IntegralCalcError::NonMonotonePoints { .. } => Self::IncorrectTable,
+            _ => Self::UnableToCalculate,
+        }
+    }
+}
+
+/// Завершает программу с кодом `reason`; с `-v` перед этим печатает код и
+/// его описание в stderr. Сообщение об ошибке печатает вызывающий.
+pub fn exit_with(reason: ExitReason) -> ! {
+    if Verbosity::current() >= Verbosity::Verbose {
+        eprintln!("{}: {} ({})", tr(Message::ExitStatus), reason.code(), tr(reason.description()));
+    }
+    exit(reason.code())
+}
+
+/// `--list-exit-codes`: таблица кодов завершения в stdout.
+pub fn list() {
+    outln!("{:>4}  {}", 0, tr(Message::ExitSuccess));
+    for &reason in ExitReason::ALL {
+        outln!("{:>4}  {}", reason.code(), tr(reason.description()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_ordered() {
+        assert!(ExitReason::ALL.windows(2).all(|pair| pair[0].code() < pair[1].code()));
+        assert!(ExitReason::ALL.iter().all(|reason| reason.code() > 0));
+    }
+
+    #[test]
+    fn errors_map_to_reasons() {
+        let cases = [
+            (IntegralCalcError::BoundsReversed { lower: 1.0, upper: 0.0 }, ExitReason::BoundsReversed),
+            (IntegralCalcError::EqualInfiniteBounds { value: f64::INFINITY }, ExitReason::NonFiniteBound),
+            (IntegralCalcError::ZeroSamples, ExitReason::ZeroSamples),
+            (IntegralCalcError::InvalidStep { step: 0.0 }, ExitReason::InvalidInterval),
+            (IntegralCalcError::SamplesNotMultiple { samples: 7, multiple: 2 }, ExitReason::IncorrectSamplesCount),
+            (IntegralCalcError::ZeroThreads, ExitReason::IncorrectThreadsCount),
+            (IntegralCalcError::Interrupted { estimate: 0.0, completed: 0.5 }, ExitReason::Interrupted),
+            (IntegralCalcError::ToleranceNotReached { best: 0.0, samples: 1 }, ExitReason::ToleranceNotReached),
+            (IntegralCalcError::UnknownFunction { name: "f".to_string() }, ExitReason::UnknownFunction),
+            (IntegralCalcError::OutputFile { path: String::new(), reason: String::new() }, ExitReason::FileError),
+            (IntegralCalcError::NonMonotonePoints { index: 1 }, ExitReason::IncorrectTable),
+            (IntegralCalcError::NonFiniteValue { x: 0.0 }, ExitReason::UnableToCalculate),
+        ];
+        for (error, reason) in cases {
+            assert_eq!(ExitReason::from(&error), reason, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn shell_codes_follow_signals() {
+        assert_eq!(ExitReason::InterruptedTwice.code(), 128 + 2);
+        assert_eq!(ExitReason::BrokenPipe.code(), 128 + 13);
+        assert_eq!(ExitReason::ALL.len(), 24);
+    }
+}
@@ -0,0 +1,462 @@
+use std::sync::Arc;
+
+use crate::dual::second_derivative_auto;
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+
+/// Глубина стека, до которой выражение вычисляется без выделения памяти.
+const INLINE_STACK: usize = 16;
+
+/// Функция одного аргумента, допустимая в выражении.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Atan,
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "tan" => Self::Tan,
+            "atan" => Self::Atan,
+            "exp" => Self::Exp,
+            "ln" => Self::Ln,
+            "sqrt" => Self::Sqrt,
+            "abs" => Self::Abs,
+            _ => return None,
+        })
+    }
+
+    fn apply<T: Float>(self, x: T) -> T {
+        match self {
+            Self::Sin => x.sin(),
+            Self::Cos => x.cos(),
+            Self::Tan => x.tan(),
+            Self::Atan => x.atan(),
+            Self::Exp => x.exp(),
+            Self::Ln => x.ln(),
+            Self::Sqrt => x.sqrt(),
+            Self::Abs => x.abs(),
+        }
+    }
+}
+
+/// Команда стековой машины, в которую компилируется выражение.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instruction {
+    Const(f64),
+    X,
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    /// Возведение в целую степень, известную при разборе.
+    PowI(i32),
+    Call(Function),
+}
+
+/// Подынтегральная функция, заданная выражением от x.
+///
+/// Допустимы числа, переменная x, константы pi и e, операции + − * / ^
+/// (степень правоассоциативна и старше унарного минуса: -x^2 = −(x²)),
+/// скобки и функции sin, cos, tan, atan, exp, ln, sqrt, abs. Выражение
+/// разбирается один раз и вычисляется по плоскому списку команд, константные
+/// подвыражения сворачиваются при разборе.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: Arc<str>,
+    program: Arc<[Instruction]>,
+    depth: usize,
+}
+
+impl Expression {
+    /// Разбирает выражение; ошибка указывает позицию (с единицы) первого
+    /// символа, который не удалось разобрать.
+    pub fn parse(source: &str) -> Result<Self, IntegralCalcError> {
+        Self::parse_with(source, true)
+    }
+
+    /// Значение выражения без переменной x, например `-pi/2` или `(1+2)/4`;
+    /// x, как и умножение без знака (`2pi`), — ошибка разбора.
+    pub fn evaluate_constant(source: &str) -> Result<f64, IntegralCalcError> {
+        Self::parse_with(source, false).map(|expression| expression.eval(0.0))
+    }
+
+    fn parse_with(source: &str, variable: bool) -> Result<Self, IntegralCalcError> {
+        let mut parser = Parser { chars: source.chars().collect(), position: 0, program: Vec::new(), variable };
+        parser.skip_whitespace();
+        if parser.peek().is_none() {
+            return Err(parser.error());
+        }
+        parser.expression()?;
+        if parser.peek().is_some() {
+            return Err(parser.error());
+        }
+        let depth = stack_depth(&parser.program);
+        Ok(Self { source: source.trim().into(), program: parser.program.into(), depth })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn evaluate<T: Float>(&self, x: T, stack: &mut [T]) -> T {
+        let mut top = 0;
+        for &instruction in self.program.iter() {
+            match instruction {
+                Instruction::Const(value) => {
+                    stack[top] = T::from_f64(value);
+                    top += 1;
+                }
+                Instruction::X => {
+                    stack[top] = x;
+                    top += 1;
+                }
+                Instruction::Neg => stack[top - 1] = -stack[top - 1],
+                Instruction::PowI(n) => stack[top - 1] = stack[top - 1].powi(n),
+                Instruction::Call(function) => stack[top - 1] = function.apply(stack[top - 1]),
+                binary => {
+                    top -= 1;
+                    let (left, right) = (stack[top - 1], stack[top]);
+                    stack[top - 1] = match binary {
+                        Instruction::Add => left + right,
+                        Instruction::Sub => left - right,
+                        Instruction::Mul => left * right,
+                        Instruction::Div => left / right,
+                        _ => left.powf(right),
+                    };
+                }
+            }
+        }
+        stack[0]
+    }
+}
+
+impl<T: Float> Integrand<T> for Expression {
+    fn eval(&self, x: T) -> T {
+        if self.depth <= INLINE_STACK {
+            self.evaluate(x, &mut [T::ZERO; INLINE_STACK])
+        } else {
+            self.evaluate(x, &mut vec![T::ZERO; self.depth])
+        }
+    }
+
+    /// Точная вторая производная через `Dual2`.
+    fn second_derivative(&self, x: T) -> Option<T> {
+        Some(second_derivative_auto(self, x))
+    }
+
+    fn name(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Наибольшая глубина стека при выполнении `program`.
+fn stack_depth(program: &[Instruction]) -> usize {
+    let mut depth = 0usize;
+    let mut max_depth = 0;
+    for instruction in program {
+        match instruction {
+            Instruction::Const(_) | Instruction::X => depth += 1,
+            Instruction::Neg | Instruction::PowI(_) | Instruction::Call(_) => {}
+            _ => depth -= 1,
+        }
+        max_depth = max_depth.max(depth);
+    }
+    max_depth
+}
+
+/// Разбор рекурсивным спуском:
+///
+/// expression = term (('+' | '-') term)*
+/// term       = unary (('*' | '/') unary)*
+/// unary      = ('-' | '+') unary | power
+/// power      = primary ('^' unary)?
+/// primary    = number | 'x' | 'pi' | 'e' | name '(' expression ')' | '(' expression ')'
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    program: Vec<Instruction>,
+    /// Допустима ли переменная x.
+    variable: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Пропускает `expected` вместе с пробелами после него, если он следующий.
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            self.skip_whitespace();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Ошибка в текущей позиции.
+    fn error(&self) -> IntegralCalcError {
+        self.error_at(self.position, self.peek().map(String::from).unwrap_or_default())
+    }
+
+    fn error_at(&self, position: usize, token: String) -> IntegralCalcError {
+        IntegralCalcError::InvalidExpression { position: position + 1, token }
+    }
+
+    fn expression(&mut self) -> Result<(), IntegralCalcError> {
+        self.term()?;
+        loop {
+            if self.eat('+') {
+                self.term()?;
+                self.emit(Instruction::Add);
+            } else if self.eat('-') {
+                self.term()?;
+                self.emit(Instruction::Sub);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<(), IntegralCalcError> {
+        self.unary()?;
+        loop {
+            if self.eat('*') {
+                self.unary()?;
+                self.emit(Instruction::Mul);
+            } else if self.eat('/') {
+                self.unary()?;
+                self.emit(Instruction::Div);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<(), IntegralCalcError> {
+        if self.eat('-') {
+            self.unary()?;
+            self.emit(Instruction::Neg);
+            Ok(())
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<(), IntegralCalcError> {
+        self.primary()?;
+        if self.eat('^') {
+            self.unary()?;
+            self.emit(Instruction::Pow);
+        }
+        Ok(())
+    }
+
+    fn primary(&mut self) -> Result<(), IntegralCalcError> {
+        let start = self.position;
+        match self.peek() {
+            Some('(') => {
+                self.eat('(');
+                self.expression()?;
+                if !self.eat(')') {
+                    return Err(self.error());
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let value = self.number();
+                let value = value.parse().map_err(|_| self.error_at(start, value))?;
+                self.emit(Instruction::Const(value));
+            }
+            Some(c) if c.is_alphabetic() => {
+                let name = self.identifier();
+                match name.as_str() {
+                    "x" if self.variable => self.emit(Instruction::X),
+                    "pi" => self.emit(Instruction::Const(std::f64::consts::PI)),
+                    "e" => self.emit(Instruction::Const(std::f64::consts::E)),
+                    _ => {
+                        let function = Function::from_name(&name).ok_or_else(|| self.error_at(start, name))?;
+                        if !self.eat('(') {
+                            return Err(self.error());
+                        }
+                        self.expression()?;
+                        if !self.eat(')') {
+                            return Err(self.error());
+                        }
+                        self.emit(Instruction::Call(function));
+                    }
+                }
+            }
+            _ => return Err(self.error()),
+        }
+        Ok(())
+    }
+
+    /// Десятичное число с необязательным порядком (`1.5e-3`); порядок
+    /// берётся, только если за `e` идут цифры, чтобы `2*e` оставалось константой.
+    fn number(&mut self) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.position += 1;
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let digits = match self.chars.get(self.position + 1) {
+                Some('+' | '-') => self.position + 2,
+                _ => self.position + 1,
+            };
+            if self.chars.get(digits).is_some_and(char::is_ascii_digit) {
+                self.position = digits;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.position += 1;
+                }
+            }
+        }
+        let number = self.chars[start..self.position].iter().collect();
+        self.skip_whitespace();
+        number
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.position += 1;
+        }
+        let name = self.chars[start..self.position].iter().collect::<String>().to_lowercase();
+        self.skip_whitespace();
+        name
+    }
+
+    /// Добавляет команду, сразу вычисляя её, если все операнды — константы.
+    fn emit(&mut self, instruction: Instruction) {
+        let folded = match (instruction, self.program.as_slice()) {
+            (Instruction::Neg, [.., Instruction::Const(a)]) => Some((1, -a)),
+            (Instruction::Call(function), [.., Instruction::Const(a)]) => Some((1, function.apply(*a))),
+            (Instruction::Pow, [.., Instruction::Const(a), Instruction::Const(b)]) => Some((2, a.powf(*b))),
+            (Instruction::Add, [.., Instruction::Const(a), Instruction::Const(b)]) => Some((2, a + b)),
+            (Instruction::Sub, [.., Instruction::Const(a), Instruction::Const(b)]) => Some((2, a - b)),
+            (Instruction::Mul, [.., Instruction::Const(a), Instruction::Const(b)]) => Some((2, a * b)),
+            (Instruction::Div, [.., Instruction::Const(a), Instruction::Const(b)]) => Some((2, a / b)),
+            _ => None,
+        };
+        if let Some((operands, value)) = folded {
+            self.program.truncate(self.program.len() - operands);
+            self.program.push(Instruction::Const(value));
+            return;
+        }
+        // Целая степень через powi: так x^2 точнее и определено при x < 0
+        if let (Instruction::Pow, [.., Instruction::Const(n)]) = (instruction, self.program.as_slice()) {
+            if n.fract() == 0.0 && n.abs() <= i32::MAX as f64 {
+                let n = *n as i32;
+                self.program.pop();
+                self.program.push(Instruction::PowI(n));
+                return;
+            }
+        }
+        self.program.push(instruction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+    use crate::integrate::calculate_integral;
+
+    fn eval(source: &str, x: f64) -> f64 {
+        Integrand::<f64>::eval(&Expression::parse(source).unwrap(), x)
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(eval("1 + 2 * 3", 0.0), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", 0.0), 9.0);
+        assert_eq!(eval("2 ^ 3 ^ 2", 0.0), 512.0);
+        assert_eq!(eval("8 / 4 / 2", 0.0), 1.0);
+        assert_eq!(eval("10 - 4 - 3", 0.0), 3.0);
+        assert_eq!(eval("x^2 + 3*x", 2.0), 10.0);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-x^2", 3.0), -9.0);
+        assert_eq!(eval("(-x)^2", 3.0), 9.0);
+        assert_eq!(eval("2^-1", 0.0), 0.5);
+        assert_eq!(eval("--x", 4.0), 4.0);
+        assert_eq!(eval("3 - -x", 1.0), 4.0);
+        assert_eq!(eval("exp(-x^2)", 0.0), 1.0);
+    }
+
+    #[test]
+    fn functions_and_constants() {
+        let x = 0.6;
+        assert!((eval("sin(x)^2 + cos(x)^2", x) - 1.0).abs() < 1e-15);
+        assert!((eval("tan(atan(x))", x) - x).abs() < 1e-15);
+        assert!((eval("ln(exp(x))", x) - x).abs() < 1e-15);
+        assert_eq!(eval("sqrt(abs(-x*x))", x), x);
+        assert_eq!(eval("pi", 0.0), std::f64::consts::PI);
+        assert_eq!(eval("e", 0.0), std::f64::consts::E);
+    }
+
+    #[test]
+    fn malformed_input_points_at_character() {
+        let position = |source: &str| match Expression::parse(source).unwrap_err() {
+            IntegralCalcError::InvalidExpression { position, .. } => position,
+            error => panic!("{}: {:?}", source, error),
+        };
+        assert_eq!(position(""), 1);
+        assert_eq!(position("1 + * 2"), 5);
+        assert_eq!(position("(x + 1"), 7);
+        assert_eq!(position("x + 1)"), 6);
+        assert_eq!(position("sin x"), 5);
+        assert_eq!(position("foo(x)"), 1);
+        assert_eq!(position("x $ 2"), 3);
+        assert_eq!(position("1.2.3"), 1);
+    }
+
+    #[test]
+    fn typed_expression_matches_builtin() {
+        let expression = Expression::parse("atan(x)/(x^4+1)").unwrap();
+        assert_eq!(expression.source(), "atan(x)/(x^4+1)");
+        let typed = calculate_integral(expression, 0.0, 1.0, 100_000).unwrap().value;
+        let builtin = calculate_integral(AtanOverQuartic, 0.0, 1.0, 100_000).unwrap().value;
+        assert!((typed - builtin).abs() < 1e-15, "{} != {}", typed, builtin);
+    }
+
+    #[test]
+    fn constant_bounds() {
+        let pi = std::f64::consts::PI;
+        assert_eq!(Expression::evaluate_constant("pi"), Ok(pi));
+        assert_eq!(Expression::evaluate_constant("-pi/2"), Ok(-pi / 2.0));
+        assert_eq!(Expression::evaluate_constant("(1+2)/4"), Ok(0.75));
+        assert_eq!(Expression::evaluate_constant("2*pi"), Ok(2.0 * pi));
+        assert_eq!(Expression::evaluate_constant("1/3"), Ok(1.0 / 3.0));
+        assert_eq!(Expression::evaluate_constant("e"), Ok(std::f64::consts::E));
+        // Умножение без знака не допускается
+        assert_eq!(
+            Expression::evaluate_constant("2pi"),
+            Err(IntegralCalcError::InvalidExpression { position: 2, token: "p".to_string() })
+        );
+        assert!(matches!(Expression::evaluate_constant("x/2"), Err(IntegralCalcError::InvalidExpression { position: 1, .. })));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::{Integrand, IntegrandRef};
+use crate::integrate::calculate_integral_with_options;
+use crate::options::IntegrationOptions;
+use crate::result::IntegrationResult;
+
+/// Экстраполяция Ричардсона по двум расчётам формулой средних прямоугольников
+/// с `samples` и `2 * samples` отсчётами: (4·I₂ₙ − Iₙ)/3 уничтожает главный
+/// член погрешности порядка h², а |I₂ₙ − Iₙ| возвращается как оценка погрешности.
+pub fn calculate_integral_extrapolated<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    calculate_integral_extrapolated_with_options(f, lower_bound, upper_bound, &options)
+}
+
+/// То же, что `calculate_integral_extrapolated`, с заданными параметрами
+/// расчёта. Для формулы `options.method` порядка p значения сводятся как
+/// (2ᵖ·I₂ₙ − Iₙ)/(2ᵖ − 1); при p = 2 это (4·I₂ₙ − Iₙ)/3.
+pub fn calculate_integral_extrapolated_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let coarse = calculate_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, options)?;
+    let samples = options.samples.checked_mul(2).ok_or(IntegralCalcError::TooManySamples {
+        requested: u64::MAX,
+        max: options.max_samples,
+    })?;
+    let fine_options = IntegrationOptions { samples, ..options.clone() };
+    let fine = calculate_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &fine_options)?;
+    let difference = fine.value - coarse.value;
+    let order = options.method.rule::<T>().order();
+    Ok(IntegrationResult {
+        value: fine.value + difference / (T::from_f64(2.0).powi(order as i32) - T::ONE),
+        samples: coarse.samples + fine.samples,
+        elapsed: coarse.elapsed + fine.elapsed,
+        remainder_bound: None,
+        error_estimate: Some(difference.abs()),
+        ..fine
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::method::Method;
+
+    fn error(method: Method, samples: u64) -> f64 {
+        let options = IntegrationOptions { samples, method, ..Default::default() };
+        let result = calculate_integral_extrapolated_with_options(f64::exp, 0.0, 1.0, &options).unwrap();
+        (result.value - (std::f64::consts::E - 1.0)).abs()
+    }
+
+    #[test]
+    fn midpoint_converges_as_h4() {
+        // При вдвое меньшем шаге погрешность падает в 2⁴ = 16 раз
+        let ratio = error(Method::Midpoint, 20) / error(Method::Midpoint, 40);
+        assert!((ratio - 16.0).abs() < 0.5, "{}", ratio);
+        let ratio = error(Method::Trapezoid, 20) / error(Method::Trapezoid, 40);
+        assert!((ratio - 16.0).abs() < 0.5, "{}", ratio);
+    }
+
+    #[test]
+    fn uses_order_of_rule() {
+        // У Симпсона p = 4: с делителем 3 вместо 15 ошибка была бы порядка h⁴
+        let ratio = error(Method::Simpson, 8) / error(Method::Simpson, 16);
+        assert!((ratio - 64.0).abs() < 4.0, "{}", ratio);
+        assert!(error(Method::Simpson, 8) < 1e-9);
+    }
+
+    #[test]
+    fn error_estimate_is_difference() {
+        let options = IntegrationOptions { samples: 100, ..Default::default() };
+        let coarse = calculate_integral_with_options(f64::exp, 0.0, 1.0, &options).unwrap();
+        let result = calculate_integral_extrapolated(f64::exp, 0.0, 1.0, 100).unwrap();
+        let fine = calculate_integral_with_options(f64::exp, 0.0, 1.0, &IntegrationOptions { samples: 200, ..options }).unwrap();
+        assert_eq!(result.error_estimate, Some((fine.value - coarse.value).abs()));
+        assert_eq!(result.samples, coarse.samples + fine.samples);
+        assert_eq!(result.remainder_bound, None);
+    }
+}
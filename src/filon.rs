@@ -0,0 +1,199 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::integrate::calculate_integral_with_rule;
+use crate::options::IntegrationOptions;
+use crate::result::IntegrationResult;
+use crate::rule::IntegrationRule;
+use crate::summation::CompensatedSum;
+
+/// Множитель при огибающей в формуле Филона.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Oscillation {
+    Sin,
+    Cos,
+}
+
+/// Формула Филона для ∫ f(x)·sin(ωx) dx или ∫ f(x)·cos(ωx) dx с медленно
+/// меняющейся огибающей f: на каждой панели из двух шагов f заменяется
+/// параболой по трём узлам, а интегралы x^k·sin(ωx) берутся точно. Поэтому
+/// погрешность определяется гладкостью f, а не частотой ω, и панелей не
+/// нужно больше, чем для самой f. При ω = 0 формула совпадает с Симпсоном.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilonRule<T: Float = f64> {
+    pub omega: T,
+    pub oscillation: Oscillation,
+}
+
+impl<T: Float> FilonRule<T> {
+    pub fn new(omega: T, oscillation: Oscillation) -> Self {
+        Self { omega, oscillation }
+    }
+}
+
+/// Моменты ∫₋₁¹ cos(θs) ds, ∫₋₁¹ s·sin(θs) ds и ∫₋₁¹ s²·cos(θs) ds. При
+/// малых θ явные формулы теряют точность на вычитании, поэтому берётся ряд.
+fn moments<T: Float>(theta: T) -> (T, T, T) {
+    let two = T::from_f64(2.0);
+    if theta.abs() >= T::ONE {
+        let (sin, cos) = (theta.sin(), theta.cos());
+        let theta2 = theta * theta;
+        return (
+            two * sin / theta,
+            two * (sin / theta2 - cos / theta),
+            two * (sin / theta + two * cos / theta2 - two * sin / (theta2 * theta)),
+        );
+    }
+    // term = (−1)^j θ^(2j) / (2j)!, odd = (−1)^j θ^(2j+1) / (2j+1)!
+    let (mut c0, mut s1, mut c2) = (CompensatedSum::new(), CompensatedSum::new(), CompensatedSum::new());
+    let mut term = T::ONE;
+    for j in 0..30u64 {
+        let odd = term * theta / T::from_u64(2 * j + 1);
+        c0.add(two * term / T::from_u64(2 * j + 1));
+        s1.add(two * odd / T::from_u64(2 * j + 3));
+        c2.add(two * term / T::from_u64(2 * j + 3));
+        if term.abs() < T::EPSILON {
+            break;
+        }
+        term = -odd * theta / T::from_u64(2 * j + 2);
+    }
+    (c0.value(), s1.value(), c2.value())
+}
+
+impl<T: Float> IntegrationRule<T> for FilonRule<T> {
+    /// Каждый из `samples` отрезков — панель с узлами на концах и в
+    /// середине; возвращается сумма интегралов по панелям, делённых на шаг.
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        if samples == 0 {
+            return T::ZERO;
+        }
+        let two = T::from_f64(2.0);
+        let step = (upper_bound - lower_bound) / T::from_u64(samples);
+        let half_step = step / two;
+        let (c0, s1, c2) = moments(self.omega * half_step);
+        let mut sum = CompensatedSum::new();
+        let mut left = f(lower_bound);
+        for panel in 0..samples {
+            let start = lower_bound + T::from_u64(panel) * step;
+            let middle = start + half_step;
+            let center = f(middle);
+            let right = f(if panel + 1 == samples { upper_bound } else { start + step });
+            // Парабола a0 + a1·s + a2·s² на s ∈ [−1, 1]
+            let a1 = (right - left) / two;
+            let a2 = (left + right) / two - center;
+            let even = center * c0 + a2 * c2;
+            let odd = a1 * s1;
+            let phase = self.omega * middle;
+            let (sin, cos) = (phase.sin(), phase.cos());
+            sum.add(match self.oscillation {
+                Oscillation::Sin => sin * even + cos * odd,
+                Oscillation::Cos => cos * even - sin * odd,
+            } / two);
+            left = right;
+        }
+        sum.value()
+    }
+
+    fn ordered_nodes(&self) -> bool {
+        false
+    }
+
+    fn order(&self) -> u32 {
+        3
+    }
+}
+
+/// ∫ₐᵇ f(x)·sin(ωx) dx по формуле Филона на `panels` панелях; f — только
+/// огибающая, множитель sin(ωx) формула учитывает сама.
+pub fn filon_sin<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    omega: T,
+    panels: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples: panels, ..Default::default() };
+    filon_with_options(f, lower_bound, upper_bound, omega, Oscillation::Sin, &options)
+}
+
+/// ∫ₐᵇ f(x)·cos(ωx) dx по формуле Филона на `panels` панелях.
+pub fn filon_cos<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    omega: T,
+    panels: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples: panels, ..Default::default() };
+    filon_with_options(f, lower_bound, upper_bound, omega, Oscillation::Cos, &options)
+}
+
+/// То же, что `filon_sin` или `filon_cos`, с заданными параметрами расчёта;
+/// `options.samples` — число панелей.
+pub fn filon_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    omega: T,
+    oscillation: Oscillation,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    if !omega.is_finite() {
+        return Err(IntegralCalcError::NonFiniteFrequency { value: omega.to_f64() });
+    }
+    calculate_integral_with_rule(&FilonRule::new(omega, oscillation), f, lower_bound, upper_bound, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrate::calculate_integral_with_method;
+    use crate::method::Method;
+
+    const TWO_PI: f64 = 2.0 * std::f64::consts::PI;
+
+    #[test]
+    fn oscillatory_against_fine_reference() {
+        let envelope = |x: f64| 1.0 / (1.0 + x);
+        for omega in [50.0, 500.0] {
+            let sine = move |x: f64| envelope(x) * (omega * x).sin();
+            let cosine = move |x: f64| envelope(x) * (omega * x).cos();
+            let reference_sin = calculate_integral_with_method(sine, 0.0, TWO_PI, 200_000, Method::Simpson).unwrap().value;
+            let reference_cos = calculate_integral_with_method(cosine, 0.0, TWO_PI, 200_000, Method::Simpson).unwrap().value;
+            // Панелей столько же, сколько нужно для самой огибающей, а не для sin(ωx)
+            let filon_sin = filon_sin(envelope, 0.0, TWO_PI, omega, 400).unwrap().value;
+            let filon_cos = filon_cos(envelope, 0.0, TWO_PI, omega, 400).unwrap().value;
+            assert!((filon_sin - reference_sin).abs() < 1e-7, "{}: {} {}", omega, filon_sin, reference_sin);
+            assert!((filon_cos - reference_cos).abs() < 1e-7, "{}: {} {}", omega, filon_cos, reference_cos);
+        }
+    }
+
+    #[test]
+    fn zero_frequency_is_simpson() {
+        let envelope = |x: f64| x.exp();
+        let filon = filon_cos(envelope, 0.0, 1.0, 0.0, 10).unwrap().value;
+        let simpson = calculate_integral_with_method(envelope, 0.0, 1.0, 20, Method::Simpson).unwrap().value;
+        assert!((filon - simpson).abs() < 1e-14, "{} {}", filon, simpson);
+        assert_eq!(filon_sin(envelope, 0.0, 1.0, 0.0, 10).unwrap().value, 0.0);
+    }
+
+    #[test]
+    fn frequency_must_be_finite() {
+        assert_eq!(
+            filon_sin(|x: f64| x, 0.0, 1.0, f64::NAN, 10).unwrap_err().to_string(),
+            IntegralCalcError::NonFiniteFrequency { value: f64::NAN }.to_string()
+        );
+    }
+}
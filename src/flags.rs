@@ -0,0 +1,247 @@
+use kryl_04::{Method, BUILTIN_FUNCTIONS};
+
+/// Что флаг принимает после себя.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagValue {
+    /// Ничего: флаг-переключатель.
+    Switch,
+    /// Число или выражение — подсказывать нечего.
+    Value,
+    /// Необязательное значение, только через `=` (`--with-reference[=N]`).
+    Attached,
+    Path,
+    Choices(&'static [&'static str]),
+    Methods,
+    Functions,
+}
+
+impl FlagValue {
+    /// Значения для дополнения; пусто, если их не перечислить.
+    pub fn choices(self) -> Vec<&'static str> {
+        match self {
+            FlagValue::Choices(choices) => choices.to_vec(),
+            FlagValue::Methods => Method::ALL.iter().map(|method| method.name()).collect(),
+            FlagValue::Functions => BUILTIN_FUNCTIONS.iter().map(|function| function.key).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Флаг ждёт значение следующим словом (или после `=`).
+    pub fn takes_value(self) -> bool {
+        !matches!(self, FlagValue::Switch | FlagValue::Attached)
+    }
+}
+
+/// Флаг командной строки: все его написания и значение.
+#[derive(Debug)]
+pub struct Flag {
+    pub names: &'static [&'static str],
+    pub value: FlagValue,
+}
+
+const fn flag(names: &'static [&'static str], value: FlagValue) -> Flag {
+    Flag { names, value }
+}
+
+/// Подкоманды — первое слово командной строки.
+pub const SUBCOMMANDS: &[&str] = &["bench", "table", "completions"];
+
+/// Оболочки подкоманды `completions`.
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+/// Все флаги: по этой таблице флаги разбираются (`arg_value`, `arg_set`),
+/// проверяются на неизвестные и дополняются в оболочке (`completions`).
+pub const FLAGS: &[Flag] = &[
+    flag(&["--lower"], FlagValue::Value),
+    flag(&["--upper"], FlagValue::Value),
+    flag(&["--samples"], FlagValue::Value),
+    flag(&["--tolerance"], FlagValue::Value),
+    flag(&["--threads"], FlagValue::Value),
+    flag(&["--method"], FlagValue::Methods),
+    flag(&["--function"], FlagValue::Functions),
+    flag(&["--expr"], FlagValue::Value),
+    flag(&["--seed"], FlagValue::Value),
+    flag(&["--with-reference"], FlagValue::Attached),
+    flag(&["--reference"], FlagValue::Switch),
+    flag(&["--reference-samples"], FlagValue::Value),
+    flag(&["--negate-reversed"], FlagValue::Switch),
+    flag(&["--propagate-nan"], FlagValue::Switch),
+    flag(&["--compare"], FlagValue::Switch),
+    flag(&["--batch"], FlagValue::Switch),
+    flag(&["--validate"], FlagValue::Switch),
+    flag(&["--input"], FlagValue::Path),
+    flag(&["--output"], FlagValue::Path),
+    flag(&["--format"], FlagValue::Choices(&["text", "csv", "json"])),
+    flag(&["--evals"], FlagValue::Value),
+    flag(&["--sort"], FlagValue::Choices(&["method", "error"])),
+    flag(&["--start"], FlagValue::Value),
+    flag(&["--rows"], FlagValue::Value),
+    flag(&["--repl"], FlagValue::Switch),
+    flag(&["--history-size"], FlagValue::Value),
+    flag(&["--plot"], FlagValue::Switch),
+    flag(&["--plot-height"], FlagValue::Value),
+    flag(&["--dump-plot"], FlagValue::Path),
+    flag(&["--dump-points"], FlagValue::Value),
+    flag(&["--2d"], FlagValue::Switch),
+    flag(&["--convergence"], FlagValue::Switch),
+    flag(&["--table"], FlagValue::Path),
+    flag(&["--precision"], FlagValue::Value),
+    flag(&["--format-number"], FlagValue::Choices(&["fixed", "scientific", "auto"])),
+    flag(&["-q", "--quiet"], FlagValue::Switch),
+    flag(&["-v", "--verbose"], FlagValue::Switch),
+    flag(&["-vv"], FlagValue::Switch),
+    flag(&["--no-warnings"], FlagValue::Switch),
+    flag(&["--interactive"], FlagValue::Switch),
+    flag(&["--no-interactive"], FlagValue::Switch),
+    flag(&["--no-color"], FlagValue::Switch),
+    flag(&["--lang"], FlagValue::Choices(&["ru", "en"])),
+    flag(&["--config"], FlagValue::Path),
+    flag(&["--print-config"], FlagValue::Switch),
+    flag(&["--list-exit-codes"], FlagValue::Switch),
+    flag(&["-h", "--help"], FlagValue::Switch),
+];
+
+/// Устаревшие флаги и то, что использовать вместо них; сами флаги
+/// по-прежнему принимаются.
+pub const DEPRECATED: &[(&str, &str)] = &[
+    ("--reference", "--with-reference"),
+    ("--reference-samples", "--with-reference=N"),
+];
+
+/// Флаг с написанием `name`.
+pub fn find(name: &str) -> Option<&'static Flag> {
+    FLAGS.iter().find(|flag| flag.names.contains(&name))
+}
+
+/// Слово командной строки после разбора по `FLAGS`.
+enum Word<'a> {
+    /// Известный флаг вместе со значением.
+    Flag,
+    /// Похоже на флаг, но в `FLAGS` его нет.
+    Unknown(&'a str),
+    /// Не флаг и не значение флага.
+    Positional(&'a str),
+}
+
+/// Разбор слов командной строки. Значения флагов пропускаются, даже если
+/// начинаются с `-` (`--lower -1`).
+fn words(args: &[String]) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            words.push(Word::Positional(arg));
+            continue;
+        }
+        let (name, attached) = match arg.split_once('=') {
+            Some((name, _)) => (name, true),
+            None => (arg.as_str(), false),
+        };
+        words.push(match find(name) {
+            Some(flag) if flag.value.takes_value() && !attached => {
+                args.next();
+                Word::Flag
+            }
+            Some(_) => Word::Flag,
+            None => Word::Unknown(arg),
+        });
+    }
+    words
+}
+
+/// Слова командной строки, похожие на флаги, которых нет в `FLAGS`.
+pub fn unknown(args: &[String]) -> Vec<&str> {
+    words(args).into_iter().filter_map(|word| match word {
+        Word::Unknown(arg) => Some(arg),
+        _ => None,
+    }).collect()
+}
+
+/// Слова командной строки, не являющиеся флагами или их значениями.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Positionals<'a> {
+    /// Подкоманда из `SUBCOMMANDS` — первое такое слово, где бы оно ни
+    /// стояло (`kryl_04 --lang en bench`).
+    pub subcommand: Option<&'a str>,
+    /// Аргументы подкоманды: у `completions` — оболочка.
+    pub arguments: Vec<&'a str>,
+    /// Лишние слова, которые некому принять.
+    pub stray: Vec<&'a str>,
+}
+
+/// Число аргументов подкоманды `subcommand`.
+fn arguments_count(subcommand: &str) -> usize {
+    match subcommand {
+        "completions" => 1,
+        _ => 0,
+    }
+}
+
+pub fn positionals(args: &[String]) -> Positionals<'_> {
+    let mut positionals = Positionals::default();
+    let mut words = words(args).into_iter().filter_map(|word| match word {
+        Word::Positional(arg) => Some(arg),
+        _ => None,
+    });
+    if let Some(first) = words.next() {
+        match SUBCOMMANDS.iter().find(|&&subcommand| subcommand == first) {
+            Some(&subcommand) => {
+                positionals.subcommand = Some(subcommand);
+                positionals.arguments = words.by_ref().take(arguments_count(subcommand)).collect();
+            }
+            None => positionals.stray.push(first),
+        }
+    }
+    positionals.stray.extend(words);
+    positionals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn subcommand_after_flags() {
+        let args = args("--lang en --lower -1 bench");
+        let positionals = positionals(&args);
+        assert_eq!(positionals.subcommand, Some("bench"));
+        assert!(positionals.arguments.is_empty());
+        assert!(positionals.stray.is_empty());
+    }
+
+    #[test]
+    fn completions_takes_shell() {
+        let args = args("--lang=en completions bash zsh");
+        let positionals = positionals(&args);
+        assert_eq!(positionals.subcommand, Some("completions"));
+        assert_eq!(positionals.arguments, ["bash"]);
+        assert_eq!(positionals.stray, ["zsh"]);
+    }
+
+    #[test]
+    fn stray_words_without_subcommand() {
+        // У `--with-reference` значение только через `=`: 5000 — лишнее слово
+        let args = args("--with-reference 5000 --samples 10 bench");
+        let positionals = positionals(&args);
+        assert_eq!(positionals.subcommand, None);
+        assert_eq!(positionals.stray, ["5000", "bench"]);
+    }
+
+    #[test]
+    fn unknown_flags() {
+        let args = args("--lower -1 --sampels 10 --format=json -x");
+        assert_eq!(unknown(&args), ["--sampels", "-x"]);
+    }
+
+    #[test]
+    fn table_has_no_duplicates() {
+        let names: Vec<&str> = FLAGS.iter().flat_map(|flag| flag.names.iter().copied()).collect();
+        for (index, name) in names.iter().enumerate() {
+            assert!(!names[index + 1..].contains(name), "{}", name);
+        }
+    }
+}
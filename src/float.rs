@@ -0,0 +1,160 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Вещественный тип, над которым ведётся интегрирование (`f32` или `f64`;
+/// `Dual2` — для вычисления производных).
+pub trait Float:
+    Copy
+    + PartialOrd
+    + Debug
+    + Display
+    + Default
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const EPSILON: Self;
+
+    fn from_f64(value: f64) -> Self;
+    fn from_u64(value: u64) -> Self;
+    fn to_f64(self) -> f64;
+
+    fn abs(self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn atan(self) -> Self;
+    fn is_finite(self) -> bool;
+    fn is_nan(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($t:ty) => {
+        impl Float for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const EPSILON: Self = <$t>::EPSILON;
+
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+
+            fn from_u64(value: u64) -> Self {
+                value as $t
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn max(self, other: Self) -> Self {
+                <$t>::max(self, other)
+            }
+
+            fn min(self, other: Self) -> Self {
+                <$t>::min(self, other)
+            }
+
+            fn powi(self, n: i32) -> Self {
+                <$t>::powi(self, n)
+            }
+
+            fn powf(self, n: Self) -> Self {
+                <$t>::powf(self, n)
+            }
+
+            fn sqrt(self) -> Self {
+                <$t>::sqrt(self)
+            }
+
+            fn exp(self) -> Self {
+                <$t>::exp(self)
+            }
+
+            fn ln(self) -> Self {
+                <$t>::ln(self)
+            }
+
+            fn sin(self) -> Self {
+                <$t>::sin(self)
+            }
+
+            fn cos(self) -> Self {
+                <$t>::cos(self)
+            }
+
+            fn tan(self) -> Self {
+                <$t>::tan(self)
+            }
+
+            fn atan(self) -> Self {
+                <$t>::atan(self)
+            }
+
+            fn is_finite(self) -> bool {
+                <$t>::is_finite(self)
+            }
+
+            fn is_nan(self) -> bool {
+                <$t>::is_nan(self)
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Корень уравнения x = cos x итерациями только через `Float`.
+    fn fixed_point<T: Float>() -> T {
+        let mut x = T::ONE;
+        for _ in 0..200 {
+            x = x.cos();
+        }
+        x
+    }
+
+    #[test]
+    fn f32_and_f64_share_the_trait() {
+        let (single, double) = (fixed_point::<f32>(), fixed_point::<f64>());
+        assert!((double - 0.739_085_133_215_160_6).abs() < 1e-15);
+        assert!((single.to_f64() - double).abs() < 1e-6);
+        assert_eq!(<f32 as Float>::EPSILON.to_f64(), f32::EPSILON as f64);
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(<f64 as Float>::from_u64(1 << 53), 9_007_199_254_740_992.0);
+        // В f32 точно представимы только 24 бита
+        assert_eq!(<f32 as Float>::from_u64((1 << 24) + 1), 16_777_216.0);
+        assert_eq!(<f32 as Float>::from_f64(0.1).to_f64(), 0.1f32 as f64);
+        assert!(<f64 as Float>::from_f64(f64::NAN).is_nan());
+        assert!(!Float::is_finite(<f32 as Float>::from_f64(1e40)));
+    }
+}
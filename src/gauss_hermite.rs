@@ -0,0 +1,140 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::summation::CompensatedSum;
+
+pub const GAUSS_HERMITE_MIN_ORDER: u32 = 1;
+pub const GAUSS_HERMITE_MAX_ORDER: u32 = 50;
+
+/// Формула Гаусса–Эрмита для ∫₋∞^∞ e^(−x²) g(x) dx по `order` узлам; точна
+/// для многочленов g степени до `2 * order - 1`.
+///
+/// Границ формула не принимает: вес e^(−x²) и бесконечная область заложены
+/// в узлы и веса, поэтому с функциями для конечного отрезка она не
+/// сочетается. Интеграл f без веса по (−∞, ∞) даёт
+/// `calculate_improper_integral` или эта формула с g(x) = f(x)·e^(x²).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaussHermiteRule {
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl GaussHermiteRule {
+    /// Узлы находятся методом Ньютона по нормированным многочленам Эрмита
+    /// с начальными приближениями по асимптотике корней.
+    pub fn new(order: u32) -> Result<Self, IntegralCalcError> {
+        if !(GAUSS_HERMITE_MIN_ORDER..=GAUSS_HERMITE_MAX_ORDER).contains(&order) {
+            return Err(IntegralCalcError::InvalidOrder {
+                order,
+                min: GAUSS_HERMITE_MIN_ORDER,
+                max: GAUSS_HERMITE_MAX_ORDER,
+            });
+        }
+        let n = order as usize;
+        let mut nodes = vec![0.0; n];
+        let mut weights = vec![0.0; n];
+        let mut z = 0.0;
+        // Корни симметричны: ищем от наибольшего к нулю
+        for i in 0..n.div_ceil(2) {
+            z = match i {
+                0 => (2.0 * n as f64 + 1.0).sqrt() - 1.85575 * (2.0 * n as f64 + 1.0).powf(-1.0 / 6.0),
+                1 => z - 1.14 * (n as f64).powf(0.426) / z,
+                2 => 1.86 * z - 0.86 * nodes[n - 1],
+                3 => 1.91 * z - 0.91 * nodes[n - 2],
+                _ => 2.0 * z - nodes[n - 1 - (i - 2)],
+            };
+            let mut derivative = 0.0;
+            for _ in 0..100 {
+                let (value, current_derivative) = hermite(n, z);
+                derivative = current_derivative;
+                let delta = value / derivative;
+                z -= delta;
+                if delta.abs() < 1e-15 * z.abs().max(1.0) {
+                    break;
+                }
+            }
+            nodes[n - 1 - i] = z;
+            nodes[i] = -z;
+            weights[n - 1 - i] = 2.0 / (derivative * derivative);
+            weights[i] = weights[n - 1 - i];
+        }
+        Ok(Self { nodes, weights })
+    }
+
+    pub fn order(&self) -> u32 {
+        self.nodes.len() as u32
+    }
+
+    pub fn nodes(&self) -> &[f64] {
+        &self.nodes
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// ∫₋∞^∞ e^(−x²) g(x) dx.
+    pub fn integrate<T, I>(&self, g: I) -> T
+    where
+        T: Float,
+        I: Integrand<T>,
+    {
+        let mut sum = CompensatedSum::new();
+        for (&node, &weight) in self.nodes.iter().zip(&self.weights) {
+            sum.add(T::from_f64(weight) * g.eval(T::from_f64(node)));
+        }
+        sum.value()
+    }
+}
+
+/// Нормированный многочлен Эрмита степени n в точке x и его производная.
+fn hermite(n: usize, x: f64) -> (f64, f64) {
+    let mut current = std::f64::consts::PI.powf(-0.25);
+    let mut previous = 0.0;
+    for j in 1..=n {
+        let next = x * (2.0 / j as f64).sqrt() * current - ((j - 1) as f64 / j as f64).sqrt() * previous;
+        previous = current;
+        current = next;
+    }
+    (current, (2.0 * n as f64).sqrt() * previous)
+}
+
+/// ∫₋∞^∞ e^(−x²) g(x) dx по формуле Гаусса–Эрмита порядка `order`.
+pub fn gauss_hermite<T, I>(g: I, order: u32) -> Result<T, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T>,
+{
+    Ok(GaussHermiteRule::new(order)?.integrate(g))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaussian_moments() {
+        let sqrt_pi = std::f64::consts::PI.sqrt();
+        assert!((gauss_hermite(|_: f64| 1.0, 1).unwrap() - sqrt_pi).abs() < 1e-14);
+        // ∫ x^(2k) e^(−x²) dx = (2k − 1)!!/2^k·√π, нечётные моменты равны нулю
+        let mut moment = sqrt_pi;
+        for k in 0..10 {
+            let order = k + 1;
+            let even = gauss_hermite(move |x: f64| x.powi(2 * k as i32), order).unwrap();
+            let odd = gauss_hermite(move |x: f64| x.powi(2 * k as i32 + 1), order).unwrap();
+            assert!((even - moment).abs() < 1e-12 * moment, "x^{}: {} {}", 2 * k, even, moment);
+            assert!(odd.abs() < 1e-10, "x^{}: {}", 2 * k + 1, odd);
+            moment *= (2 * k + 1) as f64 / 2.0;
+        }
+    }
+
+    #[test]
+    fn weights_sum_to_sqrt_pi() {
+        for order in [1, 7, 50] {
+            let rule = GaussHermiteRule::new(order).unwrap();
+            let sum: f64 = rule.weights().iter().sum();
+            assert!((sum - std::f64::consts::PI.sqrt()).abs() < 1e-12, "{}: {}", order, sum);
+        }
+        assert!(GaussHermiteRule::new(0).is_err());
+    }
+}
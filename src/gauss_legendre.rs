@@ -0,0 +1,173 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::integrate::calculate_integral_with_rule;
+use crate::options::IntegrationOptions;
+use crate::result::IntegrationResult;
+use crate::rule::IntegrationRule;
+
+pub const GAUSS_LEGENDRE_MIN_ORDER: u32 = 2;
+pub const GAUSS_LEGENDRE_MAX_ORDER: u32 = 64;
+
+/// Составная формула Гаусса–Лежандра: каждый отрезок сетки (панель)
+/// интегрируется по `order` узлам, формула точна для многочленов степени
+/// до `2 * order - 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaussLegendreRule {
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl GaussLegendreRule {
+    /// Узлы и веса на [-1, 1] находятся методом Ньютона по корням
+    /// многочлена Лежандра степени `order`.
+    pub fn new(order: u32) -> Result<Self, IntegralCalcError> {
+        if !(GAUSS_LEGENDRE_MIN_ORDER..=GAUSS_LEGENDRE_MAX_ORDER).contains(&order) {
+            return Err(IntegralCalcError::InvalidOrder {
+                order,
+                min: GAUSS_LEGENDRE_MIN_ORDER,
+                max: GAUSS_LEGENDRE_MAX_ORDER,
+            });
+        }
+        let n = order as usize;
+        let mut nodes = vec![0.0; n];
+        let mut weights = vec![0.0; n];
+        // Корни симметричны, поэтому ищем только положительную половину
+        for i in 0..n.div_ceil(2) {
+            let mut x = (std::f64::consts::PI * (i as f64 + 0.75) / (n as f64 + 0.5)).cos();
+            for _ in 0..100 {
+                let (value, derivative) = legendre(n, x);
+                let delta = value / derivative;
+                x -= delta;
+                if delta.abs() < 1e-15 {
+                    break;
+                }
+            }
+            let (_, derivative) = legendre(n, x);
+            let weight = 2.0 / ((1.0 - x * x) * derivative * derivative);
+            nodes[i] = -x;
+            nodes[n - 1 - i] = x;
+            weights[i] = weight;
+            weights[n - 1 - i] = weight;
+        }
+        Ok(Self { nodes, weights })
+    }
+
+    pub fn order(&self) -> u32 {
+        self.nodes.len() as u32
+    }
+}
+
+/// Значение многочлена Лежандра P_n(x) и его производной.
+fn legendre(n: usize, x: f64) -> (f64, f64) {
+    let mut previous = 1.0;
+    let mut current = x;
+    for k in 2..=n {
+        let next = ((2 * k - 1) as f64 * x * current - (k - 1) as f64 * previous) / k as f64;
+        previous = current;
+        current = next;
+    }
+    let derivative = n as f64 * (x * current - previous) / (x * x - 1.0);
+    (current, derivative)
+}
+
+impl<T: Float> IntegrationRule<T> for GaussLegendreRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        if samples == 0 {
+            return T::ZERO;
+        }
+        let step = (upper_bound - lower_bound) / T::from_u64(samples);
+        let half_step = step / T::from_f64(2.0);
+        let mut sum = T::ZERO;
+        for panel in 0..samples {
+            let middle = lower_bound + T::from_u64(panel) * step + half_step;
+            for (&node, &weight) in self.nodes.iter().zip(&self.weights) {
+                sum += T::from_f64(weight / 2.0) * f(middle + T::from_f64(node) * half_step);
+            }
+        }
+        sum
+    }
+
+    fn ordered_nodes(&self) -> bool {
+        false
+    }
+
+    fn order(&self) -> u32 {
+        2 * self.nodes.len() as u32
+    }
+}
+
+/// Интеграл по формуле Гаусса–Лежандра порядка `order` на `panels` равных
+/// частях [a, b]; части распределяются между потоками как обычные отрезки.
+pub fn gauss_legendre<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    order: u32,
+    panels: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples: panels, ..Default::default() };
+    gauss_legendre_with_options(f, lower_bound, upper_bound, order, &options)
+}
+
+/// То же, что `gauss_legendre`, с заданными параметрами расчёта;
+/// `options.samples` — число панелей.
+pub fn gauss_legendre_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    order: u32,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let rule = GaussLegendreRule::new(order)?;
+    calculate_integral_with_rule(&rule, f, lower_bound, upper_bound, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+    use crate::integrate::calculate_integral;
+
+    /// ∫₀¹ atan(x)/(x⁴+1) dx.
+    const ATAN_OVER_QUARTIC: f64 = 0.349446289808078;
+
+    #[test]
+    fn exact_on_polynomials() {
+        for order in [2, 3, 5, 10] {
+            for degree in 0..2 * order as i32 {
+                let f = move |x: f64| x.powi(degree);
+                let value = gauss_legendre(f, 0.0, 2.0, order, 1).unwrap().value;
+                let exact = 2f64.powi(degree + 1) / (degree + 1) as f64;
+                assert!((value - exact).abs() < 1e-12 * exact, "{} / x^{}: {}", order, degree, value);
+            }
+            // Степень 2·order уже не интегрируется точно
+            let f = move |x: f64| x.powi(2 * order as i32);
+            let exact = 2f64.powi(2 * order as i32 + 1) / (2 * order + 1) as f64;
+            assert!((gauss_legendre(f, 0.0, 2.0, order, 1).unwrap().value - exact).abs() > 1e-10);
+        }
+    }
+
+    #[test]
+    fn beats_midpoint_on_equal_evaluations() {
+        let gauss = gauss_legendre(AtanOverQuartic, 0.0, 1.0, 5, 20).unwrap();
+        let midpoint = calculate_integral(AtanOverQuartic, 0.0, 1.0, gauss.samples).unwrap();
+        assert_eq!(gauss.samples, 100);
+        let (gauss, midpoint) = ((gauss.value - ATAN_OVER_QUARTIC).abs(), (midpoint.value - ATAN_OVER_QUARTIC).abs());
+        assert!(gauss * 1e4 < midpoint, "{} {}", gauss, midpoint);
+    }
+
+    #[test]
+    fn order_is_checked() {
+        assert_eq!(GaussLegendreRule::new(1), Err(IntegralCalcError::InvalidOrder { order: 1, min: 2, max: 64 }));
+        assert!(GaussLegendreRule::new(64).is_ok());
+    }
+}
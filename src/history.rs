@@ -0,0 +1,369 @@
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::{Error as IOError, Write};
+use std::path::PathBuf;
+
+/// Размер истории по умолчанию, если не задан `--history-size`.
+pub const DEFAULT_HISTORY_SIZE: usize = 100;
+pub const MAX_HISTORY_SIZE: usize = 100_000;
+
+/// Запись истории сеанса `--repl`: условия расчёта и его итог.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Номер записи с единицы, по нему на неё ссылаются `recall N` и `$N`.
+    pub number: u64,
+    pub function: String,
+    pub method: String,
+    pub lower_bound: f64,
+    pub upper_bound: f64,
+    pub intervals: u64,
+    pub value: f64,
+    pub error_estimate: Option<f64>,
+}
+
+impl Entry {
+    /// Строка JSON Lines без перевода строки; бесконечность и NaN, которых
+    /// нет в JSON, записываются строками.
+    fn to_json(&self) -> String {
+        format!("{{\"number\":{},\"function\":{},\"method\":{},\"lower\":{},\"upper\":{},\"intervals\":{},\"value\":{},\"error_estimate\":{}}}",
+                self.number, json_string(&self.function), json_string(&self.method),
+                json_number(self.lower_bound), json_number(self.upper_bound), self.intervals,
+                json_number(self.value), self.error_estimate.map_or_else(|| "null".to_string(), json_number))
+    }
+
+    fn from_json(line: &str) -> Option<Self> {
+        let fields = parse_object(line)?;
+        let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+        let number = |name: &str| match field(name)? {
+            Field::Number(text) | Field::String(text) => text.parse::<f64>().ok(),
+            Field::Null => None,
+        };
+        let text = |name: &str| match field(name)? {
+            Field::String(text) => Some(text.clone()),
+            _ => None,
+        };
+        let integer = |name: &str| match field(name)? {
+            Field::Number(text) => text.parse::<u64>().ok(),
+            _ => None,
+        };
+        Some(Self {
+            number: integer("number")?,
+            function: text("function")?,
+            method: text("method")?,
+            lower_bound: number("lower")?,
+            upper_bound: number("upper")?,
+            intervals: integer("intervals")?,
+            value: number("value")?,
+            error_estimate: match field("error_estimate") {
+                None | Some(Field::Null) => None,
+                Some(_) => Some(number("error_estimate")?),
+            },
+        })
+    }
+}
+
+/// История сеанса: последние `capacity` записей, старые вытесняются.
+/// Номера записей не повторяются, вытесненная запись недоступна. При
+/// включённом сохранении каждая запись дописывается в файл.
+#[derive(Debug)]
+pub struct History {
+    entries: VecDeque<Entry>,
+    capacity: usize,
+    /// Номер последней записи.
+    count: u64,
+    file: Option<PathBuf>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: VecDeque::new(), capacity, count: 0, file: None }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn get(&self, number: u64) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.number == number)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    pub fn persistent(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Добавляет запись со следующим номером и возвращает номер; при
+    /// сохранении запись дописывается в файл. Ошибка записи отключает
+    /// сохранение, запись в истории сеанса остаётся.
+    pub fn push(&mut self, mut entry: Entry) -> Result<u64, IOError> {
+        self.count += 1;
+        entry.number = self.count;
+        let line = entry.to_json();
+        self.entries.push_back(entry);
+        self.evict();
+        if let Some(path) = &self.file {
+            if let Err(e) = append(path, &[line]) {
+                self.file = None;
+                return Err(e);
+            }
+        }
+        Ok(self.count)
+    }
+
+    /// Включает сохранение в `path`: записи файла идут первыми (номер записи
+    /// — её номер в файле), записи сеанса получают номера после них и
+    /// дописываются в файл. Возвращает число записей из файла и номера строк,
+    /// которые не удалось разобрать.
+    pub fn persist(&mut self, path: PathBuf) -> Result<(usize, Vec<usize>), IOError> {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut stored = Vec::new();
+        let mut skipped = Vec::new();
+        for (index, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            match Entry::from_json(line) {
+                Some(entry) => stored.push(entry),
+                None => skipped.push(index + 1),
+            }
+        }
+        let loaded = stored.len();
+        let mut count = stored.iter().map(|entry| entry.number).max().unwrap_or(0);
+        let session: Vec<Entry> = self.entries.drain(..).map(|mut entry| {
+            count += 1;
+            entry.number = count;
+            entry
+        }).collect();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        append(&path, &session.iter().map(Entry::to_json).collect::<Vec<_>>())?;
+        self.entries = stored.into_iter().chain(session).collect();
+        self.count = count;
+        self.file = Some(path);
+        self.evict();
+        Ok((loaded, skipped))
+    }
+
+    /// Отключает сохранение; файл остаётся.
+    pub fn forget(&mut self) {
+        self.file = None;
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Файл сохранённой истории: `$XDG_DATA_HOME/kryl_04/history.jsonl`, иначе
+/// `~/.local/share/kryl_04/history.jsonl`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME").filter(|value| !value.is_empty()) {
+        Some(base) => PathBuf::from(base),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".local").join("share"),
+    };
+    Some(base.join("kryl_04").join("history.jsonl"))
+}
+
+fn append(path: &PathBuf, lines: &[String]) -> Result<(), IOError> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let text: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+    file.write_all(text.as_bytes())
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Число без потери точности (`{:?}` даёт кратчайшую точную запись).
+pub fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:?}", value)
+    } else {
+        json_string(&value.to_string())
+    }
+}
+
+/// Значение поля объекта JSON; числа хранятся записью из файла.
+#[derive(Debug)]
+enum Field {
+    String(String),
+    Number(String),
+    Null,
+}
+
+/// Разбор плоского объекта JSON со строками, числами и `null`, каким его
+/// пишет `Entry::to_json`; вложенных объектов и массивов нет.
+fn parse_object(line: &str) -> Option<Vec<(String, Field)>> {
+    let mut chars = line.trim().chars().peekable();
+    let mut fields = Vec::new();
+    let skip = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    };
+    if chars.next()? != '{' {
+        return None;
+    }
+    skip(&mut chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+        return chars.next().is_none().then_some(fields);
+    }
+    loop {
+        skip(&mut chars);
+        if chars.next()? != '"' {
+            return None;
+        }
+        let key = parse_string(&mut chars)?;
+        skip(&mut chars);
+        if chars.next()? != ':' {
+            return None;
+        }
+        skip(&mut chars);
+        let value = match chars.peek()? {
+            '"' => {
+                chars.next();
+                Field::String(parse_string(&mut chars)?)
+            }
+            _ => {
+                let mut raw = String::new();
+                while chars.peek().is_some_and(|&c| c != ',' && c != '}' && !c.is_whitespace()) {
+                    raw.push(chars.next()?);
+                }
+                match raw.as_str() {
+                    "null" => Field::Null,
+                    raw if raw.parse::<f64>().is_ok() => Field::Number(raw.to_string()),
+                    _ => return None,
+                }
+            }
+        };
+        fields.push((key, value));
+        skip(&mut chars);
+        match chars.next()? {
+            ',' => continue,
+            '}' => return chars.next().is_none().then_some(fields),
+            _ => return None,
+        }
+    }
+}
+
+/// Строка JSON после открывающей кавычки, до закрывающей включительно.
+fn parse_string(chars: &mut impl Iterator<Item = char>) -> Option<String> {
+    let mut result = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(result),
+            '\\' => result.push(match chars.next()? {
+                '"' => '"',
+                '\\' => '\\',
+                '/' => '/',
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    char::from_u32(u32::from_str_radix(&code, 16).ok()?)?
+                }
+                _ => return None,
+            }),
+            c => result.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(value: f64) -> Entry {
+        Entry {
+            number: 0,
+            function: "sin(x)/x".to_string(),
+            method: "midpoint".to_string(),
+            lower_bound: 0.0,
+            upper_bound: 1.0,
+            intervals: 1000,
+            value,
+            error_estimate: None,
+        }
+    }
+
+    #[test]
+    fn ring_buffer_keeps_numbers() {
+        let mut history = History::new(2);
+        for value in [1.0, 2.0, 3.0] {
+            history.push(entry(value)).unwrap();
+        }
+        assert_eq!(history.get(1), None);
+        assert_eq!(history.get(3).map(|entry| entry.value), Some(3.0));
+        assert_eq!(history.entries().map(|entry| entry.number).collect::<Vec<_>>(), [2, 3]);
+        history.resize(1);
+        assert_eq!(history.entries().map(|entry| entry.number).collect::<Vec<_>>(), [3]);
+        assert_eq!(history.push(entry(4.0)).unwrap(), 4);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut original = entry(f64::NAN);
+        original.number = 7;
+        original.function = "\"f\"\\\n\t\u{1}ж".to_string();
+        original.lower_bound = f64::NEG_INFINITY;
+        original.upper_bound = 0.1 + 0.2;
+        original.error_estimate = Some(1e-300);
+        let line = original.to_json();
+        let parsed = Entry::from_json(&line).unwrap();
+        assert!(parsed.value.is_nan());
+        assert_eq!(Entry { value: 0.0, ..parsed }, Entry { value: 0.0, ..original });
+        assert_eq!(Entry::from_json(&entry(1.0).to_json()), Some(entry(1.0)));
+        for line in ["", "{}", "[1]", "{\"number\":1}", &line[..line.len() - 1], &format!("{} x", line)] {
+            assert_eq!(Entry::from_json(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn persistence_round_trip() {
+        let dir = std::env::temp_dir().join(format!("kryl_04_history_{}", std::process::id()));
+        let path = dir.join("history.jsonl");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut first = History::new(DEFAULT_HISTORY_SIZE);
+        first.push(entry(1.0)).unwrap();
+        assert_eq!(first.persist(path.clone()).unwrap(), (0, Vec::new()));
+        first.push(entry(2.0)).unwrap();
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"broken\n\n").unwrap();
+        // Записи сеанса нумеруются после записей файла
+        let mut second = History::new(DEFAULT_HISTORY_SIZE);
+        second.push(entry(3.0)).unwrap();
+        assert_eq!(second.persist(path.clone()).unwrap(), (2, vec![3]));
+        assert!(second.persistent());
+        let values: Vec<(u64, f64)> = second.entries().map(|entry| (entry.number, entry.value)).collect();
+        assert_eq!(values, [(1, 1.0), (2, 2.0), (3, 3.0)]);
+        second.forget();
+        second.push(entry(4.0)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 5);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
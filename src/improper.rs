@@ -0,0 +1,206 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::{Integrand, IntegrandRef};
+use crate::integrate::calculate_integral_with_options;
+use crate::options::{IntegrationOptions, ReversedBounds};
+use crate::request::{validate_request, IntegrationRequest};
+use crate::result::IntegrationResult;
+
+/// f на полубесконечном промежутке, перенесённая на [0, 1] заменой
+/// x = origin + direction·t/(1 − t), dx = dt/(1 − t)². В t = 1 (и там, где
+/// x уже не представим конечным числом) берётся предел при t → 1 (см.
+/// `limit`): у f, убывающей как x⁻², он не равен нулю, и формулы с узлом в
+/// конце отрезка (трапеции, Симпсон) без него теряли бы целую сумму h·g(1).
+struct Tail<'a, T, I: ?Sized> {
+    f: &'a I,
+    origin: T,
+    direction: T,
+}
+
+impl<T, I> Tail<'_, T, I>
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    /// Значение при 1 − t = `complement`, если x и якобиан конечны.
+    fn at(&self, complement: T) -> Option<T> {
+        if complement <= T::ZERO {
+            return None;
+        }
+        let x = self.origin + self.direction * (T::ONE - complement) / complement;
+        let jacobian = T::ONE / (complement * complement);
+        (x.is_finite() && jacobian.is_finite()).then(|| self.f.eval(x) * jacobian)
+    }
+
+    /// Предел при t → 1 по Ричардсону из двух точек левее:
+    /// 2·g(1 − δ) − g(1 − 2δ), погрешность O(δ²). Ноль, если g у конца
+    /// меняется не как гладкая функция (f убывает медленнее x⁻², и g
+    /// растёт к t = 1) или значения не конечны.
+    fn limit(&self) -> T {
+        let delta = T::EPSILON.powf(T::from_f64(1.0 / 3.0));
+        let limit = self.at(delta)
+            .zip(self.at(T::from_f64(2.0) * delta))
+            .filter(|&(near, far)| (near - far).abs() <= delta.sqrt() * near.abs().max(T::ONE))
+            .map(|(near, far)| T::from_f64(2.0) * near - far);
+        limit.filter(|limit| limit.is_finite()).unwrap_or(T::ZERO)
+    }
+}
+
+impl<T, I> Integrand<T> for Tail<'_, T, I>
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    fn eval(&self, t: T) -> T {
+        self.at(T::ONE - t).unwrap_or_else(|| self.limit())
+    }
+
+    fn name(&self) -> &str {
+        self.f.name()
+    }
+}
+
+pub(crate) fn is_infinite<T: Float>(value: T) -> bool {
+    !value.is_finite() && !value.is_nan()
+}
+
+/// Интеграл с возможно бесконечными границами. Конечные границы передаются
+/// `calculate_integral_with_options` без изменений; [a, ∞) и (−∞, b]
+/// заменой x = a ± t/(1 − t) сводятся к интегралу по [0, 1], а (−∞, ∞)
+/// делится в нуле на два таких, каждый с `samples` отсчётами.
+pub fn calculate_improper_integral<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    calculate_improper_integral_with_options(f, lower_bound, upper_bound, &options)
+}
+
+/// То же, что `calculate_improper_integral`, с заданными параметрами расчёта.
+/// `step` результата — шаг по t, `remainder_bound` не вычисляется.
+pub fn calculate_improper_integral_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    validate_request(&IntegrationRequest::new(lower_bound, upper_bound, options))?;
+    if lower_bound > upper_bound && options.reversed_bounds == ReversedBounds::Negate {
+        let mut result = calculate_improper_integral_with_options(f, upper_bound, lower_bound, options)
+            .map_err(|error| match error {
+                IntegralCalcError::Interrupted { estimate, completed } =>
+                    IntegralCalcError::Interrupted { estimate: -estimate, completed },
+                error => error,
+            })?;
+        result.value = -result.value;
+        return Ok(result);
+    }
+    let tail = |origin: T, direction: T| {
+        let tail = Tail { f: &f, origin, direction };
+        let mut result = calculate_integral_with_options(tail, T::ZERO, T::ONE, options)?;
+        result.remainder_bound = None;
+        result.singularity = result.singularity.map(|t| origin + direction * t / (T::ONE - t));
+        Ok::<_, IntegralCalcError>(result)
+    };
+    match (is_infinite(lower_bound), is_infinite(upper_bound)) {
+        (false, false) => calculate_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, options),
+        (false, true) => tail(lower_bound, T::ONE),
+        (true, false) => tail(upper_bound, -T::ONE),
+        (true, true) => {
+            // Прерванный хвост — половина всех отсчётов
+            let negative = tail(T::ZERO, -T::ONE).map_err(|error| match error {
+                IntegralCalcError::Interrupted { estimate, completed } =>
+                    IntegralCalcError::Interrupted { estimate, completed: completed / 2.0 },
+                error => error,
+            })?;
+            let positive = tail(T::ZERO, T::ONE).map_err(|error| match error {
+                IntegralCalcError::Interrupted { estimate, completed } => IntegralCalcError::Interrupted {
+                    estimate: negative.value.to_f64() + estimate,
+                    completed: 0.5 + completed / 2.0,
+                },
+                IntegralCalcError::Cancelled => IntegralCalcError::Interrupted {
+                    estimate: negative.value.to_f64(),
+                    completed: 0.5,
+                },
+                error => error,
+            })?;
+            Ok(IntegrationResult {
+                value: negative.value + positive.value,
+                intervals: negative.intervals + positive.intervals,
+                samples: negative.samples + positive.samples,
+                threads: negative.threads.max(positive.threads),
+                elapsed: negative.elapsed + positive.elapsed,
+                error_estimate: negative.error_estimate.zip(positive.error_estimate).map(|(left, right)| left + right),
+                singularity: negative.singularity.or(positive.singularity),
+                workers: [negative.workers, positive.workers].concat(),
+                ..positive
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::method::Method;
+
+    fn integral(method: Method, f: impl Integrand<f64> + Send + Sync, lower_bound: f64, upper_bound: f64) -> f64 {
+        let options = IntegrationOptions { samples: 10_000, method, ..Default::default() };
+        calculate_improper_integral_with_options(f, lower_bound, upper_bound, &options).unwrap().value
+    }
+
+    #[test]
+    fn cauchy_tail_with_closed_rules() {
+        // 1/(1 + x²) убывает как x⁻²: на t = 1 значение после замены равно 1
+        let cauchy = |x: f64| 1.0 / (1.0 + x * x);
+        for method in [Method::Trapezoid, Method::Simpson, Method::SimpsonThreeEighths, Method::Boole, Method::Midpoint] {
+            let value = integral(method, cauchy, 0.0, f64::INFINITY);
+            assert!((value - std::f64::consts::FRAC_PI_2).abs() < 1e-8, "{:?}: {}", method, value);
+            let value = integral(method, cauchy, f64::NEG_INFINITY, f64::INFINITY);
+            assert!((value - std::f64::consts::PI).abs() < 2e-8, "{:?}: {}", method, value);
+        }
+    }
+
+    #[test]
+    fn fast_decay_has_zero_limit() {
+        let gauss = |x: f64| (-x * x).exp();
+        let tail = Tail { f: &gauss, origin: 0.0, direction: 1.0 };
+        assert_eq!(tail.eval(1.0), 0.0);
+        let value = integral(Method::Simpson, gauss, f64::NEG_INFINITY, f64::INFINITY);
+        assert!((value - std::f64::consts::PI.sqrt()).abs() < 1e-12, "{}", value);
+    }
+
+    #[test]
+    fn endpoint_limit() {
+        let cauchy = |x: f64| 1.0 / (1.0 + x * x);
+        let tail = Tail { f: &cauchy, origin: 0.0, direction: 1.0 };
+        assert!((tail.eval(1.0) - 1.0).abs() < 1e-9);
+        // Здесь g(1 − c) = 1/(c² + (3c − 1)²), и у предела по Ричардсону погрешность O(δ²)
+        let shifted = Tail { f: &cauchy, origin: 2.0, direction: -1.0 };
+        assert!((shifted.eval(1.0) - 1.0).abs() < 1e-7);
+    }
+
+    #[test]
+    fn slow_decay_limit_is_zero() {
+        let slow = |x: f64| 1.0 / (1.0 + x).powf(1.5);
+        let tail = Tail { f: &slow, origin: 0.0, direction: 1.0 };
+        assert_eq!(tail.limit(), 0.0);
+    }
+
+    #[test]
+    fn finite_bounds_unchanged() {
+        let square = |x: f64| x * x;
+        let value = integral(Method::Simpson, square, 0.0, 3.0);
+        assert!((value - 9.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+
+/// Ответы на несколько идущих подряд вопросов: в одной строке можно ввести
+/// сразу несколько ответов через пробел, тогда следующие вопросы не задаются.
+#[derive(Debug, Default)]
+pub struct Answers {
+    remaining: usize,
+    pending: VecDeque<String>,
+    /// Последний ответ строки — весь её остаток, вместе с пробелами.
+    tail: bool,
+}
+
+/// Во введённой строке больше ответов, чем осталось вопросов.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnexpectedToken(pub String);
+
+/// Ответ, который не удалось принять.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError<E> {
+    /// Ответ не разобран; `E` — ошибка разбора.
+    Parse(E),
+    UnexpectedToken(UnexpectedToken),
+}
+
+impl Answers {
+    /// Ответы на `questions` вопросов.
+    pub fn new(questions: usize) -> Self {
+        Self { remaining: questions, pending: VecDeque::new(), tail: false }
+    }
+
+    /// Ответы, в которых строка с ответом на последний вопрос принимается
+    /// целиком, вместе с пробелами: так на вопрос о числе отсчётов можно
+    /// ответить `1 000 000`. В строке с ответами на несколько вопросов
+    /// пробелы по-прежнему разделяют ответы.
+    pub fn with_tail(mut self) -> Self {
+        self.tail = true;
+        self
+    }
+
+    /// Ответ на очередной вопрос: из уже введённой строки, а если её ответы
+    /// кончились — из новой строки, которую возвращает `read`. Пустая строка
+    /// — пустой ответ.
+    pub fn next(&mut self, read: impl FnOnce() -> String) -> Result<String, UnexpectedToken> {
+        if self.pending.is_empty() {
+            let line = read();
+            let line = line.trim();
+            self.pending = if self.tail && self.remaining <= 1 {
+                (!line.is_empty()).then(|| line.to_string()).into_iter().collect()
+            } else {
+                split_answers(line, self.remaining.max(1))?.into()
+            };
+        }
+        self.remaining = self.remaining.saturating_sub(1);
+        Ok(self.pending.pop_front().unwrap_or_default())
+    }
+
+    /// Очередной ответ, разобранный `parse`. Если ответ не принят, остаток
+    /// его строки отбрасывается, `on_error` получает ошибку и вопрос задаётся
+    /// заново через `read`, но не больше `retries` раз; после этого
+    /// возвращается последняя ошибка.
+    pub fn parse_next<V, E>(
+        &mut self,
+        mut read: impl FnMut() -> String,
+        parse: impl Fn(&str) -> Result<V, E>,
+        mut on_error: impl FnMut(&InputError<E>),
+        retries: u32,
+    ) -> Result<V, InputError<E>> {
+        for _ in 0..retries {
+            match self.try_next(&mut read, &parse) {
+                Err(error) => {
+                    on_error(&error);
+                    if matches!(error, InputError::Parse(_)) {
+                        // Вопрос, на который ответ не принят, остаётся открытым
+                        self.pending.clear();
+                        self.remaining += 1;
+                    }
+                }
+                result => return result,
+            }
+        }
+        self.try_next(&mut read, &parse)
+    }
+
+    fn try_next<V, E>(
+        &mut self,
+        read: &mut impl FnMut() -> String,
+        parse: &impl Fn(&str) -> Result<V, E>,
+    ) -> Result<V, InputError<E>> {
+        let answer = self.next(read).map_err(InputError::UnexpectedToken)?;
+        parse(&answer).map_err(InputError::Parse)
+    }
+}
+
+/// Разбивает строку на не больше чем `remaining` ответов.
+pub fn split_answers(line: &str, remaining: usize) -> Result<Vec<String>, UnexpectedToken> {
+    let tokens: Vec<String> = line.split_whitespace().map(String::from).collect();
+    if let Some(token) = tokens.get(remaining) {
+        return Err(UnexpectedToken(token.clone()));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_are_limited() {
+        let mut reads = 0;
+        let mut errors = 0;
+        let value = Answers::new(1).parse_next(
+            || {
+                reads += 1;
+                "1..5".to_string()
+            },
+            |line| line.parse::<f64>(),
+            |_| errors += 1,
+            5,
+        );
+        assert!(matches!(value, Err(InputError::Parse(_))));
+        // Первая попытка и пять повторов; последняя ошибка возвращается, а не печатается
+        assert_eq!((reads, errors), (6, 5));
+    }
+
+    #[test]
+    fn no_retries_fail_fast() {
+        let mut lines = ["1..5", "1.5"].into_iter();
+        let value = Answers::new(1).parse_next(|| lines.next().unwrap().to_string(), |line| line.parse::<f64>(), |_| unreachable!(), 0);
+        assert!(matches!(value, Err(InputError::Parse(_))));
+        assert_eq!(lines.next(), Some("1.5"));
+    }
+
+    #[test]
+    fn line_with_unexpected_token_is_asked_again() {
+        let mut lines = ["0 1 2", "3"].into_iter();
+        let mut errors = Vec::new();
+        let value = Answers::new(2).parse_next(|| lines.next().unwrap().to_string(), |line| line.parse::<i32>(), |error| errors.push(error.clone()), 1);
+        assert_eq!(errors, [InputError::UnexpectedToken(UnexpectedToken("2".to_string()))]);
+        assert_eq!(value, Ok(3));
+    }
+
+    /// Ответы на `questions` вопросов из строк `lines`.
+    fn answers(mut answers: Answers, questions: usize, lines: &[&str]) -> Result<Vec<String>, UnexpectedToken> {
+        let mut lines = lines.iter();
+        (0..questions).map(|_| answers.next(|| lines.next().unwrap_or(&"").to_string())).collect()
+    }
+
+    #[test]
+    fn several_answers_in_one_line() {
+        assert_eq!(answers(Answers::new(3), 3, &["0 1 1000"]).unwrap(), ["0", "1", "1000"]);
+        assert_eq!(answers(Answers::new(3), 3, &["0 1", "1000"]).unwrap(), ["0", "1", "1000"]);
+        assert_eq!(answers(Answers::new(2), 2, &["0 1 2"]), Err(UnexpectedToken("2".to_string())));
+    }
+
+    #[test]
+    fn tail_only_for_single_answer_line() {
+        let tail = || Answers::new(3).with_tail();
+        assert_eq!(answers(tail(), 3, &["0", "1", "1 000 000"]).unwrap(), ["0", "1", "1 000 000"]);
+        assert_eq!(answers(tail(), 3, &["0 1", " 1 000 "]).unwrap(), ["0", "1", "1 000"]);
+        // В строке с несколькими ответами лишнее слово — ошибка, а не часть числа
+        assert_eq!(answers(tail(), 3, &["0 1 100 5"]), Err(UnexpectedToken("5".to_string())));
+        assert_eq!(answers(tail(), 3, &["0 1 2 3 4"]), Err(UnexpectedToken("3".to_string())));
+    }
+
+    #[test]
+    fn empty_line_is_empty_answer() {
+        assert_eq!(answers(Answers::new(2).with_tail(), 2, &["", ""]).unwrap(), ["", ""]);
+    }
+
+    #[test]
+    fn rejected_answer_is_asked_again() {
+        let mut answers = Answers::new(2);
+        let mut lines = ["x 1", "0 1"].into_iter();
+        let mut errors = 0;
+        let value = answers.parse_next(|| lines.next().unwrap().to_string(), |line| line.parse::<i32>(), |_| errors += 1, 3);
+        assert_eq!(value, Ok(0));
+        assert_eq!(errors, 1);
+        assert_eq!(answers.next(String::new), Ok("1".to_string()));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::dual::second_derivative_auto;
+use crate::float::Float;
+
+/// Подынтегральная функция вместе с необязательными сведениями о ней.
+pub trait Integrand<T: Float = f64> {
+    fn eval(&self, x: T) -> T;
+
+    /// Аналитическая первая производная, если она известна.
+    fn first_derivative(&self, _x: T) -> Option<T> {
+        None
+    }
+
+    /// Аналитическая вторая производная, если она известна.
+    fn second_derivative(&self, _x: T) -> Option<T> {
+        None
+    }
+
+    fn name(&self) -> &str {
+        "f(x)"
+    }
+}
+
+impl<T, F> Integrand<T> for F
+where
+    T: Float,
+    F: Fn(T) -> T,
+{
+    fn eval(&self, x: T) -> T {
+        self(x)
+    }
+}
+
+/// Ссылка на подынтегральную функцию, сохраняющая её производную и имя,
+/// чтобы одну функцию можно было интегрировать несколько раз.
+pub(crate) struct IntegrandRef<'a, I: ?Sized>(pub(crate) &'a I);
+
+impl<T, I> Integrand<T> for IntegrandRef<'_, I>
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    fn eval(&self, x: T) -> T {
+        self.0.eval(x)
+    }
+
+    fn first_derivative(&self, x: T) -> Option<T> {
+        self.0.first_derivative(x)
+    }
+
+    fn second_derivative(&self, x: T) -> Option<T> {
+        self.0.second_derivative(x)
+    }
+
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+}
+
+/// Аналитическая производная порядка `order` (известны первая и вторая).
+pub(crate) fn analytic_derivative<T, I>(f: &I, x: T, order: u32) -> Option<T>
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    match order {
+        0 => Some(f.eval(x)),
+        1 => f.first_derivative(x),
+        2 => f.second_derivative(x),
+        _ => None,
+    }
+}
+
+/// Вторая производная по центральной разностной схеме.
+pub fn numeric_second_derivative<T, I>(f: &I, x: T) -> T
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    let h = T::EPSILON.powf(T::from_f64(0.25)) * x.abs().max(T::ONE);
+    (f.eval(x + h) - T::from_f64(2.0) * f.eval(x) + f.eval(x - h)) / (h * h)
+}
+
+/// Производная порядка `order` по центральной разностной схеме.
+pub fn numeric_derivative<T, I>(f: &I, x: T, order: u32) -> T
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    if order == 0 {
+        return f.eval(x);
+    }
+    difference(f, x, order, difference_step(x, order), T::from_f64(order as f64 / 2.0))
+}
+
+/// Производная порядка `order` по конечным разностям, не выходящим за
+/// [lower_bound, upper_bound]: если центральная схема не помещается в
+/// отрезок, берётся односторонняя в сторону его середины. Для первой и
+/// второй производных односторонние схемы второго порядка точности, для
+/// остальных — разность вперёд (назад), точная до O(h).
+pub fn numeric_derivative_within<T, I>(f: &I, x: T, order: u32, lower_bound: T, upper_bound: T) -> T
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    if order == 0 {
+        return f.eval(x);
+    }
+    // Односторонней схеме нужно не больше order + 1 шагов
+    let h = difference_step(x, order).min((upper_bound - lower_bound) / T::from_u64(order as u64 + 1));
+    let half_width = T::from_f64(order as f64 / 2.0) * h;
+    if x - half_width >= lower_bound && x + half_width <= upper_bound {
+        return difference(f, x, order, h, T::from_f64(order as f64 / 2.0));
+    }
+    // При отрицательном шаге те же формулы дают схему назад
+    let h = if x - half_width < lower_bound { h } else { -h };
+    let at = |k: T| f.eval(x + k * h);
+    let [zero, one, two, three] = [0.0, 1.0, 2.0, 3.0].map(T::from_f64);
+    match order {
+        1 => (-three * at(zero) + T::from_f64(4.0) * at(one) - at(two)) / (two * h),
+        2 => (two * at(zero) - T::from_f64(5.0) * at(one) + T::from_f64(4.0) * at(two) - at(three)) / (h * h),
+        _ => difference(&at, T::ZERO, order, T::ONE, T::from_u64(order as u64)) / h.powi(order as i32),
+    }
+}
+
+/// Шаг разностной схемы для производной порядка `order` в точке x.
+fn difference_step<T: Float>(x: T, order: u32) -> T {
+    // Шаг растёт с порядком производной, чтобы ошибка округления,
+    // делённая на h^order, не забивала результат
+    T::EPSILON.powf(T::ONE / T::from_u64(order as u64 + 2)) * x.abs().max(T::ONE)
+}
+
+/// Конечная разность порядка `order` с шагом h по узлам x + (shift − j)·h,
+/// j = 0..=order, делённая на h^order.
+fn difference<T, I>(f: &I, x: T, order: u32, h: T, shift: T) -> T
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    let mut binomial = 1.0;
+    let mut sum = T::ZERO;
+    for j in 0..=order {
+        let offset = (shift - T::from_u64(j as u64)) * h;
+        let term = T::from_f64(binomial) * f.eval(x + offset);
+        if j % 2 == 0 {
+            sum += term;
+        } else {
+            sum -= term;
+        }
+        binomial = binomial * (order - j) as f64 / (j + 1) as f64;
+    }
+    sum / h.powi(order as i32)
+}
+
+/// Встроенная функция atan(x)/(x^4+1).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AtanOverQuartic;
+
+impl<T: Float> Integrand<T> for AtanOverQuartic {
+    fn eval(&self, x: T) -> T {
+        x.atan() / (x.powi(4) + T::ONE)
+    }
+
+    fn first_derivative(&self, x: T) -> Option<T> {
+        Some(
+            (T::ONE/(x.powi(6) + x.powi(4) + x.powi(2) + T::ONE)) -
+                (T::from_f64(4.0)*x.powi(3)*x.atan()) / (x.powi(8) + T::from_f64(2.0)*x.powi(4) + T::ONE)
+        )
+    }
+
+    /// Точная вторая производная через `Dual2`.
+    fn second_derivative(&self, x: T) -> Option<T> {
+        Some(second_derivative_auto(self, x))
+    }
+
+    fn name(&self) -> &str {
+        "atan(x)/(x^4+1)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrate::calculate_integral;
+
+    #[test]
+    fn f32_and_f64_agree() {
+        let single = calculate_integral(AtanOverQuartic, 0.0f32, 1.0, 1000).unwrap();
+        let double = calculate_integral(AtanOverQuartic, 0.0f64, 1.0, 1000).unwrap();
+        assert!((single.value as f64 - double.value).abs() < 1e-5, "{} {}", single.value, double.value);
+    }
+
+    #[test]
+    fn numeric_second_derivative_of_simple_functions() {
+        let cube = |x: f64| x * x * x;
+        for x in [-2.0, -0.3, 0.0, 0.7, 5.0] {
+            let cube_second = numeric_second_derivative(&cube, x);
+            assert!((cube_second - 6.0 * x).abs() < 1e-5 * (6.0 * x).abs().max(1.0), "{}: {}", x, cube_second);
+            let sine_second = numeric_second_derivative(&f64::sin, x);
+            assert!((sine_second + x.sin()).abs() < 1e-5, "{}: {}", x, sine_second);
+        }
+    }
+
+    #[test]
+    fn one_sided_stencils_stay_within_bounds() {
+        let (lower, upper) = (0.0, 1.0);
+        let sine = |x: f64| {
+            assert!((lower..=upper).contains(&x), "{}", x);
+            x.sin()
+        };
+        for x in [lower, 1e-9, 0.5, upper - 1e-9, upper] {
+            let second = numeric_derivative_within(&sine, x, 2, lower, upper);
+            assert!((second + x.sin()).abs() < 1e-4, "{}: {}", x, second);
+            let first = numeric_derivative_within(&sine, x, 1, lower, upper);
+            assert!((first - x.cos()).abs() < 1e-8, "{}: {}", x, first);
+        }
+    }
+
+    #[test]
+    fn remaining_term_falls_back_to_numeric_derivative() {
+        let cube = |x: f64| x * x * x;
+        // max|f″| = 6 на [0, 1], граница (b−a)·h²/24·max|f″|
+        let bound = crate::integrate::get_remaining_term(&cube, 0.0, 1.0, 0.01);
+        assert!((bound - 1e-4 / 4.0).abs() < 1e-9, "{}", bound);
+    }
+
+    #[test]
+    fn builtin_derivatives_match_differences() {
+        for x in [0.0, 0.1, 0.35, 0.5, 0.8, 1.0, 1.7] {
+            let second = Integrand::<f64>::second_derivative(&AtanOverQuartic, x).unwrap();
+            let numeric = numeric_second_derivative(&AtanOverQuartic, x);
+            assert!((second - numeric).abs() < 1e-5, "{}: {} != {}", x, second, numeric);
+            let first = Integrand::<f64>::first_derivative(&AtanOverQuartic, x).unwrap();
+            assert!((first - numeric_derivative(&AtanOverQuartic, x, 1)).abs() < 1e-8, "{}", x);
+        }
+    }
+
+    #[test]
+    fn remaining_term_bounds_builtin_error() {
+        // ∫₀¹ atan(x)/(x⁴+1) dx
+        let exact = 0.349446289808078;
+        for samples in [10, 100, 1000] {
+            let result = calculate_integral(AtanOverQuartic, 0.0, 1.0, samples).unwrap();
+            let bound = crate::integrate::get_remaining_term(&AtanOverQuartic, 0.0, 1.0, result.step);
+            let error = (result.value - exact).abs();
+            assert!(error <= bound && bound < 10.0 * error, "{}: {} / {}", samples, error, bound);
+        }
+    }
+}
@@ -0,0 +1,967 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::ScopedJoinHandle;
+use std::time::Instant;
+
+use crate::cancel::{CancelToken, CANCEL_CHECK_INTERVAL};
+pub use crate::error::IntegralCalcError;
+use crate::error_bound::error_bound;
+use crate::float::Float;
+use crate::integrand::{analytic_derivative, numeric_derivative_within, Integrand};
+use crate::integrator::Integrator;
+use crate::method::Method;
+use crate::options::{ExecutionMode, IntegrationOptions};
+use crate::progress::{ProgressHook, ProgressReporter};
+use crate::result::{IntegrationResult, WorkerReport};
+use crate::rule::IntegrationRule;
+use crate::singularity::{SingularityCheck, SingularityScan};
+use crate::summation::{pairwise_sum, CompensatedSum};
+use crate::stream::IntegrationStream;
+
+/// Сумма значений f в серединах отрезков шага `step` на [lower_bound, upper_bound].
+///
+/// Число отрезков — `(upper_bound - lower_bound) / step`, округлённое до
+/// целого; середина k-го отрезка считается как lower_bound + (k + ½)·step,
+/// так что ошибка округления шага не накапливается и отрезки не теряются.
+pub fn calculate_accumulated_sum_on_range<T, F>(
+    f: &mut F,
+    lower_bound: T,
+    upper_bound: T,
+    step: T
+) -> T
+where
+    T: Float,
+    F: FnMut(T) -> T + ?Sized,
+{
+    let half_step = step / T::from_f64(2.0);
+    let samples = ((upper_bound - lower_bound) / step).to_f64().round().max(0.0) as u64;
+    let mut local_sum = CompensatedSum::new();
+    for index in 0..samples {
+        local_sum.add(f(lower_bound + T::from_u64(index) * step + half_step));
+    }
+    local_sum.value()
+}
+
+/// Длина порции `RuleCursor` в отрезках сетки (округляется вниз до кратного
+/// `granularity()`, но не меньше него).
+pub(crate) const CURSOR_PIECE_SAMPLES: u64 = 4096;
+
+/// Проход по заданному числу отрезков общей сетки по формуле `rule`,
+/// который можно продолжать участками.
+///
+/// Отрезки проходятся порциями одной длины от начала прохода, а суммы порций
+/// складываются компенсированно. Границы порций не зависят от того, сколько
+/// отрезков запрошено за один `advance`, поэтому ни частота проверок отмены
+/// и хода расчёта, ни порции `IntegrationStream` не меняют итог (в том
+/// числе у метода Монте-Карло, который берёт на каждой порции свои точки).
+#[derive(Debug, Clone)]
+pub(crate) struct RuleCursor<T: Float> {
+    lower_bound: T,
+    step: T,
+    samples: u64,
+    /// Длина порции, см. `CURSOR_PIECE_SAMPLES`.
+    piece: u64,
+    pub(crate) done: u64,
+    /// Сумма пройденных участков, см. `total`.
+    pub(crate) sum: T,
+    total: CompensatedSum<T>,
+    /// Сумма оценок погрешности участков; `None`, если формула их не даёт.
+    pub(crate) error: Option<T>,
+    pub(crate) evaluations: u64,
+    /// Проверка на особенности, если она включена.
+    pub(crate) singularity: Option<SingularityScan<T>>,
+}
+
+impl<T: Float> RuleCursor<T> {
+    pub(crate) fn new(lower_bound: T, step: T, samples: u64) -> Self {
+        Self {
+            lower_bound,
+            step,
+            samples,
+            piece: CURSOR_PIECE_SAMPLES,
+            done: 0,
+            sum: T::ZERO,
+            total: CompensatedSum::new(),
+            error: Some(T::ZERO),
+            evaluations: 0,
+            singularity: None,
+        }
+    }
+
+    /// Проходит следующие порции, пока не наберётся `max_samples` отрезков
+    /// (хотя бы одну); возвращает `true`, когда отрезки пройдены.
+    pub(crate) fn advance<R, F>(&mut self, rule: &R, f: &mut F, max_samples: u64) -> bool
+    where
+        R: IntegrationRule<T> + ?Sized,
+        F: FnMut(T) -> T + ?Sized,
+    {
+        let mut budget = max_samples.max(1);
+        while budget > 0 && self.done < self.samples {
+            let count = self.advance_piece(rule, f);
+            budget = budget.saturating_sub(count);
+        }
+        self.done == self.samples
+    }
+
+    /// Проходит одну порцию; возвращает число её отрезков.
+    fn advance_piece<R, F>(&mut self, rule: &R, f: &mut F) -> u64
+    where
+        R: IntegrationRule<T> + ?Sized,
+        F: FnMut(T) -> T + ?Sized,
+    {
+        let granularity = rule.granularity().max(1);
+        let count = (self.samples - self.done).min(self.piece.max(granularity) / granularity * granularity);
+        if count > 0 {
+            let piece_lower_bound = self.lower_bound + T::from_u64(self.done) * self.step;
+            let piece_upper_bound = self.lower_bound + T::from_u64(self.done + count) * self.step;
+            let evaluations = &mut self.evaluations;
+            let singularity = &mut self.singularity;
+            let mut counted = |x| {
+                *evaluations += 1;
+                let value = f(x);
+                if let Some(singularity) = singularity.as_mut() {
+                    singularity.observe(x, value);
+                }
+                value
+            };
+            let (sum, error) = rule.accumulate_with_error(&mut counted, piece_lower_bound, piece_upper_bound, count);
+            self.total.add(sum);
+            self.sum = self.total.value();
+            self.error = self.error.zip(error).map(|(total, error)| total + error);
+            self.done += count;
+        }
+        count
+    }
+}
+
+/// Итог прохода: сумма формулы (или, после `scaled`, значение интеграла),
+/// оценка погрешности в тех же единицах, число вычислений f, первая
+/// подозрительная на особенность точка и число пройденных отрезков (меньше
+/// заданного, если проход прерван).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Accumulated<T: Float> {
+    pub(crate) sum: T,
+    pub(crate) error: Option<T>,
+    pub(crate) evaluations: u64,
+    pub(crate) singularity: Option<T>,
+    pub(crate) done: u64,
+}
+
+impl<T: Float> Accumulated<T> {
+    pub(crate) fn scaled<R>(self, rule: &R, lower_bound: T, upper_bound: T, samples: u64) -> Self
+    where
+        R: IntegrationRule<T> + ?Sized,
+    {
+        Self {
+            sum: rule.scale(self.sum, lower_bound, upper_bound, samples),
+            error: self.error.map(|error| rule.scale_error(error, self.sum, lower_bound, upper_bound, samples)),
+            evaluations: self.evaluations,
+            singularity: self.singularity,
+            done: self.done,
+        }
+    }
+}
+
+/// Проходит `samples` отрезков шага `step` от `lower_bound` по формуле `rule`
+/// участками, между которыми проверяет флаг отмены и сообщает о ходе расчёта.
+/// При отмене возвращает накопленное к этому моменту (`done < samples`).
+pub(crate) fn accumulate_with_control<T, R, F>(
+    rule: &R,
+    f: &mut F,
+    lower_bound: T,
+    step: T,
+    samples: u64,
+    control: &RunControl,
+) -> Result<Accumulated<T>, IntegralCalcError>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+    F: FnMut(T) -> T + ?Sized,
+{
+    let interval = control.check_interval();
+    let mut cursor = RuleCursor::new(lower_bound, step, samples);
+    cursor.singularity = control.singularity.map(|check| SingularityScan::new(check, rule.ordered_nodes()));
+    let non_finite = Cell::new(None);
+    // После первого значения, не являющегося конечным числом, f больше не
+    // вычисляется ни в этом, ни в других потоках
+    let mut guarded = |x| match control.non_finite_stop {
+        Some(stop) if stop.load(Ordering::Relaxed) => T::ZERO,
+        Some(stop) => {
+            let value = f(x);
+            if !value.is_finite() {
+                non_finite.set(Some(x));
+                stop.store(true, Ordering::Relaxed);
+            }
+            value
+        }
+        None => f(x),
+    };
+    loop {
+        let before = cursor.done;
+        let finished = cursor.advance(rule, &mut guarded, interval);
+        if let Some(x) = non_finite.get() {
+            return Err(IntegralCalcError::NonFiniteValue { x: x.to_f64() });
+        }
+        if let Some(progress) = control.progress {
+            progress.report(cursor.done - before);
+        }
+        if finished || control.is_cancelled() {
+            return Ok(Accumulated {
+                sum: cursor.sum,
+                error: cursor.error,
+                evaluations: cursor.evaluations,
+                singularity: cursor.singularity.and_then(|singularity| singularity.found),
+                done: cursor.done,
+            });
+        }
+    }
+}
+
+/// Проверяет, что участки `parts` сетки из `samples` отрезков пройдены до
+/// конца. Если проход прерван, возвращает `IntegralCalcError::Interrupted`
+/// с грубой оценкой: накопленная сумма пересчитывается так, будто пройденные
+/// отрезки покрывают весь промежуток.
+fn check_interrupted<T, R>(
+    rule: &R,
+    parts: &[Accumulated<T>],
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<(), IntegralCalcError>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+{
+    let done: u64 = parts.iter().map(|part| part.done).sum();
+    match done {
+        done if done == samples => Ok(()),
+        0 => Err(IntegralCalcError::Cancelled),
+        done => Err(IntegralCalcError::Interrupted {
+            estimate: rule.scale(reduce_parts(parts).sum, lower_bound, upper_bound, done).to_f64(),
+            completed: done as f64 / samples as f64,
+        }),
+    }
+}
+
+/// Необязательные средства управления расчётом, общие для всех рабочих потоков.
+#[derive(Default)]
+pub(crate) struct RunControl<'a> {
+    pub(crate) cancel: Option<&'a CancelToken>,
+    pub(crate) progress: Option<&'a ProgressReporter<'a>>,
+    pub(crate) singularity: Option<&'a SingularityCheck>,
+    /// Общий флаг остановки по значению f, не являющемуся конечным числом;
+    /// `None` — такие значения складываются как есть.
+    pub(crate) non_finite_stop: Option<&'a AtomicBool>,
+}
+
+impl RunControl<'_> {
+    /// Сколько вычислений делать между проверками; без отмены и обработчика
+    /// хода расчёта отрезки проходятся за один раз.
+    fn check_interval(&self) -> u64 {
+        let cancel = self.cancel.map_or(u64::MAX, |_| CANCEL_CHECK_INTERVAL);
+        let progress = self.progress.map_or(u64::MAX, |progress| progress.granularity());
+        cancel.min(progress)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(|cancel| cancel.is_cancelled())
+    }
+}
+
+pub const THREADS_COUNT: usize = 32;
+pub const MAX_SAMPLES_COUNT: u64 = 1_000_000_000u64;
+pub const ASYNC_THRESHOLD_SAMPLES_COUNT: u64 = 10_000u64;
+
+/// Однопоточный проход по тем же участкам `reduction_blocks`, что и
+/// `integrate_threaded`, со свёрткой в том же порядке: результат совпадает
+/// с многопоточным до последнего бита.
+pub(crate) fn integrate_sequential<T, R, F>(
+    rule: &R,
+    f: &mut F,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    control: &RunControl,
+) -> Result<Accumulated<T>, IntegralCalcError>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+    F: FnMut(T) -> T + ?Sized,
+{
+    let step = (upper_bound - lower_bound) / T::from_u64(samples);
+    let blocks = reduction_blocks(lower_bound, upper_bound, samples, rule.granularity());
+    let parts = accumulate_blocks(rule, f, &blocks, step, control)?;
+    check_interrupted(rule, &parts, lower_bound, upper_bound, samples)?;
+    Ok(reduce_parts(&parts).scaled(rule, lower_bound, upper_bound, samples))
+}
+
+/// Проходит участки свёртки `blocks` по порядку; после прерванного участка
+/// следующие не начинаются.
+fn accumulate_blocks<T, R, F>(
+    rule: &R,
+    f: &mut F,
+    blocks: &[(T, u64)],
+    step: T,
+    control: &RunControl,
+) -> Result<Vec<Accumulated<T>>, IntegralCalcError>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+    F: FnMut(T) -> T + ?Sized,
+{
+    let mut parts = Vec::with_capacity(blocks.len());
+    for &(block_lower_bound, block_samples) in blocks {
+        let part = accumulate_with_control(rule, f, block_lower_bound, step, block_samples, control)?;
+        let interrupted = part.done < block_samples;
+        parts.push(part);
+        if interrupted {
+            break;
+        }
+    }
+    Ok(parts)
+}
+
+/// Переход от суммы значений в серединах отрезков к значению интеграла:
+/// сумма умножается на шаг (b − a) / samples.
+pub(crate) fn scale_sum<T: Float>(sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+    sum * (upper_bound - lower_bound) / T::from_u64(samples)
+}
+
+/// Число потоков по умолчанию — по числу доступных ядер.
+pub fn default_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(THREADS_COUNT)
+}
+
+/// Наибольшее число участков свёртки.
+pub(crate) const REDUCTION_BLOCKS: usize = 4096;
+
+/// Наименьшая длина участка свёртки в отрезках сетки.
+pub(crate) const MIN_BLOCK_SAMPLES: u64 = 1024;
+
+/// Участки свёртки в формате `thread_ranges`: не больше `REDUCTION_BLOCKS`,
+/// не короче `MIN_BLOCK_SAMPLES` (округлённого вверх до кратного
+/// `granularity`) отрезков, кроме случая, когда вся сетка короче. Разбиение
+/// не зависит от числа потоков: потоки только делят участки между собой, а
+/// суммы участков складываются одним и тем же попарным деревом, поэтому
+/// результат при любом числе потоков одинаков до последнего бита.
+pub(crate) fn reduction_blocks<T: Float>(lower_bound: T, upper_bound: T, samples: u64, granularity: u64) -> Vec<(T, u64)> {
+    thread_ranges(lower_bound, upper_bound, samples, block_count(samples, granularity), granularity)
+}
+
+fn block_count(samples: u64, granularity: u64) -> usize {
+    let granularity = granularity.max(1);
+    effective_threads(REDUCTION_BLOCKS, samples, MIN_BLOCK_SAMPLES.div_ceil(granularity) * granularity)
+}
+
+/// Сколько рабочих потоков из `threads_count` получат хотя бы по одному
+/// участку свёртки сетки из `samples` отрезков.
+pub(crate) fn worker_threads(threads_count: usize, samples: u64, granularity: u64) -> usize {
+    threads_count.min(block_count(samples, granularity)).max(1)
+}
+
+/// Сколько потоков из `threads_count` получат хотя бы по `granularity`
+/// отрезков из `samples`: лишним потокам работы не достаётся.
+pub(crate) fn effective_threads(threads_count: usize, samples: u64, granularity: u64) -> usize {
+    let blocks = samples / granularity.max(1);
+    threads_count.min(usize::try_from(blocks).unwrap_or(usize::MAX)).max(1)
+}
+
+/// Разбиение сетки из `samples` отрезков на части для рабочих потоков,
+/// число которых ограничено `effective_threads`.
+///
+/// Каждая часть — левая граница и число отрезков шага `(b - a) / samples`;
+/// границы частей совпадают с узлами общей сетки, число отрезков в каждой
+/// части кратно `granularity` (при кратном ему `samples`) и различается
+/// между потоками не больше чем на `granularity`.
+pub(crate) fn thread_ranges<T: Float>(
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    threads_count: usize,
+    granularity: u64,
+) -> Vec<(T, u64)> {
+    let step = (upper_bound - lower_bound) / T::from_u64(samples);
+    let threads = effective_threads(threads_count, samples, granularity) as u64;
+    let granularity = granularity.max(1);
+    let blocks = samples / granularity;
+    let node = |index: u64| if index == threads { samples } else { index * blocks / threads * granularity };
+    (0..threads)
+        .map(|index| {
+            let first = node(index);
+            (lower_bound + T::from_u64(first) * step, node(index + 1) - first)
+        })
+        .collect()
+}
+
+/// Свёртка частичных сумм попарным деревом по номеру участка, чтобы результат
+/// не зависел от порядка их завершения.
+pub(crate) fn reduce_partial_sums<T: Float>(partial_sums: &[(T, u64)]) -> (T, u64) {
+    let sums: Vec<T> = partial_sums.iter().map(|&(sum, _)| sum).collect();
+    (pairwise_sum(&sums), partial_sums.iter().map(|&(_, evaluations)| evaluations).sum())
+}
+
+/// Свёртка итогов участков в порядке их номеров.
+pub(crate) fn reduce_parts<T: Float>(parts: &[Accumulated<T>]) -> Accumulated<T> {
+    let sums: Vec<_> = parts.iter().map(|part| (part.sum, part.evaluations)).collect();
+    let (sum, evaluations) = reduce_partial_sums(&sums);
+    let error = parts.iter().try_fold(T::ZERO, |total, part| part.error.map(|error| total + error));
+    // Участки идут по возрастанию x, поэтому первая найденная точка — самая левая
+    let singularity = parts.iter().find_map(|part| part.singularity);
+    let done = parts.iter().map(|part| part.done).sum();
+    Accumulated { sum, error, evaluations, singularity, done }
+}
+
+/// Многопоточный проход: каждый поток получает собственный экземпляр
+/// подынтегральной функции от `factory` и считает по формуле `rule`
+/// подряд идущие участки свёртки `reduction_blocks`. Кроме итога возвращает
+/// участки потоков с временем их работы.
+pub(crate) fn integrate_threaded<T, R, M, F>(
+    rule: &R,
+    factory: &M,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    threads_count: usize,
+    control: &RunControl,
+) -> Result<(Accumulated<T>, Vec<WorkerReport<T>>), IntegralCalcError>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+    M: Fn() -> F + Sync + ?Sized,
+    F: FnMut(T) -> T,
+{
+    let step = (upper_bound - lower_bound) / T::from_u64(samples);
+    let blocks = reduction_blocks(lower_bound, upper_bound, samples, rule.granularity());
+    let threads = threads_count.clamp(1, blocks.len());
+    let origin = Instant::now();
+    // Потоки заимствуют factory, поэтому используем scope вместо spawn;
+    // суммы участков возвращаются из потоков и складываются по номеру участка
+    let parts = std::thread::scope(|scope| {
+        let handles: Vec<ScopedJoinHandle<_>> = (0..threads)
+            .map(|index| {
+                let blocks = &blocks[index * blocks.len() / threads..(index + 1) * blocks.len() / threads];
+                scope.spawn(move || {
+                    let start = Instant::now();
+                    let mut f = factory();
+                    accumulate_blocks(rule, &mut f, blocks, step, control).map(|parts| {
+                        let (first_lower_bound, _) = blocks[0];
+                        let intervals = blocks.iter().map(|&(_, block_samples)| block_samples).sum();
+                        let worker = WorkerReport {
+                            lower_bound: first_lower_bound,
+                            upper_bound: first_lower_bound + T::from_u64(intervals) * step,
+                            intervals,
+                            evaluations: parts.iter().map(|part| part.evaluations).sum(),
+                            blocks: blocks.len(),
+                            started: start - origin,
+                            elapsed: start.elapsed(),
+                        };
+                        (parts, worker)
+                    })
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or(Err(IntegralCalcError::WorkerPanicked)))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    let (parts, workers): (Vec<Vec<_>>, Vec<_>) = parts.into_iter().unzip();
+    let parts: Vec<_> = parts.into_iter().flatten().collect();
+    check_interrupted(rule, &parts, lower_bound, upper_bound, samples)?;
+    Ok((reduce_parts(&parts).scaled(rule, lower_bound, upper_bound, samples), workers))
+}
+
+/// Многопоточный расчёт независимо от порога `ASYNC_THRESHOLD_SAMPLES_COUNT`.
+/// При `threads == None` число потоков берётся из `default_threads()`;
+/// при срабатывании `cancel` возвращается `IntegralCalcError::Interrupted`
+/// с оценкой по пройденным отсчётам, а `progress` получает число
+/// выполненных отсчётов по ходу расчёта.
+pub fn calculate_integral_async<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    threads: Option<usize>,
+    cancel: Option<CancelToken>,
+    progress: Option<ProgressHook>,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let mut builder = Integrator::builder()
+        .bounds(lower_bound, upper_bound)
+        .samples(samples)
+        .execution(ExecutionMode::Threaded);
+    if let Some(threads) = threads {
+        builder = builder.threads(threads);
+    }
+    if let Some(cancel) = cancel {
+        builder = builder.cancel_token(cancel);
+    }
+    if let Some(progress) = progress {
+        builder = builder.progress(progress);
+    }
+    builder.build()?.run(f)
+}
+
+pub fn calculate_integral<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    calculate_integral_with_options(f, lower_bound, upper_bound, &options)
+}
+
+/// То же, что `calculate_integral`, по заданной квадратурной формуле.
+pub fn calculate_integral_with_method<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    method: Method,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples, method, ..Default::default() };
+    calculate_integral_with_options(f, lower_bound, upper_bound, &options)
+}
+
+pub fn calculate_integral_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    Integrator::builder()
+        .bounds(lower_bound, upper_bound)
+        .options(options)
+        .build()?
+        .run(f)
+}
+
+/// Расчёт по произвольной квадратурной формуле; `options.method` при этом
+/// не используется.
+pub fn calculate_integral_with_rule<T, R, I>(
+    rule: &R,
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    R: IntegrationRule<T> + ?Sized,
+    I: Integrand<T> + Send + Sync,
+{
+    Integrator::builder()
+        .bounds(lower_bound, upper_bound)
+        .options(options)
+        .build()?
+        .run_with_rule(rule, f)
+}
+
+/// Расчёт с отдельным экземпляром подынтегральной функции на каждый поток,
+/// что позволяет иметь у функции изменяемое состояние (счётчики, кэши).
+pub fn calculate_integral_with_factory<T, M, F>(
+    factory: M,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    M: Fn() -> F + Sync,
+    F: FnMut(T) -> T,
+{
+    Integrator::builder()
+        .bounds(lower_bound, upper_bound)
+        .options(options)
+        .build()?
+        .run_with_factory(factory)
+}
+
+/// Накопленный интеграл F(x_i) = ∫ₐ^{x_i} f во всех узлах сетки, начиная с (a, 0).
+/// Последний элемент совпадает с результатом `calculate_integral`, а f
+/// вычисляется в тех же узлах и столько же раз, что и при обычном расчёте.
+pub fn calculate_cumulative_integral<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<Vec<(T, T)>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    Integrator::builder()
+        .bounds(lower_bound, upper_bound)
+        .samples(samples)
+        .build()?
+        .cumulative(f)
+}
+
+/// То же, что `calculate_cumulative_integral`, с заданными параметрами
+/// расчёта (метод, потоки).
+pub fn calculate_cumulative_integral_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<Vec<(T, T)>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    Integrator::builder()
+        .bounds(lower_bound, upper_bound)
+        .options(options)
+        .build()?
+        .cumulative(f)
+}
+
+pub fn calculate_integral_stream<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    chunk: u64,
+) -> Result<IntegrationStream<T, I>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync + 'static,
+{
+    Ok(Integrator::builder()
+        .bounds(lower_bound, upper_bound)
+        .samples(samples)
+        .build()?
+        .stream(f, chunk))
+}
+
+/// Верхняя граница остаточного члена формулы средних прямоугольников;
+/// частный случай `error_bound` с `ErrorTerm::MIDPOINT`.
+pub fn get_remaining_term<T, I>(
+    f: &I,
+    lower_bound: T,
+    upper_bound: T,
+    step: T
+) -> T
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    get_remaining_term_for(Method::Midpoint, f, lower_bound, upper_bound, step)
+        .expect("у формулы средних прямоугольников известен остаточный член")
+}
+
+/// Верхняя граница остаточного члена формулы `method` (`None`, если вид
+/// остаточного члена неизвестен); аналитическая производная нужного порядка
+/// используется, если она известна, иначе — разностная, см.
+/// `numeric_derivative_within`.
+pub fn get_remaining_term_for<T, I>(
+    method: Method,
+    f: &I,
+    lower_bound: T,
+    upper_bound: T,
+    step: T
+) -> Option<T>
+where
+    T: Float,
+    I: Integrand<T> + ?Sized,
+{
+    let term = method.error_term()?;
+    // Оценка не зависит от направления интегрирования
+    let (lower_bound, upper_bound) = if lower_bound > upper_bound {
+        (upper_bound, lower_bound)
+    } else {
+        (lower_bound, upper_bound)
+    };
+    let order = term.derivative_order;
+    if analytic_derivative(f, lower_bound, order).is_none() {
+        return Some(error_bound(term, f, None, lower_bound, upper_bound, step).bound);
+    }
+    let derivative = |x| {
+        analytic_derivative(f, x, order).unwrap_or_else(|| numeric_derivative_within(f, x, order, lower_bound, upper_bound))
+    };
+    Some(error_bound(term, f, Some(&derivative), lower_bound, upper_bound, step).bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+
+    /// ∫₀¹ atan(x)/(x⁴+1) dx.
+    const ATAN_OVER_QUARTIC: f64 = 0.349446289808078;
+
+    #[test]
+    fn known_integrals() {
+        let square = calculate_integral(|x: f64| x * x, 0.0, 1.0, 1000).unwrap();
+        assert!((square.value - 1.0 / 3.0).abs() < 1e-6, "{}", square.value);
+        let sine = calculate_integral(f64::sin, 0.0, std::f64::consts::PI, 1000).unwrap();
+        assert!((sine.value - 2.0).abs() < 1e-5, "{}", sine.value);
+        let builtin = calculate_integral(AtanOverQuartic, 0.0, 1.0, 1000).unwrap();
+        assert!((builtin.value - ATAN_OVER_QUARTIC).abs() < 1e-6, "{}", builtin.value);
+    }
+
+    #[test]
+    fn async_matches_known_integral() {
+        let result = calculate_integral_async(f64::exp, 0.0, 1.0, 100_000, Some(4), None, None).unwrap();
+        assert!((result.value - (std::f64::consts::E - 1.0)).abs() < 1e-10, "{}", result.value);
+        assert_eq!(result.threads, 4);
+    }
+
+    #[test]
+    fn accumulated_sum_of_midpoints() {
+        // Середины отрезков 0.25 и 0.75
+        let sum = calculate_accumulated_sum_on_range(&mut |x: f64| x, 0.0, 1.0, 0.5);
+        assert_eq!(sum, 1.0);
+    }
+
+    #[test]
+    fn remaining_term_covers_error() {
+        let result = calculate_integral(AtanOverQuartic, 0.0, 1.0, 100).unwrap();
+        let bound = get_remaining_term(&AtanOverQuartic, 0.0, 1.0, result.step);
+        assert!((result.value - ATAN_OVER_QUARTIC).abs() <= bound, "{} > {}", (result.value - ATAN_OVER_QUARTIC).abs(), bound);
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        assert_eq!(
+            calculate_integral(|x: f64| x, 1.0, 0.0, 10).unwrap_err(),
+            IntegralCalcError::BoundsReversed { lower: 1.0, upper: 0.0 }
+        );
+    }
+
+    #[test]
+    fn capturing_closure_across_threshold() {
+        let scale = 3.0;
+        let line = move |x: f64| scale * x;
+        for samples in [ASYNC_THRESHOLD_SAMPLES_COUNT, ASYNC_THRESHOLD_SAMPLES_COUNT + 1] {
+            let result = calculate_integral(line, 0.0, 2.0, samples).unwrap();
+            assert!((result.value - 6.0).abs() < 1e-12, "{}: {}", samples, result.value);
+        }
+        assert!(calculate_integral(line, 0.0, 2.0, ASYNC_THRESHOLD_SAMPLES_COUNT + 1).unwrap().threads > 1 || default_threads() == 1);
+    }
+
+    #[test]
+    fn threads_not_dividing_samples() {
+        for (threads, samples) in [(7, 1003), (3, 10), (32, 100_003), (5, 4096 * 7 + 1)] {
+            let run = |execution| Integrator::builder()
+                .bounds(-1.0, 2.5)
+                .samples(samples)
+                .threads(threads)
+                .execution(execution)
+                .build()
+                .unwrap()
+                .run(f64::exp)
+                .unwrap();
+            let (sequential, threaded) = (run(ExecutionMode::Sequential), run(ExecutionMode::Threaded));
+            assert_eq!(sequential.value.to_bits(), threaded.value.to_bits(), "{} / {}", samples, threads);
+            assert_eq!(threaded.samples, samples);
+        }
+    }
+
+    /// Счётчик вычислений одного экземпляра функции; при удалении
+    /// добавляет своё число к общему.
+    struct Counter<'a> {
+        count: u64,
+        total: &'a std::sync::atomic::AtomicU64,
+    }
+
+    impl Counter<'_> {
+        fn count(&mut self) {
+            self.count += 1;
+        }
+    }
+
+    impl Drop for Counter<'_> {
+        fn drop(&mut self) {
+            self.total.fetch_add(self.count, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn factory_counts_evaluations() {
+        let total = std::sync::atomic::AtomicU64::new(0);
+        for (samples, threads) in [(1000, 1), (12_345, 4), (100_000, 7)] {
+            total.store(0, Ordering::Relaxed);
+            let factory = || {
+                let mut counter = Counter { count: 0, total: &total };
+                move |x: f64| {
+                    counter.count();
+                    x
+                }
+            };
+            let options = IntegrationOptions { samples, threads: Some(threads), ..Default::default() };
+            let result = calculate_integral_with_factory(factory, 0.0, 1.0, &options).unwrap();
+            // Каждой середине отрезка — ровно одно вычисление
+            assert_eq!(total.load(Ordering::Relaxed), samples);
+            assert_eq!(result.samples, samples);
+        }
+    }
+
+    #[test]
+    fn repeated_runs_are_bit_identical() {
+        let run = || calculate_integral_async(AtanOverQuartic, 0.0f64, 1.0, 1_000_003, Some(8), None, None).unwrap().value;
+        let first = run();
+        for _ in 1..20 {
+            assert_eq!(run().to_bits(), first.to_bits());
+        }
+        let options = IntegrationOptions { samples: 1_000_003, execution: ExecutionMode::Sequential, ..Default::default() };
+        let sequential = calculate_integral_with_options(AtanOverQuartic, 0.0f64, 1.0, &options).unwrap();
+        assert_eq!(sequential.value.to_bits(), first.to_bits());
+    }
+
+    #[test]
+    fn non_unit_intervals() {
+        let cases = [
+            ((|x| x) as fn(f64) -> f64, 0.0, 2.0, 2.0),
+            (|_| 1.0, 1.0, 3.0, 2.0),
+            (|x| x * x, -1.0, 1.0, 2.0 / 3.0),
+            (|x| x, -5.0, -1.0, -12.0),
+        ];
+        for (f, lower, upper, exact) in cases {
+            let sync = calculate_integral(f, lower, upper, 1000).unwrap();
+            assert!((sync.value - exact).abs() < 1e-5, "[{}, {}]: {}", lower, upper, sync.value);
+            assert_eq!(sync.step, (upper - lower) / 1000.0);
+            let threaded = calculate_integral_async(f, lower, upper, 100_000, Some(4), None, None).unwrap();
+            assert!((threaded.value - exact).abs() < 1e-9, "[{}, {}]: {}", lower, upper, threaded.value);
+        }
+    }
+
+    #[test]
+    fn async_matches_sync_around_threshold() {
+        let bounds = [(0.0, 1.0), (-2.0, 3.0), (10.0, 10.5)];
+        let around = [ASYNC_THRESHOLD_SAMPLES_COUNT - 1, ASYNC_THRESHOLD_SAMPLES_COUNT, ASYNC_THRESHOLD_SAMPLES_COUNT + 1, 3 * ASYNC_THRESHOLD_SAMPLES_COUNT + 7];
+        for (lower, upper) in bounds {
+            for samples in around {
+                let options = IntegrationOptions { samples, execution: ExecutionMode::Sequential, ..Default::default() };
+                let sync = calculate_integral_with_options(AtanOverQuartic, lower, upper, &options).unwrap();
+                let threaded = calculate_integral_async(AtanOverQuartic, lower, upper, samples, Some(32), None, None).unwrap();
+                assert!((sync.value - threaded.value).abs() <= 1e-12, "[{}, {}] / {}: {} != {}", lower, upper, samples, sync.value, threaded.value);
+                assert_eq!(threaded.samples, samples);
+            }
+        }
+    }
+
+    #[test]
+    fn awkward_sample_count_is_evaluated_exactly() {
+        let samples = 999_983;
+        let (lower, upper) = (0.0, 0.1);
+        let mut evaluations = 0u64;
+        let mut last = f64::NEG_INFINITY;
+        let sum = calculate_accumulated_sum_on_range(
+            &mut |x: f64| {
+                evaluations += 1;
+                last = x;
+                x
+            },
+            lower,
+            upper,
+            (upper - lower) / samples as f64,
+        );
+        assert_eq!(evaluations, samples);
+        // Последняя середина — на полшага левее верхней границы
+        assert!((upper - last - 0.05 / samples as f64).abs() < 1e-15, "{}", last);
+        assert!((sum / samples as f64 - 0.05).abs() < 1e-12, "{}", sum);
+
+        let total = std::sync::atomic::AtomicU64::new(0);
+        let counting = |x: f64| {
+            total.fetch_add(1, Ordering::Relaxed);
+            x
+        };
+        let result = calculate_integral(counting, lower, upper, samples).unwrap();
+        assert_eq!(total.load(Ordering::Relaxed), samples);
+        assert!((result.value - 0.005).abs() < 1e-15, "{}", result.value);
+    }
+
+    #[test]
+    fn reduction_does_not_depend_on_thread_count() {
+        let run = |threads| calculate_integral_async(AtanOverQuartic, 0.0f64, 1.0, 200_003, Some(threads), None, None).unwrap();
+        let reference = run(1);
+        assert!((reference.value - 0.349446289808078).abs() < 1e-11, "{}", reference.value);
+        for threads in [2, 3, 8, 32] {
+            let result = run(threads);
+            assert_eq!(result.value.to_bits(), reference.value.to_bits(), "{}", threads);
+            // Участки потоков идут по порядку и покрывают всю сетку
+            assert_eq!(result.workers.first().unwrap().lower_bound, 0.0);
+            for pair in result.workers.windows(2) {
+                assert!((pair[0].upper_bound - pair[1].lower_bound).abs() < 1e-12, "{}", threads);
+            }
+            assert_eq!(result.workers.iter().map(|worker| worker.intervals).sum::<u64>(), 200_003);
+        }
+    }
+
+    #[test]
+    fn panicking_worker_is_reported() {
+        let options = IntegrationOptions { samples: 100_000, threads: Some(4), execution: ExecutionMode::Threaded, ..Default::default() };
+        let factory = || |x: f64| if x > 0.9 { panic!("отказ в потоке") } else { x };
+        assert_eq!(calculate_integral_with_factory(factory, 0.0, 1.0, &options).unwrap_err(), IntegralCalcError::WorkerPanicked);
+    }
+
+    #[test]
+    fn thread_ranges_share_samples_evenly() {
+        let threads = 8;
+        let mut cases = vec![5, threads as u64];
+        cases.extend((0..threads as u64).map(|r| threads as u64 * 13 + r));
+        for samples in cases {
+            let ranges = thread_ranges(0.0, 1.0, samples, threads, 1);
+            assert_eq!(ranges.len(), threads.min(samples as usize), "{}", samples);
+            let counts: Vec<u64> = ranges.iter().map(|&(_, count)| count).collect();
+            assert_eq!(counts.iter().sum::<u64>(), samples);
+            assert!(counts.iter().all(|&count| count >= 1), "{}: {:?}", samples, counts);
+            assert!(counts.iter().max().unwrap() - counts.iter().min().unwrap() <= 1, "{}: {:?}", samples, counts);
+            // Каждая часть начинается в узле общей сетки
+            let mut first = 0;
+            for &(lower, count) in &ranges {
+                assert_eq!(lower, first as f64 * (1.0 / samples as f64));
+                first += count;
+            }
+        }
+    }
+
+    #[test]
+    fn samples_around_thread_count() {
+        for samples in [3, 8, 8 * 13 + 5] {
+            let options = IntegrationOptions { samples, threads: Some(8), execution: ExecutionMode::Threaded, ..Default::default() };
+            let result = calculate_integral_with_options(|x: f64| x, 0.0, 1.0, &options).unwrap();
+            assert!((result.value - 0.5).abs() < 1e-15, "{}: {}", samples, result.value);
+            assert_eq!(result.samples, samples);
+        }
+    }
+
+    #[test]
+    fn sweep_across_threshold_is_smooth() {
+        let run = |samples| {
+            let options = IntegrationOptions { samples, threads: Some(4), ..Default::default() };
+            let result = calculate_integral_with_options(f64::exp, 0.0, 1.0, &options).unwrap();
+            assert_eq!(result.threads > 1, samples > ASYNC_THRESHOLD_SAMPLES_COUNT);
+            result.value
+        };
+        let values: Vec<f64> = (9_990..=10_010).map(run).collect();
+        let jumps: Vec<f64> = values.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        // Погрешность ∝ 1/n²: значения растут, а скачки меняются плавно
+        assert!(jumps.iter().all(|&jump| jump > 0.0), "{:?}", jumps);
+        for window in jumps.windows(3) {
+            assert!(window[1] <= 1.5 * window[0].max(window[2]), "{:?}", window);
+            assert!(window[1] >= window[0].min(window[2]) / 1.5, "{:?}", window);
+        }
+    }
+}
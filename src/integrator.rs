@@ -0,0 +1,524 @@
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancelToken;
+use crate::cumulative::{cumulative_points, cumulative_sums};
+use crate::error_bound::{error_bound, REMAINDER_SCAN_NODES};
+use crate::float::Float;
+use crate::integrand::{analytic_derivative, numeric_derivative_within, Integrand};
+use crate::integrate::{
+    default_threads,
+    integrate_sequential,
+    integrate_threaded,
+    reduction_blocks,
+    worker_threads,
+    IntegralCalcError,
+    RunControl,
+};
+use crate::method::Method;
+use crate::monte_carlo::MonteCarloRule;
+use crate::options::{check_granularity, ExecutionMode, IntegrationOptions, ReversedBounds};
+use crate::progress::{ProgressHook, ProgressReporter};
+use crate::result::{IntegrationResult, WorkerReport};
+use crate::rule::IntegrationRule;
+use crate::singularity::SingularityCheck;
+use crate::stream::IntegrationStream;
+
+/// Настройка запуска интегрирования по шагам:
+/// `Integrator::builder().bounds(a, b).samples(n).build()?.run(f)`.
+#[derive(Debug, Clone)]
+pub struct IntegratorBuilder<T: Float = f64> {
+    bounds: Option<(T, T)>,
+    options: IntegrationOptions,
+}
+
+impl<T: Float> Default for IntegratorBuilder<T> {
+    fn default() -> Self {
+        Self {
+            bounds: None,
+            options: IntegrationOptions::default(),
+        }
+    }
+}
+
+impl<T: Float> IntegratorBuilder<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bounds(mut self, lower_bound: T, upper_bound: T) -> Self {
+        self.bounds = Some((lower_bound, upper_bound));
+        self
+    }
+
+    /// Заменяет все параметры расчёта разом.
+    pub fn options(mut self, options: &IntegrationOptions) -> Self {
+        self.options = options.clone();
+        self
+    }
+
+    pub fn samples(mut self, samples: u64) -> Self {
+        self.options.samples = samples;
+        self
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.options.method = method;
+        self
+    }
+
+    /// Число рабочих потоков; по умолчанию `default_threads()`. Потоков
+    /// никогда не запускается больше, чем участков свёртки, и от их числа
+    /// результат не зависит.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = Some(threads);
+        self
+    }
+
+    /// Число отсчётов, начиная с которого расчёт ведётся в нескольких потоках.
+    pub fn async_threshold(mut self, async_threshold: u64) -> Self {
+        self.options.async_threshold = async_threshold;
+        self
+    }
+
+    /// Однопоточный или многопоточный проход независимо от порога.
+    pub fn execution(mut self, execution: ExecutionMode) -> Self {
+        self.options.execution = execution;
+        self
+    }
+
+    /// Зерно генератора `Method::MonteCarlo`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.options.seed = seed;
+        self
+    }
+
+    pub fn max_samples(mut self, max_samples: u64) -> Self {
+        self.options.max_samples = max_samples;
+        self
+    }
+
+    pub fn cancel_token(mut self, cancel: CancelToken) -> Self {
+        self.options.cancel = Some(cancel);
+        self
+    }
+
+    pub fn progress(mut self, progress: ProgressHook) -> Self {
+        self.options.progress = Some(progress);
+        self
+    }
+
+    pub fn singularity_check(mut self, check: SingularityCheck) -> Self {
+        self.options.singularity_check = Some(check);
+        self
+    }
+
+    pub fn reversed_bounds(mut self, policy: ReversedBounds) -> Self {
+        self.options.reversed_bounds = policy;
+        self
+    }
+
+    pub fn propagate_non_finite(mut self, propagate: bool) -> Self {
+        self.options.propagate_non_finite = propagate;
+        self
+    }
+
+    pub fn build(self) -> Result<Integrator<T>, IntegralCalcError> {
+        let (lower_bound, upper_bound) = self.bounds.ok_or(IntegralCalcError::MissingBounds)?;
+        for value in [lower_bound, upper_bound] {
+            if !value.is_finite() {
+                return Err(IntegralCalcError::NonFiniteBound { value: value.to_f64() });
+            }
+        }
+        let negated = lower_bound > upper_bound;
+        if negated && self.options.reversed_bounds == ReversedBounds::Error {
+            return Err(IntegralCalcError::BoundsReversed {
+                lower: lower_bound.to_f64(),
+                upper: upper_bound.to_f64(),
+            });
+        }
+        let (lower_bound, upper_bound) = if negated { (upper_bound, lower_bound) } else { (lower_bound, upper_bound) };
+        if !(upper_bound - lower_bound).is_finite() {
+            return Err(IntegralCalcError::IntervalOverflow {
+                lower: lower_bound.to_f64(),
+                upper: upper_bound.to_f64(),
+            });
+        }
+        let mut options = self.options;
+        options.samples = options.method.adjust_samples(options.samples);
+        options.validate()?;
+        let samples = options.samples;
+        // Шаг может обратиться в нуль на отрезке из субнормальных чисел
+        let step = (upper_bound - lower_bound) / T::from_u64(samples);
+        if lower_bound < upper_bound && !(step > T::ZERO && step.is_finite()) {
+            return Err(IntegralCalcError::InvalidStep { step: step.to_f64() });
+        }
+        let threads = options.threads.unwrap_or_else(default_threads);
+        Ok(Integrator {
+            lower_bound,
+            upper_bound,
+            negated,
+            samples,
+            method: options.method,
+            monte_carlo: MonteCarloRule::new(options.seed),
+            threads: threads.min(usize::try_from(samples).unwrap_or(usize::MAX)),
+            async_threshold: options.async_threshold,
+            execution: options.execution,
+            cancel: options.cancel,
+            progress: options.progress,
+            singularity_check: options.singularity_check,
+            propagate_non_finite: options.propagate_non_finite,
+        })
+    }
+}
+
+/// Проверенная конфигурация запуска.
+#[derive(Debug, Clone)]
+pub struct Integrator<T: Float = f64> {
+    lower_bound: T,
+    upper_bound: T,
+    /// Границы были переставлены по `ReversedBounds::Negate`.
+    negated: bool,
+    samples: u64,
+    method: Method,
+    /// Формула `Method::MonteCarlo` с зерном из параметров.
+    monte_carlo: MonteCarloRule,
+    threads: usize,
+    async_threshold: u64,
+    execution: ExecutionMode,
+    cancel: Option<CancelToken>,
+    progress: Option<ProgressHook>,
+    singularity_check: Option<SingularityCheck>,
+    propagate_non_finite: bool,
+}
+
+impl<T: Float> Integrator<T> {
+    pub fn builder() -> IntegratorBuilder<T> {
+        IntegratorBuilder::new()
+    }
+
+    pub fn lower_bound(&self) -> T {
+        self.lower_bound
+    }
+
+    pub fn upper_bound(&self) -> T {
+        self.upper_bound
+    }
+
+    /// Границы были заданы в обратном порядке и переставлены: `lower_bound()`
+    /// и `upper_bound()` уже упорядочены, а `run`, `run_with_rule` и
+    /// `run_with_factory` меняют знак значения. `cumulative` и `stream`
+    /// считают интеграл по упорядоченным границам без смены знака.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Реализация формулы `method()`; у метода Монте-Карло — с зерном.
+    pub fn rule(&self) -> &dyn IntegrationRule<T> {
+        self.method.seeded_rule(&self.monte_carlo)
+    }
+
+    pub(crate) fn monte_carlo(&self) -> MonteCarloRule {
+        self.monte_carlo
+    }
+
+    /// Пойдёт ли расчёт в нескольких потоках.
+    pub fn is_threaded(&self) -> bool {
+        self.execution.is_threaded(self.samples, self.async_threshold)
+    }
+
+    pub fn step(&self) -> T {
+        (self.upper_bound - self.lower_bound) / T::from_u64(self.samples)
+    }
+
+    pub fn run<I>(&self, f: I) -> Result<IntegrationResult<T>, IntegralCalcError>
+    where
+        I: Integrand<T> + Send + Sync,
+    {
+        self.run_with_rule(self.rule(), f)
+    }
+
+    /// Расчёт по произвольной квадратурной формуле вместо `method()`.
+    pub fn run_with_rule<R, I>(&self, rule: &R, f: I) -> Result<IntegrationResult<T>, IntegralCalcError>
+    where
+        R: IntegrationRule<T> + ?Sized,
+        I: Integrand<T> + Send + Sync,
+    {
+        let mut result = self.execute(rule, || |x| f.eval(x))?;
+        // Оценку остаточного члена даём только при известной производной нужного
+        // порядка: численная по всей сетке стоила бы ещё одного полного прохода
+        // У вырожденного отрезка остаточный член уже нулевой
+        if let Some(term) = rule.error_term().filter(|_| self.lower_bound < self.upper_bound) {
+            let order = term.derivative_order;
+            if analytic_derivative(&f, self.lower_bound, order).is_some() {
+                let start = Instant::now();
+                let (lower_bound, upper_bound) = (self.lower_bound, self.upper_bound);
+                let derivative = |x| {
+                    analytic_derivative(&f, x, order)
+                        .unwrap_or_else(|| numeric_derivative_within(&f, x, order, lower_bound, upper_bound))
+                };
+                // Наибольшая производная ищется в REMAINDER_SCAN_NODES узлах при
+                // любом числе отсчётов; R ∝ hᵖ переводит границу с шагом поиска
+                // на шаг расчёта
+                let scan_step = (upper_bound - lower_bound) / T::from_u64(REMAINDER_SCAN_NODES);
+                let bound = error_bound(term, &f, Some(&derivative), lower_bound, upper_bound, scan_step);
+                result.remainder_bound = Some(bound.bound * (self.step() / scan_step).powi(term.step_power));
+                result.remainder_elapsed = start.elapsed();
+            }
+        }
+        Ok(result)
+    }
+
+    /// Расчёт, в котором `factory` создаёт отдельный экземпляр подынтегральной
+    /// функции для каждого потока (в однопоточном режиме — один). Поле
+    /// `samples` результата содержит суммарное число вычислений f.
+    pub fn run_with_factory<M, F>(&self, factory: M) -> Result<IntegrationResult<T>, IntegralCalcError>
+    where
+        M: Fn() -> F + Sync,
+        F: FnMut(T) -> T,
+    {
+        self.execute(self.rule(), factory)
+    }
+
+    fn execute<R, M, F>(&self, rule: &R, factory: M) -> Result<IntegrationResult<T>, IntegralCalcError>
+    where
+        R: IntegrationRule<T> + ?Sized,
+        M: Fn() -> F + Sync,
+        F: FnMut(T) -> T,
+    {
+        check_granularity(self.samples, rule.granularity())?;
+        let start = Instant::now();
+        // ∫ₐᵃ f = 0: f не вычисляется ни разу
+        if self.lower_bound == self.upper_bound {
+            return Ok(IntegrationResult {
+                value: T::ZERO,
+                step: T::ZERO,
+                intervals: self.samples,
+                samples: 0,
+                threads: 1,
+                elapsed: start.elapsed(),
+                remainder_bound: Some(T::ZERO),
+                remainder_elapsed: Duration::ZERO,
+                error_estimate: Some(T::ZERO),
+                singularity: None,
+                workers: Vec::new(),
+            });
+        }
+        let threaded = self.is_threaded();
+        let threads = worker_threads(self.threads, self.samples, rule.granularity());
+        let progress = self.progress.as_ref()
+            .map(|progress| ProgressReporter::new(progress, self.samples));
+        let stop = AtomicBool::new(false);
+        let control = RunControl {
+            cancel: self.cancel.as_ref(),
+            progress: progress.as_ref(),
+            singularity: self.singularity_check.as_ref(),
+            non_finite_stop: (!self.propagate_non_finite).then_some(&stop),
+        };
+        let (accumulated, workers) = if threaded {
+            integrate_threaded(rule, &factory, self.lower_bound, self.upper_bound, self.samples, threads, &control)?
+        } else {
+            let accumulated = integrate_sequential(
+                rule,
+                &mut factory(),
+                self.lower_bound,
+                self.upper_bound,
+                self.samples,
+                &control,
+            )?;
+            // Однопоточный проход — один участок на всю сетку
+            let worker = WorkerReport {
+                lower_bound: self.lower_bound,
+                upper_bound: self.upper_bound,
+                intervals: self.samples,
+                evaluations: accumulated.evaluations,
+                blocks: reduction_blocks(self.lower_bound, self.upper_bound, self.samples, rule.granularity()).len(),
+                started: Duration::ZERO,
+                elapsed: start.elapsed(),
+            };
+            (accumulated, vec![worker])
+        };
+        if let Some(x) = accumulated.singularity {
+            if self.singularity_check.is_some_and(|check| check.fatal) {
+                return Err(IntegralCalcError::SuspectedSingularity { x: x.to_f64() });
+            }
+        }
+        Ok(IntegrationResult {
+            value: if self.negated { -accumulated.sum } else { accumulated.sum },
+            step: self.step(),
+            intervals: self.samples,
+            samples: accumulated.evaluations,
+            threads: if threaded { threads } else { 1 },
+            elapsed: start.elapsed(),
+            remainder_bound: None,
+            remainder_elapsed: Duration::ZERO,
+            error_estimate: accumulated.error,
+            singularity: accumulated.singularity,
+            workers,
+        })
+    }
+
+    /// Накопленный интеграл во всех узлах сетки, см. `calculate_cumulative_integral`.
+    pub fn cumulative<I>(&self, f: I) -> Result<Vec<(T, T)>, IntegralCalcError>
+    where
+        I: Integrand<T> + Send + Sync,
+    {
+        let rule = self.rule();
+        let prefix_sums = cumulative_sums(self, rule, &f)?;
+        Ok(cumulative_points(self, rule, &prefix_sums))
+    }
+
+    /// Расчёт с промежуточными оценками после каждых `chunk` отрезков сетки
+    /// (в многопоточном режиме — каждого потока). `chunk` округляется вниз до
+    /// кратного `granularity()` формулы, но не меньше него.
+    pub fn stream<I>(&self, f: I, chunk: u64) -> IntegrationStream<T, I>
+    where
+        I: Integrand<T> + Send + Sync + 'static,
+    {
+        IntegrationStream::new(self, f, chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_bound::ErrorTerm;
+    use crate::integrand::AtanOverQuartic;
+
+    fn bound(samples: u64) -> f64 {
+        let integrator = Integrator::builder().bounds(0.0, 1.0).samples(samples).build().unwrap();
+        integrator.run(AtanOverQuartic).unwrap().remainder_bound.unwrap()
+    }
+
+    #[test]
+    fn remainder_bound_scales_with_step() {
+        let ratio = bound(10_000) / bound(20_000);
+        assert!((ratio - 4.0).abs() < 1e-9, "{}", ratio);
+    }
+
+    #[test]
+    fn remainder_bound_matches_full_scan() {
+        let samples = 100_000;
+        let step = 1.0 / samples as f64;
+        let derivative = |x| AtanOverQuartic.second_derivative(x).unwrap();
+        let full = error_bound(ErrorTerm::MIDPOINT, &AtanOverQuartic, Some(&derivative), 0.0, 1.0, step).bound;
+        let coarse = bound(samples);
+        assert!((coarse - full).abs() <= 1e-3 * full, "{} {}", coarse, full);
+    }
+
+    #[test]
+    fn remainder_bound_covers_error() {
+        let exact = 0.349_446_289_808_078;
+        for samples in [10, 100, 1000, 10_000] {
+            let integrator = Integrator::builder().bounds(0.0, 1.0).samples(samples).build().unwrap();
+            let result = integrator.run(AtanOverQuartic).unwrap();
+            assert!((result.value - exact).abs() <= result.remainder_bound.unwrap(), "{}", samples);
+        }
+    }
+
+    #[test]
+    fn zero_and_tiny_sample_counts() {
+        let line = |x: f64| x;
+        assert_eq!(crate::integrate::calculate_integral(line, 0.0, 1.0, 0).unwrap_err(), IntegralCalcError::ZeroSamples);
+        assert_eq!(
+            crate::integrate::calculate_integral_async(line, 0.0, 1.0, 0, Some(4), None, None).unwrap_err(),
+            IntegralCalcError::ZeroSamples
+        );
+        let single = crate::integrate::calculate_integral(|x: f64| x * x, 0.0, 2.0, 1).unwrap();
+        assert_eq!((single.value, single.samples), (2.0, 1));
+        // Шаг на отрезке из субнормальных чисел обращается в нуль
+        assert!(matches!(
+            Integrator::builder().bounds(0.0, 5e-324).samples(10).build(),
+            Err(IntegralCalcError::InvalidStep { .. })
+        ));
+    }
+
+    #[test]
+    fn fewer_samples_than_threads_above_threshold() {
+        let integrator = Integrator::builder().bounds(0.0, 1.0).samples(5).threads(8).async_threshold(1).build().unwrap();
+        assert!(integrator.is_threaded());
+        assert_eq!(integrator.threads(), 5);
+        let result = integrator.run(|x: f64| x).unwrap();
+        assert!((result.value - 0.5).abs() < 1e-15, "{}", result.value);
+        assert_eq!(result.samples, 5);
+    }
+
+    #[test]
+    fn degenerate_interval_is_zero() {
+        let never = |_: f64| -> f64 { unreachable!("f не должна вычисляться") };
+        for point in [0.0, 1e300, -42.5] {
+            let result = crate::integrate::calculate_integral(never, point, point, 1000).unwrap();
+            assert_eq!((result.value, result.samples), (0.0, 0), "{}", point);
+            let builtin = crate::integrate::calculate_integral(AtanOverQuartic, point, point, 1000).unwrap();
+            assert_eq!(builtin.remainder_bound, Some(0.0), "{}", point);
+            let threaded = crate::integrate::calculate_integral_async(never, point, point, 100_000, Some(4), None, None).unwrap();
+            assert_eq!((threaded.value, threaded.samples), (0.0, 0), "{}", point);
+        }
+    }
+
+    #[test]
+    fn reversed_bounds_policy() {
+        let line = |x: f64| x;
+        assert_eq!(
+            crate::integrate::calculate_integral(line, 1.0, 0.0, 1000).unwrap_err(),
+            IntegralCalcError::BoundsReversed { lower: 1.0, upper: 0.0 }
+        );
+        for execution in [ExecutionMode::Sequential, ExecutionMode::Threaded] {
+            let options = IntegrationOptions { samples: 1000, execution, reversed_bounds: ReversedBounds::Negate, ..Default::default() };
+            let result = crate::integrate::calculate_integral_with_options(line, 1.0, 0.0, &options).unwrap();
+            assert!((result.value + 0.5).abs() < 1e-15, "{:?}: {}", execution, result.value);
+        }
+        let forward = Integrator::builder().bounds(0.0, 1.0).samples(1000).build().unwrap().run(AtanOverQuartic).unwrap();
+        let reversed = Integrator::builder().bounds(1.0, 0.0).samples(1000).reversed_bounds(ReversedBounds::Negate).build().unwrap();
+        assert!(reversed.is_negated());
+        let reversed = reversed.run(AtanOverQuartic).unwrap();
+        assert_eq!(reversed.value, -forward.value);
+        assert_eq!(reversed.remainder_bound, forward.remainder_bound);
+    }
+
+    #[test]
+    fn non_finite_bounds_and_overflow() {
+        for (lower, upper) in [(f64::NAN, 1.0), (0.0, f64::NAN), (f64::NEG_INFINITY, 1.0), (0.0, f64::INFINITY)] {
+            let error = Integrator::builder().bounds(lower, upper).samples(100).build().unwrap_err();
+            assert!(matches!(error, IntegralCalcError::NonFiniteBound { .. }), "[{}, {}]: {:?}", lower, upper, error);
+        }
+        assert_eq!(
+            crate::integrate::calculate_integral(|x: f64| x, -1e308, 1e308, 100).unwrap_err(),
+            IntegralCalcError::IntervalOverflow { lower: -1e308, upper: 1e308 }
+        );
+        let message = IntegralCalcError::NonFiniteBound { value: f64::NAN }.localized(crate::messages::Lang::En).to_string();
+        assert!(message.contains("NaN"), "{}", message);
+    }
+
+    #[test]
+    fn non_finite_values_stop_the_run() {
+        let pole = |x: f64| 1.0 / (x - 0.5);
+        for (samples, execution) in [(1, ExecutionMode::Sequential), (10_001, ExecutionMode::Sequential), (100_001, ExecutionMode::Threaded)] {
+            let integrator = Integrator::builder().bounds(0.0, 1.0).samples(samples).threads(4).execution(execution).build().unwrap();
+            assert_eq!(integrator.run(pole).unwrap_err(), IntegralCalcError::NonFiniteValue { x: 0.5 }, "{}", samples);
+            let propagated = Integrator::builder()
+                .bounds(0.0, 1.0)
+                .samples(samples)
+                .threads(4)
+                .execution(execution)
+                .propagate_non_finite(true)
+                .build()
+                .unwrap()
+                .run(pole)
+                .unwrap();
+            assert!(!propagated.value.is_finite(), "{}: {}", samples, propagated.value);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+pub mod accuracy;
+pub mod adaptive;
+pub mod bench;
+pub mod builtin;
+pub mod cancel;
+pub mod clenshaw_curtis;
+pub mod convergence;
+mod cumulative;
+pub mod double;
+pub mod dual;
+pub mod error;
+pub mod error_bound;
+pub mod expression;
+pub mod extrapolation;
+pub mod filon;
+pub mod float;
+pub mod gauss_hermite;
+pub mod gauss_kronrod;
+pub mod gauss_laguerre;
+pub mod gauss_legendre;
+pub mod improper;
+pub mod integrand;
+pub mod integrate;
+pub mod integrator;
+pub mod messages;
+pub mod method;
+pub mod monte_carlo;
+pub mod multi;
+pub mod number_format;
+pub mod options;
+pub mod progress;
+pub mod quasi_monte_carlo;
+pub mod random;
+pub mod request;
+pub mod result;
+pub mod rule;
+pub mod runge;
+pub mod singularity;
+pub mod spline;
+pub mod stream;
+pub mod summation;
+pub mod table;
+pub mod tanh_sinh;
+pub mod tolerance;
+pub mod verification;
+
+pub use accuracy::{analyze_accuracy, AccuracyReport, RELATIVE_ERROR_MIN_RESULT};
+pub use adaptive::{adaptive_simpson, adaptive_simpson_with_options, ADAPTIVE_MAX_DEPTH};
+pub use bench::{bench_methods, Bench, BenchRow, BENCH_REFERENCE_PANELS};
+pub use builtin::{BuiltinFunction, BUILTIN_FUNCTIONS};
+#[cfg(unix)]
+pub use cancel::cancel_on_interrupt;
+pub use cancel::CancelToken;
+pub use clenshaw_curtis::{clenshaw_curtis_nodes, clenshaw_curtis_weights, ClenshawCurtisRule};
+pub use convergence::{
+    convergence_study,
+    convergence_study_with_options,
+    convergence_table_with_options,
+    ConvergenceRow,
+    ConvergenceStudy,
+    ConvergenceTableRow,
+};
+pub use double::calculate_integral_2d;
+pub use dual::{derivatives_auto, second_derivative_auto, Dual2};
+pub use error::{IntegralCalcError, LocalizedError};
+pub use error_bound::{error_bound, ErrorBound, ErrorTerm, REMAINDER_SCAN_NODES};
+pub use expression::Expression;
+pub use extrapolation::{calculate_integral_extrapolated, calculate_integral_extrapolated_with_options};
+pub use filon::{filon_cos, filon_sin, filon_with_options, FilonRule, Oscillation};
+pub use float::Float;
+pub use gauss_hermite::{gauss_hermite, GaussHermiteRule, GAUSS_HERMITE_MAX_ORDER, GAUSS_HERMITE_MIN_ORDER};
+pub use gauss_kronrod::GaussKronrodRule;
+pub use gauss_laguerre::{gauss_laguerre, GaussLaguerreRule, GAUSS_LAGUERRE_MAX_ORDER, GAUSS_LAGUERRE_MIN_ORDER};
+pub use gauss_legendre::{
+    gauss_legendre,
+    gauss_legendre_with_options,
+    GaussLegendreRule,
+    GAUSS_LEGENDRE_MAX_ORDER,
+    GAUSS_LEGENDRE_MIN_ORDER,
+};
+pub use improper::{calculate_improper_integral, calculate_improper_integral_with_options};
+pub use integrate::{
+    calculate_accumulated_sum_on_range,
+    calculate_cumulative_integral,
+    calculate_cumulative_integral_with_options,
+    calculate_integral,
+    calculate_integral_async,
+    calculate_integral_stream,
+    calculate_integral_with_factory,
+    calculate_integral_with_method,
+    calculate_integral_with_options,
+    calculate_integral_with_rule,
+    default_threads,
+    get_remaining_term,
+    get_remaining_term_for,
+    ASYNC_THRESHOLD_SAMPLES_COUNT,
+    MAX_SAMPLES_COUNT,
+    THREADS_COUNT,
+};
+pub use integrand::{numeric_derivative, numeric_derivative_within, numeric_second_derivative, AtanOverQuartic, Integrand};
+pub use integrator::{Integrator, IntegratorBuilder};
+pub use messages::{tr, Lang, Message};
+pub use method::Method;
+pub use monte_carlo::{
+    monte_carlo,
+    monte_carlo_nd,
+    monte_carlo_nd_with_options,
+    monte_carlo_with_options,
+    MonteCarloRule,
+    MONTE_CARLO_DEFAULT_SEED,
+};
+pub use multi::{calculate_integral_multi, calculate_integral_vector, calculate_integral_vector_with_options};
+pub use number_format::{Notation, NumberFormat, DEFAULT_PRECISION, MAX_PRECISION};
+pub use options::{ExecutionMode, IntegrationOptions, ReversedBounds};
+pub use progress::ProgressHook;
+pub use quasi_monte_carlo::{quasi_monte_carlo, quasi_monte_carlo_with_options};
+pub use random::{radical_inverse, Halton, Rng};
+pub use request::{validate_request, IntegrationRequest};
+pub use result::{IntegrationResult, WorkerReport};
+pub use rule::{
+    BooleRule,
+    IntegrationRule,
+    LeftRiemannRule,
+    MidpointRule,
+    RightRiemannRule,
+    SimpsonRule,
+    SimpsonThreeEighthsRule,
+    TrapezoidRule,
+};
+pub use runge::{calculate_integral_runge, calculate_integral_runge_with_options, runge_error, runge_refine};
+pub use singularity::SingularityCheck;
+pub use spline::{integrate_spline, CubicSpline};
+pub use stream::IntegrationStream;
+pub use summation::{pairwise_sum, CompensatedSum};
+pub use table::{integrate_table, integrate_table_simpson};
+pub use tanh_sinh::{TanhSinhRule, TANH_SINH_MAX_LEVEL};
+pub use tolerance::{integrate_to_tolerance, integrate_to_tolerance_with_options};
+pub use verification::{verify_result, VerificationReport};
@@ -1,213 +1,1942 @@
-extern crate core;
-
-use std::fmt;
-use std::fmt::{Debug, Display, Formatter};
-use std::io::{Error as IOError, stdin, stdout, Write};
-use std::process::exit;
+use std::collections::hash_map::RandomState;
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, BufWriter, Error as IOError, ErrorKind, IsTerminal, stdin, Write};
+use std::num::ParseFloatError;
 use std::str::FromStr;
-use std::sync::{Arc, Mutex};
-use std::thread::JoinHandle;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod config;
+mod flags;
+mod history;
+mod input;
+#[macro_use]
+mod report;
+mod exit_code;
+mod plot;
+mod repl;
+mod completions;
+
+#[cfg(unix)]
+use kryl_04::cancel_on_interrupt;
+use kryl_04::{
+    bench_methods,
+    calculate_integral_2d,
+    calculate_improper_integral_with_options,
+    convergence_study_with_options,
+    convergence_table_with_options,
+    runge_refine,
+    validate_request,
+    verify_result,
+    get_remaining_term_for,
+    integrate_spline,
+    integrate_table,
+    integrate_table_simpson,
+    integrate_to_tolerance_with_options,
+    AccuracyReport,
+    Bench,
+    BuiltinFunction,
+    Expression,
+    BUILTIN_FUNCTIONS,
+    CancelToken,
+    ExecutionMode,
+    Integrand,
+    IntegralCalcError,
+    IntegrationResult,
+    tr,
+    IntegrationOptions,
+    IntegrationRequest,
+    Lang,
+    Message,
+    Method,
+    ReversedBounds,
+    SingularityCheck,
+    VerificationReport,
+    MAX_SAMPLES_COUNT,
+    REMAINDER_SCAN_NODES,
+    DEFAULT_PRECISION,
+    MAX_PRECISION,
+    Notation,
+    NumberFormat,
+};
 
-pub fn get_line<'a>() -> Result<&'a str, IOError> {
+use exit_code::{exit_with, ExitReason};
+use input::{Answers, InputError, UnexpectedToken};
+use report::{ProgressLine, Verbosity};
+
+/// Строка ввода без пробелов по краям; конец ввода — ошибка `UnexpectedEof`,
+/// в отличие от пустой строки.
+pub fn get_line() -> Result<String, IOError> {
     let mut result: String = String::new();
-    stdin().read_line(&mut result)?;
-    result = String::from(result.trim());
-    let result: &'a str = result.leak::<'a>();
-    Ok(result)
+    if stdin().read_line(&mut result)? == 0 {
+        return Err(IOError::from(ErrorKind::UnexpectedEof));
+    }
+    Ok(String::from(result.trim()))
 }
 
-#[derive(Debug, Clone)]
-struct IntegralCalcError<'a> {
-    reason: &'a str,
+/// Строка ввода; если ввод исчерпан или не читается, программа завершается.
+fn read_line() -> String {
+    get_line().unwrap_or_else(|e| exit_on_input_error(&e))
+}
+
+/// Строка ввода для необязательного значения: исчерпанный ввод равносилен
+/// пустой строке, то есть значению по умолчанию.
+fn read_optional_line() -> String {
+    match get_line() {
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => String::new(),
+        line => line.unwrap_or_else(|e| exit_on_input_error(&e)),
+    }
+}
+
+fn exit_on_input_error(error: &IOError) -> ! {
+    if error.kind() == ErrorKind::UnexpectedEof {
+        report::error(tr(Message::EndOfInput));
+    } else {
+        report::error(error);
+    }
+    exit_with(ExitReason::EndOfInput);
 }
 
-impl<'a> IntegralCalcError<'a> {
-    fn new(reason: &'a str) -> Self {
-        Self {
-            reason
+/// Граница интегрирования: число, "inf", "-inf", "бесконечность" или
+/// постоянное выражение вроде `-pi/2` (см. `Expression::evaluate_constant`).
+fn parse_bound(line: &str) -> Result<f64, IntegralCalcError> {
+    let (sign, magnitude) = match line.strip_prefix('-') {
+        Some(magnitude) => (-1.0, magnitude),
+        None => (1.0, line.strip_prefix('+').unwrap_or(line)),
+    };
+    if magnitude.to_lowercase() == "бесконечность" {
+        return Ok(sign * f64::INFINITY);
+    }
+    parse_number(line).or_else(|_| Expression::evaluate_constant(line))
+}
+
+/// Печатает ошибку разбора границы и возвращает код завершения `code`.
+fn bound_failure(code: ExitReason) -> impl Fn(&IntegralCalcError) -> ExitReason {
+    move |error| {
+        report::error(error);
+        code
+    }
+}
+
+/// Вещественное число из ввода с запятой или точкой в качестве десятичного
+/// разделителя: единственная запятая в записи без точки — разделитель
+/// (`0,5`, `-3,14`, `1,5e2`), записи вроде `1,234,5` не принимаются.
+fn parse_number(line: &str) -> Result<f64, ParseFloatError> {
+    let line = line.trim();
+    if line.matches(',').count() == 1 && !line.contains('.') {
+        f64::from_str(&line.replace(',', "."))
+    } else {
+        f64::from_str(line)
+    }
+}
+
+/// Наибольшее число удвоений в режиме `--convergence`.
+const CONVERGENCE_MAX_DOUBLINGS: u32 = 20;
+
+/// Язык из флага `--lang ru|en` (`--lang=en`), иначе из `LANG`, иначе из
+/// файла настроек, иначе русский.
+fn select_lang() -> Lang {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next(),
+            Some(value) if value.starts_with('=') => Some(value[1..].to_string()),
+            _ => continue,
+        };
+        return value
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(|| {
+                report::error(tr(Message::UnknownLang));
+                exit_with(ExitReason::IncorrectArguments);
+            });
+    }
+    Lang::from_env()
+        .or_else(|| config::get("lang").map(|lang| lang.to_flag().parse().unwrap_or_else(|()| {
+            report::error(tr(Message::UnknownLang));
+            exit_with(ExitReason::IncorrectArguments);
+        })))
+        .unwrap_or_default()
+}
+
+/// Предупреждения и ошибки файла настроек; при ошибках программа завершается.
+fn report_config_problems(problems: &[config::Problem]) {
+    let path = config::current().path.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+    for problem in problems {
+        match problem {
+            config::Problem::Read { .. } => report::error(problem),
+            problem if problem.is_warning() => report::warning(format_args!("{}:{}", path, problem)),
+            problem => report::error(format_args!("{}:{}", path, problem)),
         }
     }
+    match problems.iter().find(|problem| !problem.is_warning()) {
+        Some(config::Problem::Read { .. }) => exit_with(ExitReason::FileError),
+        Some(_) => exit_with(ExitReason::IncorrectArguments),
+        None => {}
+    }
+}
+
+/// Откуда взято значение настройки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Flag,
+    Env,
+    Config,
+    Default,
 }
 
-impl<'a> Display for IntegralCalcError<'a> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "Ошибка вычисления интеграла: {:}", self.reason)
+impl Source {
+    fn message(self) -> Message {
+        match self {
+            Source::Flag => Message::SourceFlag,
+            Source::Env => Message::SourceEnv,
+            Source::Config => Message::SourceConfig,
+            Source::Default => Message::SourceDefault,
+        }
     }
 }
 
-fn function(x: f64) -> f64 {
-    x.atan() / (x.powi(4) + 1.0)
+/// Действующее значение ключа `key` и его источник: флаг, переменная
+/// окружения, файл настроек, значение по умолчанию — в порядке старшинства.
+fn effective_setting(key: &config::Key) -> Option<(String, Source)> {
+    let flag = format!("--{}", key.name);
+    let from_flag = match key.name {
+        "quiet" => (arg_set("-q") || arg_set("--quiet")).then(|| "true".to_string()),
+        "verbose" => {
+            let verbose: usize = std::env::args()
+                .skip(1)
+                .map(|arg| match arg.as_str() {
+                    "-v" | "--verbose" => 1,
+                    "-vv" => 2,
+                    _ => 0,
+                })
+                .sum();
+            (verbose > 0).then(|| verbose.min(2).to_string())
+        }
+        _ if key.expected == [config::Kind::Boolean] => arg_set(&flag).then(|| "true".to_string()),
+        _ => arg_value(&flag).or_else(|| {
+            let attached = flags::find(&flag).is_some_and(|flag| flag.value == flags::FlagValue::Attached);
+            (attached && arg_set(&flag)).then(|| "true".to_string())
+        }),
+    };
+    let from_env = || match key.env? {
+        "LANG" => Lang::from_env().map(|lang| lang.code().to_string()),
+        "NO_COLOR" => std::env::var_os("NO_COLOR").filter(|value| !value.is_empty()).map(|_| "true".to_string()),
+        name => std::env::var(name).ok().filter(|value| !value.trim().is_empty()),
+    };
+    from_flag
+        .map(|value| (value, Source::Flag))
+        .or_else(|| from_env().map(|value| (value, Source::Env)))
+        .or_else(|| config::get(key.name).map(|value| (value.to_flag(), Source::Config)))
+        .or_else(|| key.default.map(|value| (value.to_string(), Source::Default)))
+}
+
+/// `--print-config`: действующие настройки в виде файла настроек с
+/// источником каждого значения в комментарии.
+fn print_config() {
+    match &config::current().path {
+        Some(path) => outln!("# {}: {}", tr(Message::ConfigFile), path.display()),
+        None => outln!("# {}: {}", tr(Message::ConfigFile), tr(Message::NotSet)),
+    }
+    for key in config::KEYS {
+        match effective_setting(key) {
+            Some((value, source)) => {
+                let line = format!("{} = {}", key.name, config::toml_value(key, &value));
+                let source = match (source, key.env) {
+                    (Source::Env, Some(env)) => format!("{} {}", tr(source.message()), env),
+                    _ => tr(source.message()).to_string(),
+                };
+                outln!("{:<32} # {}", line, source);
+            }
+            None => outln!("# {} — {}", key.name, tr(Message::NotSet)),
+        }
+    }
 }
 
-// FIXME это первая производная
-fn second_derivative(x: f64) -> f64 {
-    (1.0/(x.powi(6) + x.powi(4) + x.powi(2) + 1.0)) -
-        (4.0*x.powi(3)*x.atan()) / (x.powi(8) + 2.0*x.powi(4) + 1.0)
+/// Значение флага `name` из командной строки, иначе из файла настроек
+/// (ключ — имя флага без `--`).
+fn flag_value(name: &str) -> Option<String> {
+    arg_value(name).or_else(|| config::get(name.trim_start_matches('-')).map(config::Value::to_flag))
 }
 
-fn calculate_accumulated_sum_on_range(
-    f: fn(f64) -> f64,
-    lower_bound: f64,
-    upper_bound: f64,
-    step: f64
-) -> f64 {
-    let mut local_sum = 0.0;
-    let mut i = lower_bound;
-    while i + step < upper_bound {
-        local_sum += f(i + step/2.0);
-        i += step;
+/// Задан ли флаг-переключатель `name` в командной строке или включён ли он
+/// (`true`) в файле настроек.
+fn flag_set(name: &str) -> bool {
+    arg_set(name) || config::get(name.trim_start_matches('-')) == Some(&config::Value::Boolean(true))
+}
+
+fn arg_set(name: &str) -> bool {
+    debug_assert!(flags::find(name).is_some(), "{} нет в flags::FLAGS", name);
+    std::env::args().skip(1).any(|arg| arg == name)
+}
+
+/// Значение флага `name` из командной строки (`--name value` или
+/// `--name=value`; у `FlagValue::Attached` — только второе).
+fn arg_value(name: &str) -> Option<String> {
+    let flag = flags::find(name);
+    debug_assert!(flag.is_some(), "{} нет в flags::FLAGS", name);
+    let attached = flag.is_some_and(|flag| flag.value == flags::FlagValue::Attached);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix(name) {
+            Some("") if !attached => return args.next(),
+            Some(value) if value.starts_with('=') => return Some(value[1..].to_string()),
+            _ => continue,
+        }
     }
-    local_sum
+    None
 }
 
-const THREADS_COUNT: i32 = 32;
+/// Пары x, y из CSV: по одной в строке, через запятую, точку с запятой или
+/// пробел. Пустые строки и строки с `#` пропускаются, первая строка — тоже,
+/// если это заголовок. При ошибке возвращает номер строки (с единицы).
+fn parse_table(text: &str) -> Result<Vec<(f64, f64)>, usize> {
+    let mut points = Vec::new();
+    let mut header_skipped = false;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<_> = line.split([',', ';', ' ', '\t']).filter(|field| !field.is_empty()).collect();
+        let point = match fields.as_slice() {
+            [x, y] => f64::from_str(x).ok().zip(f64::from_str(y).ok()),
+            _ => None,
+        };
+        match point {
+            Some(point) => points.push(point),
+            None if points.is_empty() && !header_skipped => header_skipped = true,
+            None => return Err(index + 1),
+        }
+    }
+    Ok(points)
+}
+
+/// Флаги, задающие входные данные основного режима.
+const INPUT_FLAGS: [&str; 8] =
+    ["--lower", "--upper", "--samples", "--tolerance", "--threads", "--method", "--function", "--expr"];
+
+/// Интерактивный режим — ни одно входное значение не задано флагом, не
+/// включён режим `--batch` и вывод не тихий.
+fn interactive() -> bool {
+    INPUT_FLAGS.iter().all(|flag| arg_value(flag).is_none()) && !batch() && !Verbosity::quiet()
+}
 
-fn calculate_integral_async<'a>(
-    f: fn(f64) -> f64,
-    lower_bound: f64,
-    upper_bound: f64,
-    samples: u64,
-) -> Result<f64, IntegralCalcError<'a>> {
-    if lower_bound > upper_bound {
-        return Err(IntegralCalcError::new("нижняя граница больше верхней"));
+/// Режим `--batch`; его включает и `--input path`.
+fn batch() -> bool {
+    flag_set("--batch") || flag_value("--input").is_some() || flag_set("--validate")
+}
+
+/// Сколько раз вопрос повторяется после ошибочного ответа. Повтор возможен
+/// только в интерактивном режиме с вводом с терминала: при вводе из файла
+/// или с флагами ошибка сразу завершает программу.
+const PROMPT_RETRIES: u32 = 5;
+
+fn prompt_retries() -> u32 {
+    if interactive() && stdin().is_terminal() {
+        PROMPT_RETRIES
+    } else {
+        0
     }
-    let range = upper_bound - lower_bound;
-    let mut handles: Vec<JoinHandle<_>> = Vec::new();
-    let mut current_sample = 0;
-    let threads_count = THREADS_COUNT;
-    let accumulated_sum = Arc::new(Mutex::new(0.0));
-    while current_sample < threads_count {
-        let current_lower_bound = lower_bound + current_sample as f64 * range / threads_count as f64;
-        let current_upper_bound = lower_bound + (current_sample + 1) as f64 * range / threads_count as f64;
-        let acc_sum_atomic_ref = accumulated_sum.clone();
-        let handle = std::thread::spawn(move || {
-            let local_sum = calculate_accumulated_sum_on_range(
-                f,
-                current_lower_bound,
-                current_upper_bound,
-                range / samples as f64
-            );
-            *acc_sum_atomic_ref.clone().lock().unwrap() += local_sum;
-        });
-        handles.push(handle);
-        current_sample += 1;
+}
+
+/// Вопрос `message`, ответ на который читается новой строкой.
+fn ask(message: Message) -> impl FnMut() -> String {
+    move || {
+        report::prompt(message);
+        read_line()
     }
+}
 
+/// Вопрос о необязательном значении: задаётся только в интерактивном
+/// режиме, иначе ответ пустой, то есть значение по умолчанию.
+fn ask_optional(message: Message) -> impl FnMut() -> String {
+    move || {
+        if !interactive() {
+            return String::new();
+        }
+        report::prompt(message);
+        read_optional_line()
+    }
+}
 
-    for handle in handles {
-        handle.join().unwrap();
+/// Печатает `error` при любой ошибке разбора и возвращает код завершения `code`.
+fn parse_failure<E>(error: Message, code: ExitReason) -> impl Fn(&E) -> ExitReason {
+    move |_| {
+        report::error(tr(error));
+        code
     }
+}
 
-    let result = *accumulated_sum.clone().lock().unwrap() / samples as f64;
-    Ok(result)
+/// Значение из флага `flag`, иначе очередной ответ из `answers`, разобранный
+/// `parse` (вопрос задаёт `ask`, если в уже введённой строке ответов не
+/// осталось). `report` печатает ошибку и возвращает код завершения; до
+/// завершения вопрос повторяется `prompt_retries()` раз.
+fn prompt_parse<V, E>(
+    flag: Option<&str>,
+    answers: &mut Answers,
+    ask: impl FnMut() -> String,
+    parse: impl Fn(&str) -> Result<V, E>,
+    report: impl Fn(&E) -> ExitReason,
+) -> V {
+    if let Some(value) = flag.and_then(flag_value) {
+        return parse(&value).unwrap_or_else(|e| exit_with(report(&e)));
+    }
+    let report = |error: &InputError<E>| match error {
+        InputError::Parse(error) => report(error),
+        InputError::UnexpectedToken(UnexpectedToken(token)) => {
+            report::error(format_args!("{}: {}", tr(Message::UnexpectedToken), token));
+            ExitReason::IncorrectArguments
+        }
+    };
+    let on_error = |error: &InputError<E>| {
+        report(error);
+    };
+    answers
+        .parse_next(ask, parse, on_error, prompt_retries())
+        .unwrap_or_else(|error| exit_with(report(&error)))
 }
 
-const MAX_SAMPLES_COUNT: u64 = 1_000_000_000u64;
-const ASYNC_THRESHOLD_SAMPLES_COUNT: u64 = 10_000u64;
+/// Почему не удалось разобрать число отсчётов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SamplesError {
+    Invalid,
+    /// Число не помещается в u64.
+    Overflow,
+    /// Запись вроде `1.5555k` не даёт целого числа.
+    NotInteger,
+}
 
-fn calculate_integral<'a>(
-    f: fn(f64) -> f64,
-    lower_bound: f64,
-    upper_bound: f64,
-    samples: u64,
-) -> Result<f64, IntegralCalcError<'a>> {
-    if lower_bound > upper_bound {
-        return Err(IntegralCalcError::new("нижняя граница больше верхней"));
+impl SamplesError {
+    /// Пояснение к ошибке разбора числа отсчётов.
+    fn note(self) -> Message {
+        match self {
+            SamplesError::Invalid => Message::SamplesFormats,
+            SamplesError::Overflow => Message::SamplesOverflow,
+            SamplesError::NotInteger => Message::SamplesNotInteger,
+        }
     }
-    if samples > MAX_SAMPLES_COUNT {
-        return Err(IntegralCalcError::new("превышено максимальное число отсчётов"));
+}
+
+/// Текст ошибки разбора числа отсчётов `error` с пояснением.
+fn samples_error_text(error: SamplesError) -> String {
+    format!("{} ({})", tr(Message::ParseSamplesError), tr(error.note()))
+}
+
+fn report_samples_error(error: &SamplesError) -> ExitReason {
+    report::error(samples_error_text(*error));
+    ExitReason::IncorrectSamplesCount
+}
+
+/// Число отсчётов: цифры с разделителями групп `_` или пробелами
+/// (`1_000_000`, `1 000 000`), с множителем `k` (10³), `m` или `kk` (10⁶),
+/// `g` (10⁹) в любом регистре или в записи с порядком (`1e6`, `1.5k`),
+/// если она даёт целое число. Запятая не допускается: `1,000` — не то
+/// тысяча, не то единица с десятичной запятой.
+fn parse_samples(line: &str) -> Result<u64, SamplesError> {
+    if line.contains(',') {
+        return Err(SamplesError::Invalid);
     }
-    if samples > ASYNC_THRESHOLD_SAMPLES_COUNT {
-        return calculate_integral_async(f, lower_bound, upper_bound, samples);
+    let line: String = line.chars().filter(|c| *c != '_' && !c.is_whitespace()).collect::<String>().to_lowercase();
+    let (digits, multiplier) = [("kk", 1_000_000u64), ("k", 1_000), ("m", 1_000_000), ("g", 1_000_000_000)]
+        .into_iter()
+        .find_map(|(suffix, multiplier)| line.strip_suffix(suffix).map(|digits| (digits, multiplier)))
+        .unwrap_or((&line, 1));
+    if digits.is_empty() {
+        return Err(SamplesError::Invalid);
     }
-    let step = (upper_bound - lower_bound) / samples as f64;
-    let accumulated_sum = calculate_accumulated_sum_on_range(
-        f, 
-        lower_bound, 
-        upper_bound, 
-        step
-    );
-    let result = accumulated_sum / samples as f64;
-    Ok(result)
+    if digits.chars().all(|c| c.is_ascii_digit()) {
+        return u64::from_str(digits)
+            .ok()
+            .and_then(|samples| samples.checked_mul(multiplier))
+            .ok_or(SamplesError::Overflow);
+    }
+    let samples = parse_number(digits)
+        .ok()
+        .filter(|samples| samples.is_finite() && *samples >= 0.0)
+        .ok_or(SamplesError::Invalid)?
+        * multiplier as f64;
+    if samples.fract() != 0.0 {
+        return Err(SamplesError::NotInteger);
+    }
+    // 2⁶⁴ точно представимо в f64, в отличие от u64::MAX
+    if samples >= 18_446_744_073_709_551_616.0 {
+        return Err(SamplesError::Overflow);
+    }
+    Ok(samples as u64)
+}
+
+/// Число отсчётов (см. `parse_samples`) или требуемая точность из (0, 1).
+fn parse_samples_or_tolerance(line: &str) -> Result<(u64, Option<f64>), SamplesError> {
+    parse_samples(line).map(|samples| (samples, None)).or_else(|error| {
+        match parse_number(line) {
+            Ok(tolerance) if tolerance > 0.0 && tolerance < 1.0 => Ok((0, Some(tolerance))),
+            _ => Err(error),
+        }
+    })
 }
 
-fn get_remaining_term(
-    second_derivative: fn(f64) -> f64,
-    lower_bound: f64,
-    upper_bound: f64,
-    step: f64
-) -> f64 {
-    let mut local_max = 0.0;
-    let mut i = lower_bound;
-    while i + step < upper_bound {
-        local_max = second_derivative(i).abs().max(local_max);
-        i += step;
+/// Пустая строка — `None` (значение по умолчанию), иначе значение `V`.
+fn parse_optional<V: FromStr>(line: &str) -> Result<Option<V>, V::Err> {
+    if line.is_empty() {
+        Ok(None)
+    } else {
+        V::from_str(line).map(Some)
     }
-    let factor = (upper_bound - lower_bound) * step.powi(2) / 24.0;
-    factor * local_max
 }
 
+/// Наибольшее число потоков, которое можно задать.
+const MAX_THREADS: usize = 4096;
 
-const EXIT_INCORRECT_LOWER_BOUND: i32 = 1;
-const EXIT_INCORRECT_UPPER_BOUND: i32 = 2;
-const EXIT_INCORRECT_SAMPLES_COUNT: i32 = 3;
-const EXIT_UNABLE_TO_CALCULATE: i32 = 4;
+/// Переменная окружения с числом потоков; флаг `--threads` её перекрывает.
+const THREADS_VARIABLE: &str = "KRYL_THREADS";
 
-fn main() {
-    print!("Введите нижнюю границу: ");
-    stdout().flush().unwrap();
-    let lower_bound = f64::from_str(get_line().unwrap())
-        .inspect_err(|_| {
-            eprintln!("Ошибка преобразования ввода в вещественное число");
-            exit(EXIT_INCORRECT_LOWER_BOUND);
-        })
-        .unwrap();
-    print!("Введите верхнюю границу: ");
-    stdout().flush().unwrap();
-    let upper_bound = f64::from_str(get_line().unwrap())
-        .inspect_err(|_| {
-            eprintln!("Ошибка преобразования ввода в вещественное число");
-            exit(EXIT_INCORRECT_UPPER_BOUND);
-        })
-        .unwrap();
-    print!("Введите количество отсчётов: ");
-    stdout().flush().unwrap();
-    let samples = u64::from_str(get_line().unwrap())
-        .inspect_err(|_| {
-            eprintln!("Ошибка преобразования ввода в целое число");
-            exit(EXIT_INCORRECT_SAMPLES_COUNT);
+/// Зерно метода Монте-Карло из `--seed`, иначе случайное, одно на весь
+/// запуск. Случайное зерно выводится в stderr один раз, при первом расчёте
+/// методом Монте-Карло (`stochastic`), чтобы его можно было повторить.
+fn select_seed(stochastic: bool) -> u64 {
+    static SEED: OnceLock<(u64, bool)> = OnceLock::new();
+    static REPORTED: AtomicBool = AtomicBool::new(false);
+    let &(seed, random) = SEED.get_or_init(|| seed_flag().map_or_else(|| (random_seed(), true), |seed| (seed, false)));
+    if random && stochastic && !REPORTED.swap(true, Ordering::Relaxed) {
+        eprintln!("{}: {}", tr(Message::Seed), seed);
+    }
+    seed
+}
+
+/// Зерно из `--seed`: десятичное или шестнадцатеричное с `0x`.
+fn seed_flag() -> Option<u64> {
+    let seed = flag_value("--seed")?;
+    let seed = seed.trim();
+    let parsed = match seed.strip_prefix("0x").or_else(|| seed.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => u64::from_str(seed),
+    };
+    Some(parsed.unwrap_or_else(|_| {
+        report::error(tr(Message::ParseSeedError));
+        exit_with(ExitReason::IncorrectArguments);
+    }))
+}
+
+/// Случайное зерно: ключи `RandomState` std берёт у ОС, к ним добавляется время.
+fn random_seed() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos()));
+    hasher.finish()
+}
+
+/// Число потоков от 1 до `MAX_THREADS`; пустая строка и 0 — `None`, то есть
+/// по числу ядер.
+fn parse_threads(line: &str) -> Result<Option<usize>, ()> {
+    match parse_optional::<usize>(line.trim()) {
+        Ok(None | Some(0)) => Ok(None),
+        Ok(Some(threads)) if threads <= MAX_THREADS => Ok(Some(threads)),
+        _ => Err(()),
+    }
+}
+
+/// Число потоков из `--threads`, иначе из `KRYL_THREADS`; если не задано ни
+/// то, ни другое, оно спрашивается при `ask` (только в интерактивном режиме).
+/// `None` — по числу ядер.
+fn select_threads(ask: bool) -> Option<usize> {
+    let report = parse_failure(Message::ParseThreadsError, ExitReason::IncorrectThreadsCount);
+    match std::env::var(THREADS_VARIABLE) {
+        Ok(value) if !value.trim().is_empty() && arg_value("--threads").is_none() => {
+            parse_threads(&value).unwrap_or_else(|e| exit_with(report(&e)))
+        }
+        _ => {
+            let mut question = ask_optional(Message::PromptThreads);
+            let ask = move || if ask { question() } else { String::new() };
+            prompt_parse(Some("--threads"), &mut Answers::new(1), ask, parse_threads, report)
+        }
+    }
+}
+
+/// Режим `--table path`: интеграл по табличным значениям из файла.
+fn run_table(path: &str) {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        report::error(format_args!("{}: {}", tr(Message::TableReadError), e));
+        exit_with(ExitReason::IncorrectTable);
+    });
+    let points = parse_table(&text).unwrap_or_else(|line| {
+        report::error(format_args!("{} {}", tr(Message::TableParseError), line));
+        exit_with(ExitReason::IncorrectTable);
+    });
+    let value = integrate_table(&points)
+        .inspect_err(|e| {
+            report::error(e);
+            exit_with(ExitReason::from(e));
         })
         .unwrap();
-    let result = calculate_integral(
-        function,
-        lower_bound,
-        upper_bound,
-        samples
+    report::value(Message::ApproximateValue, value);
+    if let Ok(simpson) = integrate_table_simpson(&points) {
+        report!("{}: {}", tr(Message::SimpsonValue), report::number(simpson));
+    }
+    if let Ok(spline) = integrate_spline(&points) {
+        report!("{}: {}", tr(Message::SplineValue), report::number(spline));
+    }
+}
+
+/// Запрашивает число отсчётов, см. `parse_samples`.
+fn prompt_samples(message: Message) -> u64 {
+    prompt_parse(None, &mut Answers::new(1), ask(message), parse_samples, report_samples_error)
+}
+
+/// Запрашивает границу интегрирования, см. `parse_bound`.
+fn prompt_bound(message: Message, code: ExitReason) -> f64 {
+    prompt_parse(None, &mut Answers::new(1), ask(message), parse_bound, bound_failure(code))
+}
+
+/// Запрашивает вещественное число, завершая программу с `code` при ошибке ввода.
+fn prompt_number(message: Message, code: ExitReason) -> f64 {
+    prompt_parse(None, &mut Answers::new(1), ask(message), parse_number, parse_failure(Message::ParseFloatError, code))
+}
+
+/// Режим `--2d`: двойной интеграл от f(x)·f(y) по прямоугольнику.
+fn run_2d() {
+    let x_bounds = (
+        prompt_bound(Message::PromptLowerBoundX, ExitReason::IncorrectLowerBound),
+        prompt_bound(Message::PromptUpperBoundX, ExitReason::IncorrectUpperBound),
+    );
+    let y_bounds = (
+        prompt_bound(Message::PromptLowerBoundY, ExitReason::IncorrectLowerBound),
+        prompt_bound(Message::PromptUpperBoundY, ExitReason::IncorrectUpperBound),
+    );
+    let samples_x = prompt_samples(Message::PromptSamplesX);
+    let samples_y = prompt_samples(Message::PromptSamplesY);
+    let function = prompt_function();
+    let result = calculate_integral_2d(
+        |x: f64, y: f64| function.eval(x) * function.eval(y),
+        x_bounds,
+        y_bounds,
+        samples_x,
+        samples_y,
     )
         .inspect_err(|e| {
-            eprintln!("{}", e);
-            exit(EXIT_UNABLE_TO_CALCULATE);
+            report::error(e);
+            exit_with(ExitReason::from(e));
         })
         .unwrap();
-    println!("Приближённое значение интеграла: {}", result);
-    let result_for_inaccuracy = calculate_integral_async(
-        function,
+    report!("{}: {}", tr(Message::Integrand), function.name());
+    report::value(Message::ApproximateValue, result.value);
+    report!("{}: {}, {}: {}, {}: {}, {}: {}, {}: {:?}",
+             tr(Message::Step), report::number(result.step),
+             tr(Message::Intervals), result.intervals,
+             tr(Message::Evaluations), result.samples,
+             tr(Message::Threads), result.threads,
+             tr(Message::Elapsed), result.elapsed);
+}
+
+/// Запрашивает метод; пустой ввод — метод по умолчанию.
+fn prompt_method() -> Method {
+    let method = prompt_parse(
+        Some("--method"),
+        &mut Answers::new(1),
+        ask_optional(Message::PromptMethod),
+        parse_optional::<Method>,
+        |e| {
+            report::error(e);
+            ExitReason::from(e)
+        },
+    );
+    method.unwrap_or_default()
+}
+
+/// Подынтегральная функция: встроенная или введённое выражение от x.
+#[derive(Debug, Clone)]
+enum Function {
+    Builtin(BuiltinFunction),
+    Expression(Expression),
+}
+
+impl Default for Function {
+    fn default() -> Self {
+        Function::Builtin(BuiltinFunction::default())
+    }
+}
+
+impl Integrand<f64> for Function {
+    fn eval(&self, x: f64) -> f64 {
+        match self {
+            Function::Builtin(function) => function.eval(x),
+            Function::Expression(expression) => expression.eval(x),
+        }
+    }
+
+    fn second_derivative(&self, x: f64) -> Option<f64> {
+        match self {
+            Function::Builtin(function) => function.second_derivative(x),
+            Function::Expression(expression) => expression.second_derivative(x),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Function::Builtin(function) => function.name(),
+            Function::Expression(expression) => expression.source(),
+        }
+    }
+}
+
+impl FromStr for Function {
+    type Err = IntegralCalcError;
+
+    /// Номер встроенной функции, её имя или выражение от x.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.chars().all(|c| c.is_ascii_digit()) || BuiltinFunction::find(s).is_some() {
+            return BuiltinFunction::from_str(s).map(Function::Builtin);
+        }
+        Expression::parse(s).map(Function::Expression)
+    }
+}
+
+/// Запрашивает функцию: номер или имя встроенной либо выражение от x;
+/// пустой ввод — функция по умолчанию. Флаг `--expr` задаёт выражение.
+fn prompt_function() -> Function {
+    if let Some(source) = flag_value("--expr") {
+        return Expression::parse(&source).map(Function::Expression).unwrap_or_else(|e| {
+            report::error(&e);
+            exit_with(ExitReason::from(&e));
+        });
+    }
+    let ask = || {
+        if interactive() && report::prompts() {
+            for (number, function) in BUILTIN_FUNCTIONS.iter().enumerate() {
+                outln!("  {}. {}", number + 1, function);
+            }
+        }
+        ask_optional(Message::PromptFunction)()
+    };
+    let function = prompt_parse(Some("--function"), &mut Answers::new(1), ask, parse_optional::<Function>, |e| {
+        report::error(e);
+        ExitReason::from(e)
+    });
+    function.unwrap_or_default()
+}
+
+/// `--with-reference[=N]` или `with-reference` в файле настроек (`true` или
+/// число): число отсчётов контрольного расчёта, если он нужен; без значения —
+/// `MAX_SAMPLES_COUNT`. Значение задаётся только через `=`, чтобы следующий
+/// флаг не принимался за число отсчётов. Устаревшие `--reference` и
+/// `--reference-samples N` (и одноимённые ключи файла настроек) по-прежнему
+/// включают расчёт и задают число отсчётов.
+fn with_reference() -> Option<u64> {
+    let arg = arg_value("--with-reference").map(Some).or_else(|| arg_set("--with-reference").then_some(None));
+    let value = match arg {
+        Some(value) => value,
+        None => match config::get("with-reference") {
+            Some(config::Value::Boolean(true)) => None,
+            Some(config::Value::Boolean(false)) => return None,
+            Some(value) => Some(value.to_flag()),
+            None if flag_set("--reference") => None,
+            None => return None,
+        },
+    };
+    let value = value.or_else(|| flag_value("--reference-samples"));
+    let samples = value.map_or(MAX_SAMPLES_COUNT, |value| {
+        parse_samples(&value).unwrap_or_else(|e| exit_with(report_samples_error(&e)))
+    });
+    if samples == 0 {
+        exit_on_error(&IntegralCalcError::ZeroSamples);
+    }
+    if samples > MAX_SAMPLES_COUNT {
+        exit_on_error(&IntegralCalcError::TooManySamples { requested: samples, max: MAX_SAMPLES_COUNT });
+    }
+    Some(samples)
+}
+
+/// Режим `--batch`: каждая строка ввода `a b n [метод] [функция]` — отдельный
+/// расчёт, пустые строки и строки с `#` пропускаются. Значение или ошибка
+/// выводятся с номером строки; код завершения ненулевой, если не удалось
+/// посчитать хотя бы одну строку. Метод и функция по умолчанию берутся из
+/// флагов `--method`, `--function`, `--expr`, строки читаются из файла
+/// `--input path` или стандартного ввода, значения пишутся в файл
+/// `--output path` или на стандартный вывод (ошибки — всегда в stderr).
+fn run_batch() {
+    let method = prompt_method();
+    let function = prompt_function();
+    let threads = select_threads(false);
+    let input_path = flag_value("--input");
+    let input: Box<dyn BufRead> = match &input_path {
+        Some(path) => Box::new(BufReader::new(File::open(path).unwrap_or_else(|e| {
+            exit_on_error(&IntegralCalcError::InputFile { path: path.clone(), reason: e.to_string() })
+        }))),
+        None => Box::new(stdin().lock()),
+    };
+    let format = flag_value("--format").map_or(BatchFormat::Text, |format| {
+        format.parse().unwrap_or_else(|()| {
+            report::error(tr(Message::UnknownFormat));
+            exit_with(ExitReason::IncorrectArguments);
+        })
+    });
+    let mut output = flag_value("--output").map(|path| PendingOutput::create(path).unwrap_or_else(|e| exit_on_error(&e)));
+    let mut emit = |line: &str| match &mut output {
+        Some(output) => output.write_line(line).unwrap_or_else(|e| exit_on_error(&e)),
+        None => outln!("{}", line),
+    };
+    let validate = flag_set("--validate");
+    if format == BatchFormat::Csv {
+        emit(if validate { VALIDATE_CSV_HEADER } else { CSV_HEADER });
+    }
+    let mut failed = false;
+    for (index, line) in input.lines().enumerate() {
+        let line = match (line, &input_path) {
+            (Ok(line), _) => line,
+            (Err(e), Some(path)) => exit_on_error(&IntegralCalcError::InputFile { path: path.clone(), reason: e.to_string() }),
+            (Err(e), None) => exit_on_input_error(&e),
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if validate {
+            let verdict = validate_job(line, method, &function, threads);
+            failed |= verdict.is_err();
+            match (format, verdict) {
+                (BatchFormat::Text, Ok(())) => emit(&format!("{}: {}", index + 1, tr(Message::ValidateOk))),
+                (BatchFormat::Text, Err(error)) => report::error(format_args!("{}: {}", index + 1, error)),
+                (BatchFormat::Csv, verdict) => {
+                    let mut fields = csv_job_fields(line, method);
+                    fields.push(verdict.err().unwrap_or_else(|| "ok".to_string()));
+                    emit(&csv_join(&fields));
+                }
+            }
+            continue;
+        }
+        let result = run_job(line, method, &function, threads);
+        failed |= result.is_err();
+        match (format, result) {
+            (BatchFormat::Text, Ok(result)) => emit(&format!("{}: {}", index + 1, report::number(result.value))),
+            (BatchFormat::Text, Err(error)) => report::error(format_args!("{}: {}", index + 1, error)),
+            (BatchFormat::Csv, result) => emit(&csv_row(line, method, &result)),
+        }
+    }
+    if let Some(output) = output {
+        output.commit().unwrap_or_else(|e| exit_on_error(&e));
+    }
+    if failed {
+        exit_with(ExitReason::BatchFailed);
+    }
+}
+
+/// Файл результатов: строки пишутся во временный файл рядом с `path`, который
+/// заменяет `path` только в `commit`, поэтому прерванный расчёт не оставляет
+/// наполовину записанного файла.
+struct PendingOutput {
+    path: String,
+    temporary: String,
+    writer: BufWriter<File>,
+}
+
+impl PendingOutput {
+    fn create(path: String) -> Result<Self, IntegralCalcError> {
+        let temporary = format!("{}.tmp{}", path, std::process::id());
+        let file = File::create(&temporary)
+            .map_err(|e| IntegralCalcError::OutputFile { path: path.clone(), reason: e.to_string() })?;
+        Ok(Self { path, temporary, writer: BufWriter::new(file) })
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), IntegralCalcError> {
+        writeln!(self.writer, "{}", line).map_err(|e| self.failure(e))
+    }
+
+    fn commit(mut self) -> Result<(), IntegralCalcError> {
+        self.writer.flush()
+            .and_then(|()| self.writer.get_ref().sync_all())
+            .and_then(|()| std::fs::rename(&self.temporary, &self.path))
+            .map_err(|e| self.failure(e))
+    }
+
+    /// Ошибка записи; временный файл удаляется.
+    fn failure(&self, error: IOError) -> IntegralCalcError {
+        let _ = std::fs::remove_file(&self.temporary);
+        IntegralCalcError::OutputFile { path: self.path.clone(), reason: error.to_string() }
+    }
+}
+
+/// Итог основного расчёта: сводка прохода, функция, метод, значение,
+/// параметры сетки, подозрение на особенность и оценка погрешности, если
+/// формула её даёт.
+fn report_result(function: &Function, method: Method, result: &IntegrationResult) {
+    report::run_summary(Message::PhaseMain, result);
+    report!("{}: {}", tr(Message::Integrand), function.name());
+    report!("{}: {}", tr(Message::Method), method);
+    report::value(Message::ApproximateValue, result.value);
+    report!("{}: {}, {}: {}, {}: {}, {}: {}, {}: {:?}",
+             tr(Message::Step), report::number(result.step),
+             tr(Message::Intervals), result.intervals,
+             tr(Message::Evaluations), result.samples,
+             tr(Message::Threads), result.threads,
+             tr(Message::Elapsed), result.elapsed);
+    if let Some(x) = result.singularity {
+        detail!("{} {}", tr(Message::SuspectedSingularity), report::number(x));
+    }
+    if let Some(error_estimate) = result.error_estimate {
+        report!("{}: {}", tr(Message::ErrorEstimate), report::number(error_estimate));
+    }
+}
+
+/// Сообщает об ошибке и завершает программу; для прерванного по Ctrl+C
+/// расчёта выводит и неполную оценку по пройденным отсчётам.
+fn exit_on_error(error: &IntegralCalcError) -> ! {
+    if let IntegralCalcError::Interrupted { estimate, completed } = error {
+        report::error(format_args!("{} {:.1}%", tr(Message::Interrupted), completed * 100.0));
+        outln!("{}: {}", tr(Message::PartialEstimate), report::number(*estimate));
+    } else {
+        report::error(error);
+    }
+    exit_with(ExitReason::from(error));
+}
+
+/// Один расчёт режима `--batch`; ошибка — готовый к выводу текст.
+fn run_job(line: &str, method: Method, function: &Function, threads: Option<usize>) -> Result<IntegrationResult, String> {
+    let (function, request) = parse_job(line, method, function, threads)?;
+    let IntegrationRequest { lower_bound, upper_bound, tolerance, options } = request;
+    let result = match tolerance {
+        Some(tolerance) => integrate_to_tolerance_with_options(function, lower_bound, upper_bound, tolerance, &options),
+        None => calculate_improper_integral_with_options(function, lower_bound, upper_bound, &options),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Функция и условия расчёта по строке режима `--batch`; f не вычисляется.
+fn parse_job(line: &str, method: Method, function: &Function, threads: Option<usize>)
+             -> Result<(Function, IntegrationRequest), String> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let [lower_bound, upper_bound, samples, rest @ ..] = tokens.as_slice() else {
+        return Err(tr(Message::BatchTooFewColumns).to_string());
+    };
+    let lower_bound = parse_bound(lower_bound).map_err(|e| e.to_string())?;
+    let upper_bound = parse_bound(upper_bound).map_err(|e| e.to_string())?;
+    let (samples, tolerance) = parse_samples_or_tolerance(samples).map_err(samples_error_text)?;
+    // Четвёртый столбец — метод, если это имя метода; остаток строки — функция
+    let line_method = rest.first().and_then(|name| Method::from_str(name).ok());
+    let rest = if line_method.is_some() { &rest[1..] } else { rest };
+    let function = match rest {
+        [] => function.clone(),
+        // Одно слово, которое не разбирается как функция, — скорее опечатка
+        // в имени метода: сообщаем о методе с подсказкой, а не о выражении
+        [name] if line_method.is_none() && name.chars().all(|c| c.is_ascii_alphabetic() || c == '_' || c == '-') =>
+            Function::from_str(name).map_err(|_| Method::from_str(name).unwrap_err().to_string())?,
+        _ => Function::from_str(&rest.join(" ")).map_err(|e| e.to_string())?,
+    };
+    let method = line_method.unwrap_or(method);
+    let options = IntegrationOptions { samples, method, threads, seed: select_seed(method == Method::MonteCarlo), ..Default::default() };
+    let request = IntegrationRequest::new(lower_bound, upper_bound, &options);
+    Ok((function, IntegrationRequest { tolerance, ..request }))
+}
+
+/// `--validate`: строку разбирают и проверяют так же, как перед расчётом
+/// (`validate_request`), не вычисляя f.
+fn validate_job(line: &str, method: Method, function: &Function, threads: Option<usize>) -> Result<(), String> {
+    let (_, request) = parse_job(line, method, function, threads)?;
+    validate_request(&request).map_err(|e| e.to_string())
+}
+
+/// Формат вывода режима `--batch` и подкоманды `table` (`--format text|csv`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BatchFormat {
+    /// «номер строки: значение», ошибки — в stderr.
+    Text,
+    /// Заголовок и по строке CSV на расчёт, ошибки — в столбце status.
+    Csv,
+}
+
+impl FromStr for BatchFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "csv" => Ok(Self::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+const CSV_HEADER: &str = "lower,upper,samples,method,value,abs_error,rel_error,elapsed_ms,remainder_ms,status";
+const VALIDATE_CSV_HEADER: &str = "lower,upper,samples,method,status";
+
+/// Границы, число отсчётов и метод строки `line` как введены.
+fn csv_job_fields(line: &str, method: Method) -> Vec<String> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let method = tokens.get(3).and_then(|name| Method::from_str(name).ok()).unwrap_or(method);
+    let mut fields: Vec<String> = (0..3).map(|i| tokens.get(i).copied().unwrap_or_default().to_string()).collect();
+    fields.push(method.name().to_string());
+    fields
+}
+
+fn csv_join(fields: &[String]) -> String {
+    fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")
+}
+
+/// Строка CSV для расчёта по строке `line`. Границы и число отсчётов берутся
+/// из строки как введены, поэтому сохраняются и у неудачных расчётов.
+/// Погрешность — собственная оценка метода, иначе граница остаточного
+/// члена; числа выводятся в кратчайшей записи, точно восстанавливающей f64.
+fn csv_row(line: &str, method: Method, result: &Result<IntegrationResult, String>) -> String {
+    let mut fields = csv_job_fields(line, method);
+    match result {
+        Ok(result) => {
+            let absolute = result.error_estimate.or(result.remainder_bound);
+            let relative = absolute.and_then(|absolute| AccuracyReport::from_absolute(result.value, absolute).relative);
+            fields.push(format!("{:e}", result.value));
+            fields.push(absolute.map(|absolute| format!("{:e}", absolute)).unwrap_or_default());
+            fields.push(relative.map(|relative| format!("{:e}", relative)).unwrap_or_default());
+            fields.push(format!("{:.3}", result.elapsed.as_secs_f64() * 1e3));
+            fields.push(format!("{:.3}", result.remainder_elapsed.as_secs_f64() * 1e3));
+            fields.push("ok".to_string());
+        }
+        Err(error) => {
+            fields.extend(std::iter::repeat_n(String::new(), 5));
+            fields.push(error.clone());
+        }
+    }
+    csv_join(&fields)
+}
+
+/// Поле CSV; поле с запятой, кавычкой или переводом строки берётся в кавычки.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Режим `--convergence`: таблица оценок при удвоении числа отсчётов.
+fn run_convergence() {
+    let lower_bound = prompt_bound(Message::PromptLowerBound, ExitReason::IncorrectLowerBound);
+    let upper_bound = prompt_bound(Message::PromptUpperBound, ExitReason::IncorrectUpperBound);
+    let samples = prompt_samples(Message::PromptStartSamples);
+    let tolerance = prompt_number(Message::PromptTolerance, ExitReason::ToleranceNotReached);
+    let method = prompt_method();
+    let function = prompt_function();
+    let seed = select_seed(method == Method::MonteCarlo);
+    let options = IntegrationOptions { samples, method, threads: select_threads(false), seed, ..Default::default() };
+    let study = convergence_study_with_options(
+        function.clone(),
         lower_bound,
         upper_bound,
-        MAX_SAMPLES_COUNT
+        CONVERGENCE_MAX_DOUBLINGS,
+        tolerance,
+        &options,
     )
         .inspect_err(|e| {
-            eprintln!("{}", e);
-            exit(EXIT_UNABLE_TO_CALCULATE);
+            report::error(e);
+            exit_with(ExitReason::from(e));
         })
         .unwrap();
-    println!("\"Действительное\" значение интеграла: {}", result_for_inaccuracy);
-    let absolute_inaccuracy = (result_for_inaccuracy - result).abs();
-    let relative_incaccuracy = absolute_inaccuracy / result;
-    let step = (upper_bound - lower_bound) / samples as f64;
-    println!("Абсолютная погрешность: {}", absolute_inaccuracy);
-    let remaining_term_max = get_remaining_term(second_derivative, lower_bound, upper_bound, step);
-    println!("Верхняя граница для Rn: {}", remaining_term_max);
-    println!("Абсолютная погрешность соответствует остаточному члену: {}", absolute_inaccuracy <= remaining_term_max);
-    println!("Относительная погрешность: {}%", relative_incaccuracy * 100.0);
+    report!("{}: {}", tr(Message::Integrand), function.name());
+    report!("{}: {}", tr(Message::Method), method);
+    outln!("{:>12}  {:>24}  {:>12}", tr(Message::ColumnSamples), tr(Message::ColumnValue), tr(Message::ColumnDifference));
+    for row in &study.rows {
+        let delta = row.delta.map_or_else(|| "-".to_string(), |delta| format!("{:.3e}", delta));
+        outln!("{:>12}  {:>24.16}  {:>12}", row.samples, row.value, delta);
+    }
+    if let Some(order) = study.order {
+        outln!("{}: {:.3}", tr(Message::ConvergenceOrder), order);
+    }
+    if !study.converged {
+        outln!("{}", tr(Message::NotConverged));
+    }
+}
+
+/// Подкоманда `bench`: все методы с примерно одинаковым числом вычислений f
+/// (`--evals`) на [`--lower`, `--upper`] и их погрешность относительно общего
+/// опорного значения. Вывод — таблицей, `--format csv` или `--format json`;
+/// `--sort error` упорядочивает строки по погрешности.
+fn run_bench() {
+    let lower_bound = prompt_parse(
+        Some("--lower"),
+        &mut Answers::new(1),
+        ask(Message::PromptLowerBound),
+        parse_bound,
+        bound_failure(ExitReason::IncorrectLowerBound),
+    );
+    let upper_bound = prompt_parse(
+        Some("--upper"),
+        &mut Answers::new(1),
+        ask(Message::PromptUpperBound),
+        parse_bound,
+        bound_failure(ExitReason::IncorrectUpperBound),
+    );
+    let evaluations = prompt_parse(
+        Some("--evals"),
+        &mut Answers::new(1),
+        ask(Message::PromptEvaluations),
+        parse_samples,
+        report_samples_error,
+    );
+    let format = flag_value("--format").map_or(BenchFormat::Text, |format| {
+        format.parse().unwrap_or_else(|()| {
+            report::error(tr(Message::UnknownFormat));
+            exit_with(ExitReason::IncorrectArguments);
+        })
+    });
+    let by_error = match flag_value("--sort").as_deref().map(str::trim) {
+        None | Some("method") => false,
+        Some("error") => true,
+        Some(_) => {
+            report::error(tr(Message::UnknownSort));
+            exit_with(ExitReason::IncorrectArguments);
+        }
+    };
+    let function = prompt_function();
+    // Среди формул сравнения есть Монте-Карло
+    let options = IntegrationOptions { threads: select_threads(false), seed: select_seed(true), ..Default::default() };
+    let mut bench = bench_methods(function.clone(), lower_bound, upper_bound, evaluations, &options)
+        .unwrap_or_else(|e| exit_on_error(&e));
+    if by_error {
+        bench.rows.sort_by(|a, b| a.error.total_cmp(&b.error));
+    }
+    match format {
+        BenchFormat::Text => print_bench_table(&function, &bench),
+        BenchFormat::Csv => {
+            outln!("method,samples,evaluations,value,error,elapsed_ms");
+            for row in &bench.rows {
+                outln!("{},{},{},{:e},{:e},{:.3}",
+                         row.method.name(), row.samples, row.evaluations, row.value, row.error,
+                         row.elapsed.as_secs_f64() * 1e3);
+            }
+        }
+        BenchFormat::Json => {
+            // Вместо предупреждений в stderr — массив `warnings`
+            let warnings: Vec<String> = bench.rows.iter()
+                .filter(|_| !flag_set("--no-warnings"))
+                .filter_map(|row| {
+                    AccuracyWarning::check(row.method, &function, lower_bound, upper_bound, row.samples, row.value)
+                        .map(|warning| format!("    {}", history::json_string(&format!("{}: {}", row.method, warning))))
+                })
+                .collect();
+            let rows: Vec<String> = bench.rows.iter().map(|row| format!(
+                "    {{\"method\": {}, \"samples\": {}, \"evaluations\": {}, \"value\": {}, \"error\": {}, \"elapsed_ms\": {:.3}}}",
+                history::json_string(row.method.name()), row.samples, row.evaluations,
+                history::json_number(row.value), history::json_number(row.error), row.elapsed.as_secs_f64() * 1e3,
+            )).collect();
+            outln!("{{");
+            outln!("  \"function\": {},", history::json_string(function.name()));
+            outln!("  \"reference\": {},", history::json_number(bench.reference));
+            outln!("  \"reference_error\": {},", bench.reference_error.map_or_else(|| "null".to_string(), history::json_number));
+            outln!("  \"reference_ms\": {:.3},", bench.reference_elapsed.as_secs_f64() * 1e3);
+            outln!("  \"rows\": [\n{}\n  ],", rows.join(",\n"));
+            if warnings.is_empty() {
+                outln!("  \"warnings\": []");
+            } else {
+                outln!("  \"warnings\": [\n{}\n  ]", warnings.join(",\n"));
+            }
+            outln!("}}");
+        }
+    }
+}
+
+fn print_bench_table(function: &Function, bench: &Bench) {
+    report!("{}: {}", tr(Message::Integrand), function.name());
+    let reference_error = bench.reference_error.map_or_else(String::new, |error| format!(" ± {:.1e}", error));
+    report!("{}: {}{} ({:?})", tr(Message::BenchReference), report::number(bench.reference), reference_error, bench.reference_elapsed);
+    let header = [
+        Message::Method,
+        Message::ColumnValue,
+        Message::ColumnError,
+        Message::Elapsed,
+        Message::ColumnEvaluations,
+    ]
+    .map(|message| tr(message).to_lowercase());
+    let rows: Vec<[String; 5]> = bench.rows.iter().map(|row| [
+        row.method.name().to_string(),
+        report::number(row.value),
+        format!("{:.3e}", row.error),
+        format!("{:?}", row.elapsed),
+        row.evaluations.to_string(),
+    ]).collect();
+    print_columns(&header, &rows, true);
+}
+
+/// Таблица на стандартный вывод: столбцы выровнены по правому краю, первый
+/// при `text_first` — по левому.
+fn print_columns<const N: usize>(header: &[String; N], rows: &[[String; N]], text_first: bool) {
+    let mut widths = [0; N];
+    for row in std::iter::once(header).chain(rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(header).chain(rows) {
+        let cells: Vec<String> = row.iter().zip(widths).enumerate().map(|(index, (cell, width))| {
+            if index == 0 && text_first { format!("{:<1$}", cell, width) } else { format!("{:>1$}", cell, width) }
+        }).collect();
+        outln!("{}", cells.join("  ").trim_end());
+    }
+}
+
+/// Число строк таблицы сходимости по умолчанию и наибольшее.
+const TABLE_DEFAULT_ROWS: u32 = 12;
+const TABLE_MAX_ROWS: u32 = 40;
+const TABLE_DEFAULT_START: u64 = 16;
+
+/// Подкоманда `table`: классическая таблица сходимости — n, Iₙ, |Iₙ − I₂ₙ|,
+/// наблюдаемый порядок и граница остаточного члена — для `--rows` строк,
+/// начиная с `--start` отсчётов; `--format csv` — в CSV.
+fn run_convergence_table() {
+    let lower_bound = prompt_parse(
+        Some("--lower"),
+        &mut Answers::new(1),
+        ask(Message::PromptLowerBound),
+        parse_bound,
+        bound_failure(ExitReason::IncorrectLowerBound),
+    );
+    let upper_bound = prompt_parse(
+        Some("--upper"),
+        &mut Answers::new(1),
+        ask(Message::PromptUpperBound),
+        parse_bound,
+        bound_failure(ExitReason::IncorrectUpperBound),
+    );
+    let start = flag_value("--start").map_or(TABLE_DEFAULT_START, |start| {
+        parse_samples(&start).unwrap_or_else(|e| exit_with(report_samples_error(&e)))
+    });
+    let rows = flag_value("--rows").map_or(TABLE_DEFAULT_ROWS, |rows| {
+        u32::from_str(rows.trim()).ok().filter(|rows| (1..=TABLE_MAX_ROWS).contains(rows)).unwrap_or_else(|| {
+            report::error(tr(Message::ParseRowsError));
+            exit_with(ExitReason::IncorrectArguments);
+        })
+    });
+    let format = flag_value("--format").map_or(BatchFormat::Text, |format| {
+        format.parse().unwrap_or_else(|()| {
+            report::error(tr(Message::UnknownFormat));
+            exit_with(ExitReason::IncorrectArguments);
+        })
+    });
+    let method = prompt_method();
+    let function = prompt_function();
+    let seed = select_seed(method == Method::MonteCarlo);
+    let options = IntegrationOptions { samples: start, method, threads: select_threads(false), seed, ..Default::default() };
+    let table = convergence_table_with_options(function.clone(), lower_bound, upper_bound, rows, &options)
+        .unwrap_or_else(|e| exit_on_error(&e))
+        .table();
+    if format == BatchFormat::Csv {
+        let field = |value: Option<f64>| value.map(|value| format!("{:e}", value)).unwrap_or_default();
+        outln!("n,value,difference,order,remainder_bound");
+        for row in &table {
+            outln!("{},{:e},{},{},{}",
+                     row.samples, row.value, field(row.difference), field(row.order), field(row.remainder_bound));
+        }
+        return;
+    }
+    report!("{}: {}", tr(Message::Integrand), function.name());
+    report!("{}: {}", tr(Message::Method), method);
+    let header = ["n", "Iₙ", "|Iₙ − I₂ₙ|", "p", "Rₙ"].map(str::to_string);
+    let cell = |value: Option<f64>, precision: usize| value.map_or_else(|| "-".to_string(), |value| format!("{:.1$e}", value, precision));
+    let rows: Vec<[String; 5]> = table.iter().map(|row| [
+        row.samples.to_string(),
+        format!("{:.16}", row.value),
+        cell(row.difference, 3),
+        row.order.map_or_else(|| "-".to_string(), |order| format!("{:.3}", order)),
+        cell(row.remainder_bound, 3),
+    ]).collect();
+    print_columns(&header, &rows, false);
+}
+
+/// Формат вывода подкоманды `bench` (`--format text|csv|json`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BenchFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+impl FromStr for BenchFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Высота графика `--plot` из `--plot-height` (по умолчанию
+/// `plot::DEFAULT_PLOT_HEIGHT` строк).
+fn plot_height() -> usize {
+    flag_value("--plot-height").map_or(plot::DEFAULT_PLOT_HEIGHT, |height| {
+        usize::from_str(height.trim()).ok().filter(|height| (2..=plot::MAX_PLOT_HEIGHT).contains(height)).unwrap_or_else(|| {
+            report::error(tr(Message::ParsePlotHeightError));
+            exit_with(ExitReason::IncorrectArguments);
+        })
+    })
+}
+
+/// Диалог с пользователем: `--interactive` или `--no-interactive`, иначе
+/// `interactive` из файла настроек, иначе — только если и stdin, и stdout —
+/// терминалы.
+fn select_prompts() -> bool {
+    if arg_set("--interactive") {
+        return true;
+    }
+    if arg_set("--no-interactive") {
+        return false;
+    }
+    match config::get("interactive") {
+        Some(config::Value::Boolean(enabled)) => *enabled,
+        _ => stdin().is_terminal() && std::io::stdout().is_terminal(),
+    }
+}
+
+/// Основной режим: один интеграл, без подкоманд и других режимов.
+fn main_mode() -> bool {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    flags::positionals(&args).subcommand.is_none()
+        && !["--help", "-h", "--print-config", "--list-exit-codes"].iter().any(|flag| arg_set(flag))
+        && flag_value("--table").is_none()
+        && !["--2d", "--repl", "--convergence"].iter().any(|flag| flag_set(flag))
+        && !batch()
+}
+
+/// Подробность вывода: `-q`, `--quiet` — только значение интеграла (важнее
+/// остальных флагов), `-v`, `--verbose` — сводка расчёта, `-vv` или флаг
+/// дважды — ещё и участки потоков.
+fn select_verbosity() -> Verbosity {
+    let mut verbose = 0;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-q" | "--quiet" => return Verbosity::Quiet,
+            "-v" | "--verbose" => verbose += 1,
+            "-vv" => verbose += 2,
+            _ => {}
+        }
+    }
+    // Без флагов — из файла настроек: `quiet = true`, `verbose = 1`/`true`
+    if verbose == 0 {
+        if config::get("quiet") == Some(&config::Value::Boolean(true)) {
+            return Verbosity::Quiet;
+        }
+        verbose = match config::get("verbose") {
+            Some(config::Value::Integer(level)) => *level,
+            Some(config::Value::Boolean(true)) => 1,
+            _ => 0,
+        };
+    }
+    let configured = config::get("quiet").is_some() || config::get("verbose").is_some();
+    match verbose {
+        // Без терминала вопросов нет, а вывод — как с `--quiet`: одно значение
+        ..=0 if !report::prompts() && !configured && main_mode() => Verbosity::Quiet,
+        ..=0 => Verbosity::Normal,
+        1 => Verbosity::Verbose,
+        _ => Verbosity::VeryVerbose,
+    }
+}
+
+/// Формат чисел из `--precision N` (значащих цифр, от 1 до `MAX_PRECISION`)
+/// и `--format-number fixed|scientific|auto`. Без `--precision` — 12 цифр,
+/// а в тихом режиме и в `--batch` — кратчайшая точная запись.
+fn select_number_format() -> NumberFormat {
+    let precision = match flag_value("--precision") {
+        Some(precision) => Some(
+            usize::from_str(&precision)
+                .ok()
+                .filter(|precision| (1..=MAX_PRECISION).contains(precision))
+                .unwrap_or_else(|| {
+                    report::error(tr(Message::ParsePrecisionError));
+                    exit_with(ExitReason::IncorrectArguments);
+                }),
+        ),
+        None if Verbosity::quiet() || batch() => None,
+        None => Some(DEFAULT_PRECISION),
+    };
+    let notation = flag_value("--format-number").map_or(Notation::Auto, |notation| {
+        notation.parse().unwrap_or_else(|()| {
+            report::error(tr(Message::UnknownNotation));
+            exit_with(ExitReason::IncorrectArguments);
+        })
+    });
+    NumberFormat { precision, notation }
+}
+
+fn main() {
+    // Файл настроек читается первым: в нём могут быть язык и цвет, на
+    // которых выводятся уже его собственные ошибки
+    let config_problems = config::load(arg_value("--config").as_deref());
+    // `--no-color` или непустая переменная `NO_COLOR` отключают цвет
+    let no_color = flag_set("--no-color") || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    report::set_color(!no_color);
+    Lang::set_current(select_lang());
+    report::set_prompts(select_prompts());
+    Verbosity::set_current(select_verbosity());
+    report::set_number_format(select_number_format());
+    report_config_problems(&config_problems);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let unknown = flags::unknown(&args);
+    for flag in &unknown {
+        report::error(format_args!("{}: {}", tr(Message::UnknownFlag), flag));
+    }
+    let positionals = flags::positionals(&args);
+    for word in &positionals.stray {
+        report::error(format_args!("{}: {}", tr(Message::UnexpectedArgument), word));
+    }
+    // Опечатка в флаге молча меняет смысл запуска, поэтому не продолжаем
+    if !unknown.is_empty() || !positionals.stray.is_empty() {
+        exit_with(ExitReason::IncorrectArguments);
+    }
+    for &(flag, replacement) in flags::DEPRECATED {
+        if args.iter().any(|arg| arg.split_once('=').map_or(arg.as_str(), |(name, _)| name) == flag) {
+            report::warning(format_args!("{}: {} ({} {})", tr(Message::DeprecatedFlag), flag, tr(Message::UseInstead), replacement));
+        }
+    }
+    if std::env::args().skip(1).any(|arg| arg == "--help" || arg == "-h") {
+        out!("{}", tr(Message::Help));
+        return;
+    }
+    if arg_set("--print-config") {
+        print_config();
+        return;
+    }
+    if arg_set("--list-exit-codes") {
+        exit_code::list();
+        return;
+    }
+    match positionals.subcommand {
+        Some("bench") => {
+            run_bench();
+            return;
+        }
+        Some("table") => {
+            run_convergence_table();
+            return;
+        }
+        Some("completions") => {
+            let shell = positionals.arguments.first().and_then(|shell| shell.parse().ok()).unwrap_or_else(|| {
+                report::error(tr(Message::CompletionsShell));
+                exit_with(ExitReason::IncorrectArguments);
+            });
+            out!("{}", completions::script(shell));
+            return;
+        }
+        _ => {}
+    }
+    if let Some(path) = flag_value("--table") {
+        run_table(&path);
+        return;
+    }
+    if flag_set("--2d") {
+        run_2d();
+        return;
+    }
+    if flag_set("--repl") {
+        repl::run();
+        return;
+    }
+    if batch() {
+        run_batch();
+        return;
+    }
+    if flag_set("--convergence") {
+        run_convergence();
+        return;
+    }
+    let tolerance_flag = flag_value("--tolerance");
+    if tolerance_flag.is_some() && flag_value("--samples").is_some() {
+        report::error(tr(Message::SamplesAndTolerance));
+        exit_with(ExitReason::IncorrectArguments);
+    }
+    // Границы и число отсчётов можно ввести одной строкой: `0 1 100000`;
+    // пробелы внутри числа отсчётов (`1 000 000`) допустимы, только если оно
+    // введено отдельной строкой. С `--tolerance` число отсчётов не спрашивается
+    let expected = if tolerance_flag.is_some() { &INPUT_FLAGS[..2] } else { &INPUT_FLAGS[..3] };
+    let mut answers = Answers::new(expected.iter().filter(|flag| flag_value(flag).is_none()).count());
+    if flag_value("--samples").is_none() && tolerance_flag.is_none() {
+        answers = answers.with_tail();
+    }
+    let lower_bound = prompt_parse(
+        Some("--lower"),
+        &mut answers,
+        ask(Message::PromptLowerBound),
+        parse_bound,
+        bound_failure(ExitReason::IncorrectLowerBound),
+    );
+    let upper_bound = prompt_parse(
+        Some("--upper"),
+        &mut answers,
+        ask(Message::PromptUpperBound),
+        parse_bound,
+        bound_failure(ExitReason::IncorrectUpperBound),
+    );
+    let (samples, tolerance) = match tolerance_flag {
+        Some(tolerance) => {
+            let tolerance = parse_number(&tolerance).unwrap_or_else(|_| {
+                report::error(tr(Message::ParseFloatError));
+                exit_with(ExitReason::ToleranceNotReached);
+            });
+            (0, Some(tolerance))
+        }
+        None => prompt_parse(
+            Some("--samples"),
+            &mut answers,
+            ask(Message::PromptSamples),
+            parse_samples_or_tolerance,
+            report_samples_error,
+        ),
+    };
+    let threads = select_threads(true);
+    let method = prompt_method();
+    let format = flag_value("--format").map_or(ResultFormat::Text, |format| {
+        format.parse().unwrap_or_else(|()| {
+            report::error(tr(Message::UnknownFormat));
+            exit_with(ExitReason::IncorrectArguments);
+        })
+    });
+    let reference_samples = with_reference();
+    let seed = select_seed(method == Method::MonteCarlo);
+    let cancel = CancelToken::new();
+    #[cfg(unix)]
+    cancel_on_interrupt(&cancel);
+    let progress = ProgressLine::start(Message::PhaseMain, samples);
+    let options = IntegrationOptions {
+        samples,
+        method,
+        threads,
+        seed,
+        cancel: Some(cancel),
+        progress: progress.hook(),
+        singularity_check: Some(SingularityCheck::default()),
+        // `--negate-reversed`: при a > b считается −∫ᵇₐ вместо ошибки
+        reversed_bounds: if flag_set("--negate-reversed") {
+            ReversedBounds::Negate
+        } else {
+            ReversedBounds::Error
+        },
+        // `--propagate-nan`: не останавливаться на NaN и ±∞ в значениях f
+        propagate_non_finite: flag_set("--propagate-nan"),
+        ..Default::default()
+    };
+    let function = prompt_function();
+    if flag_set("--plot") {
+        plot::show(|x| function.eval(x), lower_bound, upper_bound, plot_height());
+    }
+    if flag_set("--compare") {
+        progress.finish();
+        run_compare(&function, lower_bound, upper_bound, tolerance, IntegrationOptions { progress: None, ..options });
+        return;
+    }
+    let result = match tolerance {
+        Some(tolerance) => integrate_to_tolerance_with_options(function.clone(), lower_bound, upper_bound, tolerance, &options),
+        None => calculate_improper_integral_with_options(function.clone(), lower_bound, upper_bound, &options),
+    };
+    progress.finish();
+    let result = result.unwrap_or_else(|e| exit_on_error(&e));
+    if let Some(path) = flag_value("--dump-plot") {
+        let intervals = flag_value("--dump-points").map_or(plot::DEFAULT_DUMP_POINTS, |points| {
+            parse_samples(&points).unwrap_or_else(|e| exit_with(report_samples_error(&e)))
+        });
+        plot::dump(&path, &function, method, lower_bound, upper_bound, intervals, threads).unwrap_or_else(|e| exit_on_error(&e));
+    }
+    // С `--tolerance` точность задана явно, предупреждать не о чем; в тихом
+    // режиме предупреждение попадает только в `--format json`
+    let warning = (tolerance.is_none() && !flag_set("--no-warnings")).then(|| {
+        result.remainder_bound
+            .map(|bound| AccuracyWarning::from_bound(method, result.intervals, result.value, bound))
+            .unwrap_or_else(|| AccuracyWarning::check(method, &function, lower_bound, upper_bound, result.intervals, result.value))
+    }).flatten();
+    if let Some(warning) = warning.filter(|_| warnings_enabled()) {
+        report::warning(warning);
+    }
+    // Контрольный расчёт дольше основного и нужен только для изучения
+    // метода, поэтому выполняется лишь по `--with-reference`
+    let verification = reference_samples.map(|reference_samples| {
+        let progress = ProgressLine::start(Message::PhaseReference, reference_samples);
+        let verification = verify_result(function.clone(), lower_bound, upper_bound, &result, reference_samples,
+                                         &IntegrationOptions { progress: progress.hook(), ..options.clone() });
+        progress.finish();
+        verification.unwrap_or_else(|e| exit_on_error(&e))
+    });
+    // Без контрольного расчёта и собственной оценки формулы погрешность
+    // оценивается по правилу Рунге. С `--tolerance` точность задана явно,
+    // удвоение может быть невозможно, а в тихом текстовом выводе оценка не
+    // показывается
+    let runge = verification.is_none() && result.error_estimate.is_none() && tolerance.is_none()
+        && samples <= MAX_SAMPLES_COUNT / 2 && (format == ResultFormat::Json || !Verbosity::quiet());
+    let refined = runge.then(|| {
+        let progress = ProgressLine::start(Message::PhaseRefined, 2 * samples);
+        let refined = runge_refine(function.clone(), lower_bound, upper_bound, &result,
+                                   &IntegrationOptions { progress: progress.hook(), singularity_check: None, ..options });
+        progress.finish();
+        refined.unwrap_or_else(|e| exit_on_error(&e))
+    });
+    if format == ResultFormat::Json {
+        print_result_json(&function, method, (lower_bound, upper_bound), seed, &MainOutput {
+            result: &result,
+            refined: refined.as_ref(),
+            verification: verification.as_ref(),
+            warning,
+        });
+        return;
+    }
+    report_result(&function, method, &result);
+    let mut times = PhaseTimes {
+        main: result.elapsed,
+        remainder: result.remainder_bound.is_some().then_some(result.remainder_elapsed),
+        ..Default::default()
+    };
+    if let Some(refined) = &refined {
+        // В `refined` учтён и основной расчёт: в сводку идёт только удвоенный
+        let elapsed = refined.elapsed.saturating_sub(result.elapsed);
+        report::run_summary(Message::PhaseRefined, &IntegrationResult {
+            samples: refined.samples.saturating_sub(result.samples),
+            elapsed,
+            ..refined.clone()
+        });
+        detail!("{}: {}", tr(Message::RefinedValue), report::number(refined.value));
+        if let Some(error_estimate) = refined.error_estimate {
+            report!("{}: {}", tr(Message::ErrorEstimate), report::number(error_estimate));
+        }
+        times.reference = Some((Message::PhaseRefined, elapsed));
+    }
+    if let Some(verification) = &verification {
+        report_verification(verification);
+        times.reference = Some((Message::PhaseReference, verification.reference.elapsed));
+        if verification.remainder_bound.is_some() {
+            times.remainder = Some(verification.remainder_elapsed);
+        }
+    }
+    times.report();
+}
+
+/// Итог `verify_result`: контрольное значение, абсолютная погрешность,
+/// граница остаточного члена с проверкой и относительная погрешность.
+fn report_verification(verification: &VerificationReport) {
+    report::run_summary(Message::PhaseReference, &verification.reference);
+    detail!("{}: {}", tr(Message::ReferenceValue), report::number(verification.reference.value));
+    detail!("{}: {}", tr(Message::AbsoluteError), report::number(verification.accuracy.absolute));
+    if let (Some(bound), Some(within)) = (verification.remainder_bound, verification.within_bound()) {
+        detail!("{}: {}", tr(Message::RemainderBound), report::number(bound));
+        report::verdict(Message::ErrorWithinBound, within);
+    }
+    match verification.accuracy.relative {
+        Some(relative) => detail!("{}: {}%", tr(Message::RelativeError), report::number(relative * 100.0)),
+        None => detail!("{}", tr(Message::RelativeErrorUndefined)),
+    }
+}
+
+/// Итоги основного режима для `print_result_json`.
+struct MainOutput<'a> {
+    result: &'a IntegrationResult,
+    /// Удвоенный расчёт правила Рунге, если оценка погрешности получена им.
+    refined: Option<&'a IntegrationResult>,
+    /// Итог `--with-reference`.
+    verification: Option<&'a VerificationReport>,
+    warning: Option<AccuracyWarning>,
+}
+
+/// `--format json` основного режима: один объект в stdout; `warnings` —
+/// предупреждения о точности и особенности (пусто, если их нет или они
+/// отключены `--no-warnings`), `verification` — итог `--with-reference` или
+/// `null`.
+fn print_result_json(function: &Function, method: Method, (lower_bound, upper_bound): (f64, f64), seed: u64,
+                     output: &MainOutput) {
+    let MainOutput { result, refined, verification, warning } = *output;
+    let optional = |value: Option<f64>| value.map_or_else(|| "null".to_string(), history::json_number);
+    outln!("{{");
+    outln!("  \"function\": {},", history::json_string(function.name()));
+    outln!("  \"method\": {},", history::json_string(method.name()));
+    outln!("  \"lower\": {},", history::json_number(lower_bound));
+    outln!("  \"upper\": {},", history::json_number(upper_bound));
+    outln!("  \"value\": {},", history::json_number(result.value));
+    outln!("  \"error_estimate\": {},", optional(result.error_estimate.or(refined.and_then(|refined| refined.error_estimate))));
+    if let Some(refined) = refined {
+        outln!("  \"refined_value\": {},", history::json_number(refined.value));
+    }
+    outln!("  \"intervals\": {},", result.intervals);
+    outln!("  \"evaluations\": {},", result.samples);
+    outln!("  \"threads\": {},", result.threads);
+    if method == Method::MonteCarlo {
+        outln!("  \"seed\": {},", seed);
+    }
+    outln!("  \"elapsed_ms\": {:.3},", result.elapsed.as_secs_f64() * 1e3);
+    let mut warnings = Vec::new();
+    if let Some(warning) = warning {
+        warnings.push(format!(
+            "{{\"kind\": \"accuracy\", \"message\": {}, \"bound\": {}, \"threshold\": {}, \"suggested_samples\": {}}}",
+            history::json_string(&warning.to_string()),
+            history::json_number(warning.bound),
+            history::json_number(warning.threshold),
+            warning.suggested.map_or_else(|| "null".to_string(), |samples| samples.to_string()),
+        ));
+    }
+    if let Some(x) = result.singularity.filter(|_| !flag_set("--no-warnings")) {
+        warnings.push(format!(
+            "{{\"kind\": \"singularity\", \"message\": {}, \"x\": {}}}",
+            history::json_string(&format!("{} {}", tr(Message::SuspectedSingularity), report::number(x))),
+            history::json_number(x),
+        ));
+    }
+    if warnings.is_empty() {
+        outln!("  \"warnings\": [],");
+    } else {
+        outln!("  \"warnings\": [\n    {}\n  ],", warnings.join(",\n    "));
+    }
+    match verification {
+        Some(verification) => {
+            outln!("  \"verification\": {{");
+            outln!("    \"reference\": {},", history::json_number(verification.reference.value));
+            outln!("    \"reference_intervals\": {},", verification.reference.intervals);
+            outln!("    \"reference_ms\": {:.3},", verification.reference.elapsed.as_secs_f64() * 1e3);
+            outln!("    \"absolute_error\": {},", history::json_number(verification.accuracy.absolute));
+            outln!("    \"relative_error\": {},", optional(verification.accuracy.relative));
+            outln!("    \"remainder_bound\": {},", optional(verification.remainder_bound));
+            outln!("    \"within_bound\": {}", verification.within_bound().map_or_else(|| "null".to_string(), |within| within.to_string()));
+            outln!("  }}");
+        }
+        None => outln!("  \"verification\": null"),
+    }
+    outln!("}}");
+}
+
+/// Формат вывода основного режима (`--format text|json`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for ResultFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Граница остаточного члена считается слишком большой, если превышает эту
+/// долю значения интеграла, но не меньше `ACCURACY_ABSOLUTE_FLOOR`.
+const ACCURACY_RELATIVE_THRESHOLD: f64 = 0.01;
+const ACCURACY_ABSOLUTE_FLOOR: f64 = 1e-8;
+
+/// Предупреждение о недостаточном числе отсчётов: граница остаточного члена
+/// больше порога.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AccuracyWarning {
+    bound: f64,
+    threshold: f64,
+    /// Число отсчётов, при котором граница опустится ниже порога; `None`,
+    /// если оно больше `MAX_SAMPLES_COUNT`.
+    suggested: Option<u64>,
+}
+
+impl AccuracyWarning {
+    /// Граница остаточного члена формулы `method` на конечном [a, b] при
+    /// `samples` отсчётах и проверка её по значению `value`.
+    fn check(method: Method, function: &Function, lower_bound: f64, upper_bound: f64, samples: u64, value: f64) -> Option<Self> {
+        if !(lower_bound.is_finite() && upper_bound.is_finite()) || samples == 0 {
+            return None;
+        }
+        let term = method.error_term()?;
+        let width = (upper_bound - lower_bound).abs();
+        let coarse = get_remaining_term_for(method, function, lower_bound, upper_bound, width / REMAINDER_SCAN_NODES as f64)?;
+        // R ∝ hᵖ: граница с шагом сетки поиска переводится на шаг расчёта
+        let bound = coarse * (REMAINDER_SCAN_NODES as f64 / samples as f64).powi(term.step_power);
+        Self::from_bound(method, samples, value, bound)
+    }
+
+    /// Проверка уже посчитанной границы `bound`; число отсчётов для порога —
+    /// из R ∝ hᵖ: n' = n·(R/R')^(1/p) с округлением вверх до кратного. Значение
+    /// при n' отличается от `value` не больше чем на R + R', поэтому R' — порог
+    /// для наименьшего такого значения: R' = q·(|value| − R)/(1 + q). Если
+    /// R ≥ |value|, значение неизвестно даже по порядку, и R' — просто порог.
+    fn from_bound(method: Method, samples: u64, value: f64, bound: f64) -> Option<Self> {
+        let threshold = (ACCURACY_RELATIVE_THRESHOLD * value.abs()).max(ACCURACY_ABSOLUTE_FLOOR);
+        if bound.is_nan() || bound <= threshold {
+            return None;
+        }
+        let power = method.error_term()?.step_power;
+        let granularity = method.rule::<f64>().granularity();
+        let target = match value.abs() - bound {
+            smallest if smallest > 0.0 => {
+                (ACCURACY_RELATIVE_THRESHOLD * smallest / (1.0 + ACCURACY_RELATIVE_THRESHOLD)).max(ACCURACY_ABSOLUTE_FLOOR)
+            }
+            _ => threshold,
+        };
+        let suggested = (samples as f64 * (bound / target).powf(1.0 / f64::from(power))).ceil();
+        let suggested = (suggested < MAX_SAMPLES_COUNT as f64)
+            .then(|| (suggested as u64).div_ceil(granularity) * granularity)
+            .filter(|&suggested| suggested <= MAX_SAMPLES_COUNT);
+        Some(Self { bound, threshold, suggested })
+    }
+}
+
+impl std::fmt::Display for AccuracyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {} > {}", tr(Message::AccuracyWarning), report::number(self.bound), report::number(self.threshold))?;
+        match self.suggested {
+            Some(samples) => write!(f, "; {}: {}", tr(Message::AccuracySuggestedSamples), samples),
+            None => write!(f, "; {}", tr(Message::AccuracyUnreachable)),
+        }
+    }
+}
+
+/// Предупреждения не отключены `--no-warnings` и вывод не тихий.
+fn warnings_enabled() -> bool {
+    !flag_set("--no-warnings") && !Verbosity::quiet()
+}
+
+/// Допустимое расхождение `--compare` в единицах последнего разряда
+/// значения. Участки свёртки у обоих проходов одни и те же, так что
+/// расхождения быть не должно вовсе.
+const COMPARE_ULPS: f64 = 16.0;
+
+/// `--compare`: тот же расчёт однопоточным и многопоточным проходом
+/// (`ExecutionMode::Sequential` и `ExecutionMode::Threaded`), оба значения,
+/// их разность и время (в тихом режиме — оба значения и разность одной
+/// строкой). Расхождение больше `COMPARE_ULPS` — предупреждение и код
+/// завершения `ExitReason::CompareMismatch`.
+fn run_compare(function: &Function, lower_bound: f64, upper_bound: f64, tolerance: Option<f64>, options: IntegrationOptions) {
+    let run = |execution: ExecutionMode| {
+        let options = IntegrationOptions { execution, ..options.clone() };
+        match tolerance {
+            Some(tolerance) => integrate_to_tolerance_with_options(function.clone(), lower_bound, upper_bound, tolerance, &options),
+            None => calculate_improper_integral_with_options(function.clone(), lower_bound, upper_bound, &options),
+        }
+        .unwrap_or_else(|e| exit_on_error(&e))
+    };
+    let sequential = run(ExecutionMode::Sequential);
+    let threaded = run(ExecutionMode::Threaded);
+    let difference = (sequential.value - threaded.value).abs();
+    let allowed = COMPARE_ULPS * f64::EPSILON * sequential.value.abs().max(threaded.value.abs());
+    if Verbosity::quiet() {
+        // Как `report::value`: в тихом режиме — только числа, одной строкой
+        outln!("{} {} {:e}", report::number(sequential.value), report::number(threaded.value), difference);
+    } else {
+        report!("{}: {}", tr(Message::Integrand), function.name());
+        report!("{}: {} ({:?})", tr(Message::CompareSequential), report::number(sequential.value), sequential.elapsed);
+        report!("{}: {} ({:?}, {}: {})", tr(Message::CompareThreaded), report::number(threaded.value), threaded.elapsed,
+                tr(Message::Threads), threaded.threads);
+        report!("{}: {:e} ({}: {:e})", tr(Message::CompareDifference), difference, tr(Message::CompareTolerance), allowed);
+    }
+    // NaN в одном из значений — тоже расхождение
+    if difference > allowed || difference.is_nan() {
+        report::warning(tr(Message::CompareMismatch));
+        exit_with(ExitReason::CompareMismatch);
+    }
+}
+
+/// Время этапов основного режима по замерам вокруг вызовов библиотеки, без
+/// ожидания ввода.
+#[derive(Debug, Default)]
+struct PhaseTimes {
+    main: Duration,
+    /// Контрольный расчёт или удвоение по Рунге.
+    reference: Option<(Message, Duration)>,
+    /// Оценка остаточного члена.
+    remainder: Option<Duration>,
+}
+
+impl PhaseTimes {
+    /// «Время этапов: основной расчёт 1.2ms, …» через `report!`.
+    fn report(&self) {
+        let mut phases = vec![format!("{} {:?}", tr(Message::PhaseMain), self.main)];
+        if let Some((phase, elapsed)) = self.reference {
+            phases.push(format!("{} {:?}", tr(phase), elapsed));
+        }
+        if let Some(elapsed) = self.remainder {
+            phases.push(format!("{} {:?}", tr(Message::PhaseRemainder), elapsed));
+        }
+        report!("{}: {}", tr(Message::PhaseTimes), phases.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_formats() {
+        for (line, samples) in [("1000000", 1_000_000), ("1_000_000", 1_000_000), ("1 000 000", 1_000_000), ("1e6", 1_000_000),
+                                ("1m", 1_000_000), ("1KK", 1_000_000), ("1000k", 1_000_000), ("1.5k", 1500), ("2g", 2_000_000_000)] {
+            assert_eq!(parse_samples(line), Ok(samples), "{}", line);
+        }
+    }
+
+    #[test]
+    fn samples_reject_commas() {
+        for line in ["1,000", "1,5k", "1,000,000"] {
+            assert_eq!(parse_samples(line), Err(SamplesError::Invalid), "{}", line);
+        }
+        assert_eq!(parse_samples_or_tolerance("1,000"), Err(SamplesError::Invalid));
+        assert_eq!(parse_samples_or_tolerance("0,001"), Ok((0, Some(0.001))));
+    }
+
+    #[test]
+    fn samples_errors() {
+        assert_eq!(parse_samples(""), Err(SamplesError::Invalid));
+        assert_eq!(parse_samples("-5"), Err(SamplesError::Invalid));
+        assert_eq!(parse_samples("1.5555k"), Err(SamplesError::NotInteger));
+        assert_eq!(parse_samples("18446744073709551616"), Err(SamplesError::Overflow));
+    }
+
+    #[test]
+    fn numbers_accept_decimal_comma() {
+        assert_eq!(parse_number("0,5"), Ok(0.5));
+        assert_eq!(parse_number(" -2.5 "), Ok(-2.5));
+        assert!(parse_number("1,000.5").is_err());
+    }
+
+    #[test]
+    fn table_with_header_and_comments() {
+        let text = "x,y\n# комментарий\n0,1\n\n0.5;2\n1 3\n";
+        assert_eq!(parse_table(text), Ok(vec![(0.0, 1.0), (0.5, 2.0), (1.0, 3.0)]));
+        assert_eq!(parse_table("0,1\nx,y\n"), Err(2));
+    }
+
+    #[test]
+    fn bounds_with_decimal_comma() {
+        for (line, value) in [("0,5", 0.5), ("-3,14", -314.0 / 100.0), ("1.5", 1.5), ("1,5e2", 150.0), ("+0,25", 0.25), (" 2,0 ", 2.0)] {
+            assert_eq!(parse_number(line), Ok(value), "{}", line);
+            assert_eq!(parse_bound(line.trim()), Ok(value), "{}", line);
+        }
+        for line in ["1,234,5", "abc", "0,5,", "1,5.0", ""] {
+            assert!(parse_number(line).is_err(), "{}", line);
+        }
+        assert!(matches!(parse_bound("1,234,5"), Err(IntegralCalcError::InvalidExpression { position: 2, .. })));
+        assert!(parse_bound("garbage").is_err());
+    }
+
+    #[test]
+    fn suggested_samples_silence_the_warning() {
+        let function = Function::default();
+        for method in [Method::Midpoint, Method::Trapezoid, Method::Simpson] {
+            for samples in [4, 10, 22, 40] {
+                let value = |samples| {
+                    let options = IntegrationOptions { samples, method, ..Default::default() };
+                    calculate_improper_integral_with_options(function.clone(), 0.0, 10.0, &options).unwrap().value
+                };
+                let warning = AccuracyWarning::check(method, &function, 0.0, 10.0, samples, value(samples)).unwrap();
+                let mut suggested = warning.suggested.unwrap();
+                assert!(suggested > samples);
+                // При R ≥ |value| подсказка может понадобиться ещё раз
+                if warning.bound >= value(samples).abs() {
+                    let again = AccuracyWarning::check(method, &function, 0.0, 10.0, suggested, value(suggested));
+                    suggested = again.map_or(suggested, |warning| warning.suggested.unwrap());
+                }
+                assert_eq!(AccuracyWarning::check(method, &function, 0.0, 10.0, suggested, value(suggested)), None,
+                           "{} {} -> {}", method, samples, suggested);
+            }
+        }
+        assert_eq!(AccuracyWarning::check(Method::Midpoint, &function, 0.0, 1.0, 1000, 0.3494), None);
+        assert_eq!(AccuracyWarning::check(Method::Midpoint, &function, 0.0, f64::INFINITY, 3, 1.0), None);
+        assert_eq!(AccuracyWarning::check(Method::GaussKronrod, &function, 0.0, 10.0, 1, 0.5), None);
+    }
+
+    #[test]
+    fn unreachable_accuracy_has_no_suggestion() {
+        let warning = AccuracyWarning::from_bound(Method::Midpoint, 10, 1e-30, 1e10).unwrap();
+        assert_eq!(warning.threshold, 1e-8);
+        assert_eq!(warning.suggested, None);
+    }
 }
@@ -0,0 +1,797 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Язык сообщений.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    Ru,
+    En,
+}
+
+static CURRENT_LANG: AtomicU8 = AtomicU8::new(Lang::Ru as u8);
+
+impl Lang {
+    /// Язык, выбранный для процесса через `Lang::set_current` (по умолчанию русский).
+    pub fn current() -> Self {
+        match CURRENT_LANG.load(Ordering::Relaxed) {
+            value if value == Lang::En as u8 => Lang::En,
+            _ => Lang::Ru,
+        }
+    }
+
+    pub fn set_current(lang: Self) {
+        CURRENT_LANG.store(lang as u8, Ordering::Relaxed);
+    }
+
+    /// Код языка, который принимает `FromStr`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::Ru => "ru",
+            Lang::En => "en",
+        }
+    }
+
+    /// Язык по переменной окружения `LANG` (`ru_RU.UTF-8`, `en_US.UTF-8`, ...).
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("LANG").ok()?;
+        value.split(['_', '.', '-']).next()?.parse().ok()
+    }
+}
+
+impl FromStr for Lang {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ru" => Ok(Lang::Ru),
+            "en" => Ok(Lang::En),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Все выводимые пользователю строки, кроме сообщений об ошибках расчёта
+/// (их текст формирует `IntegralCalcError`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    PromptLowerBound,
+    PromptUpperBound,
+    PromptSamples,
+    PromptLowerBoundX,
+    PromptUpperBoundX,
+    PromptLowerBoundY,
+    PromptUpperBoundY,
+    PromptSamplesX,
+    PromptSamplesY,
+    PromptStartSamples,
+    PromptTolerance,
+    PromptThreads,
+    PromptMethod,
+    PromptFunction,
+    ParseFloatError,
+    ParseIntError,
+    ParseThreadsError,
+    SamplesAndTolerance,
+    ConfigReadError,
+    ConfigSyntax,
+    ConfigUnknownKey,
+    ConfigWrongType,
+    ConfigExpected,
+    ConfigFile,
+    Or,
+    TypeString,
+    TypeInteger,
+    TypeFloat,
+    TypeBoolean,
+    SourceFlag,
+    SourceEnv,
+    SourceConfig,
+    SourceDefault,
+    NotSet,
+    ReplPrompt,
+    ReplNoResult,
+    ReplUsageInt,
+    ReplUsageSet,
+    ReplUnknownCommand,
+    ReplHelp,
+    UnknownFlag,
+    UnexpectedArgument,
+    DeprecatedFlag,
+    UseInstead,
+    CompletionsShell,
+    AccuracyWarning,
+    AccuracySuggestedSamples,
+    AccuracyUnreachable,
+    Seed,
+    ParseSeedError,
+    ExitCompareMismatch,
+    CompareSequential,
+    CompareThreaded,
+    CompareDifference,
+    CompareTolerance,
+    CompareMismatch,
+    ValidateOk,
+    ExitStatus,
+    ExitSuccess,
+    ExitIncorrectLowerBound,
+    ExitIncorrectUpperBound,
+    ExitIncorrectSamplesCount,
+    ExitUnableToCalculate,
+    ExitBoundsReversed,
+    ExitNonFiniteBound,
+    ExitWorkerPanicked,
+    ExitIncorrectThreadsCount,
+    ExitCancelled,
+    ExitToleranceNotReached,
+    ExitIncorrectArguments,
+    ExitUnknownMethod,
+    ExitIncorrectTable,
+    ExitZeroSamples,
+    ExitInvalidInterval,
+    ExitEndOfInput,
+    ExitUnknownFunction,
+    ExitInvalidExpression,
+    ExitBatchFailed,
+    ExitFileError,
+    ExitInterrupted,
+    ExitInterruptedTwice,
+    ExitBrokenPipe,
+    DumpScriptClash,
+    ReplUsagePlot,
+    PlotInfiniteBounds,
+    PlotMinimum,
+    PlotMaximum,
+    PlotAt,
+    PlotNonFinite,
+    PlotFirstAt,
+    ParsePlotHeightError,
+    ReplUsageRecall,
+    HistoryEntry,
+    HistoryEmpty,
+    HistoryNoEntry,
+    HistoryLoaded,
+    HistorySkippedLine,
+    HistoryFileError,
+    HistorySize,
+    HistoryPersist,
+    Bounds,
+    ParseHistorySizeError,
+    ParseSamplesError,
+    SamplesFormats,
+    SamplesOverflow,
+    SamplesNotInteger,
+    UnknownLang,
+    UnknownFormat,
+    UnknownNotation,
+    ParsePrecisionError,
+    EndOfInput,
+    TableReadError,
+    TableParseError,
+    BatchTooFewColumns,
+    UnexpectedToken,
+    Progress,
+    Integrand,
+    Method,
+    ApproximateValue,
+    Interrupted,
+    PartialEstimate,
+    Step,
+    Intervals,
+    Evaluations,
+    Threads,
+    Elapsed,
+    ReferenceValue,
+    RefinedValue,
+    ErrorEstimate,
+    SimpsonValue,
+    SplineValue,
+    SuspectedSingularity,
+    ColumnSamples,
+    PromptEvaluations,
+    BenchReference,
+    ColumnError,
+    ColumnEvaluations,
+    UnknownSort,
+    ParseRowsError,
+    ColumnValue,
+    ColumnDifference,
+    ColumnWorker,
+    ColumnInterval,
+    ColumnShare,
+    ColumnStarted,
+    PhaseMain,
+    PhaseReference,
+    PhaseTimes,
+    PhaseRemainder,
+    PhaseRefined,
+    ReductionBlocks,
+    Remaining,
+    Seconds,
+    ConvergenceOrder,
+    NotConverged,
+    AbsoluteError,
+    RemainderBound,
+    ErrorWithinBound,
+    RelativeError,
+    RelativeErrorUndefined,
+    CalculationError,
+    Help,
+}
+
+impl Message {
+    pub fn text(self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ru => self.ru(),
+            Lang::En => self.en(),
+        }
+    }
+
+    fn ru(self) -> &'static str {
+        match self {
+            Self::PromptLowerBound => "Введите нижнюю границу: ",
+            Self::PromptUpperBound => "Введите верхнюю границу: ",
+            Self::PromptSamples => "Введите количество отсчётов (или требуемую точность, например 1e-8): ",
+            Self::PromptLowerBoundX => "Введите нижнюю границу по x: ",
+            Self::PromptUpperBoundX => "Введите верхнюю границу по x: ",
+            Self::PromptLowerBoundY => "Введите нижнюю границу по y: ",
+            Self::PromptUpperBoundY => "Введите верхнюю границу по y: ",
+            Self::PromptSamplesX => "Введите количество отсчётов по x: ",
+            Self::PromptSamplesY => "Введите количество отсчётов по y: ",
+            Self::PromptStartSamples => "Введите начальное количество отсчётов: ",
+            Self::PromptTolerance => "Введите требуемую точность (например 1e-10): ",
+            Self::PromptThreads => "Введите число потоков (пусто — по числу ядер): ",
+            Self::ParseThreadsError => "Число потоков должно быть целым от 0 (по числу ядер) до 4096",
+            Self::SamplesAndTolerance => "Флаги --samples и --tolerance нельзя задавать вместе",
+            Self::ConfigReadError => "Не удалось прочитать файл настроек",
+            Self::ConfigSyntax => "ожидается строка вида ключ = значение",
+            Self::ConfigUnknownKey => "неизвестный ключ",
+            Self::ConfigWrongType => "неверный тип значения ключа",
+            Self::ConfigExpected => "ожидается",
+            Self::ConfigFile => "файл настроек",
+            Self::Or => "или",
+            Self::TypeString => "строка",
+            Self::TypeInteger => "целое число",
+            Self::TypeFloat => "число",
+            Self::TypeBoolean => "true или false",
+            Self::SourceFlag => "флаг",
+            Self::SourceEnv => "переменная окружения",
+            Self::SourceConfig => "файл настроек",
+            Self::SourceDefault => "по умолчанию",
+            Self::NotSet => "не задано",
+            Self::ReplPrompt => "> ",
+            Self::ReplNoResult => "Расчётов ещё не было",
+            Self::ReplUsageInt => "Использование: int A B [N]",
+            Self::ReplUsageSet => "Использование: set samples|method|function|expr|threads|history|persist значение",
+            Self::ReplUnknownCommand => "Неизвестная команда (список — help)",
+            Self::ReplUsageRecall => "Использование: recall N",
+            Self::HistoryEntry => "Номер в истории",
+            Self::HistoryEmpty => "История пуста",
+            Self::HistoryNoEntry => "Нет записи в истории с номером",
+            Self::HistoryLoaded => "Загружено записей из файла истории",
+            Self::HistorySkippedLine => "строка файла истории не разобрана и пропущена",
+            Self::HistoryFileError => "Ошибка файла истории, сохранение отключено",
+            Self::HistorySize => "размер истории",
+            Self::HistoryPersist => "сохранение истории",
+            Self::Bounds => "Пределы",
+            Self::ParseHistorySizeError => "Размер истории должен быть целым от 1 до 100000",
+            Self::ReplUsagePlot => "Использование: plot A B",
+            Self::PlotInfiniteBounds => "График строится только на конечном отрезке [a, b] с a < b",
+            Self::PlotMinimum => "минимум",
+            Self::PlotMaximum => "максимум",
+            Self::PlotAt => "при x =",
+            Self::PlotNonFinite => "Значение функции не конечно в точках графика",
+            Self::PlotFirstAt => "первая при x =",
+            Self::ParsePlotHeightError => "Высота графика должна быть целым от 2 до 100",
+            Self::DumpScriptClash => "у файла данных уже расширение .gp, сценарию gnuplot нужно другое имя",
+            Self::ExitStatus => "Код завершения",
+            Self::ExitSuccess => "расчёт выполнен",
+            Self::ExitIncorrectLowerBound => "неверная нижняя граница",
+            Self::ExitIncorrectUpperBound => "неверная верхняя граница",
+            Self::ExitIncorrectSamplesCount => "неверное число отсчётов",
+            Self::ExitUnableToCalculate => "не удалось вычислить интеграл",
+            Self::ExitBoundsReversed => "нижняя граница больше верхней",
+            Self::ExitNonFiniteBound => "недопустимая бесконечная граница",
+            Self::ExitWorkerPanicked => "сбой рабочего потока",
+            Self::ExitIncorrectThreadsCount => "неверное число потоков",
+            Self::ExitCancelled => "расчёт отменён",
+            Self::ExitToleranceNotReached => "требуемая точность не достигнута или неверна",
+            Self::ExitIncorrectArguments => "неверные флаги или настройки",
+            Self::ExitUnknownMethod => "неизвестный метод",
+            Self::ExitIncorrectTable => "неверная таблица значений",
+            Self::ExitZeroSamples => "нулевое число отсчётов",
+            Self::ExitInvalidInterval => "недопустимый отрезок или шаг",
+            Self::ExitEndOfInput => "ввод исчерпан или не читается",
+            Self::ExitUnknownFunction => "неизвестная функция",
+            Self::ExitInvalidExpression => "ошибка в выражении",
+            Self::ExitBatchFailed => "в режиме --batch не удался хотя бы один расчёт",
+            Self::ExitFileError => "ошибка чтения или записи файла",
+            Self::ExitInterrupted => "прервано по Ctrl+C",
+            Self::ExitInterruptedTwice => "повторный Ctrl+C",
+            Self::ExitBrokenPipe => "стандартный вывод закрыт (например, | head)",
+            Self::ValidateOk => "OK",
+            Self::ExitCompareMismatch => "расхождение однопоточного и многопоточного расчёта (--compare)",
+            Self::CompareSequential => "Однопоточный расчёт",
+            Self::CompareThreaded => "Многопоточный расчёт",
+            Self::CompareDifference => "Разность",
+            Self::CompareTolerance => "допуск",
+            Self::CompareMismatch => "однопоточный и многопоточный расчёты расходятся больше допуска",
+            Self::Seed => "Зерно генератора",
+            Self::ParseSeedError => "Ошибка преобразования зерна: ожидается целое число от 0 до 2^64 − 1",
+            Self::AccuracyWarning => "Мало отсчётов: граница остаточного члена",
+            Self::AccuracySuggestedSamples => "нужно отсчётов не меньше",
+            Self::AccuracyUnreachable => "порог недостижим и при наибольшем числе отсчётов",
+            Self::UnknownFlag => "Неизвестный флаг",
+            Self::UnexpectedArgument => "Лишний аргумент",
+            Self::DeprecatedFlag => "Устаревший флаг",
+            Self::UseInstead => "используйте",
+            Self::CompletionsShell => "Укажите оболочку: kryl_04 completions bash|zsh|fish",
+            Self::ReplHelp => concat!(
+                "  int A B [N]         интеграл на [A, B], при N — с N отсчётами\n",
+                "  plot A B            график функции на [A, B] (в stderr)\n",
+                "  set samples N       число отсчётов для следующих расчётов\n",
+                "  set method M        метод\n",
+                "  set function F      встроенная функция по номеру или имени\n",
+                "  set expr E          выражение от x\n",
+                "  set threads N       число потоков, 0 — по числу ядер\n",
+                "  show                текущие настройки\n",
+                "  set history N       размер истории\n",
+                "  set persist on|off  сохранять историю в ~/.local/share/kryl_04/history.jsonl\n",
+                "  last                итог последнего расчёта\n",
+                "  history             список прошлых расчётов\n",
+                "  recall N            запись истории N; $N в пределах int — её значение\n",
+                "  quit                выход (как и конец ввода)",
+            ),
+            Self::PromptMethod => "Введите метод: midpoint, left, right, trapezoid, simpson, simpson38, boole, gk15, montecarlo, clenshaw-curtis, tanh-sinh (пусто — midpoint): ",
+            Self::PromptFunction => "Введите номер или имя функции либо выражение от x (пусто — 1): ",
+            Self::ParseFloatError => "Ошибка преобразования ввода в вещественное число; допустимые записи: 0.5, 0,5, -3.14, 1.5e2, inf",
+            Self::ParseIntError => "Ошибка преобразования ввода в целое число",
+            Self::ParseSamplesError => "Ошибка преобразования ввода в целое число или точность из (0, 1)",
+            Self::SamplesFormats => "допустимы записи 1000000, 1_000_000, 1 000 000, 1e6, 1m, 1kk, 1000k",
+            Self::SamplesOverflow => "число отсчётов больше 18446744073709551615",
+            Self::SamplesNotInteger => "число отсчётов должно быть целым",
+            Self::UnknownLang => "Неизвестный язык, допустимые значения: ru, en",
+            Self::UnknownFormat => "Неизвестный формат вывода, допустимые значения: text, csv, json (csv — у --batch и bench, json — у bench и одиночного расчёта)",
+            Self::UnknownNotation => "Неизвестная запись чисел, допустимые значения: fixed, scientific, auto",
+            Self::ParsePrecisionError => "Точность — целое число значащих цифр от 1 до 17",
+            Self::EndOfInput => "Ввод завершён досрочно",
+            Self::TableReadError => "Не удалось прочитать файл таблицы",
+            Self::TableParseError => "Ошибка преобразования пары чисел x, y в строке",
+            Self::UnexpectedToken => "Лишнее значение во вводе",
+            Self::BatchTooFewColumns => "Ожидается строка вида: нижняя граница, верхняя граница, количество отсчётов [, метод] [, функция]",
+            Self::Progress => "Выполнено",
+            Self::Integrand => "Подынтегральная функция",
+            Self::Method => "Метод",
+            Self::ApproximateValue => "Приближённое значение интеграла",
+            Self::Interrupted => "Расчёт прерван, выполнено",
+            Self::PartialEstimate => "Неполная оценка интеграла (не результат)",
+            Self::Step => "Шаг",
+            Self::Intervals => "отрезков",
+            Self::Evaluations => "вычислений функции",
+            Self::Threads => "потоков",
+            Self::Elapsed => "время",
+            Self::ReferenceValue => "\"Действительное\" значение интеграла",
+            Self::RefinedValue => "Значение при удвоенном числе отсчётов",
+            Self::ErrorEstimate => "Оценка погрешности",
+            Self::SimpsonValue => "По формуле Симпсона",
+            Self::SplineValue => "По кубическому сплайну",
+            Self::SuspectedSingularity => "Предупреждение: функция, по-видимому, имеет особенность вблизи x ≈",
+            Self::ColumnSamples => "отсчётов",
+            Self::PromptEvaluations => "Введите число вычислений функции для каждого метода: ",
+            Self::BenchReference => "Опорное значение (gk15, 10000 панелей)",
+            Self::ColumnError => "погрешность",
+            Self::ColumnEvaluations => "вычислений",
+            Self::UnknownSort => "Порядок строк: method или error",
+            Self::ParseRowsError => "Число строк должно быть целым от 1 до 40",
+            Self::ColumnValue => "значение",
+            Self::ColumnDifference => "разность",
+            Self::ColumnWorker => "поток",
+            Self::ColumnInterval => "участок",
+            Self::ColumnShare => "доля",
+            Self::ColumnStarted => "старт",
+            Self::PhaseMain => "основной расчёт",
+            Self::PhaseReference => "контрольный расчёт",
+            Self::PhaseTimes => "Время этапов",
+            Self::PhaseRemainder => "остаточный член",
+            Self::PhaseRefined => "удвоенное число отсчётов",
+            Self::ReductionBlocks => "участков попарной свёртки",
+            Self::Remaining => "осталось ≈",
+            Self::Seconds => "с",
+            Self::ConvergenceOrder => "Оценка порядка сходимости",
+            Self::NotConverged => "Заданная точность не достигнута за отведённое число удвоений",
+            Self::AbsoluteError => "Абсолютная погрешность",
+            Self::RemainderBound => "Верхняя граница для Rn",
+            Self::ErrorWithinBound => "Абсолютная погрешность соответствует остаточному члену",
+            Self::RelativeError => "Относительная погрешность",
+            Self::RelativeErrorUndefined => "относительная погрешность не определена (результат близок к нулю)",
+            Self::CalculationError => "Ошибка вычисления интеграла",
+            Self::Help => concat!(
+                "Использование: kryl_04 [флаги]\n",
+                "               kryl_04 bench --lower A --upper B --evals N [--format text|csv|json]\n",
+                "                             [--sort method|error]\n",
+                "               kryl_04 table --lower A --upper B [--start N] [--rows R] [--method M]\n",
+                "                             [--format text|csv]\n",
+                "               kryl_04 completions bash|zsh|fish\n",
+                "Вычисляет интеграл встроенной функции (по умолчанию atan(x)/(x^4+1)). Значения,\n",
+                "не заданные флагами, запрашиваются; если задан хотя бы один из флагов --lower,\n",
+                "--upper, --samples, --tolerance, --threads, --method, --function, --expr,\n",
+                "необязательные значения без вопросов берутся по умолчанию.\n",
+                "\n",
+                "  --lower A               нижняя граница (число, inf, -inf, бесконечность)\n",
+                "  --upper B               верхняя граница\n",
+                "  --samples N             количество отсчётов или требуемая точность из (0, 1)\n",
+                "  --tolerance EPS         требуемая точность вместо --samples: число отсчётов\n",
+                "                          удваивается, пока две оценки не сблизятся меньше чем на EPS\n",
+                "  --threads N             число потоков до 4096, 0 — по числу ядер (по умолчанию;\n",
+                "                          также из переменной KRYL_THREADS)\n",
+                "  --method M              метод: midpoint, left, right, trapezoid, simpson, simpson38,\n",
+                "                          boole, gk15, montecarlo, clenshaw-curtis, tanh-sinh\n",
+                "                          (также gauss, gauss-kronrod, monte-carlo; регистр не важен)\n",
+                "  --function F            функция по номеру или имени: atan, sinc, gauss, cauchy, xlnx\n",
+                "  --expr E                выражение от x, например \"exp(-x^2)*sin(3*x)\": + - * / ^,\n",
+                "                          скобки, pi, e, sin, cos, tan, atan, exp, ln, sqrt, abs\n",
+                "  --with-reference[=N]    проверить результат контрольным расчётом с N\n",
+                "                          отсчётами (по умолчанию — наибольшее допустимое); без него\n",
+                "                          погрешность оценивается удвоением числа отсчётов (Рунге)\n",
+                "  --reference             устаревший вариант --with-reference\n",
+                "  --reference-samples N   устаревший вариант --with-reference=N\n",
+                "  --negate-reversed       при a > b вычислять −∫ᵇₐ вместо ошибки\n",
+                "  --propagate-nan         не останавливаться на NaN и ±∞ в значениях функции\n",
+                "  --seed N                зерно метода montecarlo; без него выбирается случайное\n",
+                "                          и выводится в stderr\n",
+                "  --compare               посчитать в одном и в нескольких потоках и сравнить;\n",
+                "                          в тихом режиме — строка «значение значение разность»\n",
+                "  --batch                 по расчёту на каждую строку ввода «a b n [метод] [функция]»\n",
+                "  --input PATH            режим --batch со строками из файла\n",
+                "  --output PATH           значения режима --batch — в файл (заменяется целиком)\n",
+                "  --validate              проверить строки --batch без расчёта: OK или ошибка\n",
+                "  --format text|csv       формат вывода режима --batch (у bench также json);\n",
+                "                          без режима: text или json\n",
+                "  --plot                  перед расчётом нарисовать график функции в stderr (ширина —\n",
+                "                          из COLUMNS)\n",
+                "  --plot-height H         высота графика в строках, по умолчанию 12\n",
+                "  --dump-plot PATH        записать x, f(x), F(x) = ∫ₐˣ f в PATH и сценарий gnuplot\n",
+                "                          в PATH с расширением .gp\n",
+                "  --dump-points N         отрезков сетки --dump-plot, по умолчанию 1000\n",
+                "  --2d                    двойной интеграл f(x)·f(y) по прямоугольнику\n",
+                "  --repl                  команды int, set, last, quit по одной в строке (см. help)\n",
+                "  --history-size N        размер истории --repl, по умолчанию 100\n",
+                "  --convergence           таблица значений при удвоении числа отсчётов\n",
+                "  bench                   сравнение всех методов при примерно --evals вычислениях\n",
+                "                          функции с опорным значением gk15; --sort error — по погрешности\n",
+                "  table                   таблица сходимости: n, Iₙ, |Iₙ − I₂ₙ|, порядок p и граница Rₙ\n",
+                "                          для --rows строк (12) с удвоением n от --start (16)\n",
+                "  --table PATH            интеграл по таблице пар x, y из файла\n",
+                "  --precision N           значащих цифр в выводе чисел (по умолчанию 12)\n",
+                "  --format-number MODE    запись чисел: fixed, scientific или auto\n",
+                "  -q, --quiet             вывести только значение интеграла\n",
+                "  --no-warnings           не предупреждать о слишком малом числе отсчётов\n",
+                "  --interactive           задавать вопросы, даже если ввод или вывод — не терминал;\n",
+                "                          без терминала по умолчанию вопросов нет, а вывод как с -q\n",
+                "  --no-interactive        не задавать вопросов и на терминале\n",
+                "  --no-color              без цвета (также при непустой NO_COLOR)\n",
+                "  -v, --verbose           сводка расчёта в stderr; -vv — ещё и по потокам\n",
+                "  --lang ru|en            язык сообщений (по умолчанию — из LANG)\n",
+                "  --config PATH           файл настроек вместо ~/.config/kryl_04/config.toml: строки\n",
+                "                          «ключ = значение», ключи — имена флагов без --; флаги\n",
+                "                          и переменные окружения важнее файла\n",
+                "  --print-config          вывести действующие настройки и их источники\n",
+                "  --list-exit-codes       вывести коды завершения и их причины\n",
+                "  --help, -h              эта справка\n",
+            ),
+        }
+    }
+
+    fn en(self) -> &'static str {
+        match self {
+            Self::PromptLowerBound => "Enter the lower bound: ",
+            Self::PromptUpperBound => "Enter the upper bound: ",
+            Self::PromptSamples => "Enter the number of samples (or the required tolerance, e.g. 1e-8): ",
+            Self::PromptLowerBoundX => "Enter the lower bound for x: ",
+            Self::PromptUpperBoundX => "Enter the upper bound for x: ",
+            Self::PromptLowerBoundY => "Enter the lower bound for y: ",
+            Self::PromptUpperBoundY => "Enter the upper bound for y: ",
+            Self::PromptSamplesX => "Enter the number of samples for x: ",
+            Self::PromptSamplesY => "Enter the number of samples for y: ",
+            Self::PromptStartSamples => "Enter the initial number of samples: ",
+            Self::PromptTolerance => "Enter the required tolerance (e.g. 1e-10): ",
+            Self::PromptThreads => "Enter the number of threads (empty for one per core): ",
+            Self::ParseThreadsError => "The number of threads must be an integer from 0 (one per core) to 4096",
+            Self::SamplesAndTolerance => "The --samples and --tolerance flags cannot be used together",
+            Self::ConfigReadError => "Failed to read the configuration file",
+            Self::ConfigSyntax => "expected a line of the form key = value",
+            Self::ConfigUnknownKey => "unknown key",
+            Self::ConfigWrongType => "wrong value type for key",
+            Self::ConfigExpected => "expected",
+            Self::ConfigFile => "configuration file",
+            Self::Or => "or",
+            Self::TypeString => "a string",
+            Self::TypeInteger => "an integer",
+            Self::TypeFloat => "a number",
+            Self::TypeBoolean => "true or false",
+            Self::SourceFlag => "flag",
+            Self::SourceEnv => "environment variable",
+            Self::SourceConfig => "configuration file",
+            Self::SourceDefault => "default",
+            Self::NotSet => "not set",
+            Self::ReplPrompt => "> ",
+            Self::ReplNoResult => "Nothing has been calculated yet",
+            Self::ReplUsageInt => "Usage: int A B [N]",
+            Self::ReplUsageSet => "Usage: set samples|method|function|expr|threads|history|persist value",
+            Self::ReplUnknownCommand => "Unknown command (see help)",
+            Self::ReplUsageRecall => "Usage: recall N",
+            Self::HistoryEntry => "History entry",
+            Self::HistoryEmpty => "History is empty",
+            Self::HistoryNoEntry => "No history entry number",
+            Self::HistoryLoaded => "Entries loaded from the history file",
+            Self::HistorySkippedLine => "unreadable history file line skipped",
+            Self::HistoryFileError => "History file error, saving is off",
+            Self::HistorySize => "history size",
+            Self::HistoryPersist => "history saved to file",
+            Self::Bounds => "Bounds",
+            Self::ParseHistorySizeError => "History size must be an integer from 1 to 100000",
+            Self::ReplUsagePlot => "Usage: plot A B",
+            Self::PlotInfiniteBounds => "A plot needs a finite interval [a, b] with a < b",
+            Self::PlotMinimum => "minimum",
+            Self::PlotMaximum => "maximum",
+            Self::PlotAt => "at x =",
+            Self::PlotNonFinite => "The function is not finite at plot points",
+            Self::PlotFirstAt => "the first at x =",
+            Self::ParsePlotHeightError => "The plot height must be an integer from 2 to 100",
+            Self::DumpScriptClash => "the data file already has the .gp extension needed for the gnuplot script",
+            Self::ExitStatus => "Exit status",
+            Self::ExitSuccess => "success",
+            Self::ExitIncorrectLowerBound => "invalid lower bound",
+            Self::ExitIncorrectUpperBound => "invalid upper bound",
+            Self::ExitIncorrectSamplesCount => "invalid number of samples",
+            Self::ExitUnableToCalculate => "unable to calculate the integral",
+            Self::ExitBoundsReversed => "lower bound exceeds upper bound",
+            Self::ExitNonFiniteBound => "invalid infinite bound",
+            Self::ExitWorkerPanicked => "worker thread panicked",
+            Self::ExitIncorrectThreadsCount => "invalid number of threads",
+            Self::ExitCancelled => "calculation cancelled",
+            Self::ExitToleranceNotReached => "tolerance invalid or not reached",
+            Self::ExitIncorrectArguments => "invalid flags or settings",
+            Self::ExitUnknownMethod => "unknown method",
+            Self::ExitIncorrectTable => "invalid table of values",
+            Self::ExitZeroSamples => "zero samples",
+            Self::ExitInvalidInterval => "invalid interval or step",
+            Self::ExitEndOfInput => "input ended or unreadable",
+            Self::ExitUnknownFunction => "unknown function",
+            Self::ExitInvalidExpression => "invalid expression",
+            Self::ExitBatchFailed => "at least one --batch calculation failed",
+            Self::ExitFileError => "file read or write error",
+            Self::ExitInterrupted => "interrupted by Ctrl+C",
+            Self::ExitInterruptedTwice => "second Ctrl+C",
+            Self::ExitBrokenPipe => "standard output closed (e.g. | head)",
+            Self::ValidateOk => "OK",
+            Self::ExitCompareMismatch => "sequential and threaded runs disagree (--compare)",
+            Self::CompareSequential => "Sequential run",
+            Self::CompareThreaded => "Threaded run",
+            Self::CompareDifference => "Difference",
+            Self::CompareTolerance => "tolerance",
+            Self::CompareMismatch => "sequential and threaded runs differ by more than the tolerance",
+            Self::Seed => "Random seed",
+            Self::ParseSeedError => "Invalid seed: expected an integer from 0 to 2^64 − 1",
+            Self::AccuracyWarning => "Too few samples: remainder bound",
+            Self::AccuracySuggestedSamples => "samples needed at least",
+            Self::AccuracyUnreachable => "the threshold is out of reach even at the maximum sample count",
+            Self::UnknownFlag => "Unknown flag",
+            Self::UnexpectedArgument => "Unexpected argument",
+            Self::DeprecatedFlag => "Deprecated flag",
+            Self::UseInstead => "use",
+            Self::CompletionsShell => "Specify a shell: kryl_04 completions bash|zsh|fish",
+            Self::ReplHelp => concat!(
+                "  int A B [N]         integral over [A, B], with N samples if given\n",
+                "  plot A B            plot of the function over [A, B] (to stderr)\n",
+                "  set samples N       number of samples for the following calculations\n",
+                "  set method M        method\n",
+                "  set function F      built-in function by number or name\n",
+                "  set expr E          expression in x\n",
+                "  set threads N       number of threads, 0 for one per core\n",
+                "  show                current settings\n",
+                "  set history N       history size\n",
+                "  set persist on|off  save the history to ~/.local/share/kryl_04/history.jsonl\n",
+                "  last                result of the last calculation\n",
+                "  history             list of past calculations\n",
+                "  recall N            history entry N; $N in int bounds is its value\n",
+                "  quit                exit (as does the end of input)",
+            ),
+            Self::PromptMethod => "Enter the method: midpoint, left, right, trapezoid, simpson, simpson38, boole, gk15, montecarlo, clenshaw-curtis, tanh-sinh (empty for midpoint): ",
+            Self::PromptFunction => "Enter the number or name of a function, or an expression in x (empty for 1): ",
+            Self::ParseFloatError => "Failed to parse the input as a real number; accepted forms: 0.5, 0,5, -3.14, 1.5e2, inf",
+            Self::ParseIntError => "Failed to parse the input as an integer",
+            Self::ParseSamplesError => "Failed to parse the input as an integer or a tolerance in (0, 1)",
+            Self::SamplesFormats => "accepted forms: 1000000, 1_000_000, 1 000 000, 1e6, 1m, 1kk, 1000k",
+            Self::SamplesOverflow => "the number of samples exceeds 18446744073709551615",
+            Self::SamplesNotInteger => "the number of samples must be a whole number",
+            Self::UnknownLang => "Unknown language, expected one of: ru, en",
+            Self::UnknownFormat => "Unknown output format, expected one of: text, csv, json (csv for --batch and bench, json for bench and a single calculation)",
+            Self::UnknownNotation => "Unknown number notation, expected one of: fixed, scientific, auto",
+            Self::ParsePrecisionError => "Precision must be a whole number of significant digits from 1 to 17",
+            Self::EndOfInput => "Input ended prematurely",
+            Self::TableReadError => "Failed to read the table file",
+            Self::TableParseError => "Failed to parse a pair of numbers x, y on line",
+            Self::UnexpectedToken => "Unexpected extra value in the input",
+            Self::BatchTooFewColumns => "Expected a line of the form: lower bound, upper bound, number of samples [, method] [, function]",
+            Self::Progress => "Done",
+            Self::Integrand => "Integrand",
+            Self::Method => "Method",
+            Self::ApproximateValue => "Approximate value of the integral",
+            Self::Interrupted => "Calculation interrupted, done",
+            Self::PartialEstimate => "Incomplete estimate of the integral (not a result)",
+            Self::Step => "Step",
+            Self::Intervals => "intervals",
+            Self::Evaluations => "function evaluations",
+            Self::Threads => "threads",
+            Self::Elapsed => "time",
+            Self::ReferenceValue => "\"Actual\" value of the integral",
+            Self::RefinedValue => "Value with twice as many samples",
+            Self::ErrorEstimate => "Error estimate",
+            Self::SimpsonValue => "By Simpson's rule",
+            Self::SplineValue => "By cubic spline",
+            Self::SuspectedSingularity => "Warning: the integrand appears to be singular near x ≈",
+            Self::ColumnSamples => "samples",
+            Self::PromptEvaluations => "Enter the number of function evaluations for each method: ",
+            Self::BenchReference => "Reference value (gk15, 10000 panels)",
+            Self::ColumnError => "error",
+            Self::ColumnEvaluations => "evaluations",
+            Self::UnknownSort => "Row order must be method or error",
+            Self::ParseRowsError => "The number of rows must be an integer from 1 to 40",
+            Self::ColumnValue => "value",
+            Self::ColumnDifference => "difference",
+            Self::ColumnWorker => "thread",
+            Self::ColumnInterval => "interval",
+            Self::ColumnShare => "share",
+            Self::ColumnStarted => "started",
+            Self::PhaseMain => "main run",
+            Self::PhaseReference => "reference run",
+            Self::PhaseTimes => "Phase times",
+            Self::PhaseRemainder => "remainder bound",
+            Self::PhaseRefined => "twice as many samples",
+            Self::ReductionBlocks => "pairwise reduction blocks",
+            Self::Remaining => "remaining ≈",
+            Self::Seconds => "s",
+            Self::ConvergenceOrder => "Estimated order of convergence",
+            Self::NotConverged => "Tolerance not reached within the allowed number of doublings",
+            Self::AbsoluteError => "Absolute error",
+            Self::RemainderBound => "Upper bound for Rn",
+            Self::ErrorWithinBound => "Absolute error fits the remainder term",
+            Self::RelativeError => "Relative error",
+            Self::RelativeErrorUndefined => "relative error is undefined (the result is close to zero)",
+            Self::CalculationError => "Integral calculation error",
+            Self::Help => concat!(
+                "Usage: kryl_04 [flags]\n",
+                "       kryl_04 bench --lower A --upper B --evals N [--format text|csv|json]\n",
+                "                     [--sort method|error]\n",
+                "       kryl_04 table --lower A --upper B [--start N] [--rows R] [--method M]\n",
+                "                     [--format text|csv]\n",
+                "       kryl_04 completions bash|zsh|fish\n",
+                "Computes the integral of a built-in function (atan(x)/(x^4+1) by default). Values\n",
+                "not given as flags are prompted for; if any of --lower, --upper, --samples,\n",
+                "--tolerance, --threads, --method, --function, --expr is given,\n",
+                "optional values fall back to their defaults without prompting.\n",
+                "\n",
+                "  --lower A               lower bound (a number, inf, -inf)\n",
+                "  --upper B               upper bound\n",
+                "  --samples N             number of samples or a required tolerance in (0, 1)\n",
+                "  --tolerance EPS         required tolerance instead of --samples: the number of samples\n",
+                "                          is doubled until two estimates differ by less than EPS\n",
+                "  --threads N             number of threads up to 4096, 0 for one per core (default;\n",
+                "                          also from the KRYL_THREADS variable)\n",
+                "  --method M              method: midpoint, left, right, trapezoid, simpson, simpson38,\n",
+                "                          boole, gk15, montecarlo, clenshaw-curtis, tanh-sinh\n",
+                "                          (also gauss, gauss-kronrod, monte-carlo; case-insensitive)\n",
+                "  --function F            function by number or name: atan, sinc, gauss, cauchy, xlnx\n",
+                "  --expr E                expression in x, e.g. \"exp(-x^2)*sin(3*x)\": + - * / ^,\n",
+                "                          parentheses, pi, e, sin, cos, tan, atan, exp, ln, sqrt, abs\n",
+                "  --with-reference[=N]    verify the result with a reference run of N\n",
+                "                          samples (the maximum allowed by default); without it the\n",
+                "                          error is estimated by doubling the samples (Runge)\n",
+                "  --reference             deprecated form of --with-reference\n",
+                "  --reference-samples N   deprecated form of --with-reference=N\n",
+                "  --negate-reversed       compute −∫ᵇₐ instead of failing when a > b\n",
+                "  --propagate-nan         do not stop on NaN or ±∞ integrand values\n",
+                "  --seed N                montecarlo seed; without it a random one is picked and\n",
+                "                          printed to stderr\n",
+                "  --compare               run sequential and threaded passes and compare them;\n",
+                "                          when quiet, one line \"value value difference\"\n",
+                "  --batch                 one calculation per input line \"a b n [method] [function]\"\n",
+                "  --input PATH            --batch mode reading lines from a file\n",
+                "  --output PATH           write --batch values to a file (replaced as a whole)\n",
+                "  --validate              check --batch lines without calculating: OK or error\n",
+                "  --format text|csv       --batch output format (json also for bench);\n",
+                "                          a single calculation: text or json\n",
+                "  --plot                  plot the function to stderr before calculating (width\n",
+                "                          from COLUMNS)\n",
+                "  --plot-height H         plot height in lines, 12 by default\n",
+                "  --dump-plot PATH        write x, f(x), F(x) = ∫ₐˣ f to PATH and a gnuplot script\n",
+                "                          to PATH with the .gp extension\n",
+                "  --dump-points N         --dump-plot grid intervals, 1000 by default\n",
+                "  --2d                    double integral of f(x)·f(y) over a rectangle\n",
+                "  --repl                  commands int, set, last, quit one per line (see help)\n",
+                "  --history-size N        --repl history size, 100 by default\n",
+                "  --convergence           table of values as the number of samples doubles\n",
+                "  bench                   compare all methods at about --evals function evaluations\n",
+                "                          against a gk15 reference; --sort error orders by error\n",
+                "  table                   convergence table: n, Iₙ, |Iₙ − I₂ₙ|, order p and bound Rₙ\n",
+                "                          for --rows rows (12) doubling n from --start (16)\n",
+                "  --table PATH            integral of x, y pairs read from a file\n",
+                "  --precision N           significant digits of printed numbers (default 12)\n",
+                "  --format-number MODE    number notation: fixed, scientific or auto\n",
+                "  -q, --quiet             print only the integral value\n",
+                "  --no-warnings           do not warn about too few samples\n",
+                "  --interactive           show prompts even when input or output is not a terminal;\n",
+                "                          without a terminal there are no prompts and output is as -q\n",
+                "  --no-interactive        never show prompts, even on a terminal\n",
+                "  --no-color              disable colors (also when NO_COLOR is set)\n",
+                "  -v, --verbose           run summary to stderr; -vv adds per-thread details\n",
+                "  --lang ru|en            message language (default: from LANG)\n",
+                "  --config PATH           configuration file instead of ~/.config/kryl_04/config.toml:\n",
+                "                          lines \"key = value\" with flag names without -- as keys;\n",
+                "                          flags and environment variables take precedence\n",
+                "  --print-config          print the effective settings and their sources\n",
+                "  --list-exit-codes       print the exit codes and their reasons\n",
+                "  --help, -h              this help\n",
+            ),
+        }
+    }
+}
+
+/// Текст сообщения на текущем языке.
+pub fn tr(message: Message) -> &'static str {
+    message.text(Lang::current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::method::Method;
+
+    const PROMPTS: &[Message] = &[
+        Message::PromptLowerBound,
+        Message::PromptUpperBound,
+        Message::PromptSamples,
+        Message::PromptLowerBoundX,
+        Message::PromptUpperBoundX,
+        Message::PromptLowerBoundY,
+        Message::PromptUpperBoundY,
+        Message::PromptSamplesX,
+        Message::PromptSamplesY,
+        Message::PromptStartSamples,
+        Message::PromptTolerance,
+        Message::PromptThreads,
+        Message::PromptMethod,
+        Message::PromptFunction,
+        Message::PromptEvaluations,
+    ];
+
+    #[test]
+    fn codes_round_trip() {
+        for lang in [Lang::Ru, Lang::En] {
+            assert_eq!(lang.code().parse(), Ok(lang));
+        }
+        assert_eq!("EN".parse(), Ok(Lang::En));
+        assert_eq!("de".parse::<Lang>(), Err(()));
+        assert_eq!(Lang::default(), Lang::Ru);
+    }
+
+    #[test]
+    fn prompts_in_both_languages() {
+        for &prompt in PROMPTS {
+            let (ru, en) = (prompt.text(Lang::Ru), prompt.text(Lang::En));
+            assert!(ru.starts_with("Введите") && ru.ends_with(": "), "{:?}", prompt);
+            assert!(en.starts_with("Enter") && en.ends_with(": "), "{:?}", prompt);
+        }
+    }
+
+    #[test]
+    fn method_prompt_lists_every_method() {
+        for lang in [Lang::Ru, Lang::En] {
+            let prompt = Message::PromptMethod.text(lang);
+            for method in Method::ALL {
+                assert!(prompt.contains(&format!(" {},", method.name())) || prompt.contains(&format!(" {} (", method.name())), "{}", method);
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::IntegralCalcError;
+use crate::clenshaw_curtis::ClenshawCurtisRule;
+use crate::error_bound::ErrorTerm;
+use crate::float::Float;
+use crate::gauss_kronrod::GaussKronrodRule;
+use crate::monte_carlo::{MonteCarloRule, MONTE_CARLO_DEFAULT_SEED};
+use crate::rule::{
+    BooleRule,
+    IntegrationRule,
+    LeftRiemannRule,
+    MidpointRule,
+    RightRiemannRule,
+    SimpsonRule,
+    SimpsonThreeEighthsRule,
+    TrapezoidRule,
+};
+use crate::tanh_sinh::TanhSinhRule;
+
+/// Квадратурная формула, по которой ведётся расчёт.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Method {
+    /// Формула средних прямоугольников.
+    #[default]
+    Midpoint,
+    /// Формула левых прямоугольников.
+    Left,
+    /// Формула правых прямоугольников.
+    Right,
+    /// Формула трапеций.
+    Trapezoid,
+    /// Формула Симпсона; число отсчётов должно быть чётным.
+    Simpson,
+    /// Формула Симпсона 3/8; число отсчётов округляется вверх до кратного трём.
+    SimpsonThreeEighths,
+    /// Формула Буля; число отсчётов должно быть кратно четырём.
+    Boole,
+    /// Пара Гаусса–Кронрода (G7, K15); отсчёты — число панелей по 15 узлов,
+    /// результат содержит оценку погрешности.
+    GaussKronrod,
+    /// Метод Монте-Карло с зерном `MONTE_CARLO_DEFAULT_SEED`; результат
+    /// содержит стандартную ошибку оценки.
+    MonteCarlo,
+    /// Формула Кленшоу–Кёртиса; число отсчётов (промежутков между узлами)
+    /// должно быть чётным, результат содержит оценку погрешности.
+    ClenshawCurtis,
+    /// Формула tanh-sinh для особенностей на концах; отсчёты — число панелей,
+    /// результат содержит оценку погрешности.
+    TanhSinh,
+}
+
+impl Method {
+    /// Все доступные формулы в порядке вывода в справке.
+    pub const ALL: &'static [Method] = &[
+        Method::Midpoint,
+        Method::Left,
+        Method::Right,
+        Method::Trapezoid,
+        Method::Simpson,
+        Method::SimpsonThreeEighths,
+        Method::Boole,
+        Method::GaussKronrod,
+        Method::MonteCarlo,
+        Method::ClenshawCurtis,
+        Method::TanhSinh,
+    ];
+
+    /// Имя формулы, которое принимает `FromStr`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Method::Midpoint => "midpoint",
+            Method::Left => "left",
+            Method::Right => "right",
+            Method::Trapezoid => "trapezoid",
+            Method::Simpson => "simpson",
+            Method::SimpsonThreeEighths => "simpson38",
+            Method::Boole => "boole",
+            Method::GaussKronrod => "gk15",
+            Method::MonteCarlo => "montecarlo",
+            Method::ClenshawCurtis => "clenshaw-curtis",
+            Method::TanhSinh => "tanh-sinh",
+        }
+    }
+
+    /// Вид остаточного члена формулы для `error_bound`, если он известен.
+    pub fn error_term(self) -> Option<ErrorTerm> {
+        self.rule::<f64>().error_term()
+    }
+
+    /// Число отсчётов, которое формула фактически использует при запрошенных
+    /// `samples`: для `SimpsonThreeEighths` — ближайшее сверху кратное трём,
+    /// для остальных — без изменений (некратное отвергается при проверке).
+    pub fn adjust_samples(self, samples: u64) -> u64 {
+        match self {
+            Method::SimpsonThreeEighths => samples.div_ceil(3).saturating_mul(3),
+            _ => samples,
+        }
+    }
+
+    /// Реализация формулы.
+    pub fn rule<T: Float>(self) -> &'static dyn IntegrationRule<T> {
+        match self {
+            Method::Midpoint => &MidpointRule,
+            Method::Left => &LeftRiemannRule,
+            Method::Right => &RightRiemannRule,
+            Method::Trapezoid => &TrapezoidRule,
+            Method::Simpson => &SimpsonRule,
+            Method::SimpsonThreeEighths => &SimpsonThreeEighthsRule,
+            Method::Boole => &BooleRule,
+            Method::GaussKronrod => &GaussKronrodRule,
+            Method::MonteCarlo => &MonteCarloRule { seed: MONTE_CARLO_DEFAULT_SEED },
+            Method::ClenshawCurtis => &ClenshawCurtisRule,
+            Method::TanhSinh => &TanhSinhRule,
+        }
+    }
+
+    /// То же, что `rule`, но `Method::MonteCarlo` — с зерном `monte_carlo`.
+    pub(crate) fn seeded_rule<T: Float>(self, monte_carlo: &MonteCarloRule) -> &dyn IntegrationRule<T> {
+        match self {
+            Method::MonteCarlo => monte_carlo,
+            method => method.rule(),
+        }
+    }
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Другие принятые имена формул.
+const ALIASES: &[(&str, Method)] = &[
+    ("gauss", Method::GaussKronrod),
+    ("gauss-kronrod", Method::GaussKronrod),
+    ("monte-carlo", Method::MonteCarlo),
+];
+
+/// Наибольшее расстояние Левенштейна, при котором имя ещё предлагается
+/// как исправление опечатки.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+impl Method {
+    /// Формула, которая, вероятно, имелась в виду под неизвестным именем
+    /// `name`: единственная, чьё имя начинается с `name`, иначе ближайшая по
+    /// расстоянию Левенштейна, если оно не больше `MAX_SUGGESTION_DISTANCE`.
+    pub fn suggest(name: &str) -> Option<Method> {
+        let name = name.trim().to_ascii_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        let mut prefixed = names().filter(|(candidate, _)| candidate.starts_with(&name)).map(|(_, method)| method);
+        if let Some(method) = prefixed.next() {
+            return prefixed.all(|other| other == method).then_some(method);
+        }
+        names()
+            .map(|(candidate, method)| (edit_distance(&name, candidate), method))
+            .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, method)| method)
+    }
+}
+
+/// Все принятые имена формул: `Method::name` и `ALIASES`.
+fn names() -> impl Iterator<Item = (&'static str, Method)> {
+    Method::ALL.iter().map(|&method| (method.name(), method)).chain(ALIASES.iter().copied())
+}
+
+/// Расстояние Левенштейна между `a` и `b` по символам.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+impl FromStr for Method {
+    type Err = IntegralCalcError;
+
+    /// Имя из `Method::name` или одно из `ALIASES` без учёта регистра; в
+    /// ошибке — подсказка из `Method::suggest`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        names()
+            .find(|&(candidate, _)| candidate == name)
+            .map(|(_, method)| method)
+            .ok_or_else(|| IntegralCalcError::UnknownMethod {
+                name: s.trim().to_string(),
+                suggestion: Method::suggest(s),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for &method in Method::ALL {
+            assert_eq!(method.name().parse(), Ok(method));
+            assert_eq!(method.name().to_ascii_uppercase().parse(), Ok(method));
+            assert_eq!(method.to_string(), method.name());
+        }
+        assert_eq!(" Gauss ".parse(), Ok(Method::GaussKronrod));
+        assert_eq!("monte-carlo".parse(), Ok(Method::MonteCarlo));
+    }
+
+    #[test]
+    fn typos_get_suggestions() {
+        assert_eq!(Method::suggest("simpsn"), Some(Method::Simpson));
+        assert_eq!(Method::suggest("trap"), Some(Method::Trapezoid));
+        assert_eq!(Method::suggest("Midpiont"), Some(Method::Midpoint));
+        // Префикс «simpson» есть у двух формул, а расстояние до обеих больше двух
+        assert_eq!(Method::suggest("simp"), None);
+        assert_eq!(Method::suggest("romberg"), None);
+        assert_eq!(Method::suggest(""), None);
+        assert_eq!(
+            "simpsn".parse::<Method>(),
+            Err(IntegralCalcError::UnknownMethod { name: "simpsn".to_string(), suggestion: Some(Method::Simpson) })
+        );
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("boole", "boole"), 0);
+    }
+
+    #[test]
+    fn three_eighths_samples_are_rounded_up() {
+        assert_eq!(Method::SimpsonThreeEighths.adjust_samples(10), 12);
+        assert_eq!(Method::SimpsonThreeEighths.adjust_samples(9), 9);
+        assert_eq!(Method::Simpson.adjust_samples(11), 11);
+    }
+}
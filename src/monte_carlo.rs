@@ -0,0 +1,349 @@
+use std::thread::ScopedJoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::integrate::{calculate_integral_with_rule, default_threads};
+use crate::options::IntegrationOptions;
+use crate::random::Rng;
+use crate::result::IntegrationResult;
+use crate::rule::IntegrationRule;
+use crate::summation::pairwise_sum;
+
+/// Зерно, с которым работает `Method::MonteCarlo`.
+pub const MONTE_CARLO_DEFAULT_SEED: u64 = 0x6B72_796C;
+
+/// Число точек в одном блоке многомерного метода Монте-Карло: у каждого
+/// блока свой поток генератора, а между блоками проверяется флаг отмены.
+const MONTE_CARLO_ND_BLOCK: u64 = 4096;
+
+/// Метод Монте-Карло: `samples` равномерно распределённых точек, среднее
+/// значение f умножается на (b − a).
+///
+/// Каждая порция прохода берёт свои точки из отдельного потока генератора,
+/// производного от зерна и положения порции. Порции отсчитываются от начала
+/// участков свёртки `reduction_blocks` и имеют одну длину (см. `RuleCursor`),
+/// так что не зависят ни от числа потоков, ни от отмены и обработчика хода
+/// расчёта: при тех же зерне и числе отсчётов результат воспроизводится в
+/// точности. `error_estimate` результата — стандартная ошибка оценки
+/// (b − a)·σ/√n.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonteCarloRule {
+    pub seed: u64,
+}
+
+impl MonteCarloRule {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl Default for MonteCarloRule {
+    fn default() -> Self {
+        Self::new(MONTE_CARLO_DEFAULT_SEED)
+    }
+}
+
+impl<T: Float> IntegrationRule<T> for MonteCarloRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        self.accumulate_with_error(f, lower_bound, upper_bound, samples).0
+    }
+
+    /// Вместо погрешности возвращает сумму квадратов значений: она, как и
+    /// сумма, складывается по участкам, а в стандартную ошибку переводится
+    /// в `scale_error`.
+    fn accumulate_with_error(
+        &self,
+        f: &mut dyn FnMut(T) -> T,
+        lower_bound: T,
+        upper_bound: T,
+        samples: u64,
+    ) -> (T, Option<T>) {
+        let mut rng = Rng::substream(self.seed, lower_bound.to_f64().to_bits() ^ samples.rotate_left(32));
+        let width = upper_bound - lower_bound;
+        let mut sum = T::ZERO;
+        let mut squares = T::ZERO;
+        for _ in 0..samples {
+            let value = f(lower_bound + width * T::from_f64(rng.next_f64()));
+            sum += value;
+            squares += value * value;
+        }
+        (sum, Some(squares))
+    }
+
+    fn ordered_nodes(&self) -> bool {
+        false
+    }
+
+    fn order(&self) -> u32 {
+        1
+    }
+
+    fn scale_error(&self, error: T, sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        if samples < 2 {
+            return T::ZERO;
+        }
+        let count = T::from_u64(samples);
+        let mean = sum / count;
+        let variance = ((error / count - mean * mean) * count / T::from_u64(samples - 1)).max(T::ZERO);
+        (upper_bound - lower_bound) * (variance / count).sqrt()
+    }
+}
+
+/// Интеграл методом Монте-Карло по `samples` точкам с зерном `seed`.
+pub fn monte_carlo<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    seed: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    monte_carlo_with_options(f, lower_bound, upper_bound, seed, &options)
+}
+
+/// То же, что `monte_carlo`, с заданными параметрами расчёта.
+pub fn monte_carlo_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    seed: u64,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    calculate_integral_with_rule(&MonteCarloRule::new(seed), f, lower_bound, upper_bound, options)
+}
+
+/// Интеграл функции `bounds.len()` переменных по прямоугольному
+/// параллелепипеду методом Монте-Карло по `samples` точкам с зерном `seed`.
+pub fn monte_carlo_nd<T, F>(
+    f: F,
+    bounds: &[(T, T)],
+    samples: u64,
+    seed: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    F: Fn(&[T]) -> T + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    monte_carlo_nd_with_options(f, bounds, seed, &options)
+}
+
+/// То же, что `monte_carlo_nd`, с заданными параметрами расчёта.
+///
+/// Точки разбиты на блоки по `MONTE_CARLO_ND_BLOCK`, каждый со своим потоком
+/// генератора, а суммы блоков складываются попарным деревом по номеру блока,
+/// поэтому результат не зависит от числа потоков. `step` результата — объём,
+/// приходящийся на одну точку, `error_estimate` — стандартная ошибка.
+pub fn monte_carlo_nd_with_options<T, F>(
+    f: F,
+    bounds: &[(T, T)],
+    seed: u64,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    F: Fn(&[T]) -> T + Send + Sync,
+{
+    if bounds.is_empty() {
+        return Err(IntegralCalcError::EmptyBounds);
+    }
+    for (axis, &(lower, upper)) in bounds.iter().enumerate() {
+        for value in [lower, upper] {
+            if !value.is_finite() {
+                return Err(IntegralCalcError::NonFiniteAxisBound { axis, value: value.to_f64() });
+            }
+        }
+        if lower > upper {
+            return Err(IntegralCalcError::AxisBoundsReversed { axis, lower: lower.to_f64(), upper: upper.to_f64() });
+        }
+    }
+    options.validate()?;
+    let start = Instant::now();
+    let samples = options.samples;
+    let blocks = samples.div_ceil(MONTE_CARLO_ND_BLOCK);
+    let threads = if options.execution.is_threaded(samples, options.async_threshold) {
+        options.threads.unwrap_or_else(default_threads).min(usize::try_from(blocks).unwrap_or(usize::MAX))
+    } else {
+        1
+    };
+    let cancel = options.cancel.as_ref();
+    // Блоки с номерами из `range`: суммы значений и их квадратов по каждому блоку
+    let run_blocks = |range: std::ops::Range<u64>| -> Result<Vec<(T, T)>, IntegralCalcError> {
+        let mut point = vec![T::ZERO; bounds.len()];
+        let mut sums = Vec::with_capacity((range.end - range.start) as usize);
+        for block in range {
+            if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+                return Err(IntegralCalcError::Cancelled);
+            }
+            let mut rng = Rng::substream(seed, block);
+            let count = MONTE_CARLO_ND_BLOCK.min(samples - block * MONTE_CARLO_ND_BLOCK);
+            let mut sum = T::ZERO;
+            let mut squares = T::ZERO;
+            for _ in 0..count {
+                for (coordinate, &(lower, upper)) in point.iter_mut().zip(bounds) {
+                    *coordinate = lower + (upper - lower) * T::from_f64(rng.next_f64());
+                }
+                let value = f(&point);
+                sum += value;
+                squares += value * value;
+            }
+            sums.push((sum, squares));
+        }
+        Ok(sums)
+    };
+    let sums = if threads == 1 {
+        run_blocks(0..blocks)?
+    } else {
+        let per_thread = blocks.div_ceil(threads as u64);
+        let parts = std::thread::scope(|scope| {
+            let handles: Vec<ScopedJoinHandle<_>> = (0..threads as u64)
+                .map(|index| {
+                    let range = (index * per_thread).min(blocks)..((index + 1) * per_thread).min(blocks);
+                    scope.spawn(move || run_blocks(range))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(Err(IntegralCalcError::WorkerPanicked)))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        parts.into_iter().flatten().collect()
+    };
+    let sum = pairwise_sum(&sums.iter().map(|&(sum, _)| sum).collect::<Vec<_>>());
+    let squares = pairwise_sum(&sums.iter().map(|&(_, squares)| squares).collect::<Vec<_>>());
+    let volume = bounds.iter().fold(T::ONE, |volume, &(lower, upper)| volume * (upper - lower));
+    let count = T::from_u64(samples);
+    let mean = sum / count;
+    let error = if samples < 2 {
+        T::ZERO
+    } else {
+        let variance = ((squares / count - mean * mean) * count / T::from_u64(samples - 1)).max(T::ZERO);
+        volume * (variance / count).sqrt()
+    };
+    Ok(IntegrationResult {
+        value: volume * mean,
+        step: volume / count,
+        intervals: samples,
+        samples,
+        threads,
+        elapsed: start.elapsed(),
+        remainder_bound: None,
+        remainder_elapsed: Duration::ZERO,
+        error_estimate: Some(error),
+        singularity: None,
+        workers: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cancel::CancelToken;
+    use crate::integrand::AtanOverQuartic;
+    use crate::method::Method;
+    use crate::options::ExecutionMode;
+    use crate::progress::ProgressHook;
+
+    fn run(samples: u64, options: IntegrationOptions) -> f64 {
+        let options = IntegrationOptions { samples, method: Method::MonteCarlo, seed: 7, ..options };
+        crate::improper::calculate_improper_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options).unwrap().value
+    }
+
+    #[test]
+    fn progress_hook_does_not_change_value() {
+        let plain = run(100_000, IntegrationOptions::default());
+        let hooked = run(100_000, IntegrationOptions {
+            progress: Some(ProgressHook::new(|_, _| {}).granularity(100)),
+            ..Default::default()
+        });
+        assert_eq!(plain.to_bits(), hooked.to_bits());
+    }
+
+    #[test]
+    fn cancel_token_does_not_change_value() {
+        // Участки свёртки длиннее порции между проверками отмены, только если
+        // отрезков больше 4096²
+        let options = |cancel| IntegrationOptions {
+            samples: 17_000_000,
+            method: Method::MonteCarlo,
+            seed: 7,
+            cancel,
+            ..Default::default()
+        };
+        let square = |x: f64| x * x;
+        let plain = monte_carlo_with_options(square, 0.0, 1.0, 7, &options(None)).unwrap();
+        let cancellable = monte_carlo_with_options(square, 0.0, 1.0, 7, &options(Some(CancelToken::new()))).unwrap();
+        assert_eq!(plain.value.to_bits(), cancellable.value.to_bits());
+    }
+
+    #[test]
+    fn threads_do_not_change_value() {
+        let sequential = run(200_000, IntegrationOptions { execution: ExecutionMode::Sequential, ..Default::default() });
+        for threads in [2, 3, 8] {
+            let threaded = run(200_000, IntegrationOptions {
+                execution: ExecutionMode::Threaded,
+                threads: Some(threads),
+                ..Default::default()
+            });
+            assert_eq!(sequential.to_bits(), threaded.to_bits());
+        }
+    }
+
+    #[test]
+    fn seed_changes_value() {
+        let options = |seed| IntegrationOptions { samples: 10_000, method: Method::MonteCarlo, seed, ..Default::default() };
+        let first = crate::improper::calculate_improper_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options(1)).unwrap();
+        let second = crate::improper::calculate_improper_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options(2)).unwrap();
+        assert_ne!(first.value, second.value);
+        // Оба значения в пределах нескольких стандартных ошибок от точного
+        for result in [first, second] {
+            assert!((result.value - 0.349_446_289_9).abs() < 5.0 * result.error_estimate.unwrap());
+        }
+    }
+
+    #[test]
+    fn fixed_seed_is_deterministic() {
+        let first = monte_carlo(AtanOverQuartic, 0.0f64, 1.0, 50_000, 42).unwrap();
+        let second = monte_carlo(AtanOverQuartic, 0.0f64, 1.0, 50_000, 42).unwrap();
+        assert_eq!(first.value.to_bits(), second.value.to_bits());
+        assert_eq!(first.error_estimate, second.error_estimate);
+    }
+
+    #[test]
+    fn error_shrinks_as_inverse_square_root() {
+        // Стандартная ошибка при стократном числе точек уменьшается в 10 раз
+        // и покрывает фактическую погрешность
+        let estimate = |samples| {
+            let result = monte_carlo(AtanOverQuartic, 0.0f64, 1.0, samples, 1).unwrap();
+            let estimate = result.error_estimate.unwrap();
+            assert!((result.value - 0.349446289808078).abs() < 4.0 * estimate, "{}: {}", samples, result.value);
+            estimate
+        };
+        let ratio = estimate(10_000) / estimate(1_000_000);
+        assert!((9.0..11.0).contains(&ratio), "{}", ratio);
+    }
+
+    #[test]
+    fn unit_ball_volumes() {
+        let ball = |point: &[f64]| if point.iter().map(|x| x * x).sum::<f64>() <= 1.0 { 1.0 } else { 0.0 };
+        // π^(d/2)/Γ(d/2 + 1)
+        let pi = std::f64::consts::PI;
+        for (dimension, volume) in [(3, 4.0 * pi / 3.0), (4, pi * pi / 2.0), (5, 8.0 * pi * pi / 15.0), (6, pi.powi(3) / 6.0)] {
+            let bounds = vec![(-1.0, 1.0); dimension];
+            let result = monte_carlo_nd(ball, &bounds, 200_000, 3).unwrap();
+            let error = result.error_estimate.unwrap();
+            assert!((result.value - volume).abs() < 4.0 * error, "{}: {} ± {}", dimension, result.value, error);
+            assert!(error < 0.02 * volume);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::thread::ScopedJoinHandle;
+
+use crate::cancel::CancelToken;
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrate::{reduce_parts, thread_ranges, Accumulated, RuleCursor};
+use crate::integrator::Integrator;
+use crate::options::IntegrationOptions;
+use crate::rule::IntegrationRule;
+
+/// Сколько отрезков проходится за раз: значения второй и следующих
+/// компонент на этих отрезках ждут своей очереди в буфере.
+const MULTI_PIECE_SAMPLES: u64 = 4096;
+
+/// Интегралы нескольких функций по общей сетке за один проход; порядок
+/// значений совпадает с порядком `fs`.
+pub fn calculate_integral_multi<T: Float>(
+    fs: &[Box<dyn Fn(T) -> T + Send + Sync>],
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<Vec<T>, IntegralCalcError> {
+    calculate_integral_vector(
+        |x, values: &mut [T]| {
+            for (value, f) in values.iter_mut().zip(fs) {
+                *value = f(x);
+            }
+        },
+        fs.len(),
+        lower_bound,
+        upper_bound,
+        samples,
+    )
+}
+
+/// Интегралы компонент вектор-функции: `f(x, values)` записывает в `values`
+/// все `components` значений в точке x, так что общие для компонент
+/// вычисления делаются один раз на узел.
+pub fn calculate_integral_vector<T, F>(
+    f: F,
+    components: usize,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<Vec<T>, IntegralCalcError>
+where
+    T: Float,
+    F: Fn(T, &mut [T]) + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    calculate_integral_vector_with_options(f, components, lower_bound, upper_bound, &options)
+}
+
+/// То же, что `calculate_integral_vector`, с заданными параметрами расчёта.
+///
+/// Сетка и деление между потоками те же, что у `calculate_integral_with_options`,
+/// но участки проходятся частями по `MULTI_PIECE_SAMPLES` отрезков, поэтому
+/// значения совпадают с отдельными расчётами с точностью до округления
+/// (у `Method::MonteCarlo` — до статистической погрешности: точки другие).
+/// `progress` и `singularity_check` не используются.
+pub fn calculate_integral_vector_with_options<T, F>(
+    f: F,
+    components: usize,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<Vec<T>, IntegralCalcError>
+where
+    T: Float,
+    F: Fn(T, &mut [T]) + Send + Sync,
+{
+    let integrator = Integrator::builder().bounds(lower_bound, upper_bound).options(options).build()?;
+    if components == 0 {
+        return Ok(Vec::new());
+    }
+    let rule = integrator.rule();
+    let samples = integrator.samples();
+    let step = integrator.step();
+    let ranges = thread_ranges(lower_bound, upper_bound, samples, integrator.threads(), rule.granularity());
+    let cancel = options.cancel.as_ref();
+    let run = |(range_lower_bound, range_samples)| {
+        accumulate_components(rule, &f, components, range_lower_bound, step, range_samples, cancel)
+    };
+    let parts: Vec<Vec<Accumulated<T>>> = if integrator.is_threaded() {
+        std::thread::scope(|scope| {
+            let handles: Vec<ScopedJoinHandle<_>> = ranges.into_iter().map(|range| scope.spawn(move || run(range))).collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(Err(IntegralCalcError::WorkerPanicked)))
+                .collect::<Result<_, _>>()
+        })?
+    } else {
+        ranges.into_iter().map(run).collect::<Result<_, _>>()?
+    };
+    Ok((0..components)
+        .map(|component| {
+            let column: Vec<_> = parts.iter().map(|part| part[component]).collect();
+            reduce_parts(&column).scaled(rule, lower_bound, upper_bound, samples).sum
+        })
+        .collect())
+}
+
+/// Проход одного участка для всех компонент. Первая компонента вычисляет f
+/// и запоминает остальные значения, следующие проходы той же формулы по тем
+/// же отрезкам берут их из буфера в том же порядке узлов.
+fn accumulate_components<T, F>(
+    rule: &dyn IntegrationRule<T>,
+    f: &F,
+    components: usize,
+    lower_bound: T,
+    step: T,
+    samples: u64,
+    cancel: Option<&CancelToken>,
+) -> Result<Vec<Accumulated<T>>, IntegralCalcError>
+where
+    T: Float,
+    F: Fn(T, &mut [T]),
+{
+    let mut cursors = vec![RuleCursor::new(lower_bound, step, samples); components];
+    let mut values = vec![T::ZERO; components];
+    let mut recorded = Vec::new();
+    let stride = components - 1;
+    loop {
+        recorded.clear();
+        let (first, rest) = cursors.split_at_mut(1);
+        let finished = first[0].advance(
+            rule,
+            &mut |x| {
+                f(x, &mut values);
+                recorded.extend_from_slice(&values[1..]);
+                values[0]
+            },
+            MULTI_PIECE_SAMPLES,
+        );
+        for (component, cursor) in rest.iter_mut().enumerate() {
+            let mut index = component;
+            cursor.advance(
+                rule,
+                &mut |_| {
+                    let value = recorded[index];
+                    index += stride;
+                    value
+                },
+                MULTI_PIECE_SAMPLES,
+            );
+        }
+        if finished {
+            break;
+        }
+        if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(IntegralCalcError::Cancelled);
+        }
+    }
+    Ok(cursors
+        .into_iter()
+        .map(|cursor| Accumulated { sum: cursor.sum, error: cursor.error, evaluations: cursor.evaluations, singularity: None, done: cursor.done })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+    use crate::integrate::calculate_integral;
+
+    #[test]
+    fn moments_match_independent_runs() {
+        let evaluations = AtomicU64::new(0);
+        let weight = |x: f64| {
+            evaluations.fetch_add(1, Ordering::Relaxed);
+            (-x * x).exp()
+        };
+        let separate: Vec<f64> = [0, 1, 2]
+            .iter()
+            .map(|&power| calculate_integral(|x: f64| x.powi(power) * weight(x), -1.0, 2.0, 30_000).unwrap().value)
+            .collect();
+        let separate_evaluations = evaluations.swap(0, Ordering::Relaxed);
+        let moments = calculate_integral_vector(
+            |x: f64, values: &mut [f64]| {
+                let w = weight(x);
+                values.copy_from_slice(&[w, x * w, x * x * w]);
+            },
+            3,
+            -1.0,
+            2.0,
+            30_000,
+        )
+        .unwrap();
+        let shared_evaluations = evaluations.load(Ordering::Relaxed);
+        for (moment, value) in moments.iter().zip(&separate) {
+            assert!((moment - value).abs() < 1e-12, "{} != {}", moment, value);
+        }
+        assert_eq!(shared_evaluations, 30_000);
+        assert_eq!(separate_evaluations, 3 * shared_evaluations);
+    }
+
+    #[test]
+    fn order_follows_functions() {
+        let fs: Vec<Box<dyn Fn(f64) -> f64 + Send + Sync>> = vec![Box::new(|x| x * x), Box::new(|_| 1.0), Box::new(|x| x)];
+        let values = calculate_integral_multi(&fs, 0.0, 2.0, 1000).unwrap();
+        assert!((values[0] - 8.0 / 3.0).abs() < 1e-5, "{}", values[0]);
+        assert!((values[1] - 2.0).abs() < 1e-12, "{}", values[1]);
+        assert!((values[2] - 2.0).abs() < 1e-12, "{}", values[2]);
+        assert!(calculate_integral_multi::<f64>(&[], 0.0, 1.0, 10).unwrap().is_empty());
+    }
+}
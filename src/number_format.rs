@@ -0,0 +1,150 @@
+use std::str::FromStr;
+
+/// Число значащих цифр по умолчанию.
+pub const DEFAULT_PRECISION: usize = 12;
+
+/// Наибольшее осмысленное число значащих цифр f64.
+pub const MAX_PRECISION: usize = 17;
+
+/// Запись числа.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// С фиксированной точкой: `0.000000000030`; целая часть выводится
+    /// полностью, даже если в ней больше цифр, чем задано.
+    Fixed,
+    /// С порядком: `3.0e-11`.
+    Scientific,
+    /// Порядок — только для чисел меньше 1e-4 или не меньше 10^precision
+    /// (как `%g`), незначащие нули в конце дробной части отбрасываются.
+    #[default]
+    Auto,
+}
+
+impl FromStr for Notation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "fixed" => Ok(Notation::Fixed),
+            "scientific" => Ok(Notation::Scientific),
+            "auto" => Ok(Notation::Auto),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Формат вывода чисел: запись и число значащих цифр.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Значащих цифр; `None` — кратчайшая запись, по которой f64
+    /// восстанавливается в точности.
+    pub precision: Option<usize>,
+    pub notation: Notation,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self { precision: Some(DEFAULT_PRECISION), notation: Notation::Auto }
+    }
+}
+
+impl NumberFormat {
+    /// Запись `value`. NaN и бесконечности выводятся как `NaN`, `inf` и
+    /// `-inf`, отрицательный ноль — как `0`.
+    pub fn format(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        // −0 и +0 неразличимы для пользователя, знак только сбивает с толку
+        let value = if value == 0.0 { 0.0 } else { value };
+        let scientific = match self.precision {
+            Some(precision) => format!("{:.*e}", precision.max(1) - 1, value),
+            None => format!("{:e}", value),
+        };
+        // Порядок берётся после округления: 9.9999…e0 может стать 1.0e1
+        let exponent: i32 = scientific.rsplit('e').next().and_then(|exponent| exponent.parse().ok()).unwrap_or(0);
+        let digits = self.precision.unwrap_or(MAX_PRECISION) as i32;
+        match self.notation {
+            Notation::Scientific => scientific,
+            Notation::Fixed => self.fixed(value, exponent),
+            Notation::Auto if value != 0.0 && (exponent < -4 || exponent >= digits) => {
+                let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+                format!("{}e{}", trim_fraction(mantissa), exponent)
+            }
+            Notation::Auto => trim_fraction(&self.fixed(value, exponent)).to_string(),
+        }
+    }
+
+    fn fixed(&self, value: f64, exponent: i32) -> String {
+        match self.precision {
+            Some(precision) => {
+                let decimals = (precision.max(1) as i32 - 1 - exponent).max(0) as usize;
+                format!("{:.*}", decimals, value)
+            }
+            None => value.to_string(),
+        }
+    }
+}
+
+/// Отбрасывает нули в конце дробной части и оставшуюся без цифр точку.
+fn trim_fraction(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(precision: Option<usize>, notation: Notation, value: f64) -> String {
+        NumberFormat { precision, notation }.format(value)
+    }
+
+    #[test]
+    fn zero_and_negative_zero() {
+        for notation in [Notation::Fixed, Notation::Scientific, Notation::Auto] {
+            assert_eq!(format(Some(3), notation, -0.0), format(Some(3), notation, 0.0));
+        }
+        assert_eq!(format(Some(3), Notation::Auto, -0.0), "0");
+        assert_eq!(format(Some(3), Notation::Fixed, 0.0), "0.00");
+        assert_eq!(format(Some(3), Notation::Scientific, -0.0), "0.00e0");
+    }
+
+    #[test]
+    fn tiny_and_subnormal_values() {
+        assert_eq!(format(Some(12), Notation::Auto, 3e-11), "3e-11");
+        assert_eq!(format(Some(2), Notation::Fixed, 3e-11), "0.000000000030");
+        assert_eq!(format(Some(2), Notation::Scientific, 3e-11), "3.0e-11");
+        assert_eq!(format(Some(12), Notation::Auto, 5e-324), "4.94065645841e-324");
+        assert_eq!(format(None, Notation::Auto, 5e-324), "5e-324");
+    }
+
+    #[test]
+    fn large_values() {
+        assert_eq!(format(Some(12), Notation::Auto, 1e300), "1e300");
+        assert_eq!(format(Some(3), Notation::Auto, 123456.0), "1.23e5");
+        assert_eq!(format(Some(3), Notation::Fixed, 123456.0), "123456");
+        assert_eq!(format(Some(12), Notation::Auto, f64::MAX), "1.79769313486e308");
+        assert_eq!(format(Some(3), Notation::Auto, f64::NEG_INFINITY), "-inf");
+        assert_eq!(format(Some(3), Notation::Auto, f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn rounding_changes_exponent() {
+        assert_eq!(format(Some(3), Notation::Auto, 9.9999), "10");
+        assert_eq!(format(Some(3), Notation::Scientific, 9.9999), "1.00e1");
+        assert_eq!(format(Some(3), Notation::Fixed, 0.00099999), "0.00100");
+    }
+
+    #[test]
+    fn default_and_shortest() {
+        assert_eq!(NumberFormat::default().format(0.349446289808078), "0.349446289808");
+        assert_eq!(format(None, Notation::Auto, 0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format(Some(4), Notation::Auto, -2.5), "-2.5");
+        assert_eq!("Scientific".parse(), Ok(Notation::Scientific));
+        assert_eq!("engineering".parse::<Notation>(), Err(()));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::cancel::CancelToken;
+use crate::error::IntegralCalcError;
+use crate::integrate::{ASYNC_THRESHOLD_SAMPLES_COUNT, MAX_SAMPLES_COUNT};
+use crate::method::Method;
+use crate::monte_carlo::MONTE_CARLO_DEFAULT_SEED;
+use crate::progress::ProgressHook;
+use crate::singularity::SingularityCheck;
+
+/// Что делать, если нижняя граница больше верхней.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReversedBounds {
+    /// Вернуть `IntegralCalcError::BoundsReversed`.
+    #[default]
+    Error,
+    /// ∫ₐᵇ f = −∫ᵇₐ f: границы меняются местами, у значения меняется знак.
+    Negate,
+}
+
+/// Однопоточный или многопоточный проход.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutionMode {
+    /// В нескольких потоках, если отсчётов больше `async_threshold`.
+    #[default]
+    Auto,
+    /// Всегда в одном потоке.
+    Sequential,
+    /// Всегда в нескольких потоках, как `calculate_integral_async`.
+    Threaded,
+}
+
+impl ExecutionMode {
+    /// Пойдёт ли расчёт с `samples` отсчётами в нескольких потоках.
+    pub fn is_threaded(self, samples: u64, async_threshold: u64) -> bool {
+        match self {
+            Self::Auto => samples > async_threshold,
+            Self::Sequential => false,
+            Self::Threaded => true,
+        }
+    }
+}
+
+/// Параметры расчёта, не зависящие от подынтегральной функции и границ.
+///
+/// `samples` по умолчанию равно нулю и должно быть задано явно:
+/// `IntegrationOptions { samples: 1000, ..Default::default() }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrationOptions {
+    pub samples: u64,
+    pub method: Method,
+    /// Число рабочих потоков; `None` — по числу доступных ядер.
+    pub threads: Option<usize>,
+    /// Число отсчётов, начиная с которого расчёт ведётся в нескольких потоках.
+    /// Однопоточный проход делит сетку на те же участки, что и потоки, поэтому
+    /// порог влияет только на время расчёта, но не на результат.
+    pub async_threshold: u64,
+    /// Выбор между однопоточным и многопоточным проходом; кроме `Auto`
+    /// порог `async_threshold` не действует.
+    pub execution: ExecutionMode,
+    pub max_samples: u64,
+    /// Флаг отмены, который проверяют рабочие потоки.
+    pub cancel: Option<CancelToken>,
+    /// Обработчик хода расчёта.
+    pub progress: Option<ProgressHook>,
+    /// Поиск особенностей и разрывов f по ходу расчёта.
+    pub singularity_check: Option<SingularityCheck>,
+    pub reversed_bounds: ReversedBounds,
+    /// Складывать значения f, не являющиеся конечными числами, как есть
+    /// (итог — NaN или ±∞) вместо остановки с `IntegralCalcError::NonFiniteValue`.
+    pub propagate_non_finite: bool,
+    /// Зерно генератора `Method::MonteCarlo`. Точки каждой порции прохода
+    /// берутся из потока, производного от зерна и положения порции, так что
+    /// итог не зависит ни от запуска, ни от числа потоков, ни от `cancel` и
+    /// `progress`.
+    pub seed: u64,
+}
+
+impl Default for IntegrationOptions {
+    fn default() -> Self {
+        Self {
+            samples: 0,
+            method: Method::default(),
+            threads: None,
+            async_threshold: ASYNC_THRESHOLD_SAMPLES_COUNT,
+            execution: ExecutionMode::Auto,
+            max_samples: MAX_SAMPLES_COUNT,
+            cancel: None,
+            progress: None,
+            singularity_check: None,
+            reversed_bounds: ReversedBounds::Error,
+            propagate_non_finite: false,
+            seed: MONTE_CARLO_DEFAULT_SEED,
+        }
+    }
+}
+
+impl IntegrationOptions {
+    pub fn validate(&self) -> Result<(), IntegralCalcError> {
+        if self.samples == 0 {
+            return Err(IntegralCalcError::ZeroSamples);
+        }
+        if self.samples > self.max_samples {
+            return Err(IntegralCalcError::TooManySamples { requested: self.samples, max: self.max_samples });
+        }
+        if self.async_threshold > self.max_samples {
+            return Err(IntegralCalcError::ThresholdAboveMax {
+                threshold: self.async_threshold,
+                max: self.max_samples,
+            });
+        }
+        check_granularity(self.samples, self.method.rule::<f64>().granularity())?;
+        if self.threads == Some(0) {
+            return Err(IntegralCalcError::ZeroThreads);
+        }
+        Ok(())
+    }
+}
+
+/// Проверяет, что число отсчётов кратно `granularity` формулы.
+pub(crate) fn check_granularity(samples: u64, granularity: u64) -> Result<(), IntegralCalcError> {
+    if granularity > 1 && !samples.is_multiple_of(granularity) {
+        return Err(IntegralCalcError::SamplesNotMultiple { samples, multiple: granularity });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_need_samples() {
+        let options = IntegrationOptions::default();
+        assert_eq!(options.validate(), Err(IntegralCalcError::ZeroSamples));
+        assert_eq!(IntegrationOptions { samples: 1, ..options }.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_options() {
+        let options = IntegrationOptions { samples: 100, ..Default::default() };
+        let cases = [
+            (IntegrationOptions { max_samples: 10, async_threshold: 10, ..options.clone() },
+             IntegralCalcError::TooManySamples { requested: 100, max: 10 }),
+            (IntegrationOptions { max_samples: 1000, ..options.clone() },
+             IntegralCalcError::ThresholdAboveMax { threshold: ASYNC_THRESHOLD_SAMPLES_COUNT, max: 1000 }),
+            (IntegrationOptions { samples: 101, method: Method::Simpson, ..options.clone() },
+             IntegralCalcError::SamplesNotMultiple { samples: 101, multiple: 2 }),
+            (IntegrationOptions { threads: Some(0), ..options.clone() }, IntegralCalcError::ZeroThreads),
+        ];
+        for (options, error) in cases {
+            assert_eq!(options.validate(), Err(error));
+        }
+        assert_eq!(check_granularity(7, 1), Ok(()));
+        assert_eq!(check_granularity(8, 4), Ok(()));
+    }
+
+    #[test]
+    fn execution_modes() {
+        assert!(!ExecutionMode::Auto.is_threaded(100, 100));
+        assert!(ExecutionMode::Auto.is_threaded(101, 100));
+        assert!(!ExecutionMode::Sequential.is_threaded(u64::MAX, 0));
+        assert!(ExecutionMode::Threaded.is_threaded(1, 100));
+    }
+}
@@ -0,0 +1,317 @@
+use std::path::Path;
+
+use kryl_04::{calculate_cumulative_integral_with_options, tr, Integrand, IntegralCalcError, IntegrationOptions, Message, Method, NumberFormat, Notation};
+
+use super::{report, Function, PendingOutput};
+
+/// Число отрезков сетки `--dump-plot` по умолчанию.
+pub const DEFAULT_DUMP_POINTS: u64 = 1000;
+
+/// Высота графика в строках по умолчанию и наибольшая.
+pub const DEFAULT_PLOT_HEIGHT: usize = 12;
+pub const MAX_PLOT_HEIGHT: usize = 100;
+
+/// Ширина терминала, если `COLUMNS` не задана.
+const DEFAULT_COLUMNS: usize = 80;
+const MIN_PLOT_WIDTH: usize = 10;
+
+/// Точек Брайля в знаке по горизонтали и вертикали.
+const DOTS_X: usize = 2;
+const DOTS_Y: usize = 4;
+
+/// Подписи осей — четыре значащие цифры.
+const LABEL_FORMAT: NumberFormat = NumberFormat { precision: Some(4), notation: Notation::Auto };
+
+/// График, построенный `render`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plot {
+    pub lines: Vec<String>,
+    /// Наименьшее и наибольшее конечные значения: (x, f(x)).
+    pub min: Option<(f64, f64)>,
+    pub max: Option<(f64, f64)>,
+    /// Число точек, в которых f не конечна, и первая из них.
+    pub non_finite: usize,
+    pub first_non_finite: Option<f64>,
+    pub points: usize,
+}
+
+/// График f на [a, b] шириной `width` и высотой `height` знаков: по две
+/// точки Брайля на знак по x и по четыре по y, соседние точки соединены
+/// вертикальным отрезком. Строка, в которую попадает y = 0, отмечена `─`
+/// там, где нет кривой. Слева — подписи наибольшего, наименьшего значения
+/// и нуля, снизу — границы отрезка.
+pub fn render(f: impl Fn(f64) -> f64, a: f64, b: f64, width: usize, height: usize) -> Plot {
+    let points = width * DOTS_X;
+    let xs: Vec<f64> = (0..points).map(|i| a + (b - a) * (i as f64 + 0.5) / points as f64).collect();
+    let ys: Vec<f64> = xs.iter().map(|&x| f(x)).collect();
+    let finite = || xs.iter().zip(&ys).filter(|(_, y)| y.is_finite()).map(|(&x, &y)| (x, y));
+    let min = finite().fold(None, |min: Option<(f64, f64)>, point| match min {
+        Some(min) if min.1 <= point.1 => Some(min),
+        _ => Some(point),
+    });
+    let max = finite().fold(None, |max: Option<(f64, f64)>, point| match max {
+        Some(max) if max.1 >= point.1 => Some(max),
+        _ => Some(point),
+    });
+    let non_finite = ys.iter().filter(|y| !y.is_finite()).count();
+    let first_non_finite = xs.iter().zip(&ys).find(|(_, y)| !y.is_finite()).map(|(&x, _)| x);
+    let (low, high) = match (min, max) {
+        (Some((_, low)), Some((_, high))) if low < high => (low, high),
+        (Some((_, value)), _) => (value - 1.0, value + 1.0),
+        _ => (-1.0, 1.0),
+    };
+    let rows = height * DOTS_Y;
+    let row_of = |y: f64| (((high - y) / (high - low)) * (rows - 1) as f64).round().clamp(0.0, (rows - 1) as f64) as usize;
+    let mut dots = vec![vec![false; points]; rows];
+    let mut previous: Option<usize> = None;
+    for (column, &y) in ys.iter().enumerate() {
+        if !y.is_finite() {
+            previous = None;
+            continue;
+        }
+        let row = row_of(y);
+        // Вертикальный отрезок до середины пути к предыдущей точке, чтобы
+        // крутые участки не распадались на отдельные точки
+        let (from, to) = match previous {
+            Some(previous) if previous < row => ((previous + row).div_ceil(2), row),
+            Some(previous) => (row, (previous + row) / 2),
+            None => (row, row),
+        };
+        for dot in dots.iter_mut().take(to + 1).skip(from) {
+            dot[column] = true;
+        }
+        if let Some(previous) = previous {
+            let (from, to) = if previous < row { (previous, (previous + row).div_ceil(2)) } else { ((previous + row) / 2, previous) };
+            for dot in dots.iter_mut().take(to + 1).skip(from) {
+                dot[column - 1] = true;
+            }
+        }
+        previous = Some(row);
+    }
+    let zero_row = (low <= 0.0 && 0.0 <= high).then(|| row_of(0.0) / DOTS_Y);
+    let labels: Vec<String> = (0..height).map(|line| {
+        if line == 0 {
+            LABEL_FORMAT.format(high)
+        } else if line == height - 1 {
+            LABEL_FORMAT.format(low)
+        } else if Some(line) == zero_row {
+            "0".to_string()
+        } else {
+            String::new()
+        }
+    }).collect();
+    let margin = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0);
+    let mut lines = Vec::with_capacity(height + 1);
+    for (line, label) in labels.iter().enumerate() {
+        let cells: String = (0..width).map(|cell| {
+            let mut bits = 0u32;
+            for dy in 0..DOTS_Y {
+                for dx in 0..DOTS_X {
+                    if dots[line * DOTS_Y + dy][cell * DOTS_X + dx] {
+                        bits |= braille_bit(dx, dy);
+                    }
+                }
+            }
+            match bits {
+                0 if Some(line) == zero_row => '─',
+                0 => ' ',
+                bits => char::from_u32(0x2800 + bits).unwrap_or(' '),
+            }
+        }).collect();
+        let axis = if Some(line) == zero_row { '┼' } else if label.is_empty() { '│' } else { '┤' };
+        lines.push(format!("{:>margin$} {}{}", label, axis, cells.trim_end(), margin = margin));
+    }
+    let (left, right) = (LABEL_FORMAT.format(a), LABEL_FORMAT.format(b));
+    let gap = width.saturating_sub(left.chars().count() + right.chars().count()).max(1);
+    lines.push(format!("{:>margin$} └{}{:gap$}{}", "", left, "", right, margin = margin, gap = gap));
+    Plot { lines, min, max, non_finite, first_non_finite, points }
+}
+
+/// Бит точки знака Брайля: столбец `dx`, строка `dy` сверху.
+fn braille_bit(dx: usize, dy: usize) -> u32 {
+    match (dx, dy) {
+        (0, 3) => 0x40,
+        (1, 3) => 0x80,
+        (0, dy) => 1 << dy,
+        (_, dy) => 1 << (dy + 3),
+    }
+}
+
+/// Ширина графика по `COLUMNS` (без подписей слева).
+fn plot_width() -> usize {
+    let columns = std::env::var("COLUMNS").ok().and_then(|columns| columns.trim().parse().ok()).unwrap_or(DEFAULT_COLUMNS);
+    columns.saturating_sub(12).max(MIN_PLOT_WIDTH)
+}
+
+/// `--plot` и команда `plot`: график f на [a, b] в stderr, подписи наименьшего
+/// и наибольшего значений и предупреждение о точках, где f не конечна.
+/// На бесконечном отрезке графика нет — только предупреждение.
+pub fn show(f: impl Fn(f64) -> f64, a: f64, b: f64, height: usize) {
+    if !(a.is_finite() && b.is_finite()) || a >= b {
+        report::warning(tr(Message::PlotInfiniteBounds));
+        return;
+    }
+    let plot = render(f, a, b, plot_width(), height);
+    for line in &plot.lines {
+        eprintln!("{}", line);
+    }
+    if let (Some((min_x, min)), Some((max_x, max))) = (plot.min, plot.max) {
+        eprintln!("{}: {} {} {}; {}: {} {} {}",
+                  tr(Message::PlotMinimum), report::number(min), tr(Message::PlotAt), report::number(min_x),
+                  tr(Message::PlotMaximum), report::number(max), tr(Message::PlotAt), report::number(max_x));
+    }
+    if let Some(x) = plot.first_non_finite {
+        report::warning(format_args!("{}: {} / {}, {} {}",
+                                     tr(Message::PlotNonFinite), plot.non_finite, plot.points,
+                                     tr(Message::PlotFirstAt), report::number(x)));
+    }
+}
+
+/// `--dump-plot PATH`: файл данных со столбцами x, f(x), F(x) = ∫ₐˣ f на
+/// сетке из `intervals` отрезков (округляется вверх до кратного, которого
+/// требует метод, и через столько же отрезков идут строки; первая и последняя
+/// строки — ровно a и b) и рядом — сценарий
+/// gnuplot с тем же именем и расширением `.gp`, который рисует обе кривые.
+/// F считается накопленным интегралом тем же методом, так что последнее F —
+/// интеграл на этой сетке.
+pub fn dump(path: &str, function: &Function, method: Method, a: f64, b: f64, intervals: u64, threads: Option<usize>)
+            -> Result<(), IntegralCalcError> {
+    let script_path = Path::new(path).with_extension("gp").to_string_lossy().into_owned();
+    if script_path == path {
+        return Err(IntegralCalcError::OutputFile { path: script_path, reason: tr(Message::DumpScriptClash).to_string() });
+    }
+    let granularity = method.rule::<f64>().granularity();
+    let samples = method.adjust_samples(intervals).div_ceil(granularity).max(1) * granularity;
+    let options = IntegrationOptions { samples, method, threads, ..Default::default() };
+    let points = calculate_cumulative_integral_with_options(function.clone(), a, b, &options)?;
+    let mut data = PendingOutput::create(path.to_string())?;
+    data.write_line(&format!("# {}, [{}, {}], {}, {}: {}", function.name(), a, b, method, tr(Message::Intervals), samples))?;
+    data.write_line("# x f(x) F(x)")?;
+    for (x, integral) in points {
+        data.write_line(&format!("{:e} {:e} {:e}", x, function.eval(x), integral))?;
+    }
+    data.commit()?;
+    let title = format!("{}, [{}, {}], {}", function.name(), report::number(a), report::number(b), method);
+    let usage = format!("# gnuplot -p {}", script_path);
+    let mut script = PendingOutput::create(script_path)?;
+    for line in [
+        usage,
+        format!("set title {}", gnuplot_string(&title)),
+        "set xlabel \"x\"".to_string(),
+        "set grid".to_string(),
+        "set key left top".to_string(),
+        format!("plot {0} using 1:2 with lines title \"f(x)\", \\\n     {0} using 1:3 with lines title \"F(x)\"",
+                gnuplot_string(path)),
+    ] {
+        script.write_line(&line)?;
+    }
+    script.commit()
+}
+
+/// Строка gnuplot в двойных кавычках.
+fn gnuplot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_sine() {
+        let plot = render(f64::sin, 0.0, 2.0 * std::f64::consts::PI, 24, 5);
+        assert_eq!(plot.lines, [
+            " 0.9979 ┤  ⣠⠴⠋⠉⠉⠳⢤⡀",
+            "        │⢀⡞⠁      ⠙⢦⡀",
+            "      0 ┼⠋──────────⠳⣄──────────⡤",
+            "        │            ⠈⢧⡀      ⣠⠞⠁",
+            "-0.9979 ┤              ⠙⠲⣄⣀⣀⡴⠚⠁",
+            "        └0                  6.283",
+        ]);
+        assert_eq!(plot.points, 48);
+        let ((min_x, min), (max_x, max)) = (plot.min.unwrap(), plot.max.unwrap());
+        assert!((min + 0.99786).abs() < 1e-5 && (min_x - 4.778).abs() < 1e-3);
+        assert!((max - 0.99786).abs() < 1e-5 && (max_x - 1.505).abs() < 1e-3);
+        assert_eq!((plot.non_finite, plot.first_non_finite), (0, None));
+    }
+
+    #[test]
+    fn constant_without_zero_axis() {
+        let plot = render(|_| 5.0, 0.0, 1.0, 10, 3);
+        assert_eq!(plot.lines.len(), 4);
+        assert!(plot.lines[0].starts_with("6 ┤"));
+        assert!(plot.lines[2].starts_with("4 ┤"));
+        assert!(!plot.lines.iter().any(|line| line.contains('─')));
+        assert_eq!(plot.lines[1], "  │⠤⠤⠤⠤⠤⠤⠤⠤⠤⠤");
+    }
+
+    #[test]
+    fn non_finite_points_are_counted() {
+        let plot = render(|x: f64| 1.0 / x.ln(), 0.5, 1.5, 20, 4);
+        assert_eq!(plot.points, 40);
+        assert_eq!(plot.non_finite, 0);
+        let plot = render(|x: f64| if x < 0.25 { f64::NAN } else { x }, 0.0, 1.0, 10, 4);
+        assert_eq!(plot.non_finite, 5);
+        assert_eq!(plot.first_non_finite, Some(0.025));
+        assert_eq!(plot.min.map(|(x, _)| x), Some(0.275));
+        let plot = render(|_| f64::INFINITY, 0.0, 1.0, 10, 4);
+        assert_eq!((plot.min, plot.max, plot.non_finite), (None, None, 20));
+    }
+
+    #[test]
+    fn braille_dots() {
+        let all: u32 = (0..DOTS_X).flat_map(|dx| (0..DOTS_Y).map(move |dy| braille_bit(dx, dy))).sum();
+        assert_eq!(all, 0xff);
+        assert_eq!(char::from_u32(0x2800 + braille_bit(0, 0) + braille_bit(1, 3)), Some('⢁'));
+    }
+
+    /// Столбцы x, f(x), F(x) файла `--dump-plot`.
+    fn read_dump(path: &Path) -> Vec<[f64; 3]> {
+        std::fs::read_to_string(path).unwrap().lines().filter(|line| !line.starts_with('#')).map(|line| {
+            let fields: Vec<f64> = line.split_whitespace().map(|field| field.parse().unwrap()).collect();
+            [fields[0], fields[1], fields[2]]
+        }).collect()
+    }
+
+    #[test]
+    fn dump_round_trip() {
+        let dir = std::env::temp_dir().join(format!("kryl_04_dump_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("atan.dat");
+        let function = Function::default();
+        for method in [Method::Midpoint, Method::Simpson] {
+            dump(path.to_str().unwrap(), &function, method, 0.0, 1.0, 99, Some(2)).unwrap();
+            let rows = read_dump(&path);
+            // Строка — на каждые `granularity()` отрезков
+            let samples = (rows.len() as u64 - 1) * method.rule::<f64>().granularity();
+            let header = std::fs::read_to_string(&path).unwrap().lines().next().unwrap().to_string();
+            assert!(header.ends_with(&format!(": {}", samples)), "{}", header);
+            let options = IntegrationOptions { samples, method, ..Default::default() };
+            let integral = kryl_04::calculate_integral_with_options(Function::default(), 0.0, 1.0, &options).unwrap();
+            // Сетка начинается и кончается ровно на границах
+            assert_eq!(rows[0], [0.0, 0.0, 0.0]);
+            assert_eq!(rows[rows.len() - 1][0], 1.0);
+            assert!((rows[rows.len() - 1][2] - integral.value).abs() < 1e-15, "{}", method);
+            assert!(rows.windows(2).all(|pair| pair[0][0] < pair[1][0] && pair[0][2] < pair[1][2]));
+            assert!(rows.iter().all(|row| row[1] == function.eval(row[0])));
+        }
+        let script = std::fs::read_to_string(dir.join("atan.gp")).unwrap();
+        assert!(script.contains("set title \"atan(x)/(x^4+1), [0, 1], simpson\""), "{}", script);
+        assert!(script.contains(&format!("plot {} using 1:2", gnuplot_string(path.to_str().unwrap()))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dump_errors() {
+        let function = Function::default();
+        assert!(matches!(
+            dump("plot.gp", &function, Method::Midpoint, 0.0, 1.0, 10, None),
+            Err(IntegralCalcError::OutputFile { .. })
+        ));
+        assert!(matches!(
+            dump("/nonexistent/kryl_04/plot.dat", &function, Method::Midpoint, 0.0, 1.0, 10, None),
+            Err(IntegralCalcError::OutputFile { .. })
+        ));
+        assert_eq!(gnuplot_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Через сколько вычислений функции поток по умолчанию сообщает о ходе расчёта.
+pub const DEFAULT_PROGRESS_GRANULARITY: u64 = 1 << 16;
+
+/// Обработчик хода расчёта: вызывается с (выполнено отсчётов, всего отсчётов).
+///
+/// Рабочие потоки пополняют общий атомарный счётчик и вызывают обработчик
+/// без блокировки, поэтому вызовы из разных потоков могут прийти не по
+/// порядку: актуально наибольшее из переданных чисел. После возврата из
+/// функции расчёта обработчик больше не вызывается.
+#[derive(Clone)]
+pub struct ProgressHook {
+    callback: Arc<dyn Fn(u64, u64) + Send + Sync>,
+    granularity: u64,
+}
+
+impl ProgressHook {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Arc::new(callback),
+            granularity: DEFAULT_PROGRESS_GRANULARITY,
+        }
+    }
+
+    /// Через сколько вычислений каждый поток сообщает о ходе расчёта.
+    pub fn granularity(mut self, granularity: u64) -> Self {
+        self.granularity = granularity.max(1);
+        self
+    }
+}
+
+impl Debug for ProgressHook {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ProgressHook")
+            .field("granularity", &self.granularity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ProgressHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.callback, &other.callback) && self.granularity == other.granularity
+    }
+}
+
+/// Общий для рабочих потоков счётчик выполненных отсчётов.
+pub(crate) struct ProgressReporter<'a> {
+    hook: &'a ProgressHook,
+    done: AtomicU64,
+    total: u64,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub(crate) fn new(hook: &'a ProgressHook, total: u64) -> Self {
+        Self {
+            hook,
+            done: AtomicU64::new(0),
+            total,
+        }
+    }
+
+    pub(crate) fn granularity(&self) -> u64 {
+        self.hook.granularity
+    }
+
+    pub(crate) fn report(&self, count: u64) {
+        let done = self.done.fetch_add(count, Ordering::Relaxed) + count;
+        (self.hook.callback)(done, self.total);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+    use crate::integrate::calculate_integral_async;
+    use crate::integrator::Integrator;
+    use crate::options::ExecutionMode;
+
+    /// Обработчик, запоминающий наибольшее число выполненных отсчётов и
+    /// число вызовов.
+    fn recording_hook() -> (ProgressHook, Arc<AtomicU64>, Arc<AtomicU64>) {
+        let (done, calls) = (Arc::new(AtomicU64::new(0)), Arc::new(AtomicU64::new(0)));
+        let (hook_done, hook_calls) = (done.clone(), calls.clone());
+        let hook = ProgressHook::new(move |completed, total| {
+            assert!(completed <= total, "{} > {}", completed, total);
+            hook_done.fetch_max(completed, Ordering::Relaxed);
+            hook_calls.fetch_add(1, Ordering::Relaxed);
+        });
+        (hook, done, calls)
+    }
+
+    #[test]
+    fn counter_reaches_total() {
+        let samples = 1_000_003;
+        let (hook, done, calls) = recording_hook();
+        calculate_integral_async(AtanOverQuartic, 0.0f64, 1.0, samples, Some(8), None, Some(hook.granularity(10_000))).unwrap();
+        assert_eq!(done.load(Ordering::Relaxed), samples);
+        assert!(calls.load(Ordering::Relaxed) >= samples / 10_000, "{}", calls.load(Ordering::Relaxed));
+
+        let (hook, done, _) = recording_hook();
+        Integrator::builder()
+            .bounds(0.0f64, 1.0)
+            .samples(samples)
+            .execution(ExecutionMode::Sequential)
+            .progress(hook)
+            .build()
+            .unwrap()
+            .run(AtanOverQuartic)
+            .unwrap();
+        assert_eq!(done.load(Ordering::Relaxed), samples);
+    }
+
+    #[test]
+    fn granularity_is_at_least_one() {
+        let hook = ProgressHook::new(|_, _| {});
+        assert_eq!(hook.clone().granularity(0).granularity, 1);
+        assert_eq!(hook.granularity, DEFAULT_PROGRESS_GRANULARITY);
+        assert_eq!(hook, hook.clone());
+        assert_ne!(hook, ProgressHook::new(|_, _| {}));
+    }
+}
@@ -0,0 +1,128 @@
+use std::thread::ScopedJoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CANCEL_CHECK_INTERVAL;
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::integrator::Integrator;
+use crate::options::IntegrationOptions;
+use crate::random::radical_inverse;
+use crate::result::IntegrationResult;
+
+/// Основание последовательности Холтона для одномерного случая.
+const HALTON_BASE: u64 = 2;
+
+/// Квазислучайный метод Монте-Карло по последовательности Холтона: точки
+/// `skip`, `skip + 1`, …, `skip + samples - 1`.
+pub fn quasi_monte_carlo<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    skip: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    quasi_monte_carlo_with_options(f, lower_bound, upper_bound, skip, &options)
+}
+
+/// То же, что `quasi_monte_carlo`, с заданными параметрами расчёта. Потоки
+/// берут номера точек через один (поток t из T — номера t, t + T, …),
+/// так что вместе они проходят ровно первые `samples` точек при любом
+/// числе потоков.
+pub fn quasi_monte_carlo_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    skip: u64,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let integrator = Integrator::builder().bounds(lower_bound, upper_bound).options(options).build()?;
+    let start = Instant::now();
+    let samples = integrator.samples();
+    let threads = if integrator.is_threaded() { integrator.threads() } else { 1 };
+    let width = upper_bound - lower_bound;
+    let cancel = options.cancel.as_ref();
+    let leapfrog = |first: u64| -> Result<T, IntegralCalcError> {
+        let mut sum = T::ZERO;
+        let mut index = first;
+        let mut count = 0;
+        while index < samples {
+            sum += f.eval(lower_bound + width * T::from_f64(radical_inverse(skip + index, HALTON_BASE)));
+            index += threads as u64;
+            count += 1;
+            if count % CANCEL_CHECK_INTERVAL == 0 && cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+                return Err(IntegralCalcError::Cancelled);
+            }
+        }
+        Ok(sum)
+    };
+    let sums = if threads == 1 {
+        vec![leapfrog(0)?]
+    } else {
+        std::thread::scope(|scope| {
+            let handles: Vec<ScopedJoinHandle<_>> = (0..threads as u64)
+                .map(|first| scope.spawn(move || leapfrog(first)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(Err(IntegralCalcError::WorkerPanicked)))
+                .collect::<Result<Vec<_>, _>>()
+        })?
+    };
+    let sum = sums.into_iter().fold(T::ZERO, |total, sum| total + sum);
+    Ok(IntegrationResult {
+        value: sum * width / T::from_u64(samples),
+        step: width / T::from_u64(samples),
+        intervals: samples,
+        samples,
+        threads,
+        elapsed: start.elapsed(),
+        remainder_bound: None,
+        remainder_elapsed: Duration::ZERO,
+        error_estimate: None,
+        singularity: None,
+        workers: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+    use crate::monte_carlo::monte_carlo;
+    use crate::options::ExecutionMode;
+
+    /// ∫₀¹ atan(x)/(x⁴+1) dx.
+    const ATAN_OVER_QUARTIC: f64 = 0.349446289808078;
+
+    #[test]
+    fn converges_faster_than_monte_carlo() {
+        // Внутри одной порции (до 4096 точек) Монте-Карло не расслоен
+        let samples = 4000;
+        let squares: f64 = (1..=16)
+            .map(|seed| monte_carlo(AtanOverQuartic, 0.0f64, 1.0, samples, seed).unwrap().value - ATAN_OVER_QUARTIC)
+            .map(|error| error * error)
+            .sum();
+        let random = (squares / 16.0).sqrt();
+        let quasi = (quasi_monte_carlo(AtanOverQuartic, 0.0f64, 1.0, samples, 1).unwrap().value - ATAN_OVER_QUARTIC).abs();
+        assert!(quasi * 10.0 < random, "{} {}", quasi, random);
+    }
+
+    #[test]
+    fn threads_take_the_same_points() {
+        let options = |execution| IntegrationOptions { samples: 20_000, threads: Some(3), execution, ..Default::default() };
+        let sequential = quasi_monte_carlo_with_options(AtanOverQuartic, 0.0f64, 1.0, 1, &options(ExecutionMode::Sequential)).unwrap();
+        let threaded = quasi_monte_carlo_with_options(AtanOverQuartic, 0.0f64, 1.0, 1, &options(ExecutionMode::Threaded)).unwrap();
+        assert_eq!((sequential.threads, threaded.threads), (1, 3));
+        assert!((sequential.value - threaded.value).abs() < 1e-14);
+    }
+}
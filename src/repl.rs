@@ -0,0 +1,413 @@
+use std::io::ErrorKind;
+use std::str::FromStr;
+
+use kryl_04::{
+    calculate_improper_integral_with_options,
+    default_threads,
+    tr,
+    Expression,
+    Integrand,
+    IntegrationOptions,
+    IntegrationResult,
+    Message,
+    Method,
+};
+
+use super::exit_code::{exit_with, ExitReason};
+use super::history::{self, Entry, History, DEFAULT_HISTORY_SIZE, MAX_HISTORY_SIZE};
+use super::plot;
+use super::report::{self, ProgressLine};
+use super::{
+    exit_on_error,
+    flag_value,
+    get_line,
+    parse_bound,
+    parse_samples,
+    parse_threads,
+    plot_height,
+    report_result,
+    report_samples_error,
+    samples_error_text,
+    select_seed,
+    select_threads,
+    Function,
+};
+
+/// Число отсчётов по умолчанию, если не задан `--samples`.
+const DEFAULT_SAMPLES: u64 = 100_000;
+
+/// Команда режима `--repl`.
+#[derive(Debug)]
+enum Command {
+    /// `int a b [n]`: расчёт на [a, b], при `n` — с этим числом отсчётов
+    /// (только для этой команды).
+    Integrate { lower_bound: f64, upper_bound: f64, samples: Option<u64> },
+    /// `plot a b`: график f на [a, b].
+    Plot { lower_bound: f64, upper_bound: f64 },
+    /// `set имя значение`.
+    Set(Setting),
+    /// `last`: повторить итог последнего расчёта.
+    Last,
+    /// `history`: список записей истории.
+    History,
+    /// `recall n`: запись истории с номером `n`.
+    Recall(u64),
+    /// `show`: текущие настройки.
+    Show,
+    Help,
+    Quit,
+}
+
+/// Настройка, которую меняет `set`.
+#[derive(Debug)]
+enum Setting {
+    Samples(u64),
+    Method(Method),
+    Function(Function),
+    Threads(Option<usize>),
+    HistorySize(usize),
+    Persist(bool),
+}
+
+/// Настройки, общие для команд сеанса.
+struct Session {
+    samples: u64,
+    method: Method,
+    function: Function,
+    threads: Option<usize>,
+    last: Option<(Function, Method, IntegrationResult)>,
+    history: History,
+}
+
+impl Session {
+    /// Начальные настройки из флагов и файла настроек.
+    fn from_flags() -> Self {
+        let samples = flag_value("--samples").map_or(DEFAULT_SAMPLES, |samples| {
+            parse_samples(&samples).unwrap_or_else(|e| exit_with(report_samples_error(&e)))
+        });
+        let method = flag_value("--method")
+            .map_or(Ok(Method::default()), |method| Method::from_str(&method))
+            .unwrap_or_else(|e| exit_on_error(&e));
+        let function = match (flag_value("--expr"), flag_value("--function")) {
+            (Some(source), _) => Expression::parse(&source).map(Function::Expression),
+            (None, Some(name)) => Function::from_str(&name),
+            (None, None) => Ok(Function::default()),
+        }
+        .unwrap_or_else(|e| exit_on_error(&e));
+        let history_size = flag_value("--history-size").map_or(DEFAULT_HISTORY_SIZE, |size| {
+            parse_history_size(&size).unwrap_or_else(|message| {
+                report::error(message);
+                exit_with(ExitReason::IncorrectArguments);
+            })
+        });
+        Self {
+            samples,
+            method,
+            function,
+            threads: select_threads(false),
+            last: None,
+            history: History::new(history_size),
+        }
+    }
+
+    fn execute(&mut self, command: Command) {
+        match command {
+            Command::Integrate { lower_bound, upper_bound, samples } => {
+                let samples = samples.unwrap_or(self.samples);
+                let progress = ProgressLine::start(Message::PhaseMain, samples);
+                let options = IntegrationOptions {
+                    samples,
+                    method: self.method,
+                    threads: self.threads,
+                    progress: progress.hook(),
+                    seed: select_seed(self.method == Method::MonteCarlo),
+                    ..Default::default()
+                };
+                let result = calculate_improper_integral_with_options(self.function.clone(), lower_bound, upper_bound, &options);
+                progress.finish();
+                match result {
+                    Ok(result) => {
+                        report_result(&self.function, self.method, &result);
+                        let entry = Entry {
+                            number: 0,
+                            function: self.function.name().to_string(),
+                            method: self.method.to_string(),
+                            lower_bound,
+                            upper_bound,
+                            intervals: result.intervals,
+                            value: result.value,
+                            error_estimate: result.error_estimate,
+                        };
+                        match self.history.push(entry) {
+                            Ok(number) => report!("{}: {}", tr(Message::HistoryEntry), number),
+                            Err(e) => report::error(format_args!("{}: {}", tr(Message::HistoryFileError), e)),
+                        }
+                        self.last = Some((self.function.clone(), self.method, result));
+                    }
+                    Err(e) => report::error(e),
+                }
+            }
+            Command::Plot { lower_bound, upper_bound } => {
+                let function = &self.function;
+                plot::show(|x| function.eval(x), lower_bound, upper_bound, plot_height());
+            }
+            Command::Set(Setting::Samples(samples)) => self.samples = samples,
+            Command::Set(Setting::Method(method)) => self.method = method,
+            Command::Set(Setting::Function(function)) => self.function = function,
+            Command::Set(Setting::Threads(threads)) => self.threads = threads,
+            Command::Set(Setting::HistorySize(size)) => self.history.resize(size),
+            Command::Set(Setting::Persist(false)) => self.history.forget(),
+            Command::Set(Setting::Persist(true)) if self.history.persistent() => {}
+            Command::Set(Setting::Persist(true)) => match history::default_path() {
+                Some(path) => match self.history.persist(path) {
+                    Ok((loaded, skipped)) => {
+                        for line in skipped {
+                            report::warning(format_args!("{}: {}", line, tr(Message::HistorySkippedLine)));
+                        }
+                        report!("{}: {}", tr(Message::HistoryLoaded), loaded);
+                    }
+                    Err(e) => report::error(format_args!("{}: {}", tr(Message::HistoryFileError), e)),
+                },
+                None => report::error(tr(Message::HistoryFileError)),
+            },
+            Command::Last => match &self.last {
+                Some((function, method, result)) => report_result(function, *method, result),
+                None => report::error(tr(Message::ReplNoResult)),
+            },
+            Command::History => {
+                if self.history.entries().next().is_none() {
+                    report!("{}", tr(Message::HistoryEmpty));
+                }
+                for entry in self.history.entries() {
+                    let error = entry.error_estimate.map_or_else(String::new, |error| format!(" ± {}", report::number(error)));
+                    report!("{:>4}: {}, [{}, {}], {}, {}: {} = {}{}",
+                            entry.number, entry.function,
+                            report::number(entry.lower_bound), report::number(entry.upper_bound),
+                            entry.method, tr(Message::Intervals), entry.intervals,
+                            report::number(entry.value), error);
+                }
+            }
+            Command::Recall(number) => match self.history.get(number) {
+                Some(entry) => {
+                    report!("{}: {}", tr(Message::Integrand), entry.function);
+                    report!("{}: {}", tr(Message::Method), entry.method);
+                    report!("{}: [{}, {}], {}: {}",
+                            tr(Message::Bounds), report::number(entry.lower_bound), report::number(entry.upper_bound),
+                            tr(Message::Intervals), entry.intervals);
+                    report::value(Message::ApproximateValue, entry.value);
+                    if let Some(error_estimate) = entry.error_estimate {
+                        report!("{}: {}", tr(Message::ErrorEstimate), report::number(error_estimate));
+                    }
+                }
+                None => report::error(format_args!("{}: {}", tr(Message::HistoryNoEntry), number)),
+            },
+            Command::Show => {
+                report!("{}: {}", tr(Message::Integrand), self.function.name());
+                report!("{}: {}", tr(Message::Method), self.method);
+                report!("{}: {}, {}: {}",
+                        tr(Message::Intervals), self.samples,
+                        tr(Message::Threads), self.threads.unwrap_or_else(default_threads));
+                report!("{}: {}, {}: {}",
+                        tr(Message::HistorySize), self.history.capacity(),
+                        tr(Message::HistoryPersist), self.history.persistent());
+            }
+            Command::Help => report!("{}", tr(Message::ReplHelp)),
+            Command::Quit => {}
+        }
+    }
+}
+
+fn parse_history_size(line: &str) -> Result<usize, String> {
+    line.trim().parse().ok()
+        .filter(|size| (1..=MAX_HISTORY_SIZE).contains(size))
+        .ok_or_else(|| tr(Message::ParseHistorySizeError).to_string())
+}
+
+/// Граница команды `int`: `$N` заменяется значением записи истории `N`
+/// (внутри выражения — в скобках, например `$3/2`).
+fn parse_bound_with_history(token: &str, history: &History) -> Result<f64, String> {
+    let mut text = String::new();
+    let mut rest = token;
+    while let Some(start) = rest.find('$') {
+        text.push_str(&rest[..start]);
+        let digits = rest[start + 1..].find(|c: char| !c.is_ascii_digit()).map_or(rest.len(), |end| start + 1 + end);
+        let number = rest[start + 1..digits].parse().map_err(|_| tr(Message::ReplUsageInt).to_string())?;
+        let entry = history.get(number).ok_or_else(|| format!("{}: {}", tr(Message::HistoryNoEntry), number))?;
+        if start == 0 && digits == token.len() {
+            return Ok(entry.value);
+        }
+        text.push_str(&format!("({:?})", entry.value));
+        rest = &rest[digits..];
+    }
+    text.push_str(rest);
+    parse_bound(&text).map_err(|e| e.to_string())
+}
+
+/// Разбор строки сеанса; ошибка — готовый к выводу текст. Пустая строка —
+/// `None`.
+fn parse_command(line: &str, history: &History) -> Result<Option<Command>, String> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let command = match name.to_lowercase().as_str() {
+        "" => return Ok(None),
+        "int" | "integrate" => {
+            let usage = || tr(Message::ReplUsageInt).to_string();
+            let tokens: Vec<&str> = rest.split_whitespace().collect();
+            let (lower_bound, upper_bound, samples) = match tokens.as_slice() {
+                [lower_bound, upper_bound] => (lower_bound, upper_bound, None),
+                [lower_bound, upper_bound, samples] => (lower_bound, upper_bound, Some(samples)),
+                _ => return Err(usage()),
+            };
+            Command::Integrate {
+                lower_bound: parse_bound_with_history(lower_bound, history)?,
+                upper_bound: parse_bound_with_history(upper_bound, history)?,
+                samples: samples.map(|samples| parse_samples(samples).map_err(samples_error_text)).transpose()?,
+            }
+        }
+        "plot" => match rest.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [lower_bound, upper_bound] => Command::Plot {
+                lower_bound: parse_bound_with_history(lower_bound, history)?,
+                upper_bound: parse_bound_with_history(upper_bound, history)?,
+            },
+            _ => return Err(tr(Message::ReplUsagePlot).to_string()),
+        },
+        "set" => {
+            let (setting, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(tr(Message::ReplUsageSet).to_string());
+            }
+            Command::Set(match setting.to_lowercase().as_str() {
+                "samples" => Setting::Samples(parse_samples(value).map_err(samples_error_text)?),
+                "method" => Setting::Method(Method::from_str(value).map_err(|e| e.to_string())?),
+                "expr" => Setting::Function(Expression::parse(value).map(Function::Expression).map_err(|e| e.to_string())?),
+                "function" => Setting::Function(Function::from_str(value).map_err(|e| e.to_string())?),
+                "threads" => Setting::Threads(parse_threads(value).map_err(|()| tr(Message::ParseThreadsError).to_string())?),
+                "history" => Setting::HistorySize(parse_history_size(value)?),
+                "persist" => Setting::Persist(match value.to_lowercase().as_str() {
+                    "on" | "true" | "yes" => true,
+                    "off" | "false" | "no" => false,
+                    _ => return Err(tr(Message::ReplUsageSet).to_string()),
+                }),
+                _ => return Err(tr(Message::ReplUsageSet).to_string()),
+            })
+        }
+        "last" => Command::Last,
+        "history" => Command::History,
+        "recall" => Command::Recall(rest.parse().map_err(|_| tr(Message::ReplUsageRecall).to_string())?),
+        "show" => Command::Show,
+        "help" | "?" => Command::Help,
+        "quit" | "exit" | "q" => Command::Quit,
+        _ => return Err(format!("{}: {}", tr(Message::ReplUnknownCommand), name)),
+    };
+    Ok(Some(command))
+}
+
+/// Режим `--repl`: команды читаются построчно до `quit` или конца ввода;
+/// ошибка в команде сеанс не прерывает.
+pub fn run() {
+    let mut session = Session::from_flags();
+    loop {
+        report::prompt(Message::ReplPrompt);
+        let line = match get_line() {
+            Ok(line) => line,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                // Перевод строки после приглашения, чтобы оболочка начала с новой
+                report!("");
+                return;
+            }
+            Err(e) => {
+                report::error(e);
+                return;
+            }
+        };
+        match parse_command(&line, &session.history) {
+            Ok(Some(Command::Quit)) => return,
+            Ok(Some(command)) => session.execute(command),
+            Ok(None) => {}
+            Err(error) => report::error(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Option<Command>, String> {
+        parse_command(line, &History::new(DEFAULT_HISTORY_SIZE))
+    }
+
+    #[test]
+    fn integrate_commands() {
+        let Ok(Some(Command::Integrate { lower_bound, upper_bound, samples })) = parse("  INT -1 pi/2 1e6 ") else {
+            panic!();
+        };
+        assert_eq!((lower_bound, upper_bound, samples), (-1.0, std::f64::consts::FRAC_PI_2, Some(1_000_000)));
+        assert!(matches!(parse("integrate 0 1"), Ok(Some(Command::Integrate { samples: None, .. }))));
+        for line in ["int", "int 0", "int 0 1 2 3", "int 0 x", "int 0 1 ten"] {
+            assert!(parse(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn settings() {
+        assert!(matches!(parse("set samples 1e3"), Ok(Some(Command::Set(Setting::Samples(1000))))));
+        assert!(matches!(parse("set method Simpson"), Ok(Some(Command::Set(Setting::Method(Method::Simpson))))));
+        assert!(matches!(parse("set expr sin(x) / x"), Ok(Some(Command::Set(Setting::Function(Function::Expression(_)))))));
+        assert!(matches!(parse("set function atan"), Ok(Some(Command::Set(Setting::Function(_))))));
+        assert!(matches!(parse("set history 5"), Ok(Some(Command::Set(Setting::HistorySize(5))))));
+        assert!(matches!(parse("set persist on"), Ok(Some(Command::Set(Setting::Persist(true))))));
+        assert!(matches!(parse("set persist no"), Ok(Some(Command::Set(Setting::Persist(false))))));
+        for line in ["set", "set samples", "set method simpsn", "set expr sin(", "set colour red",
+                     "set history 0", "set persist maybe", "set threads -1"] {
+            assert!(parse(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn other_commands() {
+        assert!(matches!(parse(""), Ok(None)));
+        assert!(matches!(parse("   "), Ok(None)));
+        assert!(matches!(parse("last"), Ok(Some(Command::Last))));
+        assert!(matches!(parse("history"), Ok(Some(Command::History))));
+        assert!(matches!(parse("recall 3"), Ok(Some(Command::Recall(3)))));
+        assert!(matches!(parse("show"), Ok(Some(Command::Show))));
+        assert!(matches!(parse("?"), Ok(Some(Command::Help))));
+        for line in ["quit", "exit", "q", "QUIT"] {
+            assert!(matches!(parse(line), Ok(Some(Command::Quit))), "{}", line);
+        }
+        assert!(matches!(parse("plot 0 1"), Ok(Some(Command::Plot { .. }))));
+        assert!(parse("plot 0").is_err());
+        assert!(parse("recall x").is_err());
+        assert!(parse("frobnicate").is_err());
+    }
+
+    #[test]
+    fn history_references() {
+        let mut history = History::new(DEFAULT_HISTORY_SIZE);
+        for value in [0.5, 2.0] {
+            let entry = Entry {
+                number: 0,
+                function: String::new(),
+                method: String::new(),
+                lower_bound: 0.0,
+                upper_bound: 1.0,
+                intervals: 1,
+                value,
+                error_estimate: None,
+            };
+            history.push(entry).unwrap();
+        }
+        assert_eq!(parse_bound_with_history("$2", &history), Ok(2.0));
+        assert_eq!(parse_bound_with_history("$1*$2+1", &history), Ok(2.0));
+        assert_eq!(parse_bound_with_history("-$1", &history), Ok(-0.5));
+        assert!(parse_bound_with_history("$3", &history).is_err());
+        assert!(parse_bound_with_history("$", &history).is_err());
+        let Ok(Some(Command::Integrate { lower_bound, upper_bound, samples: None })) = parse_command("int $1 $2", &history) else {
+            panic!();
+        };
+        assert_eq!((lower_bound, upper_bound), (0.5, 2.0));
+    }
+}
@@ -0,0 +1,356 @@
+use std::fmt::{Arguments, Display};
+use std::io::{stderr, stdout, ErrorKind, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use kryl_04::{tr, IntegrationResult, Message, NumberFormat, ProgressHook, ASYNC_THRESHOLD_SAMPLES_COUNT};
+
+use super::exit_code::{exit_with, ExitReason};
+
+/// `print!` через `write_stdout`.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::report::write_stdout(format_args!($($arg)*))
+    };
+}
+
+/// `println!` через `write_stdout`.
+macro_rules! outln {
+    () => {
+        $crate::report::write_stdout(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::report::write_stdout(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+/// `println!` через `commentary`.
+macro_rules! report {
+    ($($arg:tt)*) => {
+        $crate::report::commentary(format_args!($($arg)*))
+    };
+}
+
+/// `println!` через `detail`.
+macro_rules! detail {
+    ($($arg:tt)*) => {
+        $crate::report::detail(format_args!($($arg)*))
+    };
+}
+
+/// Подробность вывода.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Только значение интеграла; вопросы и пояснения не выводятся.
+    Quiet,
+    #[default]
+    Normal,
+    /// `-v`: сводка каждого прохода в stderr.
+    Verbose,
+    /// `-vv`: ещё и участки рабочих потоков.
+    VeryVerbose,
+}
+
+static CURRENT_VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+impl Verbosity {
+    /// Подробность, выбранная через `Verbosity::set_current` (по умолчанию обычная).
+    pub fn current() -> Self {
+        match CURRENT_VERBOSITY.load(Ordering::Relaxed) {
+            value if value == Verbosity::Quiet as u8 => Verbosity::Quiet,
+            value if value == Verbosity::Verbose as u8 => Verbosity::Verbose,
+            value if value == Verbosity::VeryVerbose as u8 => Verbosity::VeryVerbose,
+            _ => Verbosity::Normal,
+        }
+    }
+
+    pub fn set_current(verbosity: Self) {
+        CURRENT_VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    }
+
+    pub fn quiet() -> bool {
+        Self::current() == Verbosity::Quiet
+    }
+}
+
+/// Оформление текста в терминале.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Итоговое значение — полужирным.
+    Value,
+    /// Ошибки и невыполненная проверка — красным.
+    Bad,
+    /// Выполненная проверка — зелёным.
+    Good,
+    /// Предупреждения — жёлтым.
+    Warning,
+    /// Вспомогательные величины — бледным.
+    Dim,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Value => "1",
+            Style::Bad => "31",
+            Style::Good => "32",
+            Style::Warning => "33",
+            Style::Dim => "2",
+        }
+    }
+}
+
+static STDOUT_COLOR: AtomicBool = AtomicBool::new(false);
+static STDERR_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Включает цвет для тех из stdout и stderr, что выводятся на терминал; при
+/// `enabled == false` (`--no-color`, `NO_COLOR`) и до вызова цвета нет.
+pub fn set_color(enabled: bool) {
+    STDOUT_COLOR.store(enabled && stdout().is_terminal(), Ordering::Relaxed);
+    STDERR_COLOR.store(enabled && stderr().is_terminal(), Ordering::Relaxed);
+}
+
+/// `text` в стиле `style`, если для потока включён цвет (`color`).
+fn paint(style: Style, text: impl Display, color: &AtomicBool) -> String {
+    if color.load(Ordering::Relaxed) {
+        format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Ошибка в stderr, красным на терминале.
+pub fn error(error: impl Display) {
+    eprintln!("{}", paint(Style::Bad, error, &STDERR_COLOR));
+}
+
+/// Предупреждение в stderr, жёлтым на терминале.
+pub fn warning(warning: impl Display) {
+    eprintln!("{}", paint(Style::Warning, warning, &STDERR_COLOR));
+}
+
+static NUMBER_FORMAT: OnceLock<NumberFormat> = OnceLock::new();
+
+/// Задаёт формат чисел для всего вывода; действует только первый вызов.
+pub fn set_number_format(format: NumberFormat) {
+    let _ = NUMBER_FORMAT.set(format);
+}
+
+/// Число в формате, заданном `set_number_format` (по умолчанию
+/// `NumberFormat::default()`).
+pub fn number(value: f64) -> String {
+    NUMBER_FORMAT.get_or_init(NumberFormat::default).format(value)
+}
+
+/// Вывод в stdout через заблокированный поток. Если читатель закрыл канал
+/// (`kryl_04 bench | head -1`), программа молча завершается с
+/// `ExitReason::BrokenPipe` вместо паники `println!`.
+pub fn write_stdout(args: Arguments) {
+    let mut stdout = stdout().lock();
+    if let Err(error) = stdout.write_fmt(args).and_then(|()| stdout.flush()) {
+        if error.kind() == ErrorKind::BrokenPipe {
+            exit_with(ExitReason::BrokenPipe);
+        }
+        panic!("failed printing to stdout: {}", error);
+    }
+}
+
+/// Пояснение к расчёту (заголовок, параметры сетки): на стандартный вывод,
+/// в тихом режиме не выводится.
+pub fn commentary(args: Arguments) {
+    if !Verbosity::quiet() {
+        outln!("{}", paint(Style::Dim, args, &STDOUT_COLOR));
+    }
+}
+
+/// Явно запрошенные сведения и предупреждения: в тихом режиме пишутся в
+/// stderr, чтобы на стандартном выводе осталось только значение.
+pub fn detail(args: Arguments) {
+    detail_styled(None, args);
+}
+
+/// Итог проверки «`label`: да/нет», зелёным или красным на терминале;
+/// выводится как `detail`.
+pub fn verdict(label: Message, passed: bool) {
+    let style = if passed { Style::Good } else { Style::Bad };
+    detail_styled(Some(style), format_args!("{}: {}", tr(label), passed));
+}
+
+fn detail_styled(style: Option<Style>, args: Arguments) {
+    let (quiet, color) = if Verbosity::quiet() { (true, &STDERR_COLOR) } else { (false, &STDOUT_COLOR) };
+    let text = match style {
+        Some(style) => paint(style, args, color),
+        None => args.to_string(),
+    };
+    if quiet {
+        eprintln!("{}", text);
+    } else {
+        outln!("{}", text);
+    }
+}
+
+/// Значение интеграла: «`label`: значение», в тихом режиме — одно число.
+pub fn value(label: Message, value: f64) {
+    if Verbosity::quiet() {
+        outln!("{}", number(value));
+    } else {
+        outln!("{}: {}", tr(label), paint(Style::Value, number(value), &STDOUT_COLOR));
+    }
+}
+
+static PROMPTS: AtomicBool = AtomicBool::new(true);
+
+/// Выводить ли вопросы: без терминала (`--no-interactive`) ответы читаются
+/// молча.
+pub fn set_prompts(enabled: bool) {
+    PROMPTS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn prompts() -> bool {
+    PROMPTS.load(Ordering::Relaxed)
+}
+
+/// Вопрос без перевода строки; в тихом режиме и без терминала не выводится.
+pub fn prompt(message: Message) {
+    if !Verbosity::quiet() && prompts() {
+        out!("{}", tr(message));
+    }
+}
+
+/// Период перерисовки строки хода расчёта.
+const PROGRESS_REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Строка хода расчёта в stderr: обработчик хода только обновляет атомарный
+/// счётчик, а строку 10 раз в секунду перерисовывает отдельный поток. Для
+/// коротких расчётов, в тихом режиме и когда stderr — не терминал, строки
+/// нет. Строка стирается в `finish` (или при удалении значения).
+pub struct ProgressLine {
+    done: Arc<AtomicU64>,
+    monitor: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl ProgressLine {
+    /// Строка этапа `phase` расчёта на `total` отсчётов.
+    pub fn start(phase: Message, total: u64) -> Self {
+        let done = Arc::new(AtomicU64::new(0));
+        let enabled = total > ASYNC_THRESHOLD_SAMPLES_COUNT && !Verbosity::quiet() && stderr().is_terminal();
+        let monitor = enabled.then(|| {
+            let stop = Arc::new(AtomicBool::new(false));
+            let (done, stopped) = (done.clone(), stop.clone());
+            let handle = std::thread::spawn(move || {
+                let start = Instant::now();
+                while !stopped.load(Ordering::Relaxed) {
+                    draw_progress(phase, done.load(Ordering::Relaxed), total, start.elapsed());
+                    std::thread::park_timeout(PROGRESS_REDRAW_INTERVAL);
+                }
+                eprint!("\r\x1b[2K");
+            });
+            (stop, handle)
+        });
+        Self { done, monitor }
+    }
+
+    /// Обработчик хода расчёта для `IntegrationOptions::progress`; `None`,
+    /// если строки нет.
+    pub fn hook(&self) -> Option<ProgressHook> {
+        self.monitor.as_ref().map(|_| {
+            let done = self.done.clone();
+            ProgressHook::new(move |value, _| {
+                done.fetch_max(value, Ordering::Relaxed);
+            })
+        })
+    }
+
+    /// Останавливает перерисовку и стирает строку.
+    pub fn finish(self) {}
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        if let Some((stop, handle)) = self.monitor.take() {
+            stop.store(true, Ordering::Relaxed);
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+/// «[этап] Выполнено: 42%, осталось ≈ 3 с» поверх прежней строки.
+fn draw_progress(phase: Message, done: u64, total: u64, elapsed: Duration) {
+    let fraction = (done as f64 / total as f64).min(1.0);
+    let mut line = format!("\r\x1b[2K[{}] {}: {:.0}%", tr(phase), tr(Message::Progress), fraction * 100.0);
+    if fraction > 0.0 {
+        let remaining = elapsed.as_secs_f64() * (1.0 - fraction) / fraction;
+        line += &format!(", {} {:.0} {}", tr(Message::Remaining), remaining.ceil(), tr(Message::Seconds));
+    }
+    eprint!("{}", line);
+}
+
+/// Сводка прохода `phase` в stderr: при `-v` — шаг, число потоков и
+/// вычислений, время и число участков свёртки, при `-vv` — ещё таблица участков
+/// потоков. Без `-v` ничего не вычисляется и не выводится.
+pub fn run_summary(phase: Message, result: &IntegrationResult) {
+    let verbosity = Verbosity::current();
+    if verbosity < Verbosity::Verbose {
+        return;
+    }
+    eprintln!("[{}] {}: {}, {}: {}, {}: {}, {}: {:?}",
+              tr(phase),
+              tr(Message::Step), number(result.step),
+              tr(Message::Threads), result.threads,
+              tr(Message::Evaluations), result.samples,
+              tr(Message::Elapsed), result.elapsed);
+    if result.workers.is_empty() {
+        return;
+    }
+    let blocks: usize = result.workers.iter().map(|worker| worker.blocks).sum();
+    eprintln!("[{}] {}: {}", tr(phase), tr(Message::ReductionBlocks), blocks);
+    if verbosity < Verbosity::VeryVerbose {
+        return;
+    }
+    let total: u64 = result.workers.iter().map(|worker| worker.intervals).sum();
+    let header = [
+        Message::ColumnWorker,
+        Message::ColumnInterval,
+        Message::ColumnSamples,
+        Message::ColumnShare,
+        Message::ColumnStarted,
+        Message::Elapsed,
+    ]
+    .map(|message| tr(message).to_string());
+    let rows: Vec<[String; 6]> = result.workers.iter().enumerate().map(|(index, worker)| [
+        (index + 1).to_string(),
+        format!("[{}, {}]", number(worker.lower_bound), number(worker.upper_bound)),
+        worker.intervals.to_string(),
+        format!("{:.2}%", worker.intervals as f64 * 100.0 / total.max(1) as f64),
+        format!("{:?}", worker.started),
+        format!("{:?}", worker.elapsed),
+    ]).collect();
+    let mut widths = [0; 6];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row.iter().zip(widths).map(|(cell, width)| format!("{:>1$}", cell, width)).collect();
+        eprintln!("  {}", cells.join("  "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_only_when_enabled() {
+        let (on, off) = (AtomicBool::new(true), AtomicBool::new(false));
+        assert_eq!(paint(Style::Bad, "ошибка", &on), "\x1b[31mошибка\x1b[0m");
+        assert_eq!(paint(Style::Good, 1.5, &on), "\x1b[32m1.5\x1b[0m");
+        for style in [Style::Value, Style::Bad, Style::Good, Style::Warning, Style::Dim] {
+            assert_eq!(paint(style, "текст", &off), "текст");
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::improper::is_infinite;
+use crate::integrator::Integrator;
+use crate::options::{IntegrationOptions, ReversedBounds};
+use crate::tolerance::TOLERANCE_START_SAMPLES;
+
+/// Условия расчёта без подынтегральной функции — всё, что проверяет
+/// `validate_request`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrationRequest<T: Float = f64> {
+    pub lower_bound: T,
+    pub upper_bound: T,
+    /// Требуемая точность для `integrate_to_tolerance_with_options`; `None` —
+    /// расчёт `calculate_improper_integral_with_options` с `options.samples`.
+    pub tolerance: Option<T>,
+    pub options: IntegrationOptions,
+}
+
+impl<T: Float> IntegrationRequest<T> {
+    pub fn new(lower_bound: T, upper_bound: T, options: &IntegrationOptions) -> Self {
+        Self { lower_bound, upper_bound, tolerance: None, options: options.clone() }
+    }
+
+    pub fn with_tolerance(mut self, tolerance: T) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+}
+
+/// Проверяет условия расчёта, не вычисляя f ни разу: границы (порядок,
+/// NaN, бесконечные — только без точности), число отсчётов и его кратность
+/// формуле, число потоков, допустимость точности. С этой проверки начинают
+/// `calculate_improper_integral_with_options` и
+/// `integrate_to_tolerance_with_options`, а конечные отрезки проверяются тем
+/// же `IntegratorBuilder::build`, что и в `calculate_integral_with_options`,
+/// поэтому проверка не расходится с расчётом.
+pub fn validate_request<T: Float>(request: &IntegrationRequest<T>) -> Result<(), IntegralCalcError> {
+    let (lower_bound, upper_bound) = (request.lower_bound, request.upper_bound);
+    if let Some(eps) = request.tolerance {
+        if !(eps > T::ZERO && eps.is_finite()) {
+            return Err(IntegralCalcError::InvalidTolerance { value: eps.to_f64() });
+        }
+        let mut options = request.options.clone();
+        if options.samples == 0 {
+            options.samples = TOLERANCE_START_SAMPLES.min(options.max_samples);
+        }
+        return Integrator::builder().bounds(lower_bound, upper_bound).options(&options).build().map(drop);
+    }
+    for value in [lower_bound, upper_bound] {
+        if value.is_nan() {
+            return Err(IntegralCalcError::NonFiniteBound { value: value.to_f64() });
+        }
+    }
+    if lower_bound > upper_bound && request.options.reversed_bounds == ReversedBounds::Error {
+        return Err(IntegralCalcError::BoundsReversed { lower: lower_bound.to_f64(), upper: upper_bound.to_f64() });
+    }
+    if is_infinite(lower_bound) && lower_bound == upper_bound {
+        return Err(IntegralCalcError::EqualInfiniteBounds { value: lower_bound.to_f64() });
+    }
+    // Бесконечный промежуток сводится к интегралам по [0, 1]
+    let (lower_bound, upper_bound) = if is_infinite(lower_bound) || is_infinite(upper_bound) {
+        (T::ZERO, T::ONE)
+    } else {
+        (lower_bound, upper_bound)
+    };
+    Integrator::builder().bounds(lower_bound, upper_bound).options(&request.options).build().map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+    use crate::improper::calculate_improper_integral_with_options;
+    use crate::method::Method;
+    use crate::tolerance::integrate_to_tolerance_with_options;
+
+    fn request(lower_bound: f64, upper_bound: f64, options: IntegrationOptions) -> IntegrationRequest {
+        IntegrationRequest::new(lower_bound, upper_bound, &options)
+    }
+
+    #[test]
+    fn verdicts_match_calculation() {
+        let options = IntegrationOptions { samples: 100, async_threshold: 100, ..Default::default() };
+        let requests = [
+            request(0.0, 1.0, options.clone()),
+            request(1.0, 0.0, options.clone()),
+            request(1.0, 0.0, IntegrationOptions { reversed_bounds: ReversedBounds::Negate, ..options.clone() }),
+            request(f64::NAN, 1.0, options.clone()),
+            request(0.0, f64::INFINITY, options.clone()),
+            request(f64::INFINITY, f64::INFINITY, options.clone()),
+            request(-1e308, 1e308, options.clone()),
+            request(0.0, 1.0, IntegrationOptions { samples: 0, ..options.clone() }),
+            request(0.0, 1.0, IntegrationOptions { samples: 10_000, max_samples: 1000, ..options.clone() }),
+            request(0.0, 1.0, IntegrationOptions { threads: Some(0), ..options.clone() }),
+            request(0.0, 1.0, IntegrationOptions { samples: 7, method: Method::Simpson, ..options.clone() }),
+        ];
+        for request in requests {
+            let evaluations = AtomicU64::new(0);
+            let f = |x: f64| {
+                evaluations.fetch_add(1, Ordering::Relaxed);
+                1.0 / (1.0 + x * x)
+            };
+            let verdict = validate_request(&request);
+            assert_eq!(evaluations.load(Ordering::Relaxed), 0);
+            let result = calculate_improper_integral_with_options(f, request.lower_bound, request.upper_bound, &request.options);
+            // Через `Debug`: NaN в ошибке не равен сам себе
+            assert_eq!(format!("{:?}", verdict), format!("{:?}", result.map(drop)));
+        }
+    }
+
+    #[test]
+    fn tolerance_requests() {
+        let options = IntegrationOptions { samples: 0, ..Default::default() };
+        let cauchy = |x: f64| 1.0 / (1.0 + x * x);
+        for (tolerance, lower_bound, upper_bound) in [(1e-8, 0.0, 1.0), (0.0, 0.0, 1.0), (f64::NAN, 0.0, 1.0), (1e-8, 1.0, 0.0)] {
+            let request = request(lower_bound, upper_bound, options.clone()).with_tolerance(tolerance);
+            let result = integrate_to_tolerance_with_options(cauchy, lower_bound, upper_bound, tolerance, &options);
+            assert_eq!(format!("{:?}", validate_request(&request)), format!("{:?}", result.map(drop)));
+        }
+        let request = request(0.0, f64::INFINITY, options).with_tolerance(1e-8);
+        assert!(matches!(validate_request(&request), Err(IntegralCalcError::NonFiniteBound { .. })));
+    }
+}
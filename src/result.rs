@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use crate::float::Float;
+
+/// Итог одного запуска интегрирования.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrationResult<T: Float = f64> {
+    pub value: T,
+    /// Шаг сетки.
+    pub step: T,
+    /// Число отрезков сетки, фактически использованное формулой.
+    pub intervals: u64,
+    /// Сколько раз фактически вычислялась подынтегральная функция.
+    pub samples: u64,
+    pub threads: usize,
+    pub elapsed: Duration,
+    /// Верхняя граница остаточного члена, если известна f″.
+    pub remainder_bound: Option<T>,
+    /// Время оценки остаточного члена; не входит в `elapsed`.
+    pub remainder_elapsed: Duration,
+    /// Оценка погрешности, которую формула даёт за тот же проход (например,
+    /// |G7 − K15| у Гаусса–Кронрода).
+    pub error_estimate: Option<T>,
+    /// Точка, вблизи которой f, по-видимому, имеет особенность или разрыв
+    /// (только при включённой `IntegrationOptions::singularity_check`).
+    pub singularity: Option<T>,
+    /// Участки рабочих потоков в порядке по x; пусто у методов, которые
+    /// не делят общую сетку между потоками.
+    pub workers: Vec<WorkerReport<T>>,
+}
+
+/// Участок сетки, который посчитал один рабочий поток.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkerReport<T: Float = f64> {
+    pub lower_bound: T,
+    pub upper_bound: T,
+    /// Число отрезков сетки на участке.
+    pub intervals: u64,
+    pub evaluations: u64,
+    /// Число участков свёртки, из которых состоит участок потока.
+    pub blocks: usize,
+    /// Когда поток начал работу, от начала прохода.
+    pub started: Duration,
+    pub elapsed: Duration,
+}
+
+impl<T: Float> IntegrationResult<T> {
+    pub fn value(&self) -> T {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::integrand::AtanOverQuartic;
+    use crate::integrate::{calculate_integral, calculate_integral_with_options};
+    use crate::options::{ExecutionMode, IntegrationOptions};
+
+    #[test]
+    fn fields_describe_the_run() {
+        let result = calculate_integral(AtanOverQuartic, 0.0f64, 1.0, 1000).unwrap();
+        assert_eq!(result.value(), result.value);
+        assert!((result.value - 0.349446289808078).abs() < 1e-7);
+        assert_eq!((result.step, result.intervals, result.samples), (1e-3, 1000, 1000));
+        // f″ функции известна: граница остаточного члена считается вместе с результатом
+        let bound = result.remainder_bound.unwrap();
+        assert!((result.value - 0.349446289808078).abs() <= bound && bound < 1e-6, "{}", bound);
+    }
+
+    #[test]
+    fn workers_cover_the_grid() {
+        let options = IntegrationOptions { samples: 100_000, threads: Some(3), execution: ExecutionMode::Threaded, ..Default::default() };
+        let result = calculate_integral_with_options(AtanOverQuartic, 0.0f64, 1.0, &options).unwrap();
+        assert_eq!(result.threads, 3);
+        assert_eq!(result.workers.len(), 3);
+        assert_eq!(result.workers[0].lower_bound, 0.0);
+        for pair in result.workers.windows(2) {
+            assert!((pair[0].upper_bound - pair[1].lower_bound).abs() < 1e-15);
+        }
+        assert_eq!(result.workers.iter().map(|worker| worker.intervals).sum::<u64>(), result.intervals);
+        assert_eq!(result.workers.iter().map(|worker| worker.evaluations).sum::<u64>(), result.samples);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::error_bound::ErrorTerm;
+use crate::float::Float;
+use crate::integrate::scale_sum;
+use crate::summation::CompensatedSum;
+
+/// Составная квадратурная формула.
+///
+/// `accumulate` возвращает взвешенную сумму значений f по `samples` равным
+/// отрезкам [lower_bound, upper_bound]. Суммы по соседним участкам общей
+/// сетки обязаны складываться в сумму по их объединению: именно так расчёт
+/// делится между потоками и порциями между проверками отмены. Число отрезков
+/// каждого участка, кроме, возможно, последнего, кратно `granularity()`.
+/// Перевод суммы по всей сетке в значение интеграла делает `scale`.
+pub trait IntegrationRule<T: Float = f64>: Sync {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T;
+
+    /// Порядок точности: погрешность убывает как h^order.
+    fn order(&self) -> u32;
+
+    /// Кратность числа отрезков в одном участке.
+    fn granularity(&self) -> u64 {
+        1
+    }
+
+    /// Вычисляет ли формула f в узлах по возрастанию x при равном шаге
+    /// (не считая возвратов назад): только тогда среди значений ищутся скачки.
+    fn ordered_nodes(&self) -> bool {
+        true
+    }
+
+    /// Значение интеграла по сумме `accumulate` на всей сетке.
+    fn scale(&self, sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        sum * (upper_bound - lower_bound) / T::from_u64(samples)
+    }
+
+    /// То же, что `accumulate`, вместе с оценкой погрешности в тех же единицах,
+    /// если формула умеет давать её за тот же проход.
+    fn accumulate_with_error(
+        &self,
+        f: &mut dyn FnMut(T) -> T,
+        lower_bound: T,
+        upper_bound: T,
+        samples: u64,
+    ) -> (T, Option<T>) {
+        (self.accumulate(f, lower_bound, upper_bound, samples), None)
+    }
+
+    /// Перевод суммы оценок погрешности по всей сетке в оценку погрешности
+    /// интеграла; `sum` — сумма `accumulate` по той же сетке.
+    fn scale_error(&self, error: T, _sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        self.scale(error, lower_bound, upper_bound, samples).abs()
+    }
+
+    /// Вид остаточного члена, если он известен.
+    fn error_term(&self) -> Option<ErrorTerm> {
+        None
+    }
+}
+
+/// Формула средних прямоугольников.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MidpointRule;
+
+impl<T: Float> IntegrationRule<T> for MidpointRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        if samples == 0 {
+            return T::ZERO;
+        }
+        let step = (upper_bound - lower_bound) / T::from_u64(samples);
+        let half_step = step / T::from_f64(2.0);
+        let mut sum = CompensatedSum::new();
+        // Узел считается от номера, а не накоплением шага: иначе ошибка
+        // округления растёт с длиной участка, и участки разных потоков
+        // давали бы разные узлы
+        for index in 0..samples {
+            sum.add(f(lower_bound + T::from_u64(index) * step + half_step));
+        }
+        sum.value()
+    }
+
+    fn order(&self) -> u32 {
+        2
+    }
+
+    fn scale(&self, sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        scale_sum(sum, lower_bound, upper_bound, samples)
+    }
+
+    fn error_term(&self) -> Option<ErrorTerm> {
+        Some(ErrorTerm::MIDPOINT)
+    }
+}
+
+/// Сумма значений f в левых (`offset` = 0) или правых (`offset` = 1) концах отрезков.
+fn endpoint_sum<T: Float>(f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64, offset: u64) -> T {
+    if samples == 0 {
+        return T::ZERO;
+    }
+    let step = (upper_bound - lower_bound) / T::from_u64(samples);
+    let mut sum = CompensatedSum::new();
+    for index in offset..samples + offset {
+        sum.add(f(lower_bound + T::from_u64(index) * step));
+    }
+    sum.value()
+}
+
+/// Формула левых прямоугольников.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeftRiemannRule;
+
+impl<T: Float> IntegrationRule<T> for LeftRiemannRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        endpoint_sum(f, lower_bound, upper_bound, samples, 0)
+    }
+
+    fn order(&self) -> u32 {
+        1
+    }
+
+    fn error_term(&self) -> Option<ErrorTerm> {
+        Some(ErrorTerm::RIEMANN)
+    }
+}
+
+/// Формула правых прямоугольников.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RightRiemannRule;
+
+impl<T: Float> IntegrationRule<T> for RightRiemannRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        endpoint_sum(f, lower_bound, upper_bound, samples, 1)
+    }
+
+    fn order(&self) -> u32 {
+        1
+    }
+
+    fn error_term(&self) -> Option<ErrorTerm> {
+        Some(ErrorTerm::RIEMANN)
+    }
+}
+
+/// Сумма составной замкнутой формулы Ньютона–Котса: `weights` — веса узлов
+/// одной панели из `weights.len() - 1` отрезков; на общей границе соседних
+/// панелей веса складываются. `samples` кратно числу отрезков панели.
+fn newton_cotes_sum<T: Float>(
+    f: &mut dyn FnMut(T) -> T,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    weights: &[f64],
+) -> T {
+    if samples == 0 {
+        return T::ZERO;
+    }
+    let panel = weights.len() as u64 - 1;
+    let first = weights[0];
+    let last = weights[weights.len() - 1];
+    let step = (upper_bound - lower_bound) / T::from_u64(samples);
+    let mut sum = CompensatedSum::new();
+    sum.add(T::from_f64(first) * f(lower_bound));
+    sum.add(T::from_f64(last) * f(upper_bound));
+    for index in 1..samples {
+        let weight = match index % panel {
+            0 => first + last,
+            position => weights[position as usize],
+        };
+        sum.add(T::from_f64(weight) * f(lower_bound + T::from_u64(index) * step));
+    }
+    sum.value()
+}
+
+/// Формула трапеций: значения на концах участка берутся с весом ½, поэтому
+/// общая граница соседних участков в сумме получает вес 1.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrapezoidRule;
+
+impl<T: Float> IntegrationRule<T> for TrapezoidRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        newton_cotes_sum(f, lower_bound, upper_bound, samples, &[0.5, 0.5])
+    }
+
+    fn order(&self) -> u32 {
+        2
+    }
+
+    fn error_term(&self) -> Option<ErrorTerm> {
+        Some(ErrorTerm::TRAPEZOID)
+    }
+}
+
+/// Формула Симпсона с весами 1-4-2-4-…-4-1 и множителем h/3. Число отрезков
+/// должно быть чётным: нечётное `samples` отвергается с
+/// `IntegralCalcError::SamplesNotMultiple`, а не округляется.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimpsonRule;
+
+impl<T: Float> IntegrationRule<T> for SimpsonRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        newton_cotes_sum(f, lower_bound, upper_bound, samples, &[1.0, 4.0, 1.0])
+    }
+
+    fn order(&self) -> u32 {
+        4
+    }
+
+    fn granularity(&self) -> u64 {
+        2
+    }
+
+    fn scale(&self, sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        sum * (upper_bound - lower_bound) / T::from_u64(3 * samples)
+    }
+
+    fn error_term(&self) -> Option<ErrorTerm> {
+        Some(ErrorTerm::SIMPSON)
+    }
+}
+
+/// Формула Симпсона 3/8 с весами 1-3-3-2-3-3-…-1 и множителем 3h/8; число
+/// отрезков кратно трём (`Method::SimpsonThreeEighths` округляет его вверх).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimpsonThreeEighthsRule;
+
+impl<T: Float> IntegrationRule<T> for SimpsonThreeEighthsRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        newton_cotes_sum(f, lower_bound, upper_bound, samples, &[1.0, 3.0, 3.0, 1.0])
+    }
+
+    fn order(&self) -> u32 {
+        4
+    }
+
+    fn granularity(&self) -> u64 {
+        3
+    }
+
+    fn scale(&self, sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        sum * T::from_f64(3.0) * (upper_bound - lower_bound) / T::from_u64(8 * samples)
+    }
+
+    fn error_term(&self) -> Option<ErrorTerm> {
+        Some(ErrorTerm::SIMPSON_THREE_EIGHTHS)
+    }
+}
+
+/// Формула Буля с весами 7-32-12-32-14-…-32-7 и множителем 2h/45. Число
+/// отрезков должно быть кратно четырём, иначе —
+/// `IntegralCalcError::SamplesNotMultiple`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BooleRule;
+
+impl<T: Float> IntegrationRule<T> for BooleRule {
+    fn accumulate(&self, f: &mut dyn FnMut(T) -> T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        newton_cotes_sum(f, lower_bound, upper_bound, samples, &[7.0, 32.0, 12.0, 32.0, 7.0])
+    }
+
+    fn order(&self) -> u32 {
+        6
+    }
+
+    fn granularity(&self) -> u64 {
+        4
+    }
+
+    fn scale(&self, sum: T, lower_bound: T, upper_bound: T, samples: u64) -> T {
+        sum * T::from_f64(2.0) * (upper_bound - lower_bound) / T::from_u64(45 * samples)
+    }
+
+    fn error_term(&self) -> Option<ErrorTerm> {
+        Some(ErrorTerm::BOOLE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::{AtanOverQuartic, Integrand};
+    use crate::integrate::calculate_integral_with_rule;
+    use crate::options::IntegrationOptions;
+
+    fn integral<R: IntegrationRule<f64>>(rule: &R, f: impl Integrand<f64> + Send + Sync, lower_bound: f64, upper_bound: f64, samples: u64) -> f64 {
+        let options = IntegrationOptions { samples, ..Default::default() };
+        calculate_integral_with_rule(rule, f, lower_bound, upper_bound, &options).unwrap().value
+    }
+
+    /// Формула, определённая вне библиотеки: две точки Гаусса на каждом
+    /// отрезке сетки.
+    struct TwoPointGauss;
+
+    impl IntegrationRule<f64> for TwoPointGauss {
+        fn accumulate(&self, f: &mut dyn FnMut(f64) -> f64, lower_bound: f64, upper_bound: f64, samples: u64) -> f64 {
+            let step = (upper_bound - lower_bound) / samples as f64;
+            let offset = step / (2.0 * 3f64.sqrt());
+            (0..samples)
+                .map(|index| lower_bound + (index as f64 + 0.5) * step)
+                .map(|middle| (f(middle - offset) + f(middle + offset)) / 2.0)
+                .sum()
+        }
+
+        fn order(&self) -> u32 {
+            4
+        }
+    }
+
+    #[test]
+    fn midpoint_and_trapezoid_on_square() {
+        // На [0, 1] с шагом h: у средних прямоугольников 1/3 − h²/12, у трапеций 1/3 + h²/6
+        let square = |x: f64| x * x;
+        for samples in [10, 100, 20_000] {
+            let h = 1.0 / samples as f64;
+            assert!((integral(&MidpointRule, square, 0.0, 1.0, samples) - (1.0 / 3.0 - h * h / 12.0)).abs() < 1e-14);
+            assert!((integral(&TrapezoidRule, square, 0.0, 1.0, samples) - (1.0 / 3.0 + h * h / 6.0)).abs() < 1e-14);
+        }
+    }
+
+    #[test]
+    fn third_party_rule() {
+        // Две точки Гаусса точны на кубах, в том числе при разбиении на потоки
+        let cube = |x: f64| x * x * x - x;
+        for samples in [1, 7, 20_001] {
+            assert!((integral(&TwoPointGauss, cube, 0.0, 2.0, samples) - 2.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn trapezoid_and_midpoint_bracket_convex() {
+        let exact = std::f64::consts::E - 1.0;
+        for samples in [1, 10, 1000] {
+            let midpoint = integral(&MidpointRule, f64::exp, 0.0, 1.0, samples);
+            let trapezoid = integral(&TrapezoidRule, f64::exp, 0.0, 1.0, samples);
+            assert!(midpoint < exact && exact < trapezoid, "{}: {} {}", samples, midpoint, trapezoid);
+        }
+    }
+
+    #[test]
+    fn simpson_is_exact_on_cubics() {
+        let cubic = |x: f64| 4.0 * x * x * x - 3.0 * x * x + 2.0;
+        let antiderivative = |x: f64| x.powi(4) - x.powi(3) + 2.0 * x;
+        let exact = antiderivative(3.0) - antiderivative(-1.0);
+        for samples in [2, 10, 1000] {
+            let value = integral(&SimpsonRule, cubic, -1.0, 3.0, samples);
+            assert!((value - exact).abs() < 1e-12, "{}: {}", samples, value);
+        }
+        assert!((integral(&MidpointRule, cubic, -1.0, 3.0, 10) - exact).abs() > 1e-3);
+    }
+
+    #[test]
+    fn simpson_rules_on_builtin() {
+        let reference = integral(&BooleRule, AtanOverQuartic, 0.0, 1.0, 100_000);
+        let simpson = integral(&SimpsonRule, AtanOverQuartic, 0.0, 1.0, 60) - reference;
+        let three_eighths = integral(&SimpsonThreeEighthsRule, AtanOverQuartic, 0.0, 1.0, 60) - reference;
+        assert!(simpson.abs() < 1e-7 && three_eighths.abs() < 1e-7, "{} {}", simpson, three_eighths);
+        // Остаточные члены h⁴/180 и h⁴/80: при равном шаге у 3/8 погрешность в 9/4 раза больше
+        let ratio = three_eighths / simpson;
+        assert!((ratio - 2.25).abs() < 0.1, "{}", ratio);
+    }
+
+    #[test]
+    fn boole_is_exact_on_quintics() {
+        let quintic = |x: f64| 6.0 * x.powi(5) - x.powi(4) + 2.0 * x;
+        let antiderivative = |x: f64| x.powi(6) - x.powi(5) / 5.0 + x * x;
+        let exact = antiderivative(2.0) - antiderivative(-1.0);
+        for samples in [4, 40, 400] {
+            let value = integral(&BooleRule, quintic, -1.0, 2.0, samples);
+            assert!((value - exact).abs() < 1e-11, "{}: {}", samples, value);
+        }
+    }
+
+    #[test]
+    fn boole_error_decreases() {
+        let reference = integral(&BooleRule, AtanOverQuartic, 0.0, 1.0, 100_000);
+        let errors: Vec<f64> = [4, 16, 64, 256]
+            .map(|samples| (integral(&BooleRule, AtanOverQuartic, 0.0, 1.0, samples) - reference).abs())
+            .to_vec();
+        assert!(errors.windows(2).all(|pair| pair[1] < pair[0]), "{:?}", errors);
+    }
+}
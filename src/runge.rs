@@ -0,0 +1,101 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::improper::calculate_improper_integral_with_options;
+use crate::integrand::{Integrand, IntegrandRef};
+use crate::options::IntegrationOptions;
+use crate::result::IntegrationResult;
+
+/// Оценка погрешности более точного из двух значений по правилу Рунге:
+/// |I₂ₙ − Iₙ| / (2^p − 1), где p — порядок формулы (для формулы средних
+/// прямоугольников p = 2 и знаменатель равен 3).
+pub fn runge_error<T: Float>(coarse: T, fine: T, order: u32) -> T {
+    (fine - coarse).abs() / (T::from_f64(2.0).powi(order as i32) - T::ONE)
+}
+
+/// Интеграл при `samples` и `2 * samples` отсчётах; возвращается результат
+/// второго расчёта с оценкой погрешности по правилу Рунге.
+pub fn calculate_integral_runge<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let options = IntegrationOptions { samples, ..Default::default() };
+    calculate_integral_runge_with_options(f, lower_bound, upper_bound, &options)
+}
+
+/// То же, что `calculate_integral_runge`, с заданными параметрами расчёта.
+/// Бесконечные границы допускаются, как в `calculate_improper_integral`.
+pub fn calculate_integral_runge_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let coarse = calculate_improper_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, options)?;
+    runge_refine(f, lower_bound, upper_bound, &coarse, options)
+}
+
+/// Второй расчёт правила Рунге для уже полученного при `options.samples`
+/// результата `coarse`: считает интеграл при вдвое большем числе отсчётов и
+/// записывает в `error_estimate` оценку `runge_error` вместо собственной
+/// оценки формулы. `samples` и `elapsed` итога учитывают оба расчёта.
+pub fn runge_refine<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    coarse: &IntegrationResult<T>,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let samples = options.samples.saturating_mul(2);
+    let fine_options = IntegrationOptions { samples, ..options.clone() };
+    let mut fine = calculate_improper_integral_with_options(f, lower_bound, upper_bound, &fine_options)?;
+    fine.error_estimate = Some(runge_error(coarse.value, fine.value, options.method.rule::<T>().order()));
+    fine.samples += coarse.samples;
+    fine.elapsed += coarse.elapsed;
+    Ok(fine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::method::Method;
+
+    #[test]
+    fn tracks_true_error() {
+        let cases = [
+            (f64::exp as fn(f64) -> f64, 0.0, 1.0, std::f64::consts::E - 1.0),
+            (f64::sin, 0.0, 2.0, 1.0 - 2f64.cos()),
+            (|x| 1.0 / x, 1.0, 3.0, 3f64.ln()),
+        ];
+        for (f, lower_bound, upper_bound, exact) in cases {
+            for method in [Method::Midpoint, Method::Trapezoid, Method::Simpson] {
+                let options = IntegrationOptions { samples: 64, method, ..Default::default() };
+                let result = calculate_integral_runge_with_options(f, lower_bound, upper_bound, &options).unwrap();
+                let error = (result.value - exact).abs();
+                let ratio = result.error_estimate.unwrap() / error;
+                assert!((0.8..1.25).contains(&ratio), "{} на [{}, {}]: {}", method, lower_bound, upper_bound, ratio);
+            }
+        }
+    }
+
+    #[test]
+    fn counts_both_runs() {
+        let result = calculate_integral_runge(f64::exp, 0.0, 1.0, 100).unwrap();
+        assert_eq!(result.samples, 300);
+        assert_eq!(result.intervals, 200);
+        assert_eq!(runge_error(1.0, 1.75, 2), 0.25);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::float::Float;
+
+/// Настройки поиска точек, вблизи которых подынтегральная функция,
+/// по-видимому, имеет особенность или разрыв.
+///
+/// Подозрительным считается значение f, по модулю большее `threshold` или
+/// не являющееся конечным числом, а также скачок: разность соседних значений
+/// больше предыдущей разности и её изменения более чем в `jump_factor` раз.
+/// Скачки ищутся только у формул с упорядоченными узлами
+/// (`IntegrationRule::ordered_nodes`: прямоугольники, Ньютон–Котс) и не
+/// обнаруживаются на стыке участков разных потоков; у остальных проверяется
+/// только порог.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SingularityCheck {
+    pub threshold: f64,
+    pub jump_factor: f64,
+    /// Прерывать расчёт с `IntegralCalcError::SuspectedSingularity` вместо
+    /// предупреждения в результате.
+    pub fatal: bool,
+}
+
+impl Default for SingularityCheck {
+    fn default() -> Self {
+        Self {
+            threshold: 1e12,
+            jump_factor: 2.5,
+            fatal: false,
+        }
+    }
+}
+
+/// Состояние проверки на одном проходе по сетке.
+#[derive(Debug, Clone)]
+pub(crate) struct SingularityScan<T: Float> {
+    threshold: T,
+    jump_factor: T,
+    /// Искать ли скачки: узлы идут по возрастанию x.
+    jumps: bool,
+    /// Последний узел и значение в нём.
+    last: Option<(T, T)>,
+    /// Две последние разности соседних значений, более ранняя первой.
+    differences: [Option<T>; 2],
+    /// Первая найденная подозрительная точка.
+    pub(crate) found: Option<T>,
+}
+
+impl<T: Float> SingularityScan<T> {
+    pub(crate) fn new(check: &SingularityCheck, ordered_nodes: bool) -> Self {
+        Self {
+            threshold: T::from_f64(check.threshold),
+            jump_factor: T::from_f64(check.jump_factor),
+            jumps: ordered_nodes,
+            last: None,
+            differences: [None, None],
+            found: None,
+        }
+    }
+
+    pub(crate) fn observe(&mut self, x: T, value: T) {
+        if self.found.is_some() {
+            return;
+        }
+        if !value.is_finite() || value.abs() > self.threshold {
+            self.found = Some(x);
+            return;
+        }
+        if !self.jumps {
+            return;
+        }
+        match self.last {
+            Some((last_x, last_value)) if x > last_x => {
+                let difference = value - last_value;
+                if let [Some(earlier), Some(previous)] = self.differences {
+                    // Порог шумов округления, чтобы почти постоянная f не давала ложных скачков
+                    let noise = T::EPSILON.sqrt() * (value.abs() + last_value.abs());
+                    let trend = previous.abs().max((previous - earlier).abs());
+                    if difference.abs() > noise && difference.abs() > self.jump_factor * trend {
+                        self.found = Some((last_x + x) / T::from_f64(2.0));
+                        return;
+                    }
+                }
+                self.differences = [self.differences[1], Some(difference)];
+            }
+            // Узлы пошли не по порядку: начинаем сравнение заново
+            _ => self.differences = [None, None],
+        }
+        self.last = Some((x, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::improper::calculate_improper_integral_with_options;
+    use crate::integrand::AtanOverQuartic;
+    use crate::method::Method;
+    use crate::options::IntegrationOptions;
+
+    fn options(method: Method, samples: u64) -> IntegrationOptions {
+        IntegrationOptions {
+            samples,
+            method,
+            singularity_check: Some(SingularityCheck::default()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn monte_carlo_gauss_has_no_singularity() {
+        let gauss = |x: f64| (-x * x).exp();
+        let result = calculate_improper_integral_with_options(gauss, -3.0, 3.0, &options(Method::MonteCarlo, 100_000)).unwrap();
+        assert_eq!(result.singularity, None);
+    }
+
+    #[test]
+    fn monte_carlo_atan_has_no_singularity() {
+        let result = calculate_improper_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options(Method::MonteCarlo, 100_000)).unwrap();
+        assert_eq!(result.singularity, None);
+    }
+
+    #[test]
+    fn midpoint_finds_jump() {
+        let step = |x: f64| if x < 0.5 { x } else { x + 1.0 };
+        let result = calculate_improper_integral_with_options(step, 0.0, 1.0, &options(Method::Midpoint, 1000)).unwrap();
+        let x = result.singularity.unwrap();
+        assert!((x - 0.5).abs() < 1e-3, "{}", x);
+    }
+
+    #[test]
+    fn unordered_scan_checks_threshold_only() {
+        let check = SingularityCheck::default();
+        let mut scan = SingularityScan::new(&check, false);
+        for (x, value) in [(0.1, 0.0), (0.2, 0.0), (0.3, 0.0), (0.4, 1e6)] {
+            scan.observe(x, value);
+        }
+        assert_eq!(scan.found, None);
+        scan.observe(0.5, 1e13);
+        assert_eq!(scan.found, Some(0.5));
+    }
+
+    #[test]
+    fn ordered_scan_finds_jump() {
+        let mut scan = SingularityScan::new(&SingularityCheck::default(), true);
+        for (x, value) in [(0.1, 0.1), (0.2, 0.2), (0.3, 0.3), (0.4, 10.0)] {
+            scan.observe(x, value);
+        }
+        assert!((scan.found.unwrap() - 0.35_f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reciprocal_across_zero() {
+        let reciprocal = |x: f64| 1.0 / x;
+        let result = calculate_improper_integral_with_options(reciprocal, -1.0, 2.0, &options(Method::Midpoint, 1001)).unwrap();
+        let x = result.singularity.unwrap();
+        assert!(x.abs() < 1e-2, "{}", x);
+        let fatal = IntegrationOptions {
+            singularity_check: Some(SingularityCheck { fatal: true, ..Default::default() }),
+            ..options(Method::Midpoint, 1001)
+        };
+        assert!(matches!(
+            calculate_improper_integral_with_options(reciprocal, -1.0, 2.0, &fatal),
+            Err(crate::error::IntegralCalcError::SuspectedSingularity { x }) if x.abs() < 1e-2
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::summation::CompensatedSum;
+use crate::table::validate;
+
+/// Естественный кубический сплайн по табличным значениям (x, y): на отрезке
+/// [xᵢ, xᵢ₊₁] s(x) = a + b·t + c·t² + d·t³, t = x − xᵢ, а вторая производная
+/// на концах равна нулю. По двум точкам сплайн вырождается в отрезок прямой.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubicSpline<T: Float = f64> {
+    knots: Vec<T>,
+    coefficients: Vec<[T; 4]>,
+}
+
+impl<T: Float> CubicSpline<T> {
+    /// Строит сплайн; требования к точкам те же, что у `integrate_table`.
+    ///
+    /// Вторые производные в узлах находятся прогонкой. Система всегда имеет
+    /// строгое диагональное преобладание (2(hᵢ₋₁ + hᵢ) против hᵢ₋₁ + hᵢ),
+    /// поэтому прогонка устойчива при сколь угодно неравномерном шаге.
+    pub fn natural(points: &[(T, T)]) -> Result<Self, IntegralCalcError> {
+        validate(points)?;
+        let n = points.len();
+        let two = T::from_f64(2.0);
+        let six = T::from_f64(6.0);
+        let steps: Vec<T> = points.windows(2).map(|pair| pair[1].0 - pair[0].0).collect();
+        let slopes: Vec<T> = points.windows(2).zip(&steps).map(|(pair, &h)| (pair[1].1 - pair[0].1) / h).collect();
+        // Прямой ход прогонки по внутренним узлам 1..n-1; moments[0] = moments[n-1] = 0
+        let mut moments = vec![T::ZERO; n];
+        let mut diagonal = vec![T::ZERO; n];
+        let mut right = vec![T::ZERO; n];
+        for i in 1..n - 1 {
+            diagonal[i] = two * (steps[i - 1] + steps[i]);
+            right[i] = six * (slopes[i] - slopes[i - 1]);
+            if i > 1 {
+                let factor = steps[i - 1] / diagonal[i - 1];
+                let eliminated = factor * right[i - 1];
+                diagonal[i] -= factor * steps[i - 1];
+                right[i] -= eliminated;
+            }
+        }
+        for i in (1..n - 1).rev() {
+            moments[i] = (right[i] - steps[i] * moments[i + 1]) / diagonal[i];
+        }
+        let coefficients = (0..n - 1)
+            .map(|i| {
+                let h = steps[i];
+                [
+                    points[i].1,
+                    slopes[i] - h * (two * moments[i] + moments[i + 1]) / six,
+                    moments[i] / two,
+                    (moments[i + 1] - moments[i]) / (six * h),
+                ]
+            })
+            .collect();
+        Ok(Self { knots: points.iter().map(|&(x, _)| x).collect(), coefficients })
+    }
+
+    /// Узлы сплайна.
+    pub fn knots(&self) -> &[T] {
+        &self.knots
+    }
+
+    /// Коэффициенты [a, b, c, d] на каждом отрезке между соседними узлами.
+    pub fn coefficients(&self) -> &[[T; 4]] {
+        &self.coefficients
+    }
+
+    /// Значение сплайна в точке x; вне таблицы продолжается крайний отрезок.
+    pub fn eval(&self, x: T) -> T {
+        let segment = self.knots[1..self.knots.len() - 1]
+            .partition_point(|&knot| knot <= x);
+        let [a, b, c, d] = self.coefficients[segment];
+        let t = x - self.knots[segment];
+        a + t * (b + t * (c + t * d))
+    }
+
+    /// Точный интеграл сплайна от первого узла до последнего.
+    pub fn integral(&self) -> T {
+        let mut sum = CompensatedSum::new();
+        for (segment, &[a, b, c, d]) in self.coefficients.iter().enumerate() {
+            let h = self.knots[segment + 1] - self.knots[segment];
+            sum.add(h * (a + h * (b / T::from_f64(2.0) + h * (c / T::from_f64(3.0) + h * d / T::from_f64(4.0)))));
+        }
+        sum.value()
+    }
+}
+
+/// Интеграл по табличным значениям через естественный кубический сплайн.
+pub fn integrate_spline<T: Float>(points: &[(T, T)]) -> Result<T, IntegralCalcError> {
+    Ok(CubicSpline::natural(points)?.integral())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::integrate_table;
+
+    #[test]
+    fn beats_trapezoid_on_sine() {
+        let points: Vec<(f64, f64)> = (0..=20)
+            .map(|index| index as f64 * std::f64::consts::PI / 20.0)
+            .map(|x| (x, x.sin()))
+            .collect();
+        let spline = (integrate_spline(&points).unwrap() - 2.0).abs();
+        let trapezoid = (integrate_table(&points).unwrap() - 2.0).abs();
+        assert!(spline * 10.0 < trapezoid, "{} {}", spline, trapezoid);
+    }
+
+    #[test]
+    fn interpolates_knots() {
+        let points = [(0.0, 1.0), (0.3, -2.0), (1.0, 0.5), (2.5, 4.0)];
+        let spline = CubicSpline::natural(&points).unwrap();
+        for (x, y) in points {
+            assert!((spline.eval(x) - y).abs() < 1e-14, "{}: {}", x, spline.eval(x));
+        }
+        // По двум точкам — отрезок прямой
+        assert_eq!(integrate_spline(&[(0.0, 0.0), (2.0, 2.0)]), Ok(2.0));
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::Integrand;
+use crate::integrate::{reduce_partial_sums, reduction_blocks, RuleCursor};
+use crate::integrator::Integrator;
+use crate::method::Method;
+use crate::monte_carlo::MonteCarloRule;
+
+/// Сообщение рабочего потока: номер участка свёртки, накопленная по нему
+/// сумма, число пройденных отрезков, число вычислений и признак завершения
+/// участка.
+type Progress<T> = (usize, T, u64, u64, bool);
+
+/// Итератор по промежуточным оценкам интеграла.
+///
+/// Каждый элемент — пара (число выполненных вычислений, оценка). Промежуточные
+/// оценки экстраполируют сумму по пройденным отрезкам на весь отрезок. Суммы
+/// набираются по тем же участкам `reduction_blocks` и складываются тем же
+/// попарным деревом, что и в `Integrator::run`, поэтому последний элемент
+/// совпадает с его результатом до последнего бита.
+pub struct IntegrationStream<T: Float, I> {
+    state: StreamState<T, I>,
+    method: Method,
+    monte_carlo: MonteCarloRule,
+    lower_bound: T,
+    upper_bound: T,
+    samples: u64,
+    /// Сумма, пройденные отрезки и вычисления по каждому участку свёртки.
+    blocks: Vec<(T, u64, u64)>,
+    finished: bool,
+}
+
+enum StreamState<T: Float, I> {
+    Sequential {
+        f: I,
+        /// Участки свёртки и номер текущего.
+        ranges: Vec<(T, u64)>,
+        current: usize,
+        cursor: RuleCursor<T>,
+        step: T,
+        chunk: u64,
+    },
+    Threaded {
+        receiver: Receiver<Progress<T>>,
+        handles: Vec<JoinHandle<()>>,
+        running: usize,
+    },
+}
+
+impl<T, I> IntegrationStream<T, I>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync + 'static,
+{
+    pub(crate) fn new(integrator: &Integrator<T>, f: I, chunk: u64) -> Self {
+        let chunk = chunk.max(1);
+        let lower_bound = integrator.lower_bound();
+        let upper_bound = integrator.upper_bound();
+        let step = integrator.step();
+        let method = integrator.method();
+        let monte_carlo = integrator.monte_carlo();
+        let ranges = reduction_blocks(lower_bound, upper_bound, integrator.samples(), method.rule::<T>().granularity());
+        let blocks = vec![(T::ZERO, 0, 0); ranges.len()];
+        let state = if integrator.is_threaded() {
+            // Потоки делят участки свёртки так же, как в `integrate_threaded`
+            let threads = integrator.threads().clamp(1, ranges.len());
+            let ranges = Arc::new(ranges);
+            let f = Arc::new(f);
+            let (sender, receiver) = channel();
+            let handles = (0..threads)
+                .map(|index| {
+                    let f = f.clone();
+                    let ranges = ranges.clone();
+                    let sender = sender.clone();
+                    std::thread::spawn(move || {
+                        let rule = method.seeded_rule(&monte_carlo);
+                        let first = index * ranges.len() / threads;
+                        let last = (index + 1) * ranges.len() / threads;
+                        for (block, &(block_lower_bound, block_samples)) in ranges[first..last].iter().enumerate() {
+                            let mut cursor = RuleCursor::new(block_lower_bound, step, block_samples);
+                            loop {
+                                let finished = cursor.advance(rule, &mut |x| f.eval(x), chunk);
+                                let message = (first + block, cursor.sum, cursor.done, cursor.evaluations, finished);
+                                // Получатель мог быть уже отброшен — тогда просто завершаемся
+                                if sender.send(message).is_err() {
+                                    return;
+                                }
+                                if finished {
+                                    break;
+                                }
+                            }
+                        }
+                    })
+                })
+                .collect();
+            StreamState::Threaded {
+                receiver,
+                handles,
+                running: blocks.len(),
+            }
+        } else {
+            let (first_lower_bound, first_samples) = ranges[0];
+            StreamState::Sequential {
+                f,
+                cursor: RuleCursor::new(first_lower_bound, step, first_samples),
+                ranges,
+                current: 0,
+                step,
+                chunk,
+            }
+        };
+        Self {
+            state,
+            method,
+            monte_carlo,
+            lower_bound,
+            upper_bound,
+            samples: integrator.samples(),
+            blocks,
+            finished: false,
+        }
+    }
+}
+
+impl<T: Float, I> IntegrationStream<T, I> {
+    fn estimate(&self, sum: T, done: u64, finished: bool) -> T {
+        let rule = self.method.seeded_rule(&self.monte_carlo);
+        if finished || done == 0 {
+            rule.scale(sum, self.lower_bound, self.upper_bound, self.samples)
+        } else {
+            // Среднее по уже пройденным отрезкам распространяем на весь отрезок
+            let mean = sum / T::from_u64(done);
+            rule.scale(mean * T::from_u64(self.samples), self.lower_bound, self.upper_bound, self.samples)
+        }
+    }
+}
+
+impl<T, I> Iterator for IntegrationStream<T, I>
+where
+    T: Float,
+    I: Integrand<T>,
+{
+    type Item = Result<(u64, T), IntegralCalcError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let (method, monte_carlo) = (self.method, self.monte_carlo);
+        let finished = match &mut self.state {
+            StreamState::Sequential { f, ranges, current, cursor, step, chunk } => {
+                let rule = method.seeded_rule(&monte_carlo);
+                // Порция может захватить конец одного участка и начало следующего
+                let mut budget = *chunk;
+                while budget > 0 && *current < ranges.len() {
+                    let before = cursor.done;
+                    let finished = cursor.advance(rule, &mut |x| f.eval(x), budget);
+                    budget = budget.saturating_sub(cursor.done - before);
+                    self.blocks[*current] = (cursor.sum, cursor.done, cursor.evaluations);
+                    if finished {
+                        *current += 1;
+                        if let Some(&(block_lower_bound, block_samples)) = ranges.get(*current) {
+                            *cursor = RuleCursor::new(block_lower_bound, *step, block_samples);
+                        }
+                    }
+                }
+                *current == ranges.len()
+            }
+            StreamState::Threaded { receiver, handles, running } => {
+                let Ok((block, sum, done, evaluations, finished)) = receiver.recv() else {
+                    // Все отправители пропали раньше, чем сообщили о завершении
+                    self.finished = true;
+                    for handle in handles.drain(..) {
+                        let _ = handle.join();
+                    }
+                    return Some(Err(IntegralCalcError::WorkerPanicked));
+                };
+                self.blocks[block] = (sum, done, evaluations);
+                if finished {
+                    *running -= 1;
+                }
+                *running == 0
+            }
+        };
+        if finished {
+            self.finished = true;
+            if let StreamState::Threaded { handles, .. } = &mut self.state {
+                for handle in handles.drain(..) {
+                    if handle.join().is_err() {
+                        return Some(Err(IntegralCalcError::WorkerPanicked));
+                    }
+                }
+            }
+        }
+        let sums: Vec<_> = self.blocks.iter().map(|&(sum, done, _)| (sum, done)).collect();
+        let (sum, done) = reduce_partial_sums(&sums);
+        let evaluations = self.blocks.iter().map(|&(_, _, evaluations)| evaluations).sum();
+        Some(Ok((evaluations, self.estimate(sum, done, finished))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::integrand::AtanOverQuartic;
+    use crate::integrator::Integrator;
+    use crate::method::Method;
+    use crate::options::ExecutionMode;
+
+    fn last_value(integrator: &Integrator, chunk: u64) -> f64 {
+        integrator.stream(AtanOverQuartic, chunk).last().unwrap().unwrap().1
+    }
+
+    #[test]
+    fn last_value_matches_run() {
+        for execution in [ExecutionMode::Sequential, ExecutionMode::Threaded] {
+            for method in [Method::Midpoint, Method::Trapezoid, Method::MonteCarlo] {
+                let integrator = Integrator::builder()
+                    .bounds(0.0, 1.0)
+                    .samples(20_001)
+                    .method(method)
+                    .execution(execution)
+                    .threads(3)
+                    .build()
+                    .unwrap();
+                let expected = integrator.run(AtanOverQuartic).unwrap().value;
+                for chunk in [1, 777, 5000, 1_000_000] {
+                    assert_eq!(last_value(&integrator, chunk).to_bits(), expected.to_bits(), "{:?} {:?} {}", execution, method, chunk);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn evaluations_are_increasing() {
+        let integrator = Integrator::builder().bounds(0.0, 1.0).samples(20_001).build().unwrap();
+        let evaluations: Vec<u64> = integrator.stream(AtanOverQuartic, 777).map(|item| item.unwrap().0).collect();
+        assert!(evaluations.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(*evaluations.last().unwrap(), 20_001);
+    }
+}
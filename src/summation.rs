@@ -0,0 +1,97 @@
+use crate::float::Float;
+
+/// Сумма с компенсацией ошибки округления по Ноймайеру: потерянные при
+/// каждом сложении младшие разряды накапливаются отдельно, поэтому
+/// погрешность не растёт с числом слагаемых, даже если они разного знака.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompensatedSum<T: Float = f64> {
+    sum: T,
+    compensation: T,
+}
+
+impl<T: Float> CompensatedSum<T> {
+    pub fn new() -> Self {
+        Self { sum: T::ZERO, compensation: T::ZERO }
+    }
+
+    pub fn add(&mut self, value: T) {
+        let sum = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - sum) + value;
+        } else {
+            self.compensation += (value - sum) + self.sum;
+        }
+        self.sum = sum;
+    }
+
+    pub fn value(&self) -> T {
+        self.sum + self.compensation
+    }
+
+    /// Добавляет другую сумму вместе с её поправкой.
+    pub fn merge(&mut self, other: Self) {
+        self.add(other.sum);
+        self.compensation += other.compensation;
+    }
+}
+
+/// Сумма попарным деревом: половины складываются рекурсивно, поэтому
+/// порядок сложений определяется только длиной `values`.
+pub fn pairwise_sum<T: Float>(values: &[T]) -> T {
+    fn tree<T: Float>(values: &[T]) -> CompensatedSum<T> {
+        match values {
+            [] => CompensatedSum::new(),
+            [value] => {
+                let mut sum = CompensatedSum::new();
+                sum.add(*value);
+                sum
+            }
+            _ => {
+                let (left, right) = values.split_at(values.len() / 2);
+                let mut sum = tree(left);
+                sum.merge(tree(right));
+                sum
+            }
+        }
+    }
+    tree(values).value()
+}
+
+impl<T: Float> Default for CompensatedSum<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrate::calculate_integral;
+
+    #[test]
+    fn compensated_sum_keeps_small_terms() {
+        let mut sum = CompensatedSum::new();
+        for value in [1e16, 1.0, -1e16, 1.0] {
+            sum.add(value);
+        }
+        assert_eq!(sum.value(), 2.0);
+        let mut naive = 0.0;
+        for _ in 0..10_000_000 {
+            naive += 0.1;
+        }
+        let mut compensated = CompensatedSum::new();
+        for _ in 0..10_000_000 {
+            compensated.add(0.1);
+        }
+        assert!((naive - 1e6f64).abs() > 1e-6);
+        assert_eq!(compensated.value(), 1e6);
+    }
+
+    #[test]
+    fn constant_with_many_samples() {
+        // 10⁸ значений 0.1: при наивном сложении итог уходит на тысячи ulp
+        let result = calculate_integral(|_: f64| 0.1, 0.0, 1.0, 100_000_000).unwrap();
+        let ulps = (result.value.to_bits() as i64 - 0.1f64.to_bits() as i64).abs();
+        assert!(ulps <= 4, "{} ({} ulp)", result.value, ulps);
+    }
+}
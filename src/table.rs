@@ -0,0 +1,105 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::summation::CompensatedSum;
+
+/// Проверяет таблицу: не меньше двух точек, все значения конечны, x строго
+/// возрастают. В ошибке указывается номер первой неподходящей точки.
+pub(crate) fn validate<T: Float>(points: &[(T, T)]) -> Result<(), IntegralCalcError> {
+    if points.len() < 2 {
+        return Err(IntegralCalcError::TooFewPoints { count: points.len() });
+    }
+    for (index, &(x, y)) in points.iter().enumerate() {
+        if !x.is_finite() || !y.is_finite() {
+            return Err(IntegralCalcError::NonFinitePoint { index });
+        }
+        if index > 0 {
+            let previous = points[index - 1].0;
+            if x == previous {
+                return Err(IntegralCalcError::DuplicatePoint { index });
+            }
+            if x < previous {
+                return Err(IntegralCalcError::NonMonotonePoints { index });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Интеграл по табличным значениям (x, y) составной формулой трапеций;
+/// шаг между точками может быть любым.
+pub fn integrate_table<T: Float>(points: &[(T, T)]) -> Result<T, IntegralCalcError> {
+    validate(points)?;
+    let mut sum = CompensatedSum::new();
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        sum.add((x1 - x0) * (y0 + y1) / T::from_f64(2.0));
+    }
+    Ok(sum.value())
+}
+
+/// Интеграл по табличным значениям составной формулой Симпсона. Шаг должен
+/// быть постоянным (с точностью до ошибок округления), иначе —
+/// `IntegralCalcError::NonUniformPoints`, а число точек нечётным, иначе —
+/// `IntegralCalcError::SamplesNotMultiple`.
+pub fn integrate_table_simpson<T: Float>(points: &[(T, T)]) -> Result<T, IntegralCalcError> {
+    validate(points)?;
+    let intervals = points.len() as u64 - 1;
+    if !intervals.is_multiple_of(2) {
+        return Err(IntegralCalcError::SamplesNotMultiple { samples: intervals, multiple: 2 });
+    }
+    let step = (points[points.len() - 1].0 - points[0].0) / T::from_u64(intervals);
+    let tolerance = T::EPSILON.sqrt() * step;
+    for index in 1..points.len() {
+        if (points[index].0 - points[index - 1].0 - step).abs() > tolerance {
+            return Err(IntegralCalcError::NonUniformPoints { index });
+        }
+    }
+    let mut sum = CompensatedSum::new();
+    for (index, &(_, y)) in points.iter().enumerate() {
+        let weight = match index {
+            0 => 1.0,
+            _ if index == points.len() - 1 => 1.0,
+            _ if index % 2 == 1 => 4.0,
+            _ => 2.0,
+        };
+        sum.add(T::from_f64(weight) * y);
+    }
+    Ok(sum.value() * step / T::from_f64(3.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampled(f: impl Fn(f64) -> f64, xs: &[f64]) -> Vec<(f64, f64)> {
+        xs.iter().map(|&x| (x, f(x))).collect()
+    }
+
+    #[test]
+    fn trapezoid_is_exact_on_lines() {
+        // Шаг может быть любым
+        let points = sampled(|x| 3.0 * x - 1.0, &[0.0, 0.1, 0.5, 1.7, 2.0]);
+        assert!((integrate_table(&points).unwrap() - 4.0).abs() < 1e-14);
+    }
+
+    #[test]
+    fn simpson_is_exact_on_cubics() {
+        let xs: Vec<f64> = (0..=10).map(|index| index as f64 / 5.0).collect();
+        let points = sampled(|x| x * x * x - 2.0 * x, &xs);
+        assert!((integrate_table_simpson(&points).unwrap() - 0.0).abs() < 1e-14);
+        assert_eq!(
+            integrate_table_simpson(&points[..10]),
+            Err(IntegralCalcError::SamplesNotMultiple { samples: 9, multiple: 2 })
+        );
+        let uneven = sampled(|x| x, &[0.0, 0.5, 2.0]);
+        assert_eq!(integrate_table_simpson(&uneven), Err(IntegralCalcError::NonUniformPoints { index: 1 }));
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert_eq!(integrate_table(&[(0.0, 1.0)]), Err(IntegralCalcError::TooFewPoints { count: 1 }));
+        assert_eq!(integrate_table(&[(0.0, 1.0), (1.0, f64::NAN)]), Err(IntegralCalcError::NonFinitePoint { index: 1 }));
+        assert_eq!(integrate_table(&[(0.0, 1.0), (0.0, 2.0)]), Err(IntegralCalcError::DuplicatePoint { index: 1 }));
+        assert_eq!(integrate_table(&[(1.0, 1.0), (0.0, 2.0)]), Err(IntegralCalcError::NonMonotonePoints { index: 1 }));
+    }
+}
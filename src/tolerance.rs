@@ -0,0 +1,109 @@
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::integrand::{Integrand, IntegrandRef};
+use crate::integrate::calculate_integral_with_options;
+use crate::options::IntegrationOptions;
+use crate::request::{validate_request, IntegrationRequest};
+use crate::result::IntegrationResult;
+
+/// С какого числа отсчётов начинается уточнение, если оно не задано.
+pub const TOLERANCE_START_SAMPLES: u64 = 16;
+
+/// Удваивает число отсчётов, пока две последовательные оценки не станут
+/// отличаться меньше чем на `eps`, и возвращает последнюю из них. Если
+/// формула не даёт своей оценки погрешности, `error_estimate` — разность
+/// этих двух оценок.
+pub fn integrate_to_tolerance<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    eps: T,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    integrate_to_tolerance_with_options(f, lower_bound, upper_bound, eps, &IntegrationOptions::default())
+}
+
+/// То же, что `integrate_to_tolerance`, с заданными параметрами расчёта;
+/// `options.samples` задаёт начальное число отсчётов (0 — `TOLERANCE_START_SAMPLES`),
+/// а `options.max_samples` — предел, после которого возвращается
+/// `IntegralCalcError::ToleranceNotReached` с лучшей полученной оценкой.
+pub fn integrate_to_tolerance_with_options<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    eps: T,
+    options: &IntegrationOptions,
+) -> Result<IntegrationResult<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    validate_request(&IntegrationRequest::new(lower_bound, upper_bound, options).with_tolerance(eps))?;
+    let mut options = options.clone();
+    if options.samples == 0 {
+        options.samples = TOLERANCE_START_SAMPLES.min(options.max_samples);
+    }
+    let mut previous = calculate_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &options)?;
+    loop {
+        let Some(samples) = options.samples.checked_mul(2).filter(|&samples| samples <= options.max_samples) else {
+            return Err(IntegralCalcError::ToleranceNotReached {
+                best: previous.value.to_f64(),
+                samples: previous.samples,
+            });
+        };
+        options.samples = samples;
+        let current = calculate_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &options)?;
+        let difference = (current.value - previous.value).abs();
+        if difference < eps {
+            return Ok(IntegrationResult { error_estimate: current.error_estimate.or(Some(difference)), ..current });
+        }
+        previous = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+    use crate::method::Method;
+
+    /// ∫₀¹ atan(x)/(x⁴+1) dx.
+    const EXACT: f64 = 0.349446289808078;
+
+    #[test]
+    fn tighter_tolerance_needs_more_samples() {
+        let loose = integrate_to_tolerance(AtanOverQuartic, 0.0, 1.0, 1e-4).unwrap();
+        let tight = integrate_to_tolerance(AtanOverQuartic, 0.0, 1.0, 1e-8).unwrap();
+        assert!(tight.samples > loose.samples, "{} <= {}", tight.samples, loose.samples);
+        for (result, eps) in [(loose, 1e-4), (tight, 1e-8)] {
+            assert!(result.error_estimate.unwrap() < eps, "{:?}", result.error_estimate);
+            assert!((result.value - EXACT).abs() < eps, "{}: {}", eps, result.value);
+        }
+    }
+
+    #[test]
+    fn rule_estimate_is_kept() {
+        let options = IntegrationOptions { method: Method::GaussKronrod, ..Default::default() };
+        let result = integrate_to_tolerance_with_options(f64::exp, 0.0, 1.0, 1e-10, &options).unwrap();
+        assert!(result.error_estimate.unwrap() < 1e-10);
+        assert!((result.value - (std::f64::consts::E - 1.0)).abs() < 1e-12, "{}", result.value);
+    }
+
+    #[test]
+    fn limit_and_invalid_tolerance() {
+        let options = IntegrationOptions { max_samples: 1000, async_threshold: 100, ..Default::default() };
+        match integrate_to_tolerance_with_options(AtanOverQuartic, 0.0, 1.0, 1e-12, &options) {
+            Err(IntegralCalcError::ToleranceNotReached { best, samples }) => {
+                assert_eq!(samples, 512);
+                assert!((best - EXACT).abs() < 1e-6, "{}", best);
+            }
+            result => panic!("{:?}", result),
+        }
+        for eps in [0.0, -1e-3, f64::NAN] {
+            assert!(matches!(integrate_to_tolerance(AtanOverQuartic, 0.0, 1.0, eps), Err(IntegralCalcError::InvalidTolerance { .. })), "{}", eps);
+        }
+    }
+}
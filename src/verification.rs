@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+
+use crate::accuracy::{analyze_accuracy, AccuracyReport};
+use crate::error::IntegralCalcError;
+use crate::float::Float;
+use crate::improper::calculate_improper_integral_with_options;
+use crate::integrand::{Integrand, IntegrandRef};
+use crate::integrate::get_remaining_term_for;
+use crate::options::IntegrationOptions;
+use crate::result::IntegrationResult;
+
+/// Проверка результата контрольным расчётом: `verify_result`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationReport<T: Float = f64> {
+    /// Контрольный расчёт.
+    pub reference: IntegrationResult<T>,
+    /// Погрешность проверяемого результата относительно контрольного.
+    pub accuracy: AccuracyReport<T>,
+    /// Граница остаточного члена при шаге проверяемого результата; `None`,
+    /// если у формулы её нет или отрезок бесконечен.
+    pub remainder_bound: Option<T>,
+    /// Время оценки остаточного члена.
+    pub remainder_elapsed: Duration,
+}
+
+impl<T: Float> VerificationReport<T> {
+    /// Не превышает ли погрешность границу остаточного члена.
+    pub fn within_bound(&self) -> Option<bool> {
+        self.remainder_bound.map(|bound| self.accuracy.absolute <= bound)
+    }
+}
+
+/// Проверяет `result`, посчитанный с параметрами `options`: повторяет расчёт
+/// при `reference_samples` отсчётах (не больше `options.max_samples`,
+/// иначе `TooManySamples`) и сравнивает с ним, а также оценивает
+/// остаточный член. Граница берётся из `result.remainder_bound`, если
+/// библиотека уже посчитала её вместе с результатом. `cancel` и `progress`
+/// из `options` действуют на контрольный расчёт.
+pub fn verify_result<T, I>(
+    f: I,
+    lower_bound: T,
+    upper_bound: T,
+    result: &IntegrationResult<T>,
+    reference_samples: u64,
+    options: &IntegrationOptions,
+) -> Result<VerificationReport<T>, IntegralCalcError>
+where
+    T: Float,
+    I: Integrand<T> + Send + Sync,
+{
+    let reference_options = IntegrationOptions {
+        samples: reference_samples,
+        async_threshold: 0,
+        singularity_check: None,
+        ..options.clone()
+    };
+    let reference = calculate_improper_integral_with_options(IntegrandRef(&f), lower_bound, upper_bound, &reference_options)?;
+    let accuracy = analyze_accuracy(result.value, reference.value);
+    let (remainder_bound, remainder_elapsed) = match result.remainder_bound {
+        Some(bound) => (Some(bound), result.remainder_elapsed),
+        None => {
+            let start = Instant::now();
+            let finite = lower_bound.is_finite() && upper_bound.is_finite();
+            let bound = finite.then(|| get_remaining_term_for(options.method, &f, lower_bound, upper_bound, result.step)).flatten();
+            (bound, if bound.is_some() { start.elapsed() } else { Duration::ZERO })
+        }
+    };
+    Ok(VerificationReport { reference, accuracy, remainder_bound, remainder_elapsed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrand::AtanOverQuartic;
+
+    #[test]
+    fn reference_is_within_bound() {
+        let options = IntegrationOptions { samples: 1000, ..Default::default() };
+        let result = calculate_improper_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options).unwrap();
+        let report = verify_result(AtanOverQuartic, 0.0, 1.0, &result, 100_000, &options).unwrap();
+        assert_eq!(report.reference.intervals, 100_000);
+        assert_eq!(report.remainder_bound, result.remainder_bound);
+        assert_eq!(report.within_bound(), Some(true));
+        assert!(report.accuracy.absolute > 0.0);
+    }
+
+    #[test]
+    fn bound_is_computed_without_library_bound() {
+        let square = |x: f64| x * x;
+        let options = IntegrationOptions { samples: 100, ..Default::default() };
+        let result = calculate_improper_integral_with_options(square, 0.0, 1.0, &options).unwrap();
+        assert_eq!(result.remainder_bound, None);
+        let report = verify_result(square, 0.0, 1.0, &result, 10_000, &options).unwrap();
+        // Для x² остаточный член формулы средних прямоугольников точно равен h²/24
+        let bound = report.remainder_bound.unwrap();
+        assert!((bound - 1e-4 / 12.0).abs() < 1e-9, "{}", bound);
+        assert_eq!(report.within_bound(), Some(true));
+    }
+
+    #[test]
+    fn infinite_bounds_have_no_remainder_bound() {
+        let cauchy = |x: f64| 1.0 / (1.0 + x * x);
+        let options = IntegrationOptions { samples: 1000, ..Default::default() };
+        let result = calculate_improper_integral_with_options(cauchy, 0.0, f64::INFINITY, &options).unwrap();
+        let report = verify_result(cauchy, 0.0, f64::INFINITY, &result, 10_000, &options).unwrap();
+        assert_eq!(report.remainder_bound, None);
+        assert_eq!(report.within_bound(), None);
+    }
+
+    #[test]
+    fn too_many_reference_samples() {
+        let options = IntegrationOptions { samples: 10, max_samples: 1000, async_threshold: 100, ..Default::default() };
+        let result = calculate_improper_integral_with_options(AtanOverQuartic, 0.0, 1.0, &options).unwrap();
+        assert!(matches!(
+            verify_result(AtanOverQuartic, 0.0, 1.0, &result, 2000, &options),
+            Err(IntegralCalcError::TooManySamples { .. })
+        ));
+    }
+}
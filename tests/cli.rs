@@ -0,0 +1,998 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Запуск программы без конфигурации и истории пользователя; `input` подаётся
+/// в stdin.
+fn run_with_input(args: &[&str], input: &str) -> Output {
+    let home = env!("CARGO_TARGET_TMPDIR");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kryl_04"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", home)
+        .env("XDG_DATA_HOME", home)
+        .env_remove("KRYL_THREADS")
+        .env_remove("NO_COLOR")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn run(args: &[&str]) -> Output {
+    run_with_input(args, "")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn table_file_is_integrated() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("table_file_is_integrated.csv");
+    // y = x² в пяти точках на [0, 2]: Симпсон точен, трапеции дают 2.75
+    std::fs::write(&path, "x,y\n0,0\n0.5,0.25\n1,1\n1.5,2.25\n2,4\n").unwrap();
+    let output = run(&["--interactive", "--no-color", "--lang", "en", "--table", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let output = stdout(&output);
+    assert!(output.contains("2.75"), "{}", output);
+    assert!(output.contains("2.666666"), "{}", output);
+    let missing = run(&["--lang", "en", "--table", "/nonexistent/table.csv"]);
+    assert!(!missing.status.success());
+}
+
+#[test]
+fn degenerate_interval_reports_zero() {
+    let output = run(&["--interactive", "--no-color", "--lang", "en", "--lower", "2", "--upper", "2", "--samples", "1000", "--with-reference"]);
+    assert!(output.status.success());
+    let output = stdout(&output);
+    assert!(output.contains("Approximate value of the integral: 0\n"), "{}", output);
+    assert!(output.contains("Upper bound for Rn: 0\n"), "{}", output);
+    assert!(output.contains("relative error is undefined"), "{}", output);
+    assert!(!output.contains("NaN") && !output.contains("false"), "{}", output);
+}
+
+#[test]
+fn negate_reversed_flips_sign() {
+    let reversed = ["--interactive", "--no-color", "--lang", "en", "--lower", "1", "--upper", "0", "--samples", "1000"];
+    let output = run(&[&reversed[..], &["--negate-reversed", "--format", "json"]].concat());
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("\"value\": -0.34944"), "{}", stdout(&output));
+    let output = run(&reversed);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("lower bound (1) is greater than the upper bound (0)"));
+}
+
+#[test]
+fn non_finite_input_is_rejected() {
+    let args = ["--interactive", "--no-color", "--lang", "en"];
+    for (input, code, message) in [
+        ("nan\n1\n100\n\n\n\n", 6, "a bound is not a finite number (NaN)"),
+        ("0\nnan\n100\n\n\n\n", 6, "a bound is not a finite number (NaN)"),
+        ("0\n1\nnan\n", 3, "Failed to parse the input"),
+        ("0\n1\ninf\n", 3, "Failed to parse the input"),
+        ("-1e308\n1e308\n100\n\n\n\n", 15, "the length of the interval [-1e308, 1e308] is not a finite number"),
+    ] {
+        let output = run_with_input(&args, input);
+        assert_eq!(output.status.code(), Some(code), "{:?}", input);
+        assert!(String::from_utf8_lossy(&output.stderr).contains(message), "{:?}: {}", input, String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+#[test]
+fn empty_line_differs_from_end_of_input() {
+    let args = ["--interactive", "--no-color", "--lang", "en"];
+    let empty = run_with_input(&args, "0\n1\n\n");
+    assert_eq!(empty.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&empty.stderr).contains("Failed to parse the input"));
+    let ended = run_with_input(&args, "0\n1\n");
+    assert_eq!(ended.status.code(), Some(16));
+    assert!(String::from_utf8_lossy(&ended.stderr).contains("Input ended prematurely"));
+}
+
+#[test]
+fn piped_session_skips_reference_run() {
+    let args = ["--interactive", "--no-color", "--lang", "en"];
+    let output = run_with_input(&args, "0\n1\n1000\n\n\n\nn\n");
+    assert!(output.status.success());
+    let output = stdout(&output);
+    assert!(output.contains("Approximate value of the integral: 0.3494"), "{}", output);
+    assert!(!output.contains("reference run"), "{}", output);
+    assert!(output.contains("Error estimate"), "{}", output);
+    let chosen = stdout(&run_with_input(&[&args[..], &["--with-reference=5000"]].concat(), "0\n1\n1000\n\n\n\n"));
+    assert!(chosen.contains("reference run"), "{}", chosen);
+    let too_many = run_with_input(&[&args[..], &["--with-reference=2000000000"]].concat(), "0\n1\n1000\n\n\n\n");
+    assert_eq!(too_many.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&too_many.stderr).contains("maximum number of samples exceeded"));
+}
+
+#[test]
+fn truncated_input_ends_cleanly() {
+    let args = ["--interactive", "--no-color", "--lang", "en"];
+    for input in ["", "0\n", "0\n1\n", "0"] {
+        let output = run_with_input(&args, input);
+        assert_eq!(output.status.code(), Some(16), "{:?}", input);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Input ended prematurely"), "{:?}: {}", input, stderr);
+        assert!(!stderr.contains("Failed to parse"), "{:?}: {}", input, stderr);
+    }
+    // Последняя строка без перевода строки принимается
+    let output = run_with_input(&args, "0\n1\n1000");
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Approximate value of the integral: 0.3494"));
+}
+
+#[test]
+fn flags_replace_prompts() {
+    let output = run(&["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "100000"]);
+    assert!(output.status.success());
+    let value: f64 = stdout(&output).trim().parse().unwrap();
+    assert!((value - 0.349446289808078).abs() < 1e-10, "{}", value);
+    // Границы, не заданные флагами, запрашиваются
+    let mixed = run_with_input(&["--interactive", "--no-color", "--lang", "en", "--lower", "0", "--samples", "1000"], "1\n\n\n\n");
+    let mixed = stdout(&mixed);
+    assert!(mixed.contains("Enter the upper bound"), "{}", mixed);
+    assert!(!mixed.contains("Enter the lower bound"), "{}", mixed);
+    assert!(mixed.contains("Approximate value of the integral: 0.3494"), "{}", mixed);
+}
+
+#[test]
+fn invalid_flag_value_matches_prompt_exit_code() {
+    let flag = run(&["--lang", "en", "--lower", "0", "--upper", "1..5", "--samples", "100"]);
+    let prompt = run_with_input(&["--interactive", "--lang", "en", "--lower", "0"], "1..5\n");
+    assert_eq!(flag.status.code(), Some(2));
+    assert_eq!(prompt.status.code(), flag.status.code());
+    assert!(String::from_utf8_lossy(&flag.stderr).contains("invalid expression at position 1"));
+}
+
+#[test]
+fn help_in_both_languages() {
+    let en = stdout(&run(&["--help", "--lang", "en"]));
+    let ru = stdout(&run(&["--help", "--lang", "ru"]));
+    assert!(en.starts_with("Usage: kryl_04"), "{}", en);
+    assert!(ru.starts_with("Использование: kryl_04"), "{}", ru);
+    for flag in ["--lower", "--upper", "--samples"] {
+        assert!(en.contains(flag) && ru.contains(flag), "{}", flag);
+    }
+}
+
+#[test]
+fn answers_on_one_line() {
+    let one_line = run_with_input(&["-q"], "0 1 100000\n");
+    assert!(one_line.status.success());
+    let split = run_with_input(&["-q"], "0\n1 100000\n");
+    assert_eq!(stdout(&one_line), stdout(&split));
+    let value: f64 = stdout(&one_line).trim().parse().unwrap();
+    assert!((value - 0.349446289808078).abs() < 1e-10, "{}", value);
+    let extra = run_with_input(&["--lang", "en"], "0 1 100 extra\n");
+    assert_eq!(extra.status.code(), Some(11));
+    assert!(String::from_utf8_lossy(&extra.stderr).contains("extra"), "{}", String::from_utf8_lossy(&extra.stderr));
+}
+
+/// Расчёт по умолчанию — ∫₀¹ atan(x)/(x⁴+1) средними прямоугольниками.
+const DEFAULT_RUN: &[&str] = &["--interactive", "--no-color", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1000"];
+
+#[test]
+fn chosen_function_is_named() {
+    let args = [DEFAULT_RUN, &["--function", "gauss"]].concat();
+    let output = stdout(&run(&args));
+    assert!(output.starts_with("Integrand: exp(-x^2)\n"), "{}", output);
+    assert!(output.contains("Approximate value of the integral: 0.7468"), "{}", output);
+    let by_number = stdout(&run(&[DEFAULT_RUN, &["--function", "3"]].concat()));
+    assert_eq!(by_number.lines().next(), output.lines().next());
+}
+
+#[test]
+fn expression_flag_matches_builtin() {
+    let args = ["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1000"];
+    let typed = run(&[&args[..], &["--expr", "atan(x)/(x^4+1)"]].concat());
+    assert!(typed.status.success());
+    assert_eq!(stdout(&typed), stdout(&run(&args)));
+    let malformed = run(&[&args[..], &["--expr", "sin(x"]].concat());
+    assert_eq!(malformed.status.code(), Some(18));
+    assert!(String::from_utf8_lossy(&malformed.stderr).contains("position 6"), "{}", String::from_utf8_lossy(&malformed.stderr));
+}
+
+#[test]
+fn batch_continues_past_failures() {
+    let jobs = "# моменты\n0 1 1000\n\n0 2 1000 simpson x^2\n1 0 10\n0 1 100 midpoint sin(x\n-1 1 1000 midpoint 1+0\n";
+    let output = run_with_input(&["--batch", "--lang", "en"], jobs);
+    assert_eq!(output.status.code(), Some(19));
+    assert_eq!(stdout(&output), "2: 0.34944635378301364\n4: 2.6666666666666665\n7: 2\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("5: Integral calculation error: the lower bound (1) is greater than the upper bound (0)"), "{}", stderr);
+    assert!(stderr.contains("6: Integral calculation error: the expression ends unexpectedly at position 6"), "{}", stderr);
+    let clean = run_with_input(&["--batch", "--lang", "en"], "0 1 1000\n\n# comment\n");
+    assert!(clean.status.success());
+    assert_eq!(stdout(&clean), "1: 0.34944635378301364\n");
+    // Одно неизвестное слово — опечатка в методе, а не выражение
+    let typo = run_with_input(&["--batch", "--lang", "en"], "0 1 1000 simpsn\n0 1 1000 gauss\n");
+    let stderr = String::from_utf8_lossy(&typo.stderr);
+    assert!(stderr.contains("1: ") && stderr.contains("did you mean simpson?"), "{}", stderr);
+    assert!(stdout(&typo).starts_with("2: "), "{}", stdout(&typo));
+}
+
+#[test]
+fn input_and_output_files() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("input_and_output_files");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let (input, output) = (dir.join("jobs.txt"), dir.join("results.txt"));
+    std::fs::write(&input, "0 1 1000\n0 2 1000 simpson x^2\n").unwrap();
+    let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+    std::fs::write(output, "старое содержимое\n").unwrap();
+    let run_files = run(&["--lang", "en", "--input", input, "--output", output]);
+    assert!(run_files.status.success());
+    assert!(stdout(&run_files).is_empty());
+    assert_eq!(std::fs::read_to_string(output).unwrap(), "1: 0.34944635378301364\n2: 2.6666666666666665\n");
+    // Запись через временный файл: после переименования в каталоге только два файла
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+    let missing = run(&["--lang", "en", "--input", "/nonexistent/jobs.txt"]);
+    assert_eq!(missing.status.code(), Some(20));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("failed to read the file /nonexistent/jobs.txt"));
+    let unwritable = run(&["--lang", "en", "--input", input, "--output", "/nonexistent/dir/results.txt"]);
+    assert_eq!(unwritable.status.code(), Some(20));
+    assert!(String::from_utf8_lossy(&unwritable.stderr).contains("failed to write the file /nonexistent/dir/results.txt"));
+}
+
+/// Поля строки CSV: запятые внутри кавычек не разделяют поля, `""` — кавычка.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                fields.last_mut().unwrap().push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    assert!(!quoted, "{}", line);
+    fields
+}
+
+#[test]
+fn batch_csv_parses() {
+    let jobs = "0 1 1000\n1 0 10\n0 1 10 midpoint x,2\n0 2 1000 simpson x^2\n";
+    let output = run_with_input(&["--batch", "--format", "csv", "--lang", "en"], jobs);
+    assert_eq!(output.status.code(), Some(19));
+    let output = stdout(&output);
+    let rows: Vec<Vec<String>> = output.lines().map(csv_fields).collect();
+    assert_eq!(rows[0], ["lower", "upper", "samples", "method", "value", "abs_error", "rel_error", "elapsed_ms", "remainder_ms", "status"]);
+    assert_eq!(rows.len(), 5);
+    assert!(rows.iter().all(|row| row.len() == rows[0].len()), "{}", output);
+    // Значения печатаются с точностью, достаточной для обратного чтения
+    let text = stdout(&run_with_input(&["--batch", "--lang", "en"], jobs));
+    let values: Vec<f64> = text.lines().map(|line| line.split_once(": ").unwrap().1.parse().unwrap()).collect();
+    assert_eq!(rows[1][4].parse::<f64>().unwrap(), values[0]);
+    assert_eq!(rows[4][4].parse::<f64>().unwrap(), values[1]);
+    let (error, relative): (f64, f64) = (rows[1][5].parse().unwrap(), rows[1][6].parse().unwrap());
+    assert!(error > 0.0 && error < 1e-6, "{}", error);
+    assert!((relative - error / values[0]).abs() < 1e-20, "{}", relative);
+    assert_eq!(rows[1][9], "ok");
+    // Неудачные расчёты сохраняют входные столбцы
+    assert_eq!(&rows[2][..4], ["1", "0", "10", "midpoint"]);
+    assert!(rows[2][9].contains("greater than the upper bound"), "{}", rows[2][9]);
+    assert_eq!(rows[3][9], "Integral calculation error: invalid expression at position 2: unexpected \",\"");
+}
+
+#[test]
+fn quiet_prints_single_float() {
+    let output = run(&["-q", "--interactive", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1e6"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert_eq!(text.lines().count(), 1, "{}", text);
+    assert!(text.ends_with('\n'));
+    let value: f64 = text.trim().parse().unwrap();
+    assert!((value - 0.349446289808078).abs() < 1e-12, "{}", value);
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+    let failed = run(&["-q", "--lang", "en", "--lower", "1", "--upper", "0", "--samples", "10"]);
+    assert_eq!(failed.status.code(), Some(5));
+    assert!(failed.stdout.is_empty());
+    assert!(!failed.stderr.is_empty());
+}
+
+#[test]
+fn very_verbose_lists_every_thread() {
+    let args = ["--no-color", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "100000"];
+    for threads in ["1", "3", "4"] {
+        let output = run(&[&args[..], &["-vv", "--threads", threads]].concat());
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        let main_run: Vec<&str> = stderr.lines().skip_while(|line| !line.trim_start().starts_with("thread")).skip(1)
+            .take_while(|line| !line.starts_with('[')).collect();
+        assert_eq!(main_run.len(), threads.parse::<usize>().unwrap(), "{}", stderr);
+        assert!(stderr.starts_with(&format!("[main run] Step: 1e-5, threads: {}", threads)), "{}", stderr);
+        assert!(stdout(&output).contains("Approximate value of the integral: 0.3494"), "{}", stdout(&output));
+    }
+    let verbose = String::from_utf8_lossy(&run(&[&args[..], &["-v", "--threads", "4"]].concat()).stderr).into_owned();
+    assert!(verbose.contains("reduction blocks") && !verbose.contains("share"), "{}", verbose);
+}
+
+#[test]
+fn bound_flags_accept_decimal_comma() {
+    let comma = run(&["--lang", "en", "--lower", "0,5", "--upper", "1,5", "--samples", "10", "--expr", "x"]);
+    let dot = run(&["--lang", "en", "--lower", "0.5", "--upper", "1.5", "--samples", "10", "--expr", "x"]);
+    assert!(comma.status.success());
+    assert_eq!(stdout(&comma), stdout(&dot));
+    assert_eq!(stdout(&comma).trim(), "1");
+    let ambiguous = run(&["--lang", "en", "--lower", "1,234,5", "--upper", "2", "--samples", "10"]);
+    assert_eq!(ambiguous.status.code(), Some(1));
+}
+
+#[test]
+fn constant_bounds_at_prompt() {
+    let output = run_with_input(&["-q", "--expr", "cos(x)"], "-pi/2\npi/2\n100000\n");
+    assert!(output.status.success());
+    let value: f64 = stdout(&output).trim().parse().unwrap();
+    assert!((value - 2.0).abs() < 1e-9, "{}", value);
+    let output = run_with_input(&["--lang", "en"], "2pi\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("position 2: unexpected \"p\""), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn pipe_has_no_escape_codes() {
+    let args = ["--interactive", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1000", "--with-reference=4000"];
+    let output = run(&args);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Absolute error fits the remainder term"), "{}", stdout(&output));
+    let failed = run(&["--interactive", "--lang", "en", "--lower", "1", "--upper", "0", "--samples", "10"]);
+    assert!(!failed.status.success());
+    for output in [&output, &failed] {
+        assert!(!output.stdout.contains(&0x1b), "{}", stdout(output));
+        assert!(!output.stderr.contains(&0x1b), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+#[test]
+fn sigint_prints_partial_result() {
+    let child = Command::new(env!("CARGO_BIN_EXE_kryl_04"))
+        .args(["--interactive", "--no-color", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1000000000"])
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Обработчик устанавливается до расчёта, а расчёт идёт несколько секунд
+    std::thread::sleep(std::time::Duration::from_millis(700));
+    let killed = Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(killed.success());
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(21));
+    let text = stdout(&output);
+    let estimate = text.lines().find_map(|line| line.strip_prefix("Incomplete estimate of the integral (not a result): ")).unwrap();
+    assert!(estimate.parse::<f64>().unwrap().is_finite(), "{}", text);
+    assert!(!text.contains("Approximate value of the integral"), "{}", text);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Calculation interrupted, done"));
+}
+
+/// Запуск с переменной окружения `KRYL_THREADS`.
+fn run_with_threads_env(args: &[&str], threads: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kryl_04"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("KRYL_THREADS", threads)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn thread_count_from_environment() {
+    let args = ["-q", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1000003"];
+    let verbose = [&args[1..], &["-v"]].concat();
+    let values: Vec<String> = ["1", "2", "8"].iter().map(|threads| stdout(&run_with_threads_env(&args, threads))).collect();
+    for (value, threads) in values.iter().zip(["1", "2", "8"]) {
+        assert_eq!(value, &values[0], "{}", threads);
+        let stderr = String::from_utf8_lossy(&run_with_threads_env(&verbose, threads).stderr).into_owned();
+        assert!(stderr.contains(&format!("threads: {},", threads)), "{}", stderr);
+    }
+    assert!(values[0].trim().parse::<f64>().is_ok(), "{}", values[0]);
+    // Флаг важнее переменной окружения
+    let flag = run_with_threads_env(&[&verbose[..], &["--threads", "3"]].concat(), "2");
+    assert!(String::from_utf8_lossy(&flag.stderr).contains("threads: 3,"));
+    assert_eq!(stdout(&run_with_threads_env(&[&args[..], &["--threads", "3"]].concat(), "2")), values[0]);
+    for invalid in ["5000", "abc"] {
+        let output = run_with_threads_env(&args, invalid);
+        assert_eq!(output.status.code(), Some(8), "{}", invalid);
+    }
+}
+
+#[test]
+fn methods_have_own_labels_and_values() {
+    let coarse = ["--interactive", "--no-color", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "10"];
+    let report = |method: &str| stdout(&run(&[&coarse[..], &["--method", method]].concat()));
+    let (midpoint, trapezoid) = (report("midpoint"), report("Trapezoid"));
+    assert!(midpoint.contains("Method: midpoint\n"), "{}", midpoint);
+    assert!(trapezoid.contains("Method: trapezoid\n"), "{}", trapezoid);
+    let value = |report: &str| -> f64 {
+        report.lines().find_map(|line| line.strip_prefix("Approximate value of the integral: ")).unwrap().parse().unwrap()
+    };
+    // У средних прямоугольников погрешность вдвое меньше и другого знака
+    let exact = 0.349446289808078;
+    let (midpoint_error, trapezoid_error) = (value(&midpoint) - exact, value(&trapezoid) - exact);
+    assert!(midpoint_error * trapezoid_error < 0.0, "{} {}", midpoint_error, trapezoid_error);
+    assert!((trapezoid_error / midpoint_error + 2.0).abs() < 0.2, "{} {}", midpoint_error, trapezoid_error);
+    let typo = run(&["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "10", "--method", "simpsn"]);
+    assert_eq!(typo.status.code(), Some(12));
+    assert!(String::from_utf8_lossy(&typo.stderr).contains("did you mean simpson?"));
+}
+
+#[test]
+fn tolerance_picks_sample_count() {
+    let args = ["--interactive", "--no-color", "--lang", "en", "--lower", "0", "--upper", "1"];
+    let intervals = |tolerance: &str| -> (u64, f64) {
+        let output = stdout(&run(&[&args[..], &["--tolerance", tolerance]].concat()));
+        let intervals = output.split("intervals: ").nth(1).and_then(|rest| rest.split(',').next()).unwrap().parse().unwrap();
+        let estimate = output.lines().find_map(|line| line.strip_prefix("Error estimate: ")).unwrap().parse().unwrap();
+        (intervals, estimate)
+    };
+    let (loose, tight) = (intervals("1e-4"), intervals("1e-8"));
+    assert!(tight.0 > loose.0, "{:?} {:?}", loose, tight);
+    assert!(loose.1 < 1e-4 && tight.1 < 1e-8, "{:?} {:?}", loose, tight);
+    let both = run(&[&args[..], &["--tolerance", "1e-6", "--samples", "100"]].concat());
+    assert_eq!(both.status.code(), Some(11));
+}
+
+fn print_config_line(output: &Output, key: &str) -> String {
+    let prefix = format!("{} = ", key);
+    stdout(output).lines().find(|line| line.starts_with(&prefix)).unwrap().to_string()
+}
+
+#[test]
+fn config_precedence() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("config_precedence.toml");
+    std::fs::write(&path, "threads = 3\nmethod = \"simpson\"\nrows = 7\n").unwrap();
+    let path = path.to_str().unwrap();
+    let output = run_with_threads_env(&["--config", path, "--print-config", "--lang", "en", "--method", "trapezoid"], "5");
+    assert!(output.status.success());
+    // Флаг перекрывает файл, переменная окружения — тоже файл
+    assert!(print_config_line(&output, "method").contains("\"trapezoid\""), "{}", stdout(&output));
+    assert!(print_config_line(&output, "threads").starts_with("threads = 5 "));
+    assert!(print_config_line(&output, "rows").starts_with("rows = 7 "));
+    assert!(print_config_line(&output, "start").starts_with("start = 16 "));
+    let output = run(&["--config", path, "--print-config", "--lang", "en", "--threads", "2"]);
+    assert!(print_config_line(&output, "threads").starts_with("threads = 2 "));
+    assert!(print_config_line(&output, "method").contains("\"simpson\""));
+}
+
+#[test]
+fn malformed_config() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("malformed_config.toml");
+    std::fs::write(&path, "threads = 3\nbogus = 1\n").unwrap();
+    let path = path.to_str().unwrap();
+    let output = run(&["--config", path, "--print-config", "--lang", "en"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains(":2: unknown key \"bogus\""));
+    std::fs::write(path, "precision = \"x\"\nmethod midpoint\n").unwrap();
+    let output = run(&["--config", path, "--print-config", "--lang", "en"]);
+    assert_eq!(output.status.code(), Some(11));
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(stderr.contains(":1: wrong value type for key \"precision\": expected an integer"), "{}", stderr);
+    assert!(stderr.contains(":2: "), "{}", stderr);
+    let output = run(&["--config", "/nonexistent/kryl_04.toml", "--print-config", "--lang", "en"]);
+    assert_eq!(output.status.code(), Some(20));
+}
+
+#[test]
+fn scripted_repl_session() {
+    let script = "int 0 1 1000\nbogus\nset samples 1e3\nset method simpson\nint 0 1\nint 0\nlast\nshow\n";
+    let output = run_with_input(&["--repl", "--lang", "en", "--no-color"], script);
+    // Конец ввода без `quit` завершает сеанс без ошибки
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let values: Vec<&str> = text.lines().filter(|line| line.starts_with("Approximate value")).collect();
+    // Третий раз значение выводит команда `last`
+    assert_eq!(values.len(), 3, "{}", text);
+    assert_eq!(values[1], values[2]);
+    assert_ne!(values[0], values[1]);
+    assert!(text.contains("Method: midpoint") && text.contains("Method: simpson"));
+    assert!(text.contains("History entry: 2"));
+    assert!(text.contains("intervals: 1000"));
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(stderr.contains("bogus"), "{}", stderr);
+    assert!(stderr.contains("Usage: int"), "{}", stderr);
+    let output = run_with_input(&["--repl", "--lang", "en"], "quit\nint 0 1\n");
+    assert!(output.status.success());
+    assert!(!stdout(&output).contains("Approximate value"));
+}
+
+fn run_repl_with_data(data: &std::path::Path, input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kryl_04"))
+        .args(["--repl", "--lang", "en", "--no-color"])
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("XDG_DATA_HOME", data)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    stdout(&output)
+}
+
+#[test]
+fn repl_history_and_persistence() {
+    let data = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("repl_history_and_persistence");
+    let _ = std::fs::remove_dir_all(&data);
+    let text = run_repl_with_data(&data, "int 0 1 1000\nint 0 $1 1000\nset persist on\nhistory\nrecall 2\n");
+    assert!(text.contains("   2: atan(x)/(x^4+1), [0, 0.349446353783], midpoint"), "{}", text);
+    assert!(text.contains("Bounds: [0, 0.349446353783], intervals: 1000"), "{}", text);
+    assert!(text.contains("Entries loaded from the history file: 0"));
+    let file = data.join("kryl_04").join("history.jsonl");
+    assert_eq!(std::fs::read_to_string(&file).unwrap().lines().count(), 2);
+    // Без `set persist on` файл не читается и не пополняется
+    let text = run_repl_with_data(&data, "int 0 1 10\nrecall 2\n");
+    assert!(!text.contains("Bounds:"), "{}", text);
+    assert_eq!(std::fs::read_to_string(&file).unwrap().lines().count(), 2);
+    let text = run_repl_with_data(&data, "set persist on\nint 0 $2 1000\n");
+    assert!(text.contains("Entries loaded from the history file: 2"), "{}", text);
+    assert!(text.contains("History entry: 3"), "{}", text);
+    assert!(text.contains("Step: 5.95786330264e-5"), "{}", text);
+    assert_eq!(std::fs::read_to_string(&file).unwrap().lines().count(), 3);
+}
+
+#[test]
+fn bench_has_row_per_method() {
+    let output = run(&["bench", "--lang", "en", "--lower", "0", "--upper", "1", "--evals", "2000"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    for method in ["midpoint", "left", "right", "trapezoid", "simpson", "simpson38", "boole", "gk15", "montecarlo", "clenshaw-curtis", "tanh-sinh"] {
+        assert_eq!(text.lines().filter(|line| line.split_whitespace().next() == Some(method)).count(), 1, "{}: {}", method, text);
+    }
+    let output = run(&["bench", "--lang", "en", "--lower", "0", "--upper", "1", "--evals", "2000", "--format", "csv", "--sort", "error"]);
+    let text = stdout(&output);
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("method,samples,evaluations,value,error,elapsed_ms"));
+    let rows: Vec<Vec<String>> = lines.map(csv_fields).collect();
+    assert_eq!(rows.len(), 11);
+    let errors: Vec<f64> = rows.iter().map(|row| row[4].parse().unwrap()).collect();
+    assert!(errors.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", errors);
+    // Ошибки растут в порядке точности формул
+    let position = |method: &str| rows.iter().position(|row| row[0] == method).unwrap();
+    assert!(position("gk15") < position("simpson"));
+    assert!(position("simpson") < position("midpoint"));
+    assert!(position("midpoint") < position("left"));
+    let output = run(&["bench", "--lang", "en", "--lower", "0", "--upper", "1", "--evals", "2000", "--format", "json"]);
+    let text = stdout(&output);
+    assert_eq!(text.matches("{\"method\": ").count(), 11, "{}", text);
+    assert!(text.trim_end().ends_with('}'));
+}
+
+#[test]
+fn table_order_settles_near_two() {
+    let output = run(&["table", "--lang", "en", "--lower", "0", "--upper", "1", "--start", "16", "--rows", "12"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let rows: Vec<Vec<&str>> = text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|fields| fields.len() == 5 && fields[0].parse::<u64>().is_ok())
+        .collect();
+    assert_eq!(rows.len(), 12, "{}", text);
+    assert_eq!(rows[11][0], "32768");
+    assert_eq!(rows[11][2..4], ["-", "-"]);
+    // Последние строки с порядком
+    for row in &rows[7..10] {
+        let order: f64 = row[3].parse().unwrap();
+        assert!((order - 2.0).abs() < 0.2, "{}", text);
+    }
+    let output = run(&["table", "--lang", "en", "--lower", "0", "--upper", "1", "--rows", "4", "--format", "csv"]);
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "n,value,difference,order,remainder_bound");
+    assert_eq!(lines.len(), 5);
+    assert!(lines[4].starts_with("128,") && lines[4].contains(",,,"), "{}", text);
+}
+
+fn run_with_columns(args: &[&str], input: &str, columns: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kryl_04"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("XDG_DATA_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("COLUMNS", columns)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn plot_goes_to_stderr() {
+    let args = ["--lang", "en", "--no-color", "--expr", "sin(x)", "--lower", "0", "--upper", "2*pi", "--samples", "1000", "--plot", "--plot-height", "5"];
+    let output = run_with_columns(&args, "", "36");
+    assert!(output.status.success());
+    // stdout остаётся пригодным для разбора
+    assert!(stdout(&output).trim().parse::<f64>().unwrap().abs() < 1e-9);
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 7, "{}", stderr);
+    assert_eq!(lines[0], " 0.9979 ┤  ⣠⠴⠋⠉⠉⠳⢤⡀");
+    assert!(lines[2].starts_with("      0 ┼"), "{}", stderr);
+    assert_eq!(lines[2].chars().count(), 9 + 24);
+    assert_eq!(lines[5], "        └0                  6.283");
+    assert!(lines[6].starts_with("minimum: -0.99"), "{}", stderr);
+}
+
+#[test]
+fn plot_reports_non_finite_points() {
+    let output = run_with_columns(&["--repl", "--lang", "en", "--no-color", "--plot-height", "3"], "set expr sqrt(x)\nplot -1 1\nplot 0 inf\n", "40");
+    assert!(output.status.success());
+    assert!(stdout(&output).is_empty() || !stdout(&output).contains('┤'));
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(stderr.contains("not finite at plot points: 28 / 56, the first at x = -0.982142857143"), "{}", stderr);
+    assert!(stderr.contains("A plot needs a finite interval"), "{}", stderr);
+}
+
+#[test]
+fn dump_plot_matches_integral() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("dump_plot_matches_integral");
+    std::fs::create_dir_all(&dir).unwrap();
+    let data = dir.join("plot.dat");
+    let data = data.to_str().unwrap();
+    let output = run(&["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1000", "--dump-plot", data, "--dump-points", "1000"]);
+    assert!(output.status.success());
+    let value: f64 = stdout(&output).trim().parse().unwrap();
+    let text = std::fs::read_to_string(data).unwrap();
+    let last = text.lines().last().unwrap();
+    let fields: Vec<f64> = last.split_whitespace().map(|field| field.parse().unwrap()).collect();
+    assert_eq!(fields[0], 1.0);
+    assert!((fields[2] - value).abs() < 1e-15, "{} vs {}", last, value);
+    assert_eq!(text.lines().filter(|line| !line.starts_with('#')).count(), 1001);
+    assert!(std::fs::read_to_string(dir.join("plot.gp")).unwrap().contains("using 1:3 with lines title \"F(x)\""));
+    let output = run(&["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "10", "--dump-plot", "/nonexistent/kryl_04/plot.dat"]);
+    assert_eq!(output.status.code(), Some(20));
+}
+
+fn json_number_field(text: &str, key: &str) -> f64 {
+    let start = text.find(&format!("\"{}\": ", key)).unwrap() + key.len() + 4;
+    let end = text[start..].find([',', '\n']).unwrap() + start;
+    text[start..end].parse().unwrap()
+}
+
+#[test]
+fn elapsed_time_grows_with_samples() {
+    // 10⁷ отсчётов вместо 10⁸, чтобы отладочная сборка укладывалась в секунды
+    let elapsed = |samples: &str| {
+        let output = run(&["--lang", "en", "--lower", "0", "--upper", "1", "--samples", samples, "--format", "json"]);
+        assert!(output.status.success());
+        json_number_field(&stdout(&output), "elapsed_ms")
+    };
+    let small = elapsed("1e4");
+    let large = elapsed("1e7");
+    assert!(small > 0.0);
+    assert!(large > 10.0 * small, "{} vs {}", large, small);
+}
+
+#[test]
+fn exit_status_per_failure() {
+    let table = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("exit_status_per_failure.csv");
+    std::fs::write(&table, "x\n").unwrap();
+    let table = table.to_str().unwrap();
+    let cases: &[(&[&str], i32)] = &[
+        (&["--lower", "x", "--upper", "1", "--samples", "10"], 1),
+        (&["--lower", "0", "--upper", "x", "--samples", "10"], 2),
+        (&["--lower", "0", "--upper", "1", "--samples", "x"], 3),
+        (&["--lower", "0", "--upper", "1", "--samples", "10", "--expr", "x/0"], 4),
+        (&["--lower", "1", "--upper", "0", "--samples", "10"], 5),
+        (&["--lower", "inf", "--upper", "inf", "--samples", "10"], 6),
+        (&["--lower", "0", "--upper", "1", "--samples", "10", "--threads", "x"], 8),
+        (&["--lower", "0", "--upper", "1", "--tolerance", "0"], 10),
+        (&["--lower", "0", "--upper", "1", "--samples", "10", "--tolerance", "1e-3"], 11),
+        (&["--lower", "0", "--upper", "1", "--samples", "10", "--method", "simpsn"], 12),
+        (&["--table", table], 13),
+        (&["--lower", "0", "--upper", "1", "--samples", "0"], 14),
+        (&["--lower", "-1e308", "--upper", "1e308", "--samples", "10"], 15),
+        (&["--lower", "0", "--upper", "1"], 16),
+        (&["--lower", "0", "--upper", "1", "--samples", "10", "--expr", "x+"], 18),
+        (&["--batch", "--input", "/nonexistent/kryl_04.csv"], 20),
+        (&["--lower", "0", "--upper", "1", "--samples", "10"], 0),
+    ];
+    for &(args, code) in cases {
+        let args: Vec<&str> = ["--lang", "en"].iter().chain(args).copied().collect();
+        let output = run(&args);
+        assert_eq!(output.status.code(), Some(code), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    }
+    // С `-v` код и его описание печатаются перед выходом
+    let output = run(&["--lang", "en", "-v", "--lower", "1", "--upper", "0", "--samples", "10"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("5 (lower bound exceeds upper bound)"));
+}
+
+#[test]
+fn exit_code_table_is_listed() {
+    let output = run(&["--lang", "en", "--list-exit-codes"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let codes: Vec<i32> = text.lines().map(|line| line.split_whitespace().next().unwrap().parse().unwrap()).collect();
+    assert_eq!(codes.len(), 25);
+    assert_eq!(codes[..3], [0, 1, 2]);
+    assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(text.contains(" 141  standard output closed"), "{}", text);
+}
+
+#[test]
+fn validate_reports_each_job() {
+    let jobs = "0 1 100\n1 0 100\n0 1 0\n0 inf 100\nx 1 10\n0 1 10 sin(x)/\n";
+    let output = run_with_input(&["--lang", "en", "--validate"], jobs);
+    assert_eq!(output.status.code(), Some(19));
+    let text = stdout(&output) + &String::from_utf8_lossy(&output.stderr);
+    for verdict in ["1: OK", "2: Integral calculation error: the lower bound (1) is greater", "3: Integral calculation error: the number of samples",
+                    "4: OK", "5: Integral calculation error: invalid expression", "6: Integral calculation error: the expression ends"] {
+        assert!(text.contains(verdict), "{}: {}", verdict, text);
+    }
+    // Ни одного значения интеграла
+    assert!(!text.contains("0.34"), "{}", text);
+    let output = run_with_input(&["--lang", "en", "--validate"], "0 1 100\n-1 1 1e6 x*x\n");
+    assert!(output.status.success());
+    let output = run_with_input(&["--lang", "en", "--validate", "--format", "csv"], "0 1 100\n1 0 5\n");
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "lower,upper,samples,method,status");
+    assert_eq!(lines[1], "0,1,100,midpoint,ok");
+    assert_eq!(csv_fields(lines[2])[..4], ["1", "0", "5", "midpoint"]);
+}
+
+#[test]
+fn compare_difference_is_within_tolerance() {
+    for (method, threads) in [("midpoint", "4"), ("simpson", "3"), ("gk15", "7"), ("boole", "2")] {
+        let args = ["--interactive", "--no-color", "--lang", "en", "--compare", "--lower", "0", "--upper", "1",
+                    "--samples", "100008", "--method", method, "--threads", threads];
+        let output = run(&args);
+        assert!(output.status.success(), "{}: {}", method, String::from_utf8_lossy(&output.stderr));
+        let text = stdout(&output);
+        let line = text.lines().find_map(|line| line.strip_prefix("Difference: ")).unwrap();
+        let (difference, tolerance) = line.strip_suffix(')').and_then(|line| line.split_once(" (tolerance: ")).unwrap();
+        let (difference, tolerance): (f64, f64) = (difference.parse().unwrap(), tolerance.parse().unwrap());
+        assert!(tolerance > 0.0 && difference <= tolerance, "{}: {}", method, text);
+        assert!(text.contains(&format!("threads: {})", threads)), "{}", text);
+    }
+}
+
+#[test]
+fn piped_run_has_no_prompts() {
+    for lang in ["ru", "en"] {
+        let output = run_with_input(&["--lang", lang], "0\n1\n1000\n");
+        assert!(output.status.success());
+        // Ровно одна строка — значение
+        assert_eq!(stdout(&output), "0.34944635378301364\n");
+        assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+        let prompted = stdout(&run_with_input(&["--lang", lang, "--interactive", "--no-color"], "0\n1\n1000\n"));
+        assert!(prompted.contains(if lang == "ru" { "Введите нижнюю границу" } else { "Enter the lower bound" }), "{}", prompted);
+    }
+    let config = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("piped_run_has_no_prompts.toml");
+    std::fs::write(&config, "interactive = true\n").unwrap();
+    let config = config.to_str().unwrap();
+    let output = run_with_input(&["--lang", "en", "--config", config, "--no-color"], "0\n1\n1000\n");
+    assert!(stdout(&output).contains("Enter the lower bound"));
+    let output = run_with_input(&["--lang", "en", "--config", config, "--no-interactive"], "0\n1\n1000\n");
+    assert_eq!(stdout(&output), "0.34944635378301364\n");
+}
+
+#[test]
+fn same_seed_gives_identical_output() {
+    let args = ["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "100000", "--method", "montecarlo", "--seed", "5"];
+    let first = run(&[&args[..], &["--threads", "1"]].concat());
+    assert!(first.status.success());
+    for threads in ["1", "3", "8"] {
+        let output = run(&[&args[..], &["--threads", threads]].concat());
+        assert_eq!(output.stdout, first.stdout, "{}", threads);
+    }
+    // Без --seed зерно выбирается случайно и печатается так, чтобы запуск можно было повторить
+    let unseeded = run(&args[..args.len() - 2]);
+    let stderr = String::from_utf8_lossy(&unseeded.stderr).into_owned();
+    let seed = stderr.lines().find_map(|line| line.strip_prefix("Random seed: ")).unwrap();
+    let repeated = run(&[&args[..args.len() - 2], &["--seed", seed]].concat());
+    assert_eq!(repeated.stdout, unseeded.stdout);
+    assert!(!String::from_utf8_lossy(&repeated.stderr).contains("Random seed"));
+}
+
+#[test]
+fn batch_reports_random_seed_once() {
+    let jobs = "0 1 1000 montecarlo\n0 2 1000 montecarlo\n0 1 1000 simpson\n";
+    let output = run_with_input(&["--batch", "--format", "csv", "--lang", "en"], jobs);
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let seeds: Vec<&str> = stderr.lines().filter_map(|line| line.strip_prefix("Random seed: ")).collect();
+    assert_eq!(seeds.len(), 1, "{}", stderr);
+    let repeated = run_with_input(&["--batch", "--format", "csv", "--lang", "en", "--seed", seeds[0]], jobs);
+    // Время в строках разное, значения — те же
+    let values = |output: &Output| stdout(output).lines().map(|line| line.split(',').nth(4).unwrap().to_string()).collect::<Vec<_>>();
+    assert_eq!(values(&repeated), values(&output));
+    assert!(!String::from_utf8_lossy(&repeated.stderr).contains("Random seed"));
+}
+
+#[test]
+fn phase_times_include_remainder() {
+    let output = run(&["--interactive", "--no-color", "--lang", "en", "--lower", "0", "--upper", "1", "--samples", "1000"]);
+    assert!(output.status.success());
+    let times = stdout(&output).lines().find(|line| line.starts_with("Phase times")).unwrap().to_string();
+    assert!(times.contains("remainder bound"), "{}", times);
+}
+
+#[test]
+fn default_run_has_no_verification() {
+    let output = stdout(&run(DEFAULT_RUN));
+    assert!(!output.contains("\"Actual\" value"), "{}", output);
+    assert!(!output.contains("reference run"), "{}", output);
+    assert!(output.contains("Value with twice as many samples"), "{}", output);
+    assert!(output.contains("Error estimate"), "{}", output);
+}
+
+#[test]
+fn with_reference_adds_verification() {
+    let args = [DEFAULT_RUN, &["--with-reference=4000"]].concat();
+    let output = stdout(&run(&args));
+    assert!(output.contains("\"Actual\" value"), "{}", output);
+    assert!(output.contains("Absolute error"), "{}", output);
+    assert!(output.contains("reference run"), "{}", output);
+}
+
+#[test]
+fn deprecated_reference_flags_still_work() {
+    let args = [DEFAULT_RUN, &["--reference", "--reference-samples", "4000"]].concat();
+    let output = run(&args);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("\"Actual\" value"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Deprecated flag: --reference (use --with-reference)"), "{}", stderr);
+    assert!(stderr.contains("Deprecated flag: --reference-samples"), "{}", stderr);
+}
+
+#[test]
+fn json_has_runge_error_estimate() {
+    let output = stdout(&run(&["--lower", "0", "--upper", "1", "--samples", "1000", "--format", "json"]));
+    assert!(output.contains("\"refined_value\": "), "{}", output);
+    assert!(!output.contains("\"error_estimate\": null"), "{}", output);
+    assert!(output.contains("\"verification\": null"), "{}", output);
+}
+
+#[test]
+fn json_embeds_verification() {
+    let output = stdout(&run(&["--lower", "0", "--upper", "1", "--samples", "1000", "--format", "json", "--with-reference=4000"]));
+    assert!(output.contains("\"verification\": {"), "{}", output);
+    assert!(output.contains("\"reference_intervals\": 4000"), "{}", output);
+    assert!(output.contains("\"within_bound\": true"), "{}", output);
+}
+
+#[test]
+fn json_lists_accuracy_warning() {
+    let output = stdout(&run(&["--lower", "0", "--upper", "10", "--samples", "3", "--format", "json"]));
+    assert!(output.contains("\"kind\": \"accuracy\""), "{}", output);
+    assert!(output.contains("\"suggested_samples\": 174"), "{}", output);
+    let quiet = stdout(&run(&["--lower", "0", "--upper", "10", "--samples", "3", "--format", "json", "--no-warnings"]));
+    assert!(quiet.contains("\"warnings\": [],"), "{}", quiet);
+}
+
+#[test]
+fn json_lists_singularity() {
+    let output = stdout(&run(&["--lower", "-1", "--upper", "1", "--samples", "1000", "--expr", "1/x", "--format", "json"]));
+    assert!(output.contains("\"kind\": \"singularity\""), "{}", output);
+}
+
+#[test]
+fn compare_prints_values_when_quiet() {
+    let output = run(&["-q", "--compare", "--lower", "0", "--upper", "1", "--samples", "100000", "--threads", "4"]);
+    assert!(output.status.success());
+    let line = stdout(&output);
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    assert_eq!(fields.len(), 3, "{}", line);
+    assert_eq!(fields[0], fields[1]);
+    assert_eq!(fields[2].parse::<f64>().unwrap(), 0.0);
+}
+
+#[test]
+fn compare_reports_both_runs() {
+    let output = stdout(&run(&["--interactive", "--lang", "en", "--compare", "--lower", "0", "--upper", "1", "--samples", "100000"]));
+    assert!(output.contains("Sequential run: "), "{}", output);
+}
+
+/// Запуск со стандартным выводом в канал, читатель которого уже закрыт, как
+/// у `| head` после первой строки.
+fn run_with_closed_stdout(args: &[&str]) -> Output {
+    let home = env!("CARGO_TARGET_TMPDIR");
+    let (reader, writer) = std::io::pipe().unwrap();
+    drop(reader);
+    Command::new(env!("CARGO_BIN_EXE_kryl_04"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", home)
+        .env("XDG_DATA_HOME", home)
+        .stdin(Stdio::null())
+        .stdout(writer)
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn closed_stdout_exits_quietly() {
+    for args in [&["--list-exit-codes"][..], &["bench", "--lower", "0", "--upper", "1", "--evals", "10000"]] {
+        let output = run_with_closed_stdout(args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!stderr.contains("panicked"), "{:?}: {}", args, stderr);
+        assert_eq!(output.status.code(), Some(141), "{:?}: {}", args, stderr);
+    }
+}
+
+#[test]
+fn subcommand_after_flags() {
+    let output = run(&["--lang", "en", "bench", "--lower", "0", "--upper", "1", "--evals", "10000", "--interactive"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Reference value"), "{}", stdout(&output));
+    let output = run(&["--lang", "en", "completions", "fish"]);
+    assert!(stdout(&output).starts_with("complete -c kryl_04"), "{}", stdout(&output));
+}
+
+#[test]
+fn stray_argument_is_reported() {
+    let output = run(&["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "10", "stray"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unexpected argument: stray"), "{}", stderr);
+    assert_eq!(output.status.code(), Some(11));
+}
+
+#[test]
+fn unknown_flag_is_rejected() {
+    for args in [&["--lang", "en", "--metod", "simpson"][..], &["--lang", "en", "--lowr", "0"]] {
+        let output = run(args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Unknown flag: --"), "{:?}: {}", args, stderr);
+        assert_eq!(output.status.code(), Some(11), "{:?}: {}", args, stderr);
+    }
+}
+
+#[test]
+fn completions_require_known_shell() {
+    let output = run(&["completions", "tcsh"]);
+    assert_eq!(output.status.code(), Some(11));
+}
+
+#[test]
+fn extra_word_in_answer_line_is_rejected() {
+    let output = run_with_input(&[], "0 1 100 5\n");
+    assert_eq!(output.status.code(), Some(11));
+    assert!(stdout(&output).is_empty());
+    let output = run_with_input(&["-q"], "0 1\n1 000\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim(), "0.34944635378301364");
+}
+
+#[test]
+fn comma_in_samples_is_rejected() {
+    let output = run(&["--lower", "0", "--upper", "1", "--samples", "1,000"]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn accuracy_warning_fires_only_when_needed() {
+    let args = ["--interactive", "--no-color", "--lang", "en", "--lower", "0", "--upper", "10"];
+    let warned = |extra: &[&str]| {
+        let output = run(&[&args[..], extra].concat());
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stderr).contains("Too few samples")
+    };
+    assert!(warned(&["--samples", "4"]));
+    assert!(!warned(&["--samples", "4", "--no-warnings"]));
+    assert!(!warned(&["--samples", "100000"]));
+    // Подсказанного числа отсчётов достаточно
+    let output = run(&[&args[..], &["--samples", "22"]].concat());
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let suggested = stderr.rsplit(": ").next().unwrap().trim();
+    assert!(!warned(&["--samples", suggested]), "{}", stderr);
+    let quiet = run(&["-q", "--lang", "en", "--lower", "0", "--upper", "10", "--samples", "4"]);
+    assert!(quiet.stderr.is_empty(), "{}", String::from_utf8_lossy(&quiet.stderr));
+    let json = stdout(&run(&["--lang", "en", "--lower", "0", "--upper", "1", "--samples", "100000", "--format", "json"]));
+    assert!(json.contains("\"warnings\": [],"), "{}", json);
+}